@@ -106,6 +106,7 @@ tests!{
     WindowSetPositionAndSize => App::window_set_position_and_size,
     WindowDemandAttention => App::window_demand_attention,
     WindowDemandUrgentAttention => App::window_demand_urgent_attention,
+    KitchenSink => App::kitchen_sink,
 }
 
 
@@ -194,6 +195,11 @@ impl App {
         self.main_window = Some(self.context()?.create_window(&WindowSettings {
             opengl: None,
             high_dpi: true,
+            parent: None,
+            position: None,
+            centered: false,
+            override_redirect: false,
+            wm_class: None,
         })?);
         self.main_window()?.clear()?;
         run_next_test()
@@ -313,6 +319,15 @@ impl App {
         self.main_window()?.demand_urgent_attention()?;
         wait_for_approval()
     }
+    #[cfg(not(feature="headless"))]
+    fn kitchen_sink(&mut self) -> test::Result {
+        // Play with it (close its windows, plug controllers in and out...) or wait for it to time out.
+        let counts = ::kitchen_sink::run(self.context()?, Some(Duration::from_secs(20)))?;
+        if counts.window == 0 {
+            return failed("The kitchen sink's windows received no events; Were they starved by other events?");
+        }
+        run_next_test()
+    }
     fn window_set_position(&mut self) -> test::Result {
         self.main_window()?.set_position(Vec2::new(100, 100))?;
         wait_for_approval()
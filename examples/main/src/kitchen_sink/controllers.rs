@@ -0,0 +1,95 @@
+//! Two players' controllers, which rumble while any of their buttons is held.
+
+use dmc::{Context, Event};
+use dmc::device::{DeviceID, VibrationState};
+
+/// How many controllers we care about; Any other controller is ignored.
+const MAX_PLAYERS: usize = 2;
+
+#[derive(Debug, Default)]
+pub struct Controllers {
+    /// The controller of each player, if any. Indices are player indices.
+    players: [Option<Player>; MAX_PLAYERS],
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Player {
+    controller: DeviceID,
+    held_buttons: u32,
+}
+
+impl Controllers {
+    /// Handles controller-related events, returning `false` for any other event.
+    pub fn handle_event(&mut self, context: &Context, ev: &Event) -> bool {
+        match *ev {
+            Event::DeviceConnected { device, ref info, .. } => {
+                if info.controller().is_none() || self.player_index(device).is_some() {
+                    return false;
+                }
+                match self.players.iter().position(Option::is_none) {
+                    Some(i) => {
+                        info!("Player {} joined with {:?}", i + 1, device);
+                        self.players[i] = Some(Player { controller: device, held_buttons: 0, });
+                        if let Err(e) = context.set_controller_player_index(device, Some(i as u32)) {
+                            debug!("Could not set the player index of {:?}: {}", device, e);
+                        }
+                    },
+                    None => info!("Ignoring {:?}, since there are already {} players", device, MAX_PLAYERS),
+                }
+                true
+            },
+            Event::DeviceDisconnected { device, .. } => match self.player_index(device) {
+                Some(i) => {
+                    info!("Player {} left", i + 1);
+                    self.players[i] = None;
+                    true
+                },
+                None => false,
+            },
+            Event::ControllerButtonPressed { controller, .. } => {
+                self.update_held_buttons(context, controller, 1);
+                true
+            },
+            Event::ControllerButtonReleased { controller, .. } => {
+                self.update_held_buttons(context, controller, -1);
+                true
+            },
+            Event::ControllerAxisMotion { .. } => true,
+            _ => false,
+        }
+    }
+    /// Stops all rumbling, so that controllers don't keep vibrating after we quit.
+    pub fn stop_all(&mut self, context: &Context) {
+        for player in self.players.iter_mut().filter_map(Option::as_mut) {
+            player.held_buttons = 0;
+            set_rumble(context, player.controller, false);
+        }
+    }
+    fn player_index(&self, controller: DeviceID) -> Option<usize> {
+        self.players.iter().position(|p| p.map(|p| p.controller) == Some(controller))
+    }
+    fn update_held_buttons(&mut self, context: &Context, controller: DeviceID, delta: i32) {
+        let i = match self.player_index(controller) {
+            Some(i) => i,
+            None => return,
+        };
+        let player = self.players[i].as_mut().unwrap();
+        let was_held = player.held_buttons > 0;
+        player.held_buttons = (player.held_buttons as i32 + delta).max(0) as u32;
+        let is_held = player.held_buttons > 0;
+        if was_held != is_held {
+            set_rumble(context, controller, is_held);
+        }
+    }
+}
+
+fn set_rumble(context: &Context, controller: DeviceID, enable: bool) {
+    let magnitude = if enable { 0x8000 } else { 0 };
+    let vibration = VibrationState {
+        strong_magnitude: magnitude,
+        weak_magnitude: magnitude,
+    };
+    if let Err(e) = context.controller_set_vibration(controller, &vibration) {
+        debug!("Could not set the vibration of {:?}: {}", controller, e);
+    }
+}
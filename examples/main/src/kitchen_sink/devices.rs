@@ -0,0 +1,41 @@
+//! Keeping track of connected devices as they are plugged in and out.
+
+use std::collections::HashMap;
+use dmc::Event;
+use dmc::device::DeviceID;
+
+/// The name of each connected device, by ID.
+#[derive(Debug, Default)]
+pub struct DeviceList {
+    names: HashMap<DeviceID, String>,
+}
+
+impl DeviceList {
+    /// Handles hotplug events, returning `false` for any other event.
+    pub fn handle_event(&mut self, ev: &Event) -> bool {
+        match *ev {
+            Event::DeviceConnected { device, ref info, .. } | Event::DeviceInfoChanged { device, ref info, .. } => {
+                let name = info.name().unwrap_or("Unnamed device").to_owned();
+                info!("Device {:?} connected: {}", device, name);
+                self.names.insert(device, name);
+                true
+            },
+            Event::DeviceDisconnected { device, .. } => {
+                info!("Device {:?} disconnected", device);
+                self.names.remove(&device);
+                true
+            },
+            _ => false,
+        }
+    }
+    /// A one-line summary, such as `2 devices: Xbox 360 Controller, USB Keyboard`.
+    pub fn summary(&self) -> String {
+        let mut names: Vec<_> = self.names.values().map(String::as_str).collect();
+        names.sort();
+        match names.len() {
+            0 => "no device".to_owned(),
+            1 => format!("1 device: {}", names[0]),
+            n => format!("{} devices: {}", n, names.join(", ")),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! A skeleton for a game that exercises several subsystems at once, and doubles as living
+//! documentation of how they fit together:
+//!
+//! - Two windows (one OpenGL, one plain), with events routed to them by `WindowHandle`;
+//! - Up to two controllers, which rumble while any of their buttons is held;
+//! - Hotplug handling, with the list of connected devices shown in the plain window's title;
+//! - Clean shutdown when the last window is closed.
+//!
+//! Run it with `cargo run -- kitchen_sink`. It also runs as part of the manual tests, in which
+//! case it quits by itself after a while.
+//!
+//! There's no clipboard API yet, so there's no clipboard paste either.
+
+mod windows;
+mod controllers;
+mod devices;
+
+use std::time::{Duration, Instant};
+use dmc::{Context, Event};
+use dmc::error::Result;
+use self::windows::Windows;
+use self::controllers::Controllers;
+use self::devices::DeviceList;
+
+/// How many events of each kind were handled, so that one subsystem flooding the queue
+/// (e.g controller axes) can be told apart from another one being starved (e.g window events).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct EventCounts {
+    pub window: u64,
+    pub controller: u64,
+    pub device: u64,
+    pub other: u64,
+}
+
+/// Runs the kitchen sink until the last window is closed, or `timeout` elapses.
+pub fn run(context: &Context, timeout: Option<Duration>) -> Result<EventCounts> {
+    let mut windows = Windows::new(context)?;
+    let mut controllers = Controllers::default();
+    let mut devices = DeviceList::default();
+    let mut counts = EventCounts::default();
+    let start = Instant::now();
+
+    'main_loop: while !timeout.map_or(false, |timeout| start.elapsed() >= timeout) {
+        for ev in context.events_poll_iter() {
+            if let Event::Quit = ev {
+                break 'main_loop;
+            }
+            if windows.handle_event(&ev) {
+                counts.window += 1;
+            } else if devices.handle_event(&ev) {
+                // Controllers need to know about hotplug too, so don't stop there.
+                controllers.handle_event(context, &ev);
+                windows.set_status(&devices.summary())?;
+                counts.device += 1;
+            } else if controllers.handle_event(context, &ev) {
+                counts.controller += 1;
+            } else {
+                counts.other += 1;
+            }
+            if windows.is_empty() {
+                break 'main_loop;
+            }
+        }
+        windows.draw()?;
+    }
+
+    controllers.stop_all(context);
+    info!("Kitchen sink handled {:?}", counts);
+    Ok(counts)
+}
@@ -0,0 +1,164 @@
+//! The kitchen sink's two windows, and routing of window events by `WindowHandle`.
+
+use std::mem;
+use dmc::{
+    Context, Window, WindowSettings, Event,
+    GLPixelFormatSettings, GLDefaultPixelFormatChooser, GLContextSettings, GLContext,
+};
+use dmc::error::Result;
+use dmc::window::WindowHandle;
+
+const GL_COLOR_BUFFER_BIT: u32 = 0x4000;
+
+type GlClearColor = extern "system" fn(f32, f32, f32, f32);
+type GlClear = extern "system" fn(u32);
+
+/// An OpenGL window, which is cleared to a color that cycles over time.
+struct GLWindow {
+    // Dropped before the window, as it should.
+    gl_context: GLContext,
+    window: Window,
+    gl_clear_color: GlClearColor,
+    gl_clear: GlClear,
+    frame: u64,
+}
+
+impl GLWindow {
+    fn new(context: &Context) -> Result<Self> {
+        let pixel_format = GLPixelFormatSettings::default();
+        let window = context.create_window(&WindowSettings {
+            opengl: Some(&GLDefaultPixelFormatChooser::from(&pixel_format)),
+            high_dpi: false,
+            parent: None,
+            position: None,
+            centered: true,
+            override_redirect: false,
+            wm_class: None,
+        })?;
+        window.set_title("Kitchen sink (OpenGL)")?;
+        let gl_context = window.create_gl_context(&GLContextSettings::default())?;
+        window.make_gl_context_current(Some(&gl_context))?;
+        // No need for GL bindings just for clearing the screen.
+        let (gl_clear_color, gl_clear) = unsafe {
+            let clear_color = gl_context.proc_address("glClearColor");
+            let clear = gl_context.proc_address("glClear");
+            assert!(!clear_color.is_null() && !clear.is_null());
+            (mem::transmute::<_, GlClearColor>(clear_color), mem::transmute::<_, GlClear>(clear))
+        };
+        window.show()?;
+        Ok(Self { gl_context, window, gl_clear_color, gl_clear, frame: 0, })
+    }
+    fn draw(&mut self) -> Result<()> {
+        self.window.make_gl_context_current(Some(&self.gl_context))?;
+        let t = (self.frame % 120) as f32 / 120.;
+        (self.gl_clear_color)(t, 0.25, 1. - t, 1.);
+        (self.gl_clear)(GL_COLOR_BUFFER_BIT);
+        self.frame += 1;
+        self.window.gl_swap_buffers()
+    }
+}
+
+/// The kitchen sink's windows. Each one is dropped as soon as the user closes it.
+pub struct Windows {
+    gl: Option<GLWindow>,
+    plain: Option<Window>,
+}
+
+impl Windows {
+    pub fn new(context: &Context) -> Result<Self> {
+        let gl = GLWindow::new(context)?;
+        let plain = context.create_window(&WindowSettings {
+            opengl: None,
+            high_dpi: false,
+            parent: None,
+            position: None,
+            centered: false,
+            override_redirect: false,
+            wm_class: None,
+        })?;
+        plain.set_title("Kitchen sink (no device)")?;
+        plain.show()?;
+        plain.clear()?;
+        Ok(Self { gl: Some(gl), plain: Some(plain), })
+    }
+    pub fn is_empty(&self) -> bool {
+        self.gl.is_none() && self.plain.is_none()
+    }
+    /// Shows a one-line status in the plain window's title, since there's no text rendering here.
+    pub fn set_status(&self, status: &str) -> Result<()> {
+        match self.plain.as_ref() {
+            Some(plain) => plain.set_title(&format!("Kitchen sink ({})", status)),
+            None => Ok(()),
+        }
+    }
+    pub fn draw(&mut self) -> Result<()> {
+        if let Some(gl) = self.gl.as_mut() {
+            gl.draw()?;
+        }
+        Ok(())
+    }
+    /// Handles the event if it targets one of our windows, returning `false` otherwise.
+    pub fn handle_event(&mut self, ev: &Event) -> bool {
+        let window = match event_window(ev) {
+            Some(window) => window,
+            None => return false,
+        };
+        let is_gl = self.gl.as_ref().map_or(false, |gl| gl.window.handle() == window);
+        let is_plain = self.plain.as_ref().map_or(false, |plain| plain.handle() == window);
+        if !is_gl && !is_plain {
+            // Someone else's window, or one we already closed (we can't tell them apart).
+            return false;
+        }
+        match *ev {
+            Event::WindowCloseRequested { .. } => {
+                info!("Closing {} window", if is_gl { "OpenGL" } else { "plain" });
+                if is_gl {
+                    self.gl = None;
+                } else {
+                    self.plain = None;
+                }
+            },
+            Event::WindowNeedsRedraw { .. } | Event::WindowResized { .. } if is_plain => {
+                if let Some(plain) = self.plain.as_ref() {
+                    if let Err(e) = plain.clear() {
+                        warn!("Could not clear the plain window: {}", e);
+                    }
+                }
+            },
+            _ => (),
+        }
+        true
+    }
+}
+
+/// Gets the window that an event targets, if any.
+fn event_window(ev: &Event) -> Option<WindowHandle> {
+    match *ev {
+        Event::WindowShown { window, .. }
+        | Event::WindowHidden { window, .. }
+        | Event::WindowNeedsRedraw { window, .. }
+        | Event::WindowMoved { window, .. }
+        | Event::WindowResized { window, .. }
+        | Event::WindowMinimized { window, .. }
+        | Event::WindowMaximized { window, .. }
+        | Event::WindowUnminized { window, .. }
+        | Event::WindowCloseRequested { window, .. }
+        | Event::WindowScaleFactorChanged { window, .. }
+        | Event::WindowRefreshRateChanged { window, .. }
+        | Event::MouseEnter { window, .. }
+        | Event::MouseLeave { window, .. }
+        | Event::MouseButtonPressed { window, .. }
+        | Event::MouseButtonReleased { window, .. }
+        | Event::MouseScroll { window, .. }
+        | Event::MouseMotion { window, .. }
+        | Event::KeyboardFocusGained { window, .. }
+        | Event::KeyboardFocusLost { window, .. }
+        | Event::KeyboardTextChar { window, .. }
+        | Event::KeyboardTextString { window, .. }
+        | Event::KeyboardTextComposition { window, .. }
+        | Event::KeyboardKeyPressed { window, .. }
+        | Event::KeyboardKeyReleased { window, .. }
+        => Some(window),
+        _ => None,
+    }
+}
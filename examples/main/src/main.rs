@@ -9,6 +9,8 @@ mod early;
 mod app;
 #[cfg(x11)]
 mod app_x11;
+#[cfg(not(feature="headless"))]
+mod kitchen_sink;
 
 use std::thread;
 use std::env;
@@ -25,6 +27,7 @@ fn main() {
     let args: Vec<_> = env::args().collect();
     match args.get(1).map(String::as_str) {
         Some("simple") => run_simple_app(app),
+        Some("kitchen_sink") => run_kitchen_sink(app),
         Some(_) => unimplemented!{},
         None => run_all_tests_and_report(app),
     }
@@ -49,6 +52,16 @@ fn run_simple_app(mut app: App) {
     }
 }
 
+#[cfg(feature="headless")]
+fn run_kitchen_sink(app: App) {}
+#[cfg(not(feature="headless"))]
+fn run_kitchen_sink(app: App) {
+    // The kitchen sink creates its own windows.
+    drop(app);
+    let context = dmc::Context::new().unwrap();
+    kitchen_sink::run(&context, None).unwrap();
+}
+
 fn wait_for_approval(app: &mut App) {
     let timeout = Duration::from_secs(4);
     let start = Instant::now();
@@ -0,0 +1,74 @@
+//! Per-application directories for data, cache and configuration files (à la `SDL_GetPrefPath`).
+//!
+//! These don't require a `Context`, so they can be used before any windowing is set up
+//! (e.g to decide where log files go).
+
+use std::fs;
+use std::path::{Path, PathBuf, Component};
+use error::{Result, failed, invalid_arg};
+use os;
+
+/// The kind of per-application directory to look up.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum AppDirKind {
+    Data,
+    Cache,
+    Config,
+}
+
+/// Gets (creating it if needed) the directory where the application should store its data.
+///
+/// On Linux and BSDs, this is `$XDG_DATA_HOME/<org>/<app>`, where `$XDG_DATA_HOME` defaults to
+/// `~/.local/share`.  
+/// On Windows, this is `<FOLDERID_RoamingAppData>\<org>\<app>`.
+///
+/// `org` and `app` must be non-empty and must not contain path separators.
+/// Failing to create the directory (e.g because the home directory is read-only) is an error.
+pub fn app_data_dir(org: &str, app: &str) -> Result<PathBuf> {
+    app_dir(AppDirKind::Data, org, app)
+}
+/// Gets (creating it if needed) the directory where the application should store files that
+/// may be deleted at any time without harm.
+///
+/// On Linux and BSDs, this is `$XDG_CACHE_HOME/<org>/<app>`, where `$XDG_CACHE_HOME` defaults to
+/// `~/.cache`.  
+/// On Windows, this is `<FOLDERID_LocalAppData>\<org>\<app>`, since caches shouldn't roam.
+///
+/// See `app_data_dir()` for the requirements on `org` and `app`.
+pub fn app_cache_dir(org: &str, app: &str) -> Result<PathBuf> {
+    app_dir(AppDirKind::Cache, org, app)
+}
+/// Gets (creating it if needed) the directory where the application should store its settings.
+///
+/// On Linux and BSDs, this is `$XDG_CONFIG_HOME/<org>/<app>`, where `$XDG_CONFIG_HOME` defaults to
+/// `~/.config`.  
+/// On Windows, this is the same as `app_data_dir()`.
+///
+/// See `app_data_dir()` for the requirements on `org` and `app`.
+pub fn app_config_dir(org: &str, app: &str) -> Result<PathBuf> {
+    app_dir(AppDirKind::Config, org, app)
+}
+
+fn app_dir(kind: AppDirKind, org: &str, app: &str) -> Result<PathBuf> {
+    check_path_component("org", org)?;
+    check_path_component("app", app)?;
+    let path = os::app_dir_base(kind)?.join(org).join(app);
+    if let Err(e) = fs::create_dir_all(&path) {
+        return failed(format!("Could not create `{}`: {}", path.display(), e));
+    }
+    Ok(path)
+}
+
+fn check_path_component(what: &str, s: &str) -> Result<()> {
+    let is_single_component = {
+        let mut components = Path::new(s).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(c)), None) => c == s,
+            _ => false,
+        }
+    };
+    if !is_single_component || s.contains('/') || s.contains('\\') {
+        return invalid_arg(format!("`{}` must be a single, non-empty path component (got {:?})", what, s));
+    }
+    Ok(())
+}
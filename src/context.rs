@@ -1,12 +1,65 @@
 //! The `Context` structure, which is also the main entry point for this crate.
 
+use std::cell::{Cell, RefCell};
+use std::ffi::CString;
 use os::OsContext;
-use error::Result;
+use log_verbosity;
+use stats::ContextStats;
+use device::controller::{ScheduledVibration, ControllerGlyphRule};
+use error::{Result, invalid_arg, unsupported};
 
 /// Common and globally needed platform-specific data.
 /// This is the entry point for creating various objects such as `Window`s and `GLContext`s.
+///
+/// Several `Context`s may coexist (e.g an editor and an embedded game view), each with its own
+/// event queue. Events from devices that are not tied to a window (e.g controllers on Linux) are
+/// reported once to each `Context` of the same thread, which share the underlying device handles.
+/// Per-device settings, such as `set_controller_event_mode()` and player indices, are shared as well.  
+/// Hints (see `hint::set_hint()`) and the log verbosity are process-wide.
+///
+/// Objects created from a `Context` (`Window`s, `GLContext`s, `Cursor`s, etc) keep its
+/// platform-specific resources alive, so they may outlive it: the connection to the display
+/// is only closed once the last of them is dropped. They remain usable, but events for them can
+/// only be retrieved via a `Context`.  
+/// None of these objects can be sent to another thread, which is what e.g Windows requires.
 #[derive(Debug)]
-pub struct Context(pub(crate) OsContext);
+pub struct Context {
+    pub(crate) os: OsContext,
+    /// See `log_events()`.
+    pub(crate) log_events: Cell<bool>,
+    /// See `stats()`.
+    pub(crate) stats: RefCell<ContextStats>,
+    /// See `controller_rumble_test()`.
+    pub(crate) scheduled_vibrations: RefCell<Vec<ScheduledVibration>>,
+    /// See `add_controller_glyph_rule()`.
+    pub(crate) controller_glyph_rules: RefCell<Vec<ControllerGlyphRule>>,
+}
+
+/// Escape hatches for X11 atoms that this crate doesn't know about.
+///
+/// Atoms are returned as `u64` so that the `x11` crate's types don't leak into the public API.
+/// These return `None` on other backends.
+#[cfg(feature = "x11-escape-hatch")]
+impl Context {
+    /// Gets the atom for the given name via `XInternAtom()`.
+    ///
+    /// If `only_if_exists` is `true` and the atom doesn't exist yet, this returns `None`.
+    pub fn x11_intern_atom(&self, name: &str, only_if_exists: bool) -> Option<u64> {
+        #[cfg(x11)]
+        let atom = self.intern_atom(name, !only_if_exists).ok().map(|atom| atom as u64);
+        #[cfg(not(x11))]
+        let atom = { let _ = (name, only_if_exists); None };
+        atom
+    }
+    /// Gets the name of the given atom via `XGetAtomName()`, or `None` if it isn't a valid atom.
+    pub fn x11_atom_name(&self, atom: u64) -> Option<String> {
+        #[cfg(x11)]
+        let name = self.atom_name(atom as _).ok();
+        #[cfg(not(x11))]
+        let name = { let _ = atom; None };
+        name
+    }
+}
 
 #[cfg(nightly)]
 mod nightly {
@@ -15,17 +68,189 @@ mod nightly {
     impl !Sync for Context {}
 }
 
+/// A platform-specific display backend.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum BackendKind {
+    X11,
+    Windows,
+    MacOS,
+    Emscripten,
+}
+
+#[cfg(x11)]
+const CURRENT_BACKEND: BackendKind = BackendKind::X11;
+#[cfg(target_os="windows")]
+const CURRENT_BACKEND: BackendKind = BackendKind::Windows;
+#[cfg(target_os="macos")]
+const CURRENT_BACKEND: BackendKind = BackendKind::MacOS;
+#[cfg(target_os="emscripten")]
+const CURRENT_BACKEND: BackendKind = BackendKind::Emscripten;
+
+impl BackendKind {
+    /// Gets the backend this crate was built with.
+    pub fn current() -> Self {
+        CURRENT_BACKEND
+    }
+}
+
+/// Details about the display backend a `Context` is connected to, see `Context::backend_info()`.
+///
+/// Versions are `(major, minor)` pairs, and are `None` when they don't apply to the backend
+/// or when the corresponding extension is unavailable.
+/// These are mostly useful in bug reports and logs.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BackendInfo {
+    /// The backend this crate was built with.
+    pub kind: BackendKind,
+    /// (X11-only) The vendor string of the X server (e.g "The X.Org Foundation").
+    pub server_vendor: Option<String>,
+    /// (X11-only) The vendor-specific release number of the X server.
+    pub server_vendor_release: Option<u32>,
+    /// (X11-only) The version of the core X protocol.
+    pub protocol_version: Option<(u32, u32)>,
+    /// (X11-only) The version of GLX supported by both the client library and the server.
+    pub glx_version: Option<(u32, u32)>,
+    /// (X11-only) The version of the X Input extension.
+    pub xinput_version: Option<(u32, u32)>,
+    /// (X11-only) The version of the X Rendering extension.
+    pub xrender_version: Option<(u32, u32)>,
+}
+
+impl BackendInfo {
+    pub(crate) fn new(kind: BackendKind) -> Self {
+        Self {
+            kind,
+            server_vendor: None,
+            server_vendor_release: None,
+            protocol_version: None,
+            glx_version: None,
+            xinput_version: None,
+            xrender_version: None,
+        }
+    }
+}
+
+/// How much this crate logs via the `log` crate. See `Context::set_log_verbosity()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only warnings and errors.
+    Quiet = 0,
+    /// Warnings, errors, and `info`-level messages.
+    Normal = 1,
+    /// Everything, including `debug` and `trace` messages.
+    Verbose = 2,
+}
+
+/// Creation-time settings for a `Context`.
+///
+/// The `Default` value is what `Context::new()` uses.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ContextOptions {
+    /// (X11-only) The name given to `XOpenDisplay()`. `None` means the `DISPLAY` environment variable.
+    ///
+    /// This is ignored by other backends.
+    pub x11_display_name: Option<CString>,
+    /// Whether a missing display server is tolerated, e.g on a CI machine.
+    ///
+    /// No backend supports headless contexts yet; for now, setting this only makes a missing
+    /// display be reported as an `Unsupported` error instead of a `Failed` one.
+    pub allow_headless: bool,
+    /// The acceptable backends, by order of preference. An empty list accepts any backend.
+    ///
+    /// Only one backend is built in per target, so this fails early if it's not in the list.
+    pub backend_preference: Vec<BackendKind>,
+    /// The application's name, which X11 windows use for their `WM_CLASS` instead of the
+    /// executable's name. It goes along with `_NET_WM_PID`, which is always set.
+    pub app_name: Option<String>,
+    /// Whether controllers that are already plugged in when the `Context` is created get
+    /// an `Event::DeviceConnected` with `initial: true`, before any other device event.
+    ///
+    /// This lets applications handle "a controller is available" in a single place, instead
+    /// of also going through `devices()` at startup.
+    ///
+    /// This is ignored on Windows, which doesn't report controller events yet.
+    pub replay_initial_devices: bool,
+}
+
+impl ContextOptions {
+    /// Checks these options without allocating any native resource.
+    fn validate(&self) -> Result<()> {
+        let &Self {
+            x11_display_name: _, allow_headless: _, ref backend_preference, ref app_name,
+            replay_initial_devices: _,
+        } = self;
+        for (i, backend) in backend_preference.iter().enumerate() {
+            if backend_preference[..i].contains(backend) {
+                return invalid_arg(format!("{:?} appears more than once in backend_preference", backend));
+            }
+        }
+        if !backend_preference.is_empty() && !backend_preference.contains(&BackendKind::current()) {
+            return unsupported(format!("None of the preferred backends {:?} is available (only {:?} is)", backend_preference, BackendKind::current()));
+        }
+        if let Some(ref app_name) = *app_name {
+            if app_name.is_empty() {
+                return invalid_arg("app_name must not be empty");
+            }
+            if app_name.contains('\0') {
+                return invalid_arg("app_name must not contain NUL characters");
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Context {
     /// Attempts to get one handle to the platform-specific display backend.
     /// 
     /// You must assume that it is invalid to have more than one Context at a time (it's also pointless).  
     /// It may work on some platforms (because we can make it so), but otherwise this could cause failures
     /// at various points.
+    ///
+    /// This is a shorthand for `with_options()` with default options.
     pub fn new() -> Result<Self> {
-        OsContext::new().map(Context)
+        Self::with_options(&ContextOptions::default())
+    }
+    /// Same as `new()`, but with explicit creation-time options.
+    ///
+    /// Invalid options are reported before any native resource is allocated.
+    pub fn with_options(options: &ContextOptions) -> Result<Self> {
+        options.validate()?;
+        OsContext::with_options(options).map(Context::from_os)
+    }
+    /// Wraps a freshly created `OsContext`. This is the only place where a `Context` is built.
+    pub(crate) fn from_os(os: OsContext) -> Self {
+        Context {
+            os,
+            log_events: Cell::new(false),
+            stats: RefCell::new(ContextStats::default()),
+            scheduled_vibrations: RefCell::new(Vec::new()),
+            controller_glyph_rules: RefCell::new(Vec::new()),
+        }
+    }
+    /// Gets details about the display backend this `Context` is connected to, such as the
+    /// X server's vendor and the versions of the extensions in use.
+    pub fn backend_info(&self) -> BackendInfo {
+        self.os.backend_info()
     }
     /// Undoes any mouse trap caused by any window.
     pub fn untrap_mouse(&self) -> Result<()> {
-        self.0.untrap_mouse()
+        self.os.untrap_mouse()
+    }
+    /// Limits what this crate logs, independently of the global logger's level, so that your
+    /// own logs can stay verbose while this crate's chatter is silenced.
+    ///
+    /// Warnings and errors are always logged, unless disabled via `Hint::LogLevel`.  
+    /// This applies to the whole process (log messages aren't tied to a `Context`), and
+    /// persists after this `Context` is dropped.
+    pub fn set_log_verbosity(&self, verbosity: Verbosity) {
+        log_verbosity::set(verbosity)
+    }
+    /// Gets the current verbosity, which is `Verbosity::Normal` by default in debug builds,
+    /// and `Verbosity::Quiet` in release builds.
+    ///
+    /// If a finer level was set via `Hint::LogLevel`, this returns the closest `Verbosity`.
+    pub fn log_verbosity(&self) -> Verbosity {
+        log_verbosity::get()
     }
 }
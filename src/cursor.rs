@@ -2,9 +2,9 @@
 
 use std::time::Duration;
 use os::OsCursor;
-use error::Result;
+use error::{self, Result};
 use context::Context;
-use window::Window;
+use window::{Window, RgbaImage};
 use super::{Vec2, Rgba, Extent2};
 
 #[derive(Debug)]
@@ -53,27 +53,74 @@ pub struct RgbaCursorData {
     pub rgba: Vec<Rgba<u8>>,
 }
 
+impl RgbaCursorData {
+    /// Creates cursor data from an image, e.g the same one that is used as a window icon.
+    ///
+    /// `hotspot` must be within the image's bounds.
+    pub fn from_image(image: &RgbaImage, hotspot: Vec2<u32>) -> Result<Self> {
+        image.validate()?;
+        validate_hotspot(image.size, hotspot)?;
+        Ok(Self { hotspot, size: image.size, rgba: image.pixels.clone() })
+    }
+    /// Creates cursor data from tightly packed 8-bit R, G, B, A bytes, reusing the buffer
+    /// without copying whenever possible (see `RgbaImage::from_raw()`).
+    ///
+    /// Colors are expected to be straight (i.e not premultiplied by alpha). Some backends
+    /// (e.g XRender cursors on X11) want premultiplied colors instead; If `premultiply` is
+    /// `true`, they are premultiplied in place.
+    ///
+    /// `hotspot` must be within the image's bounds.
+    pub fn from_raw(size: Extent2<u32>, hotspot: Vec2<u32>, bytes: Vec<u8>, premultiply: bool) -> Result<Self> {
+        let RgbaImage { size, mut pixels } = RgbaImage::from_raw(size, bytes)?;
+        validate_hotspot(size, hotspot)?;
+        if premultiply {
+            premultiply_alpha(&mut pixels);
+        }
+        Ok(Self { hotspot, size, rgba: pixels })
+    }
+}
+
+fn validate_hotspot(size: Extent2<u32>, hotspot: Vec2<u32>) -> Result<()> {
+    if hotspot.x >= size.w || hotspot.y >= size.h {
+        return error::invalid_arg(format!("The hotspot ({}, {}) is outside of the {}x{} image", hotspot.x, hotspot.y, size.w, size.h));
+    }
+    Ok(())
+}
+
+fn premultiply_alpha(pixels: &mut [Rgba<u8>]) {
+    for p in pixels.iter_mut() {
+        let a = p.a as u16;
+        p.r = ((p.r as u16 * a + 127) / 255) as u8;
+        p.g = ((p.g as u16 * a + 127) / 255) as u8;
+        p.b = ((p.b as u16 * a + 127) / 255) as u8;
+    }
+}
+
 impl Context {
     /// Creates a usable cursor from a well-known system cursor identifier.
     pub fn create_system_cursor(&self, s: SystemCursor) -> Result<Cursor> {
-        self.0.create_system_cursor(s).map(Cursor)
+        self.os.create_system_cursor(s).map(Cursor)
     }
     /// Gets the best size for new cursors that is closest to `size_hint`.
     pub fn best_cursor_size(&self, size_hint: Extent2<u32>) -> Result<Extent2<u32>> {
-        self.0.best_cursor_size(size_hint)
+        self.os.best_cursor_size(size_hint)
     }
     /// Creates a new alpha-blended cursor from RGBA data.
     pub fn create_rgba_cursor(&self, data: &RgbaCursorData) -> Result<Cursor> {
-        self.0.create_rgba_cursor(data).map(Cursor)
+        self.os.create_rgba_cursor(data).map(Cursor)
     }
     /// Creates a new animated, alpha-blended cursor from RGBA frames.
     pub fn create_animated_rgba_cursor(&self, frames: &[RgbaCursorAnimFrame]) -> Result<Cursor> {
-        self.0.create_animated_rgba_cursor(frames).map(Cursor)
+        self.os.create_animated_rgba_cursor(frames).map(Cursor)
     }
 }
 
 impl Window {
     /// Makes the cursor hidden as long as it stays within window.
+    ///
+    /// This persists across focus changes: the hidden (or custom) cursor is reapplied
+    /// whenever the window regains focus or the mouse re-enters it, since some window
+    /// managers reset it to the default arrow.
     pub fn hide_cursor(&self) -> Result<()> {
         self.0.hide_cursor()
     }
@@ -9,11 +9,26 @@ impl Context {
     ///
     /// On some platforms, there is only one.
     pub fn desktops(&self) -> Result<Vec<Desktop>> {
-        self.0.desktops()
+        self.os.desktops()
     }
     /// Gets the current desktop as an index in the array of desktops.
     pub fn current_desktop(&self) -> Result<usize> {
-        self.0.current_desktop()
+        self.os.current_desktop()
+    }
+    /// Gets the user's accessibility text/UI scaling preference, where `1.` means "no scaling".
+    ///
+    /// This is independent of the monitors' DPI; applications should multiply the size of
+    /// their UI elements by both. Once this has been called, changes are reported via
+    /// `Event::AccessibilityScaleFactorChanged`.
+    ///
+    /// On Linux, this is GNOME's `text-scaling-factor` setting, which is read via the
+    /// `gsettings` tool. Other desktop environments are not supported. Only the first call
+    /// blocks on `gsettings`; Later calls return the last known value.  
+    /// On Windows, this is the "Make text bigger" setting (`TextScaleFactor` in the
+    /// `Software\Microsoft\Accessibility` registry key).  
+    /// On macOS, this would be `NSFont.systemFontSize / 13`, as a proxy.
+    pub fn accessibility_scale_factor(&self) -> Result<f64> {
+        self.os.accessibility_scale_factor()
     }
 }
 
@@ -84,18 +84,41 @@
 //! The task of identifying devices from USB IDs is best left to another library, but if you know
 //! in advance the subset of devices you want to support, just look up their USB IDs in some public
 //! online database, and write a function that uses these.
+//!
+//! For the common case of picking between Xbox-style and PlayStation-style button prompts,
+//! `ControllerInfo::button_glyph_hint()` makes a coarse guess from the vendor ID and name, and
+//! `ControllerInfo::button_label()` gives the matching label (e.g "Cross" instead of "A").  
+//! If your users have controllers that are guessed wrong, teach this crate about them with
+//! `Context::add_controller_glyph_rule()`.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::{Duration, Instant};
 use context::Context;
+use event::Event;
 use os::{self, OsControllerState, OsControllerInfo};
-use super::{DeviceID, ButtonState, AxisInfo, Result};
+use super::{DeviceID, ButtonState, AxisInfo, Result, ResultExt, not_supported_by_device};
 
 /// Opaque container for a snapshot of a controller's full state.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ControllerState(pub(crate) OsControllerState);
 
 /// Information for a controller.
+// The second field is the glyph hint from the rules of the `Context` this came from, if any matched.
 #[derive(Debug, Clone, PartialEq)]
-pub struct ControllerInfo(pub(crate) OsControllerInfo);
+pub struct ControllerInfo(pub(crate) OsControllerInfo, pub(crate) Option<ButtonGlyphHint>);
+
+/// A vibration pulse that `Context::controller_rumble_test()` plays once `at` is reached.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct ScheduledVibration {
+    controller: DeviceID,
+    at: Instant,
+    vibration: VibrationState,
+}
+
+/// How long each motor rumbles during `Context::controller_rumble_test()`.
+const RUMBLE_TEST_STEP: Duration = Duration::from_millis(300);
 
 /// A rumble effect description.
 #[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -108,6 +131,175 @@ pub struct VibrationState {
     pub weak_magnitude: u16,
 }
 
+/// Which set of button glyphs best matches a controller, see `ControllerInfo::button_glyph_hint()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ButtonGlyphHint {
+    /// A, B, X and Y face buttons, as on Xbox controllers. Most third-party gamepads follow this layout.
+    Xbox,
+    /// Cross, Circle, Square and Triangle face buttons, as on DualShock and DualSense controllers.
+    PlayStation,
+    /// A, B, X and Y face buttons, but with A and B (and X and Y) swapped compared to Xbox controllers.
+    Nintendo,
+    /// The controller isn't recognized; Labels are generic names (see `ControllerButton::display_name()`).
+    Generic,
+}
+
+/// A rule for guessing a controller's `ButtonGlyphHint`, see `Context::add_controller_glyph_rule()`.
+///
+/// A rule matches a controller if all of its criteria that are `Some` match. A rule with no
+/// criteria never matches.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ControllerGlyphRule {
+    /// Matches controllers with this USB vendor ID.
+    pub vendor_id: Option<u16>,
+    /// Matches controllers which name contains this string, ignoring ASCII case.
+    pub name_contains: Option<String>,
+    /// The glyphs to use for matching controllers.
+    pub glyphs: ButtonGlyphHint,
+}
+
+/// A label for a controller button or axis, for use in UI prompts such as "Press [Cross]".
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ButtonLabel {
+    /// A short text, as printed on the controller where possible (e.g "A", "Cross", "LB", "ZR").
+    pub text: Cow<'static, str>,
+    /// The controller family the text is meant for; Useful for picking actual glyph images.
+    pub glyphs: ButtonGlyphHint,
+}
+
+// Checked after the user's rules. Vendor IDs come first since they're more reliable than names.
+const BUILTIN_GLYPH_RULES: &[(Option<u16>, Option<&str>, ButtonGlyphHint)] = &[
+    (Some(0x045e), None, ButtonGlyphHint::Xbox), // Microsoft
+    (Some(0x054c), None, ButtonGlyphHint::PlayStation), // Sony
+    (Some(0x057e), None, ButtonGlyphHint::Nintendo),
+    (None, Some("xbox"), ButtonGlyphHint::Xbox),
+    (None, Some("x-box"), ButtonGlyphHint::Xbox),
+    (None, Some("playstation"), ButtonGlyphHint::PlayStation),
+    (None, Some("dualshock"), ButtonGlyphHint::PlayStation),
+    (None, Some("dualsense"), ButtonGlyphHint::PlayStation),
+    (None, Some("nintendo"), ButtonGlyphHint::Nintendo),
+    (None, Some("joy-con"), ButtonGlyphHint::Nintendo),
+];
+
+fn glyph_rule_matches(rule_vendor_id: Option<u16>, rule_name: Option<&str>, vendor_id: Option<u16>, name: Option<&str>) -> bool {
+    if rule_vendor_id.is_none() && rule_name.is_none() {
+        return false;
+    }
+    let vendor_matches = match rule_vendor_id {
+        None => true,
+        Some(rule_vendor_id) => vendor_id == Some(rule_vendor_id),
+    };
+    let name_matches = match (rule_name, name) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(rule_name), Some(name)) => name.to_ascii_lowercase().contains(&rule_name.to_ascii_lowercase()),
+    };
+    vendor_matches && name_matches
+}
+
+impl ButtonGlyphHint {
+    /// Guesses the glyphs for a controller from its USB vendor ID and name, as
+    /// `ControllerInfo::button_glyph_hint()` does when none of its `Context`'s rules match.
+    ///
+    /// Only the built-in rules are checked here: the vendor IDs of Microsoft, Sony and Nintendo
+    /// are recognized, as well as some well-known names. Rules added via
+    /// `Context::add_controller_glyph_rule()` only apply to that `Context`'s `ControllerInfo`s.
+    pub fn infer(vendor_id: Option<u16>, name: Option<&str>) -> Self {
+        BUILTIN_GLYPH_RULES.iter()
+            .find(|&&(rule_vendor_id, rule_name, _)| glyph_rule_matches(rule_vendor_id, rule_name, vendor_id, name))
+            .map(|&(_, _, glyphs)| glyphs)
+            .unwrap_or(ButtonGlyphHint::Generic)
+    }
+}
+
+impl ButtonLabel {
+    /// Gets the label of `button` on controllers of the given family.
+    ///
+    /// Buttons are matched by position, so `ControllerButton::A` (the south button) is "Cross" on
+    /// PlayStation controllers and "B" on Nintendo ones. Buttons that don't have a specific
+    /// label fall back to `ControllerButton::display_name()`.
+    pub fn for_button(button: ControllerButton, glyphs: ButtonGlyphHint) -> Self {
+        use self::ControllerButton as B;
+        use self::ButtonGlyphHint as G;
+        let text = match (glyphs, button) {
+            (G::Xbox, B::LShoulder) => "LB",
+            (G::Xbox, B::LShoulder2) => "LT",
+            (G::Xbox, B::RShoulder) => "RB",
+            (G::Xbox, B::RShoulder2) => "RT",
+            (G::Xbox, B::LStickClick) => "LS",
+            (G::Xbox, B::RStickClick) => "RS",
+            (G::Xbox, B::Select) => "View",
+            (G::Xbox, B::Start) => "Menu",
+            (G::Xbox, B::Mode) => "Xbox",
+
+            (G::PlayStation, B::A) => "Cross",
+            (G::PlayStation, B::B) => "Circle",
+            (G::PlayStation, B::X) => "Square",
+            (G::PlayStation, B::Y) => "Triangle",
+            (G::PlayStation, B::LShoulder) => "L1",
+            (G::PlayStation, B::LShoulder2) => "L2",
+            (G::PlayStation, B::RShoulder) => "R1",
+            (G::PlayStation, B::RShoulder2) => "R2",
+            (G::PlayStation, B::LStickClick) => "L3",
+            (G::PlayStation, B::RStickClick) => "R3",
+            (G::PlayStation, B::Select) => "Share",
+            (G::PlayStation, B::Start) => "Options",
+            (G::PlayStation, B::Mode) => "PS",
+
+            (G::Nintendo, B::A) => "B",
+            (G::Nintendo, B::B) => "A",
+            (G::Nintendo, B::X) => "Y",
+            (G::Nintendo, B::Y) => "X",
+            (G::Nintendo, B::LShoulder) => "L",
+            (G::Nintendo, B::LShoulder2) => "ZL",
+            (G::Nintendo, B::RShoulder) => "R",
+            (G::Nintendo, B::RShoulder2) => "ZR",
+            (G::Nintendo, B::LStickClick) => "L Stick",
+            (G::Nintendo, B::RStickClick) => "R Stick",
+            (G::Nintendo, B::Select) => "-",
+            (G::Nintendo, B::Start) => "+",
+            (G::Nintendo, B::Mode) => "Home",
+
+            _ => return Self { text: button.display_name(), glyphs, },
+        };
+        Self { text: text.into(), glyphs, }
+    }
+    /// Gets the label of `axis` on controllers of the given family.
+    ///
+    /// Only triggers have family-specific labels; Other axes use `ControllerAxis::display_name()`.
+    pub fn for_axis(axis: ControllerAxis, glyphs: ButtonGlyphHint) -> Self {
+        use self::ControllerAxis as A;
+        use self::ButtonGlyphHint as G;
+        let text = match (glyphs, axis) {
+            (G::Xbox, A::LTrigger) => "LT",
+            (G::Xbox, A::RTrigger) => "RT",
+            (G::PlayStation, A::LTrigger) => "L2",
+            (G::PlayStation, A::RTrigger) => "R2",
+            (G::Nintendo, A::LTrigger) => "ZL",
+            (G::Nintendo, A::RTrigger) => "ZR",
+            _ => return Self { text: axis.display_name(), glyphs, },
+        };
+        Self { text: text.into(), glyphs, }
+    }
+}
+
+/// How a controller's button and axis changes are reported, see `Context::set_controller_event_mode()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum EventMode {
+    /// Changes are reported as `ControllerButtonPressed`, `ControllerButtonReleased` and
+    /// `ControllerAxisMotion` events. This is the default.
+    Events,
+    /// No button or axis event is reported for the controller; Use `Context::controller_state()`
+    /// and friends to read its current state.
+    PollOnly,
+}
+
+impl Default for EventMode {
+    fn default() -> Self {
+        EventMode::Events
+    }
+}
+
 impl VibrationState {
     /// The maximum value for a vibration state. This is a convenience for not writing
     /// `::std::u16::MAX`.
@@ -211,6 +403,47 @@ impl ControllerButton {
     pub const MAX_BASE: Option<u32> = os::device_consts::MAX_BASE_BUTTONS;
     /// Platform-specific hard limit on the number of `Num` buttons. `None` means that no limit is known.
     pub const MAX_NUM: Option<u32> = os::device_consts::MAX_NUM_BUTTONS;
+
+    /// Gets a generic, human-readable name for this button (e.g "Left Shoulder"), for use in UI prompts.
+    ///
+    /// Names are in English and follow the Xbox naming scheme for face buttons. Use
+    /// `ControllerInfo::button_glyph_hint()` to decide whether to show something else instead.
+    pub fn display_name(&self) -> Cow<'static, str> {
+        let name = match *self {
+            ControllerButton::DpadUp => "D-Pad Up",
+            ControllerButton::DpadDown => "D-Pad Down",
+            ControllerButton::DpadLeft => "D-Pad Left",
+            ControllerButton::DpadRight => "D-Pad Right",
+            ControllerButton::X => "X",
+            ControllerButton::Y => "Y",
+            ControllerButton::Z => "Z",
+            ControllerButton::A => "A",
+            ControllerButton::B => "B",
+            ControllerButton::C => "C",
+            ControllerButton::LShoulder => "Left Shoulder",
+            ControllerButton::LShoulder2 => "Left Trigger",
+            ControllerButton::RShoulder => "Right Shoulder",
+            ControllerButton::RShoulder2 => "Right Trigger",
+            ControllerButton::LStickClick => "Left Stick Click",
+            ControllerButton::RStickClick => "Right Stick Click",
+            ControllerButton::Select => "Select",
+            ControllerButton::Start => "Start",
+            ControllerButton::Mode => "Home",
+            ControllerButton::GearDown => "Gear Down",
+            ControllerButton::GearUp => "Gear Up",
+            ControllerButton::Trigger => "Trigger",
+            ControllerButton::Pinkie => "Pinkie",
+            ControllerButton::Dead => "Dead",
+            // Indices start at 0, but people count from 1.
+            ControllerButton::Thumb(i) => return format!("Thumb {}", i + 1).into(),
+            ControllerButton::Top(i) => return format!("Top {}", i + 1).into(),
+            ControllerButton::Base(i) => return format!("Base {}", i + 1).into(),
+            // These are literally named after their number.
+            ControllerButton::Num(n) => return format!("{}", n).into(),
+            ControllerButton::Other(code) => return format!("Button {}", code).into(),
+        };
+        name.into()
+    }
 }
 
 /// A known controller axis.
@@ -273,6 +506,56 @@ pub enum ControllerAxis {
 impl ControllerAxis {
     /// Platform-specific hard limit on the number of hats (each hat offers a `HatX` and `HatY` axis). `None` means that no limit is known.
     pub const MAX_HAT: Option<u32> = os::device_consts::MAX_HAT_AXES;
+
+    /// Gets a generic, human-readable name for this axis (e.g "Right Trigger"), for use in UI prompts.
+    pub fn display_name(&self) -> Cow<'static, str> {
+        let name = match *self {
+            ControllerAxis::LX => "Left Stick X",
+            ControllerAxis::LY => "Left Stick Y",
+            ControllerAxis::RX => "Right Stick X",
+            ControllerAxis::RY => "Right Stick Y",
+            ControllerAxis::DpadX => "D-Pad X",
+            ControllerAxis::DpadY => "D-Pad Y",
+            ControllerAxis::JoystickX => "X Axis",
+            ControllerAxis::JoystickY => "Y Axis",
+            ControllerAxis::JoystickZ => "Z Axis",
+            ControllerAxis::JoystickRotationX => "X Rotation",
+            ControllerAxis::JoystickRotationY => "Y Rotation",
+            ControllerAxis::JoystickRotationZ => "Z Rotation",
+            ControllerAxis::HatX(i) => return format!("Hat {} X", i + 1).into(),
+            ControllerAxis::HatY(i) => return format!("Hat {} Y", i + 1).into(),
+            ControllerAxis::LTrigger => "Left Trigger",
+            ControllerAxis::RTrigger => "Right Trigger",
+            ControllerAxis::Throttle => "Throttle",
+            ControllerAxis::Rudder => "Rudder",
+            ControllerAxis::Wheel => "Wheel",
+            ControllerAxis::Gas => "Gas",
+            ControllerAxis::Brake => "Brake",
+            ControllerAxis::Other(code) => return format!("Axis {}", code).into(),
+        };
+        name.into()
+    }
+
+    /// Does this axis go from "released" to "fully pressed" (triggers and pedals), as opposed to
+    /// going both ways around a center?
+    ///
+    /// For `Other` axes, we can only guess from the range: it is unipolar if it doesn't go below zero.
+    fn is_unipolar(&self, range: &Range<f64>) -> bool {
+        match *self {
+            ControllerAxis::LTrigger | ControllerAxis::RTrigger
+            | ControllerAxis::Throttle | ControllerAxis::Gas | ControllerAxis::Brake => true,
+            ControllerAxis::Other(_) => range.start >= 0. && range.end >= 0.,
+            _ => false,
+        }
+    }
+    /// Normalizes `value` to `[0, 1]` if this axis is unipolar, and to `[-1, 1]` otherwise.
+    fn normalize(&self, value: f64, info: &AxisInfo) -> Option<f64> {
+        if self.is_unipolar(&info.range()) {
+            info.normalize_unsigned(value)
+        } else {
+            info.normalize_signed(value)
+        }
+    }
 }
 
 
@@ -305,6 +588,23 @@ impl ControllerInfo {
     pub fn axis(&self, axis: ControllerAxis) -> Option<&AxisInfo> {
         self.0.axis(axis)
     }
+    /// Suggests which set of button glyphs to display in UI prompts for this controller.
+    ///
+    /// This is based on the rules of the `Context` this info was obtained from (see
+    /// `Context::add_controller_glyph_rule()`), then on the USB vendor ID and the device's name
+    /// (see `ButtonGlyphHint::infer()`), so it is a guess at best: a third-party controller
+    /// made for PlayStation consoles may be reported as `Generic`. Always let users override it.
+    pub fn button_glyph_hint(&self) -> ButtonGlyphHint {
+        self.1.unwrap_or_else(|| ButtonGlyphHint::infer(self.0.vendor_id(), self.0.name()))
+    }
+    /// Gets the label to display for the given button of this controller, see `ButtonLabel::for_button()`.
+    pub fn button_label(&self, button: ControllerButton) -> ButtonLabel {
+        ButtonLabel::for_button(button, self.button_glyph_hint())
+    }
+    /// Gets the label to display for the given axis of this controller, see `ButtonLabel::for_axis()`.
+    pub fn axis_label(&self, axis: ControllerAxis) -> ButtonLabel {
+        ButtonLabel::for_axis(axis, self.button_glyph_hint())
+    }
 }
 
 impl ControllerState {
@@ -316,20 +616,80 @@ impl ControllerState {
     pub fn axis(&self, axis: ControllerAxis) -> Option<f64> {
         self.0.axis(axis)
     }
+    /// Gets the state of the given axis, normalized using its `AxisInfo` from `info`.
+    ///
+    /// Triggers and pedals are normalized to `[0, 1]`; other axes are normalized to `[-1, 1]`,
+    /// keeping zero at zero when the range contains it.  
+    /// Returns `None` if the controller doesn't have this axis, or if its range is empty or reversed.
+    ///
+    /// Dead zones are not applied.
+    pub fn normalized_axis(&self, axis: ControllerAxis, info: &ControllerInfo) -> Option<f64> {
+        let value = self.axis(axis)?;
+        axis.normalize(value, info.axis(axis)?)
+    }
+    /// Gets the state of all axes that can be normalized, as `normalized_axis()` does.
+    pub fn normalized_axes(&self, info: &ControllerInfo) -> HashMap<ControllerAxis, f64> {
+        self.0.axes().into_iter().filter_map(|axis| {
+            self.normalized_axis(axis, info).map(|value| (axis, value))
+        }).collect()
+    }
 }
 
 impl Context {
+    /// Gets information about the controller which ID is given.
+    ///
+    /// This is the same as `devices()[&controller].controller()`, but only looks up that device.
+    pub fn controller_info(&self, controller: DeviceID) -> Result<ControllerInfo> {
+        let mut info = self.os.controller_info(controller)?;
+        self.apply_controller_glyph_rules(&mut info);
+        Ok(info)
+    }
     /// Gets a snapshot of a controller's current state, which ID is given.
     pub fn controller_state(&self, controller: DeviceID) -> Result<ControllerState> {
-        self.0.controller_state(controller)
+        self.os.controller_state(controller)
+    }
+    /// Gets a snapshot of every connected controller's current state, in a single pass.
+    ///
+    /// This is meant for games that read all their controllers once per frame. Compared to
+    /// calling `controller_state()` for each controller, pending input is only pumped once,
+    /// and each controller is only looked up once.
+    ///
+    /// On Linux, this costs one non-blocking `read()` per controller, which also makes the
+    /// states fresher than those of `controller_state()` (which reflect the last time events
+    /// were pumped). `tests/controller_states_bench.rs` measures both approaches with virtual
+    /// controllers, for a given machine.
+    ///
+    /// Controllers that are found to be disconnected in the meantime are left out.
+    pub fn all_controller_states(&self) -> Result<HashMap<DeviceID, ControllerState>> {
+        self.os.all_controller_states()
     }
     /// Gets the current state of a button for the controller which ID is given.
     pub fn controller_button_state(&self, controller: DeviceID, button: ControllerButton) -> Result<ButtonState> {
-        self.0.controller_button_state(controller, button)
+        self.os.controller_button_state(controller, button)
     }
     /// Gets the current state of an axis for the controller which ID is given.
     pub fn controller_axis_state(&self, controller: DeviceID, axis: ControllerAxis) -> Result<f64> {
-        self.0.controller_axis_state(controller, axis)
+        self.os.controller_axis_state(controller, axis)
+    }
+    /// Gets the current state of an axis for the controller which ID is given, normalized using
+    /// the axis' `AxisInfo`.
+    ///
+    /// As with `ControllerState::normalized_axis()`, triggers and pedals are mapped to `[0, 1]`
+    /// (see `AxisInfo::normalize_unsigned()`), and other axes to `[-1, 1]` (see `AxisInfo::normalize_signed()`).  
+    /// Returns `NotSupportedByDevice` if no `AxisInfo` is known for this axis, or if its range is empty or reversed.
+    ///
+    /// Dead zones are not applied.
+    pub fn controller_axis_state_normalized(&self, controller: DeviceID, axis: ControllerAxis) -> Result<f64> {
+        let value = self.controller_axis_state(controller, axis)?;
+        let info = self.controller_info(controller)?;
+        let axis_info = match info.axis(axis) {
+            Some(axis_info) => axis_info,
+            None => return not_supported_by_device(format!("No axis info is known for {:?}", axis)),
+        };
+        match axis.normalize(value, axis_info) {
+            Some(normalized) => Ok(normalized),
+            None => not_supported_by_device(format!("The range of {:?} is empty or reversed", axis)),
+        }
     }
     /// Sets the vibration state for the controller which ID is given, if the device supports it.
     ///
@@ -340,7 +700,118 @@ impl Context {
     /// vibration state for a controller and then exits.  
     /// If you want to be extra sure, reset it yourself when your application exits.
     pub fn controller_set_vibration(&self, controller: DeviceID, vibration: &VibrationState) -> Result<()> {
-        self.0.controller_set_vibration(controller, vibration)
+        self.os.controller_set_vibration(controller, vibration)
+    }
+    /// Plays a short, full-strength rumble pulse on the controller which ID is given, which
+    /// stops by itself after `duration`.
+    ///
+    /// This is meant for "identify controller" buttons in settings screens.  
+    /// This does nothing (and returns `Ok(())`) if the controller doesn't support rumble.
+    /// The pulse replaces the current vibration state, if any; On Linux, `duration` is capped to
+    /// about 32 seconds.
+    pub fn controller_identify(&self, controller: DeviceID, duration: Duration) -> Result<()> {
+        self.os.controller_rumble_pulse(controller, &VibrationState::MAX, duration).recover_not_supported(|| Ok(()))
+    }
+    /// Rumbles the strong motor of the controller which ID is given, then its weak motor,
+    /// for 0.3 seconds each, then stops.
+    ///
+    /// Unlike `controller_identify()`, which is a single pulse, this tells the motors apart, which
+    /// is handy for controller setup screens.  
+    /// This returns right away; the weak motor's step happens as events are polled or waited for
+    /// (waiting never goes past it), so it may be late if no event is retrieved in the meantime.
+    /// Each step stops by itself, though, so the controller never keeps rumbling. Calling this again for the same
+    /// controller restarts the pattern.  
+    /// This does nothing (and returns `Ok(())`) if the controller doesn't support rumble.
+    pub fn controller_rumble_test(&self, controller: DeviceID) -> Result<()> {
+        let now = Instant::now();
+        let strong = VibrationState { strong_magnitude: 0xffff, weak_magnitude: 0, };
+        let weak = VibrationState { strong_magnitude: 0, weak_magnitude: 0xffff, };
+        let mut scheduled = self.scheduled_vibrations.borrow_mut();
+        scheduled.retain(|s| s.controller != controller);
+        match self.os.controller_rumble_pulse(controller, &strong, RUMBLE_TEST_STEP) {
+            Ok(()) => (),
+            Err(e) => return Err(e).recover_not_supported(|| Ok(())),
+        }
+        scheduled.push(ScheduledVibration { controller, at: now + RUMBLE_TEST_STEP, vibration: weak, });
+        Ok(())
+    }
+    /// When the next pulse scheduled by `controller_rumble_test()` is due, if any.
+    pub(crate) fn next_scheduled_vibration(&self) -> Option<Instant> {
+        self.scheduled_vibrations.borrow().iter().map(|s| s.at).min()
+    }
+    /// Plays the pulses scheduled by `controller_rumble_test()` which are due.
+    pub(crate) fn run_scheduled_vibrations(&self) {
+        let due = {
+            let mut scheduled = self.scheduled_vibrations.borrow_mut();
+            if scheduled.is_empty() {
+                return;
+            }
+            let now = Instant::now();
+            let (due, pending): (Vec<_>, Vec<_>) = scheduled.drain(..).partition(|s| s.at <= now);
+            *scheduled = pending;
+            due
+        };
+        for s in due {
+            if let Err(e) = self.os.controller_rumble_pulse(s.controller, &s.vibration, RUMBLE_TEST_STEP) {
+                debug!("Could not set the scheduled vibration of {:?}: {}", s.controller, e);
+            }
+        }
+    }
+    /// Sets how button and axis changes are reported for the controller which ID is given.
+    ///
+    /// With `EventMode::PollOnly`, the controller's state is still kept up-to-date as events are
+    /// polled, which is useful for e.g fixed-timestep simulations that read stick positions
+    /// once per step and don't want to churn through a stream of `ControllerAxisMotion` events.
+    /// Connection and disconnection events are reported regardless.
+    pub fn set_controller_event_mode(&self, controller: DeviceID, mode: EventMode) -> Result<()> {
+        self.os.set_controller_event_mode(controller, mode)
+    }
+    /// Gets the player index of the controller which ID is given, if it has one.
+    ///
+    /// Player indices start at zero (i.e "player 1" is index 0).
+    /// When a controller is connected, it is automatically assigned the lowest index
+    /// that no other controller holds. Its index is released when it is disconnected.
+    /// Given the same sequence of connections and disconnections, the same indices are assigned.
+    pub fn controller_player_index(&self, controller: DeviceID) -> Result<Option<u32>> {
+        self.os.controller_player_index(controller)
+    }
+    /// Sets (or removes, with `None`) the player index of the controller which ID is given.
+    ///
+    /// If another controller already holds that index, it is given this controller's previous
+    /// index instead (so they are swapped).
+    /// An `Event::ControllerPlayerIndexChanged` is queued for each controller whose index changed.
+    ///
+    /// Where possible, the index is also reflected on the hardware: on Linux, the
+    /// player LEDs exposed by the kernel (e.g `/sys/class/leds/*:player-1`) are updated
+    /// if permissions allow it.
+    pub fn set_controller_player_index(&self, controller: DeviceID, player_index: Option<u32>) -> Result<()> {
+        self.os.set_controller_player_index(controller, player_index)
+    }
+    /// Adds a rule for `ControllerInfo::button_glyph_hint()`, which takes precedence over
+    /// the built-in ones and the ones that were added before it.
+    ///
+    /// Rules only apply to this `Context`, and to the `ControllerInfo`s it gives out afterwards
+    /// (through `controller_info()`, `devices()` and device events).
+    pub fn add_controller_glyph_rule(&self, rule: ControllerGlyphRule) {
+        self.controller_glyph_rules.borrow_mut().push(rule)
+    }
+    /// Sets the glyph hint given by the most recent of our rules that matches `info`, if any.
+    pub(crate) fn apply_controller_glyph_rules(&self, info: &mut ControllerInfo) {
+        let (vendor_id, name) = (info.0.vendor_id(), info.0.name());
+        let glyphs = self.controller_glyph_rules.borrow().iter().rev()
+            .find(|rule| glyph_rule_matches(rule.vendor_id, rule.name_contains.as_ref().map(String::as_str), vendor_id, name))
+            .map(|rule| rule.glyphs);
+        info.1 = glyphs;
+    }
+    /// Applies our glyph rules to the `ControllerInfo` that `ev` carries, if any.
+    pub(crate) fn apply_controller_glyph_rules_to_event(&self, ev: &mut Event) {
+        match *ev {
+            Event::DeviceConnected { ref mut info, .. }
+            | Event::DeviceInfoChanged { ref mut info, .. } => if let Some(controller) = info.controller_mut() {
+                self.apply_controller_glyph_rules(controller);
+            },
+            _ => (),
+        }
     }
 }
 
@@ -47,6 +47,37 @@ impl ::std::error::Error for Error {
     }
 }
 
+/// Combinators for `device::Result`, for the common "drop it if it's gone, fall back if it can't
+/// do that" patterns of input handling code.
+///
+/// This is a trait because `device::Result` is a type alias, which can't have inherent methods.
+pub trait ResultExt<T> {
+    /// Replaces a `DeviceDisconnected` error by the value returned by `f`.
+    ///
+    /// Other errors are returned as-is, so this is mostly useful for removing a device from
+    /// some active set without having to match on the error.
+    fn map_disconnected<F: FnOnce() -> T>(self, f: F) -> Result<T>;
+    /// On a `NotSupportedByDevice` error, tries `f` instead.
+    ///
+    /// Other errors are returned as-is.
+    fn recover_not_supported<F: FnOnce() -> Result<T>>(self, f: F) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn map_disconnected<F: FnOnce() -> T>(self, f: F) -> Result<T> {
+        match self {
+            Err(Error::DeviceDisconnected(_)) => Ok(f()),
+            other => other,
+        }
+    }
+    fn recover_not_supported<F: FnOnce() -> Result<T>>(self, f: F) -> Result<T> {
+        match self {
+            Err(Error::NotSupportedByDevice { .. }) => f(),
+            other => other,
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub(crate) fn disconnected_at<T>(instant: EventInstant) -> Result<T> {
     Err(Error::DeviceDisconnected(Some(instant)))
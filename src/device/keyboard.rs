@@ -16,34 +16,87 @@ pub struct KeyboardInfo;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyboardState(pub(crate) OsKeyboardState);
 
+/// The state of the modifiers that select which character a key produces, see `Context::keysym_to_char()`.
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct ModifiersState {
+    /// Is either Shift key held?
+    pub shift: bool,
+    /// Is Caps Lock active?
+    pub caps_lock: bool,
+    /// Is Num Lock active? This only affects the keypad.
+    pub num_lock: bool,
+    /// Is AltGr (a.k.a "ISO Level 3 Shift") held?
+    pub alt_gr: bool,
+}
+
 impl Context {
     /// Gets the ID for the main keyboard, if any.
     pub fn main_keyboard(&self) -> Result<DeviceID> {
-        self.0.main_keyboard()
+        self.os.main_keyboard()
     }
     /// Captures the current state of the keyboard which ID is given.
     pub fn keyboard_state(&self, keyboard: DeviceID) -> Result<KeyboardState> {
-        self.0.keyboard_state(keyboard)
+        self.os.keyboard_state(keyboard)
     }
     /// Captures the current state of a keyboard's key (by scan code) which ID is given.
     pub fn keyboard_keycode_state(&self, keyboard: DeviceID, keycode: Keycode) -> Result<KeyState> {
-        self.0.keyboard_keycode_state(keyboard, keycode)
+        self.os.keyboard_keycode_state(keyboard, keycode)
     }
     /// Captures the current state of a keyboard's key (by virtual code) which ID is given.
     pub fn keyboard_keysym_state(&self, keyboard: DeviceID, keysym: Keysym) -> Result<KeyState> {
-        self.0.keyboard_keysym_state(keyboard, keysym)
+        self.os.keyboard_keysym_state(keyboard, keysym)
     }
     /// Gets the friendly name for the given key.
     pub fn keysym_name(&self, keysym: Keysym) -> Result<String> {
-        self.0.keysym_name(keysym)
+        self.os.keysym_name(keysym)
+    }
+    /// Gets the character that the key for `keysym` produces in the main keyboard's current
+    /// layout group, when `modifiers` are applied; or `None` if it isn't printable (e.g `F1`, `Left`).
+    ///
+    /// This is a lightweight alternative to text input (see `Event::KeyboardTextChar`) for simple
+    /// cases such as a debug console. It knows nothing about dead keys, the Compose key or
+    /// input methods.
+    pub fn keysym_to_char(&self, keysym: Keysym, modifiers: ModifiersState) -> Option<char> {
+        self.os.keysym_to_char(keysym, modifiers)
     }
     /// Translates a scan code to a key code for the keyboard which ID is given.
+    ///
+    /// This gives the primary (unshifted) key code in the keyboard's current layout group,
+    /// see `keyboard_current_group()`.
     pub fn keysym_from_keycode(&self, keyboard: DeviceID, keycode: Keycode) -> Result<Keysym> {
-        self.0.keysym_from_keycode(keyboard, keycode)
+        self.os.keysym_from_keycode(keyboard, keycode)
+    }
+    /// Gets the index of the keyboard's active layout group (i.e which layout is in use, when
+    /// the user has configured several, e.g Russian and English).
+    ///
+    /// Changes are reported by `Event::KeyboardLayoutGroupChanged`.  
+    /// On X11, this is the keyboard's own XKB group, but changes are only reported for the core
+    /// keyboard.
+    pub fn keyboard_current_group(&self, keyboard: DeviceID) -> Result<u32> {
+        self.os.keyboard_current_group(keyboard)
+    }
+    /// Switches the keyboard's active layout group, see `keyboard_current_group()`.
+    ///
+    /// This affects the whole desktop, not just this application.
+    pub fn set_keyboard_current_group(&self, keyboard: DeviceID, group: u32) -> Result<()> {
+        self.os.set_keyboard_current_group(keyboard, group)
+    }
+    /// Gets the names of the keyboard's layout groups (e.g `"English (US)"`, `"Russian"`), indexed by group.
+    pub fn keyboard_group_names(&self, keyboard: DeviceID) -> Result<Vec<String>> {
+        self.os.keyboard_group_names(keyboard)
     }
     /// Retrieves the scan code that would translate to the given key code for the keyboard which ID is given.
     pub fn keycode_from_keysym(&self, keyboard: DeviceID, keysym: Keysym) -> Result<Keycode> {
-        self.0.keycode_from_keysym(keyboard, keysym)
+        self.os.keycode_from_keysym(keyboard, keysym)
+    }
+    /// Lists every scan code of the main keyboard's current layout, along with its
+    /// primary (unshifted) key code and that key code's name.
+    ///
+    /// Scan codes that aren't mapped to anything are skipped.  
+    /// This is meant for diagnostics, e.g attaching the output to bug reports
+    /// about keyboard layouts.
+    pub fn dump_keymap(&self) -> Vec<(Keycode, Keysym, String)> {
+        self.os.dump_keymap()
     }
 }
 
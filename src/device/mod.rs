@@ -162,6 +162,9 @@
 //!   By prefetching everything ASAP and keeping it warm for later, this crate gives you
 //!   the opportunity to keep using a device's information even after it is unplugged.
 //!
+//! On memory-constrained targets, `Hint::LazyDeviceInfo` makes the Linux backend
+//! query controller capabilities again on each request instead of caching them per device.
+//!
 //!
 //! ## Why aren't `Info` structs plain-old-data structs with public fields?
 //!
@@ -227,6 +230,9 @@
 //! I would advise writing a utility function that performs the calculation that _you_ think
 //! make sense, as long as it doesn't `assert`, so your application doesn't panic just because
 //! of unexpected axis ranges.
+//!
+//! That said, `ControllerState::normalized_axis()` and `normalized_axes()` are provided for the
+//! common cases; check their documentation to see if their definition suits you.
 
 use uuid::Uuid as Guid;
 use std::collections::HashMap;
@@ -248,6 +254,8 @@ pub mod tablet;
 pub use self::tablet::*;
 pub mod controller;
 pub use self::controller::*;
+#[cfg(feature = "usb-ids")]
+mod usb_ids_db;
 
 /// A button or key state, i.e "up" or "down".
 ///
@@ -325,6 +333,34 @@ impl AxisInfo {
     ///
     /// (See this StackOverflow answer)[https://stackoverflow.com/a/17041513/7972165].
     pub fn driver_noise_filter(&self) -> Option<f64> { self.0.driver_noise_filter() }
+    /// Maps `value` from this axis' range to `[-1, 1]`.
+    ///
+    /// If zero is within the range, it is kept at zero (the range's center is often not exactly
+    /// zero, e.g `[-32768, 32767]`).  
+    /// Returns `None` if the range is empty or reversed. Dead zones are not applied.
+    pub fn normalize_signed(&self, value: f64) -> Option<f64> {
+        let Range { start: min, end: max } = self.range();
+        if !(min < max) {
+            return None;
+        }
+        let normalized = if min < 0. && max > 0. {
+            if value >= 0. { value / max } else { value / -min }
+        } else {
+            (value - min) * 2. / (max - min) - 1.
+        };
+        Some(normalized.max(-1.).min(1.))
+    }
+    /// Maps `value` from this axis' range to `[0, 1]`, which suits axes that go from "released"
+    /// to "fully pressed", such as triggers and pedals.
+    ///
+    /// Returns `None` if the range is empty or reversed. Dead zones are not applied.
+    pub fn normalize_unsigned(&self, value: f64) -> Option<f64> {
+        let Range { start: min, end: max } = self.range();
+        if !(min < max) {
+            return None;
+        }
+        Some(((value - min) / (max - min)).max(0.).min(1.))
+    }
 }
 
 /// Information about a HID (often fetched once when the device is detected).
@@ -383,6 +419,12 @@ impl DeviceInfo {
     /// This is a string because there's no uniform representation for driver versions across
     /// platforms. If you really care, this still allows you to attempt to parse it yourself.
     pub fn driver_version(&self) -> Option<&str> { self.0.driver_version() }
+    /// Where the device is physically connected, as advertised by the backend,
+    /// e.g `usb-0000:00:14.0-2/input0` on Linux.
+    ///
+    /// Unlike the serial, this tells apart two identical devices plugged into different ports,
+    /// but changes if a device is plugged into another port.
+    pub fn physical_location(&self) -> Option<&str> { self.0.physical_location() }
     /// Does this device denote an actual, physical one?
     ///
     /// This is a pretty tough question to answer and the meaning, albeit vague, is not
@@ -396,6 +438,7 @@ impl DeviceInfo {
     pub fn is_physical(&self) -> Option<bool> { self.0.is_physical() }
     /// If this device is a controller, then controller-specific info is stored here.
     pub fn controller(&self) -> Option<&ControllerInfo> { self.0.controller() }
+    pub(crate) fn controller_mut(&mut self) -> Option<&mut ControllerInfo> { self.0.controller_mut() }
     /// If this device is a mouse, then mouse-specific info is stored here.
     pub fn mouse(&self) -> Option<&MouseInfo> { self.0.mouse() }
     /// If this device is a keyboard, then keyboard-specific info is stored here.
@@ -404,6 +447,26 @@ impl DeviceInfo {
     pub fn touch(&self) -> Option<&TouchInfo> { self.0.touch() }
     /// If this device is a tablet, then tablet-specific info is stored here.
     pub fn tablet(&self) -> Option<&TabletInfo> { self.0.tablet() }
+    /// A one-line description of this device, for logging, e.g `"Xbox Wireless Controller" 045e:02e0 [controller]`.
+    pub fn summary(&self) -> String {
+        let mut s = match self.name() {
+            Some(name) => format!("{:?}", name),
+            None => "<unnamed>".to_owned(),
+        };
+        if let Some(UsbIDs { vendor_id, product_id }) = self.usb_ids() {
+            s += &format!(" {:04x}:{:04x}", vendor_id, product_id);
+        }
+        let kinds = [
+            ("controller", self.controller().is_some()),
+            ("mouse", self.mouse().is_some()),
+            ("keyboard", self.keyboard().is_some()),
+            ("touch", self.touch().is_some()),
+            ("tablet", self.tablet().is_some()),
+        ];
+        let kinds: Vec<_> = kinds.iter().filter(|&&(_, is)| is).map(|&(kind, _)| kind).collect();
+        s += &format!(" [{}]", kinds.join(", "));
+        s
+    }
 }
 
 #[allow(missing_docs)]
@@ -415,6 +478,20 @@ pub struct UsbIDs {
     pub product_id: u16,
 }
 
+#[cfg(feature = "usb-ids")]
+impl UsbIDs {
+    /// Looks up the vendor's name in the embedded subset of the USB ID database (`usb.ids`).
+    ///
+    /// This is useful as a fallback when `DeviceInfo::vendor_name()` returns `None`.
+    pub fn vendor_name_db(&self) -> Option<&'static str> {
+        usb_ids_db::vendor_name(self.vendor_id)
+    }
+    /// Looks up the product's name in the embedded subset of the USB ID database (`usb.ids`).
+    pub fn product_name_db(&self) -> Option<&'static str> {
+        usb_ids_db::product_name(self.vendor_id, self.product_id)
+    }
+}
+
 /// Mostly taken from the `BUS_*` constants in Linux's `input.h`.
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -453,7 +530,11 @@ impl Context {
     /// If we _can_ get the `DeviceInfo` (99.9% of cases), then we want to do this only once, because it may
     /// be expensive, and then get rid of it by handing it over to you.
     pub fn devices(&self) -> Result<HashMap<DeviceID, DeviceInfo>> {
-        self.0.devices()
+        let mut devices = self.os.devices()?;
+        for controller in devices.values_mut().filter_map(DeviceInfo::controller_mut) {
+            self.apply_controller_glyph_rules(controller);
+        }
+        Ok(devices)
     }
     /// Checks if the given device is still connected.  
     ///
@@ -463,6 +544,16 @@ impl Context {
     /// This is normally not useful and prone to race conditions; If the device is connected
     /// right now, that doesn't mean it will still be connected within the next few milliseconds.
     pub fn ping_device(&self, id: DeviceID) -> Result<()> {
-        self.0.ping_device(id)
+        self.os.ping_device(id)
+    }
+    /// Cheaply checks if the given `DeviceID` still refers to a device that this crate knows
+    /// about, i.e that it isn't stale (see the module-level docs).
+    ///
+    /// Unlike `ping_device()`, this does not talk to the device; It only looks the ID up
+    /// in the tables that are kept up-to-date as events are pumped. So, it returns `false` as
+    /// soon as the device's `DeviceDisconnected` event was reported, and may still return
+    /// `true` for a device that was unplugged but whose event wasn't pumped yet.
+    pub fn is_device_id_current(&self, id: DeviceID) -> bool {
+        self.os.is_device_id_current(id)
     }
 }
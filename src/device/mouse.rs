@@ -91,11 +91,11 @@ impl WindowMouseState {
 impl Context {
     /// Gets the ID for the main mouse, if any.
     pub fn main_mouse(&self) -> Result<DeviceID> {
-        self.0.main_mouse()
+        self.os.main_mouse()
     }
     /// Captures the current state of the mouse which ID is given.
     pub fn mouse_state(&self, mouse: DeviceID) -> Result<MouseState> {
-        self.0.mouse_state(mouse)
+        self.os.mouse_state(mouse)
     }
 }
 
@@ -47,6 +47,29 @@ pub enum TabletStylusKind {
     FiveButtonPuck,
 }
 
+/// A tool that a tablet can sense, as reported by `Event::TabletToolProximity`.
+///
+/// Some styli have two ends (e.g a pen tip and an eraser), in which case each end is a distinct tool.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum TabletTool {
+    /// A regular pen tip.
+    Pen,
+    /// The eraser end of a stylus.
+    Eraser,
+    /// A brush-like stylus (e.g Wacom's Stroke Pen).
+    Brush,
+    /// A pencil-like stylus (e.g Wacom's Inking Pen).
+    Pencil,
+    /// An airbrush.
+    Airbrush,
+    /// A tablet mouse or puck.
+    Mouse,
+    /// A lens cursor.
+    Lens,
+    /// The tablet didn't tell.
+    Unknown,
+}
+
 /// A tablet pad button is a single platform-specific integer for now.
 pub type TabletPadButton = i32;
 
@@ -146,7 +169,7 @@ impl TabletState {
 impl Context {
     /// Fetches the current state of a tablet which ID is given.
     pub fn tablet_state(&self, tablet: DeviceID) -> Result<TabletState> {
-        self.0.tablet_state(tablet)
+        self.os.tablet_state(tablet)
     }
 }
 
@@ -0,0 +1,63 @@
+//! A compact subset of the USB ID database (http://www.linux-usb.org/usb.ids),
+//! restricted to vendors and products that are relevant to this crate (i.e input devices).
+//!
+//! Both tables MUST be kept sorted by ID, because lookups are binary searches.
+
+static VENDORS: &'static [(u16, &'static str)] = &[
+    (0x03f0, "HP, Inc"),
+    (0x0408, "Quanta Computer, Inc."),
+    (0x044f, "ThrustMaster, Inc."),
+    (0x045e, "Microsoft Corp."),
+    (0x046a, "Cherry GmbH"),
+    (0x046d, "Logitech, Inc."),
+    (0x04d9, "Holtek Semiconductor, Inc."),
+    (0x04f2, "Chicony Electronics Co., Ltd"),
+    (0x054c, "Sony Corp."),
+    (0x056a, "Wacom Co., Ltd"),
+    (0x057e, "Nintendo Co., Ltd"),
+    (0x05ac, "Apple, Inc."),
+    (0x06a3, "Saitek PLC"),
+    (0x0738, "Mad Catz, Inc."),
+    (0x093a, "Pixart Imaging, Inc."),
+    (0x0e6f, "Logic3"),
+    (0x0f0d, "Hori Co., Ltd"),
+    (0x1038, "SteelSeries ApS"),
+    (0x1532, "Razer USA, Ltd"),
+    (0x17ef, "Lenovo"),
+    (0x1b1c, "Corsair"),
+    (0x28de, "Valve Software"),
+    (0x413c, "Dell Computer Corp."),
+];
+
+static PRODUCTS: &'static [(u16, u16, &'static str)] = &[
+    (0x045e, 0x028e, "Xbox360 Controller"),
+    (0x045e, 0x028f, "Xbox360 Wireless Controller"),
+    (0x045e, 0x02d1, "Xbox One Controller"),
+    (0x045e, 0x02dd, "Xbox One Controller (Firmware 2015)"),
+    (0x045e, 0x02ea, "Xbox One S Controller"),
+    (0x045e, 0x0719, "Xbox 360 Wireless Adapter"),
+    (0x046d, 0xc216, "F310 Gamepad [DirectInput Mode]"),
+    (0x046d, 0xc21d, "F310 Gamepad [XInput Mode]"),
+    (0x046d, 0xc21e, "F510 Gamepad [XInput Mode]"),
+    (0x046d, 0xc21f, "F710 Wireless Gamepad [XInput Mode]"),
+    (0x046d, 0xc24f, "G29 Driving Force Racing Wheel [PS3]"),
+    (0x046d, 0xc262, "G920 Driving Force Racing Wheel"),
+    (0x046d, 0xc52b, "Unifying Receiver"),
+    (0x054c, 0x0268, "Batoh Device / PlayStation 3 Controller"),
+    (0x054c, 0x05c4, "DualShock 4 [CUH-ZCT1x]"),
+    (0x054c, 0x09cc, "DualShock 4 [CUH-ZCT2x]"),
+    (0x054c, 0x0ba0, "Dualshock4 Wireless Adaptor"),
+    (0x054c, 0x0ce6, "DualSense wireless controller (PS5)"),
+    (0x057e, 0x0306, "Wii Remote Controller RVL-003"),
+    (0x057e, 0x2009, "Switch Pro Controller"),
+    (0x28de, 0x1102, "Wired Steam Controller"),
+    (0x28de, 0x1142, "Wireless Steam Controller"),
+];
+
+pub fn vendor_name(vendor_id: u16) -> Option<&'static str> {
+    VENDORS.binary_search_by_key(&vendor_id, |&(id, _)| id).ok().map(|i| VENDORS[i].1)
+}
+
+pub fn product_name(vendor_id: u16, product_id: u16) -> Option<&'static str> {
+    PRODUCTS.binary_search_by_key(&(vendor_id, product_id), |&(v, p, _)| (v, p)).ok().map(|i| PRODUCTS[i].2)
+}
@@ -1,22 +1,51 @@
 //! `Error` and `Result` types for this crate.
+//!
+//! # Migrating from `WindowOpResult`
+//!
+//! Every window operation now returns `error::Result<T>`, which works with `?`.
+//!
+//! - `WindowOpResult::Success` becomes `Ok(_)`;
+//! - `WindowOpResult::Failed` becomes `ErrorKind::Failed`;
+//! - `WindowOpResult::Unsupported` becomes `ErrorKind::Unsupported { .. }`;
+//! - `WindowOpResult::Unimplemented` becomes `ErrorKind::Unimplemented`.
+//!
+//! Code that only has an `ErrorKind` at hand can turn it into an `Error` via `From`.  
+//! The old `Capabilities` struct is superseded by `Window::capability()`.
 use std::fmt::{self, Display, Formatter};
 
 pub(crate) type CowStr = ::std::borrow::Cow<'static, str>;
 
 /// Different kinds of errors reported by most faillible operations.
+///
+/// More kinds may be added in the future, so matching on this requires a wildcard arm.
+#[non_exhaustive]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum ErrorKind {
     /// Operation not supported for target platform / current build settings.
     ///
     /// For instance, trying to open more than one window on targets that don't support it.
-    Unsupported,
+    Unsupported {
+        /// `true` if the operation could be supported, provided that
+        /// it is explicitly enabled first (e.g via a `Hint` or a Cargo feature).
+        requires_optin: bool,
+    },
+    /// The operation makes sense for the target platform, but this crate doesn't implement it (yet).
+    ///
+    /// Unlike `Unsupported`, this might go away in a future version of this crate.
+    Unimplemented,
     /// Some arguments were invalid; You could retry with different ones.
     InvalidArgument,
+    /// The operation requires a resource that is currently held by someone else (e.g another
+    /// application is grabbing the mouse); Retrying later may succeed.
+    Busy,
     /// Operation is supported and arguments were valid, but the operation failed for other
     /// reasons (e.g user-specific environment).
     ///
     /// For instance, on X11-based targets, the user's X11 server may lack some required extensions.
     Failed,
+    /// The display server did not respond in time, as configured by
+    /// `Hint::XlibRoundtripTimeout`. The connection is most likely dead or frozen.
+    DisplayTimeout,
 }
 
 /// An `ErrorKind` packed with an optional `reason` string.
@@ -35,8 +64,12 @@ impl ErrorKind {
     pub(crate) fn describe_quick(&self) -> &'static str {
         match *self {
             ErrorKind::InvalidArgument => "Invalid argument(s)",
-            ErrorKind::Unsupported => "Unsupported operation for target platform",
+            ErrorKind::Unsupported { requires_optin: false } => "Unsupported operation for target platform",
+            ErrorKind::Unsupported { requires_optin: true } => "Unsupported operation unless explicitly opted in",
+            ErrorKind::Unimplemented => "Operation not implemented for target platform",
+            ErrorKind::Busy => "Resource is busy",
             ErrorKind::Failed => "Operation has failed",
+            ErrorKind::DisplayTimeout => "The display server did not respond in time",
         }
     }
 }
@@ -53,6 +86,11 @@ impl ::std::error::Error for ErrorKind {
     }
 }
 
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self { kind, reason: None, }
+    }
+}
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -79,16 +117,28 @@ mod utils {
 
     impl Error {
         pub(crate) fn unsupported<S: Into<CowStr>>(s: S) -> Self {
-            Self { kind: ErrorKind::Unsupported, reason: Some(s.into()), }
+            Self { kind: ErrorKind::Unsupported { requires_optin: false }, reason: Some(s.into()), }
+        }
+        pub(crate) fn unsupported_requires_optin<S: Into<CowStr>>(s: S) -> Self {
+            Self { kind: ErrorKind::Unsupported { requires_optin: true }, reason: Some(s.into()), }
+        }
+        pub(crate) fn unimplemented<S: Into<CowStr>>(s: S) -> Self {
+            Self { kind: ErrorKind::Unimplemented, reason: Some(s.into()), }
         }
         pub(crate) fn invalid_arg<S: Into<CowStr>>(s: S) -> Self {
             Self { kind: ErrorKind::InvalidArgument, reason: Some(s.into()), }
         }
+        pub(crate) fn busy<S: Into<CowStr>>(s: S) -> Self {
+            Self { kind: ErrorKind::Busy, reason: Some(s.into()), }
+        }
         pub(crate) fn failed<S: Into<CowStr>>(s: S) -> Self {
             Self { kind: ErrorKind::Failed, reason: Some(s.into()), }
         }
+        pub(crate) fn display_timeout<S: Into<CowStr>>(s: S) -> Self {
+            Self { kind: ErrorKind::DisplayTimeout, reason: Some(s.into()), }
+        }
         pub(crate) fn unsupported_unexplained() -> Self {
-            Self { kind: ErrorKind::Unsupported, reason: None, }
+            Self { kind: ErrorKind::Unsupported { requires_optin: false }, reason: None, }
         }
         pub(crate) fn invalid_arg_unexplained() -> Self {
             Self { kind: ErrorKind::InvalidArgument, reason: None, }
@@ -101,12 +151,24 @@ mod utils {
     pub(crate) fn unsupported<T, S: Into<CowStr>>(s: S) -> self::Result<T> {
         Err(Error::unsupported(s))
     }
+    pub(crate) fn unsupported_requires_optin<T, S: Into<CowStr>>(s: S) -> self::Result<T> {
+        Err(Error::unsupported_requires_optin(s))
+    }
+    pub(crate) fn unimplemented<T, S: Into<CowStr>>(s: S) -> self::Result<T> {
+        Err(Error::unimplemented(s))
+    }
     pub(crate) fn invalid_arg<T, S: Into<CowStr>>(s: S) -> self::Result<T> {
         Err(Error::invalid_arg(s))
     }
+    pub(crate) fn busy<T, S: Into<CowStr>>(s: S) -> self::Result<T> {
+        Err(Error::busy(s))
+    }
     pub(crate) fn failed<T, S: Into<CowStr>>(s: S) -> self::Result<T> {
         Err(Error::failed(s))
     }
+    pub(crate) fn display_timeout<T, S: Into<CowStr>>(s: S) -> self::Result<T> {
+        Err(Error::display_timeout(s))
+    }
     pub(crate) fn unsupported_unexplained<T>() -> self::Result<T> {
         Err(Error::unsupported_unexplained())
     }
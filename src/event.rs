@@ -65,13 +65,16 @@
 //! See the `device` module's FAQ.
 
 use std::cmp::Ordering;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::fmt::{self, Display, Formatter};
 use std::ops::{Add, Sub, AddAssign, SubAssign};
 use timeout::Timeout;
 use super::{Vec2, Extent2, Rect};
 use context::Context;
 use error;
 use window::WindowHandle;
+use pointer_barrier::PointerBarrierID;
 use os::{OsEventInstant, OsUnprocessedEvent};
 use device::*;
 
@@ -101,6 +104,13 @@ impl EventInstant {
             _ => self.0.duration_since(earlier.0),
         }).unwrap_or(None)
     }
+    /// Returns the amount of time elapsed since this instant, e.g to measure input latency.
+    ///
+    /// Returns `None` if the current time can't be read from the same clock, which is the case
+    /// for X11 event timestamps (they come from the X server's clock).
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.0.now_from_same_clock().and_then(|now| EventInstant(now).duration_since(*self))
+    }
     /// Returns the absolute difference from another `EventInstant` to this one, as a `Duration`,
     /// if they originate from the same time source.
     pub fn abs_sub(&self, other: Self) -> Option<Duration> {
@@ -117,6 +127,12 @@ impl Sub<EventInstant> for EventInstant {
         self.duration_since(rhs)
     }
 }
+/// The format is platform-specific, and meant for logging only.
+impl Display for EventInstant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 impl Add<Duration> for EventInstant {
     type Output = Self;
     fn add(self, rhs: Duration) -> Self {
@@ -144,7 +160,7 @@ impl Context {
     /// Are raw device events supported ? (i.e `MouseMotionRaw`,
     /// `KeyboardKeyPressedRaw`, etc).
     pub fn supports_raw_device_events(&self) -> error::Result<bool> {
-        self.0.supports_raw_device_events()
+        self.os.supports_raw_device_events()
     }
     /// Polls for any event in the queue.
     pub fn poll_event(&self) -> Option<Event> {
@@ -154,6 +170,79 @@ impl Context {
     pub fn wait_event(&self, timeout: Timeout) -> Option<Event> {
         self.events_wait_iter(timeout).next()
     }
+    /// Waits for any event in the queue, like `wait_event()`, but also returns when it was
+    /// handed over to you.
+    ///
+    /// This is meant for measuring the latency this crate (and your event loop) adds on top of
+    /// the platform's; See `DeliveredEvent`. `poll_event()` and `wait_event()` don't pay for this.
+    pub fn next_event_with_metadata(&self, timeout: Timeout) -> Option<DeliveredEvent> {
+        let event = self.events_wait_iter(timeout).next()?;
+        let delivered_at = Instant::now();
+        let delivered_instant = event.instant().and_then(|instant| instant.0.now_from_same_clock()).map(EventInstant);
+        if let (Some(instant), Some(delivered)) = (event.instant(), delivered_instant) {
+            // Other clocks (e.g evdev's CLOCK_REALTIME) may legitimately jump backwards.
+            if instant.0.is_monotonic() {
+                debug_assert!(
+                    delivered + MAX_CLOCK_SKEW >= instant,
+                    "{} was delivered at {}, before it happened", event, delivered
+                );
+            }
+        }
+        Some(DeliveredEvent { event, delivered_at, delivered_instant })
+    }
+    /// Polls for controller events only, skipping any other event source.
+    ///
+    /// This is a cheaper alternative to `poll_event()` for threads that only handle controller
+    /// input (connection, disconnection, buttons, axes...). Other events (windows, keyboard, mouse,
+    /// etc) never appear through this path; they stay in the queue until `poll_event()` or
+    /// `wait_event()` is called.
+    ///
+    /// On Linux, this only pumps the evdev and udev queues, and doesn't touch the X11 queue.
+    pub fn poll_controller_event(&self) -> Option<Event> {
+        self.run_scheduled_vibrations();
+        let mut ev = self.os.poll_controller_event();
+        if let Some(ref mut ev) = ev {
+            self.apply_controller_glyph_rules_to_event(ev);
+            self.count_event(ev);
+            self.log_event(ev);
+        }
+        ev
+    }
+    /// Opts into reporting SIGTERM and SIGINT (e.g Ctrl+C in a terminal) as `Event::Quit`,
+    /// instead of letting them terminate the process right away.
+    ///
+    /// Signals that are caught while events are being waited for are reported as soon as possible.
+    /// Several signals caught in a row are reported as a single `Event::Quit`.  
+    /// On Linux, see also `termination_signal_fd()`.
+    ///
+    /// Each `Context` that calls this gets its own `Event::Quit`. The previous signal handlers are
+    /// restored when the last of these contexts is dropped.
+    pub fn catch_termination_signals(&self) -> error::Result<()> {
+        self.os.catch_termination_signals()
+    }
+    /// Enables or disables logging every event (in their one-line `Display` form, at the `trace` level)
+    /// right before they are returned to you.
+    ///
+    /// This is disabled by default.  
+    /// Since enabling it is an explicit request, these messages bypass `Hint::LogLevel` and
+    /// `set_log_verbosity()`, which would otherwise drop `trace`-level messages by default.
+    /// The global logger's own level still applies.
+    pub fn log_events(&self, enable: bool) {
+        self.log_events.set(enable);
+    }
+    pub(crate) fn log_event(&self, ev: &Event) {
+        if self.log_events.get() {
+            // Not `trace!()`, which is filtered by `log_verbosity`.
+            log!(::log::Level::Trace, "{}", ev);
+        }
+    }
+    /// The earliest moment at which events need to be pumped, regardless of incoming events.
+    fn next_timer_deadline(&self) -> Option<Instant> {
+        match (self.next_scheduled_vibration(), self.os.next_timer_deadline()) {
+            (Some(a), Some(b)) => Some(::std::cmp::min(a, b)),
+            (a, b) => a.or(b),
+        }
+    }
     /// Returns an iterator that polls for events in the queue.
     pub fn events_poll_iter(&self) -> Iter {
         self.events_wait_iter(Timeout::none())
@@ -164,6 +253,46 @@ impl Context {
     }
 }
 
+/// How much earlier than an event's `instant` its delivery may appear to be.
+///
+/// Event timestamps are truncated to the resolution of their source (milliseconds or
+/// microseconds), while the delivery instant is read with nanosecond resolution.
+const MAX_CLOCK_SKEW: Duration = Duration::from_millis(1);
+
+/// An `Event`, along with the moment it was removed from the queue.
+///
+/// See `Context::next_event_with_metadata()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveredEvent {
+    /// The event itself.
+    pub event: Event,
+    /// The moment the event was removed from the queue, from the monotonic clock.
+    ///
+    /// Unlike `EventInstant`s, this is comparable across all events.
+    pub delivered_at: Instant,
+    /// The moment the event was removed from the queue, read from the same clock as the event's
+    /// `instant`.
+    ///
+    /// This is `None` if the event doesn't have an `instant`, or if its clock can't be read
+    /// locally (see `EventInstant::elapsed()`).
+    pub delivered_instant: Option<EventInstant>,
+}
+
+impl DeliveredEvent {
+    /// Returns the time elapsed between the event's `instant` and its delivery, if both are known.
+    ///
+    /// This is the latency added by the platform's queues, this crate, and the time your
+    /// application took to get to the event.  
+    /// This is also `None` if the event's clock jumped backwards in the meantime, which some
+    /// clocks (e.g the system time, which evdev uses on Linux) may do.
+    pub fn latency(&self) -> Option<Duration> {
+        match (self.event.instant(), self.delivered_instant) {
+            (Some(instant), Some(delivered)) => delivered.duration_since(instant),
+            _ => None,
+        }
+    }
+}
+
 /// An iterator that yields events, removing them from the system queue.
 #[derive(Debug)]
 pub struct Iter<'c> {
@@ -174,7 +303,32 @@ pub struct Iter<'c> {
 impl<'c> Iterator for Iter<'c> {
     type Item = Event;
     fn next(&mut self) -> Option<Event> {
-        self.context.0.next_event(self.timeout)
+        // Scheduled vibrations and window flashes are only handled as events are pumped, so
+        // don't wait past their deadline, then resume waiting for what's left of the timeout.
+        let end = self.timeout.duration().and_then(|d| Instant::now().checked_add(d));
+        loop {
+            self.context.run_scheduled_vibrations();
+            let timer_deadline = match (self.context.next_timer_deadline(), end) {
+                (Some(t), Some(end)) if t >= end => None,
+                (t, _) => t,
+            };
+            let timeout = match timer_deadline.or(end) {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    Timeout::from(if deadline > now { deadline - now } else { Duration::default() })
+                },
+                None => self.timeout,
+            };
+            let mut ev = self.context.os.next_event(timeout);
+            if let Some(ref mut ev) = ev {
+                self.context.apply_controller_glyph_rules_to_event(ev);
+                self.context.count_event(ev);
+                self.context.log_event(ev);
+            }
+            if ev.is_some() || timer_deadline.is_none() {
+                return ev;
+            }
+        }
     }
 }
 
@@ -216,7 +370,6 @@ impl UnprocessedEvent {
 // - Drag'n drop
 // - OpenGL context loss
 // - Screen plugged/unplugged
-// - Audio device plugged/unplugged
 // - Trackball features for the mouse.
 // - Missing 'instant' field for most events
 
@@ -269,6 +422,21 @@ pub enum Event {
     /// Perform clean-up operations here. (Window: WM_ENDSESSION)
     SessionEnding,
 
+    /// The system has resumed from suspend (or hibernation).
+    ///
+    /// A lot of time may have passed since the previous event, which is worth knowing
+    /// e.g for resetting frame timers. On Linux, this is detected lazily when polling events.
+    SystemResumed,
+
+    /// The user's accessibility scaling preference has changed.
+    /// See `Context::accessibility_scale_factor()`.
+    AccessibilityScaleFactorChanged { new_scale: f64, },
+
+    /// Events were discarded because the internal event queue was full, see
+    /// `Context::set_event_queue_policy()`. `dropped` is the number of discarded events since
+    /// the previous `EventQueueOverflowed`.
+    EventQueueOverflowed { dropped: u64, },
+
     // 
     // Window events
     //
@@ -276,43 +444,100 @@ pub enum Event {
     WindowHidden         { window: WindowHandle, },
     WindowNeedsRedraw    { window: WindowHandle, zone: Rect<u32, u32>, more_to_follow: usize, },
     WindowMoved          { window: WindowHandle, position: Vec2<i32>, by_user: bool, },
-    WindowResized        { window: WindowHandle, size: Extent2<u32>, by_user: bool, },
+    /// `size` is in desktop coordinates and `canvas_size` in raster-space pixels, as returned
+    /// by `Window::size()` and `Window::canvas_size()` respectively. They are equal unless the
+    /// window is High-DPI-enabled.
+    WindowResized        { window: WindowHandle, size: Extent2<u32>, canvas_size: Extent2<u32>, by_user: bool, },
     WindowMinimized      { window: WindowHandle, },
     WindowMaximized      { window: WindowHandle, },
     WindowUnminized      { window: WindowHandle, }, // XXX
     WindowCloseRequested { window: WindowHandle, },
+    /// The window has moved to a monitor with a different scale factor, see `Window::scale_factor()`.
+    ///
+    /// `suggested_size` is the window's current size multiplied by `new / old`, which preserves
+    /// its logical size. The window is not resized automatically.
+    WindowScaleFactorChanged { window: WindowHandle, old: f64, new: f64, suggested_size: Extent2<u32>, },
+    /// The refresh rate (in Hz) of the window's monitor changed, either because the window moved
+    /// to another monitor or because the monitor's mode changed. See `Window::current_refresh_rate()`.
+    WindowRefreshRateChanged { window: WindowHandle, refresh_rate: f64, },
     // NOTE: A lot of other window events missing
 
     //
     // HIDs
     //
 
-    DeviceConnected { device: DeviceID, instant: EventInstant, info: DeviceInfo },
+    /// A device was plugged in.
+    ///
+    /// `initial` is `true` if the device was already present when the `Context` was created,
+    /// which only happens if `ContextOptions::replay_initial_devices` is set. In that case,
+    /// `instant` is when the device was plugged in, which may be well before the `Context` existed.
+    DeviceConnected { device: DeviceID, instant: EventInstant, info: DeviceInfo, initial: bool, },
     DeviceInfoChanged { device: DeviceID, instant: EventInstant, info: DeviceInfo },
+    /// The ranges of the device's axes changed at runtime, e.g because a tablet switched modes.
+    ///
+    /// Values reported afterwards (such as tablet pressure) are normalized against the new ranges.
+    /// On X11, this is reported for `XIDeviceChangedEvent`s, for devices that don't otherwise
+    /// have a `DeviceInfo`.
+    DeviceRemapped { device: DeviceID, instant: EventInstant, },
     DeviceDisconnected { device: DeviceID, instant: EventInstant, },
 
+    //
+    // Audio devices
+    //
+
+    /// An audio output (playback) device was plugged in. `device_node` is the path
+    /// of the device node, e.g `/dev/snd/pcmC1D0p` on Linux.
+    ///
+    /// This crate doesn't do audio; this is only a hint that your audio backend
+    /// may want to re-enumerate its devices (e.g when headphones are plugged in).
+    AudioOutputDeviceAdded    { device_node: PathBuf, },
+    /// An audio output (playback) device was unplugged.
+    AudioOutputDeviceRemoved  { device_node: PathBuf, },
+    /// An audio capture (recording) device was plugged in. See `AudioOutputDeviceAdded`.
+    AudioCaptureDeviceAdded   { device_node: PathBuf, },
+    /// An audio capture (recording) device was unplugged.
+    AudioCaptureDeviceRemoved { device_node: PathBuf, },
+
     // User note: in MouseScroll, the y value is positive when "scrolling up"
     // (that is, pushing the wheel forwards) and negative otherwise.
     MouseEnter             { mouse: DeviceID, window: WindowHandle, instant: EventInstant, is_grabbed: bool,  is_focused: bool, },
     MouseLeave             { mouse: DeviceID, window: WindowHandle, instant: EventInstant, was_grabbed: bool, was_focused: bool, },
-    MouseButtonPressed     { mouse: DeviceID, window: WindowHandle, instant: EventInstant, button: MouseButton, clicks: Option<u32>, },
-    MouseButtonReleased    { mouse: DeviceID, window: WindowHandle, instant: EventInstant, button: MouseButton, },
+    // User note: `position` is window-relative and `root_position` is in desktop coordinates.
+    // Both come from the same native event, so they're consistent with each other, and they
+    // keep subpixel precision when the platform provides it (e.g XI2).
+    // `outside` is only ever `true` while `window` holds an `InputGrab` and the button was
+    // pressed outside of it (see `Window::grab_input()`).
+    MouseButtonPressed     { mouse: DeviceID, window: WindowHandle, instant: EventInstant, button: MouseButton, clicks: Option<u32>, position: Vec2<f64>, root_position: Vec2<f64>, outside: bool, },
+    MouseButtonReleased    { mouse: DeviceID, window: WindowHandle, instant: EventInstant, button: MouseButton, position: Vec2<f64>, root_position: Vec2<f64>, },
     MouseScroll            { mouse: DeviceID, window: WindowHandle, instant: EventInstant, scroll: Vec2<f64>, },
     MouseMotion            { mouse: DeviceID, window: WindowHandle, instant: EventInstant, position: Vec2<f64>, root_position: Vec2<f64>, },
     MouseButtonPressedRaw  { mouse: DeviceID, instant: EventInstant, button: MouseButton, },
     MouseButtonReleasedRaw { mouse: DeviceID, instant: EventInstant, button: MouseButton, },
     MouseScrollRaw         { mouse: DeviceID, instant: EventInstant, scroll: Vec2<f64>, },
     MouseMotionRaw         { mouse: DeviceID, instant: EventInstant, displacement: Vec2<f64>, },
+    /// The mouse cursor hit a pointer barrier (see `Context::create_pointer_barrier()`).
+    /// `delta` is the motion the mouse would have made if the barrier wasn't there.
+    PointerBarrierHit      { mouse: DeviceID, instant: EventInstant, barrier: PointerBarrierID, root_position: Vec2<f64>, delta: Vec2<f64>, },
 
     // Keyboard
     KeyboardFocusGained    { keyboard: DeviceID, window: WindowHandle, },
     KeyboardFocusLost      { keyboard: DeviceID, window: WindowHandle, },
     KeyboardTextChar       { keyboard: DeviceID, window: WindowHandle, instant: EventInstant, char: char, is_repeat: bool, repeat_count: u32, },
     KeyboardTextString     { keyboard: DeviceID, window: WindowHandle, instant: EventInstant, text: String, is_repeat: bool, repeat_count: u32, },
+    /// The text currently being composed via an input method (a.k.a "preedit" text).
+    /// An empty `text` means that the composition ended or was cancelled, e.g when text input
+    /// gets disabled via `Window::disable_text_input()`; Applications should then clear
+    /// their preedit display.
+    KeyboardTextComposition { window: WindowHandle, text: String, },
     KeyboardKeyPressed     { keyboard: DeviceID, window: WindowHandle, instant: EventInstant, key: Key, is_repeat: bool, repeat_count: u32, },
     KeyboardKeyReleased    { keyboard: DeviceID, window: WindowHandle, instant: EventInstant, key: Key, },
     KeyboardKeyPressedRaw  { keyboard: DeviceID, instant: EventInstant, key: Key, },
     KeyboardKeyReleasedRaw { keyboard: DeviceID, instant: EventInstant, key: Key, },
+    /// The keyboard's active layout group changed (e.g the user switched from a Russian layout to
+    /// an English one), see `Context::keyboard_current_group()`.
+    ///
+    /// Key events that follow are resolved against the new group.
+    KeyboardLayoutGroupChanged { keyboard: DeviceID, group: u32, },
 
     // Touch (Touchpad, Touch-screen, ....)
     TouchFingerPressed  { touch: DeviceID, instant: EventInstant, finger: u32, pressure: f64, normalized_position: Vec2<f64>, },
@@ -321,6 +546,16 @@ pub enum Event {
     TouchMultiGesture   { touch: DeviceID, instant: EventInstant, nb_fingers: usize, rotation_radians: f64, pinch: f64, normalized_center: Vec2<f64>, },
     // NOTE: Missing raw events
 
+    // User note: in `TabletToolProximity`, `tool_serial` is the unique serial number of the
+    // stylus, if the tablet reports it (e.g Wacom Intuos and above). This allows telling
+    // apart several styli that are used on the same tablet, e.g to give each one its own settings.
+    // Stylus events come from the tool that last entered proximity.
+    // The two ends of a stylus (tip and eraser) may share the same serial.
+
+    /// A tool entered (`entering == true`) or left the tablet's sensing area.
+    /// This is not tied to any window; drawing apps typically hide their brush cursor when
+    /// the tool leaves, and switch to erasing when `tool` is `TabletTool::Eraser`.
+    TabletToolProximity           { tablet: DeviceID, instant: EventInstant, tool_serial: Option<u64>, tool: TabletTool, entering: bool, },
     TabletPadButtonPressed        { tablet: DeviceID, instant: EventInstant, window: WindowHandle, button: TabletPadButton, },
     TabletPadButtonReleased       { tablet: DeviceID, instant: EventInstant, window: WindowHandle, button: TabletPadButton, },
     TabletStylusButtonPressed     { tablet: DeviceID, instant: EventInstant, window: WindowHandle, button: TabletStylusButton, },
@@ -348,6 +583,15 @@ pub enum Event {
     // - min > 0 && 0 < max
     // - min < 0 && 0 < max
     // - min > 0 && 0 > max (we never know! there might be buggy drivers).
+    /// A controller's player index changed, see `Context::controller_player_index()`.
+    ControllerPlayerIndexChanged { controller: DeviceID, instant: EventInstant, player_index: Option<u32>, },
+    /// Some of a controller's events were dropped because they were not read fast enough.
+    ///
+    /// This is reported right before the controller's state is resynchronized, after which a
+    /// `ControllerAxisMotion` is reported for each axis whose value changed in the meantime.
+    /// These carry the time of the resync rather than that of the actual (unknown) motion.
+    /// Applications may want to e.g reset gesture recognizers at this point.
+    ControllerEventsDropped { controller: DeviceID, instant: EventInstant, },
 }
 
 
@@ -365,40 +609,54 @@ impl Event {
             Event::AppEnteredForeground => None,
             Event::SessionEndRequested => None,
             Event::SessionEnding => None,
+            Event::SystemResumed => None,
+            Event::AccessibilityScaleFactorChanged { new_scale: _, } => None,
+            Event::EventQueueOverflowed { dropped: _, } => None,
             Event::WindowShown          { window: _, } => None,
             Event::WindowHidden         { window: _, } => None,
             Event::WindowNeedsRedraw    { window: _, zone: _, more_to_follow: _, } => None,
             Event::WindowMoved          { window: _, position: _, by_user: _, } => None,
-            Event::WindowResized        { window: _, size: _, by_user: _, } => None,
+            Event::WindowResized        { window: _, size: _, canvas_size: _, by_user: _, } => None,
             Event::WindowMinimized      { window: _, } => None,
             Event::WindowMaximized      { window: _, } => None,
             Event::WindowUnminized      { window: _, } => None,
             Event::WindowCloseRequested { window: _, } => None,
-            Event::DeviceConnected      { device: _, instant, info: _, } => Some(instant),
+            Event::WindowScaleFactorChanged { window: _, old: _, new: _, suggested_size: _, } => None,
+            Event::WindowRefreshRateChanged { window: _, refresh_rate: _, } => None,
+            Event::DeviceConnected      { device: _, instant, info: _, initial: _, } => Some(instant),
             Event::DeviceInfoChanged    { device: _, instant, info: _, } => Some(instant),
+            Event::DeviceRemapped       { device: _, instant, } => Some(instant),
             Event::DeviceDisconnected   { device: _, instant, } => Some(instant),
+            Event::AudioOutputDeviceAdded    { device_node: _, } => None,
+            Event::AudioOutputDeviceRemoved  { device_node: _, } => None,
+            Event::AudioCaptureDeviceAdded   { device_node: _, } => None,
+            Event::AudioCaptureDeviceRemoved { device_node: _, } => None,
             Event::MouseEnter             { mouse: _, instant, window: _, is_grabbed: _,  is_focused: _, } => Some(instant),
             Event::MouseLeave             { mouse: _, instant, window: _, was_grabbed: _, was_focused: _, } => Some(instant),
             Event::MouseMotion            { mouse: _, instant, window: _, position: _, root_position: _, } => Some(instant),
-            Event::MouseButtonPressed     { mouse: _, instant, window: _, button: _, clicks: _, } => Some(instant),
-            Event::MouseButtonReleased    { mouse: _, instant, window: _, button: _, } => Some(instant),
+            Event::MouseButtonPressed     { mouse: _, instant, window: _, button: _, clicks: _, position: _, root_position: _, outside: _, } => Some(instant),
+            Event::MouseButtonReleased    { mouse: _, instant, window: _, button: _, position: _, root_position: _, } => Some(instant),
             Event::MouseScroll            { mouse: _, instant, window: _, scroll: _, } => Some(instant),
             Event::MouseButtonPressedRaw  { mouse: _, instant, button: _, } => Some(instant),
             Event::MouseButtonReleasedRaw { mouse: _, instant, button: _, } => Some(instant),
             Event::MouseScrollRaw         { mouse: _, instant, scroll: _, } => Some(instant),
             Event::MouseMotionRaw         { mouse: _, instant, displacement: _, } => Some(instant),
+            Event::PointerBarrierHit      { mouse: _, instant, barrier: _, root_position: _, delta: _, } => Some(instant),
             Event::KeyboardFocusGained    { keyboard: _, window: _, } => None,
             Event::KeyboardFocusLost      { keyboard: _, window: _, } => None,
             Event::KeyboardTextChar       { keyboard: _, window: _, instant, char: _, is_repeat: _, repeat_count: _, } => Some(instant),
             Event::KeyboardTextString     { keyboard: _, window: _, instant, text: _, is_repeat: _, repeat_count: _, } => Some(instant),
+            Event::KeyboardTextComposition { window: _, text: _, } => None,
             Event::KeyboardKeyPressed     { keyboard: _, window: _, instant, key: _, is_repeat: _, repeat_count: _, } => Some(instant),
             Event::KeyboardKeyReleased    { keyboard: _, window: _, instant, key: _, } => Some(instant),
             Event::KeyboardKeyPressedRaw  { keyboard: _, instant, key: _, } => Some(instant),
             Event::KeyboardKeyReleasedRaw { keyboard: _, instant, key: _, } => Some(instant),
+            Event::KeyboardLayoutGroupChanged { keyboard: _, group: _, } => None,
             Event::TouchFingerPressed  { touch: _, instant, finger: _, pressure: _, normalized_position: _, } => Some(instant),
             Event::TouchFingerReleased { touch: _, instant, finger: _, pressure: _, normalized_position: _, } => Some(instant),
             Event::TouchFingerMotion   { touch: _, instant, finger: _, pressure: _, normalized_motion:   _, } => Some(instant),
             Event::TouchMultiGesture   { touch: _, instant, nb_fingers: _, rotation_radians: _, pinch: _, normalized_center: _, } => Some(instant),
+            Event::TabletToolProximity           { tablet: _, instant, tool_serial: _, tool: _, entering: _, } => Some(instant),
             Event::TabletPadButtonPressed        { tablet: _, instant, window: _, button: _, } => Some(instant),
             Event::TabletPadButtonReleased       { tablet: _, instant, window: _, button: _, } => Some(instant),
             Event::TabletStylusButtonPressed     { tablet: _, instant, window: _, button: _, } => Some(instant),
@@ -418,6 +676,107 @@ impl Event {
             Event::ControllerButtonPressed  { controller: _, instant, button: _, } => Some(instant),
             Event::ControllerButtonReleased { controller: _, instant, button: _, } => Some(instant),
             Event::ControllerAxisMotion     { controller: _, instant, axis: _, value: _, } => Some(instant),
+            Event::ControllerPlayerIndexChanged { controller: _, instant, player_index: _, } => Some(instant),
+            Event::ControllerEventsDropped { controller: _, instant, } => Some(instant),
+        }
+    }
+}
+
+/// A concise one-line summary, for logging. Use `Debug` for the full picture.
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fn key(key: &Key) -> String {
+            match key.sym {
+                Some(sym) => format!("{:?}", sym),
+                None => format!("code:{:?}", (key.code.0)),
+            }
+        }
+        match *self {
+            Event::UnprocessedEvent(_) => write!(f, "UnprocessedEvent"),
+            Event::Quit => write!(f, "Quit"),
+            Event::AppBeingTerminatedByOS => write!(f, "AppBeingTerminatedByOS"),
+            Event::AppLowMemory => write!(f, "AppLowMemory"),
+            Event::AppWillEnterBackground => write!(f, "AppWillEnterBackground"),
+            Event::AppEnteredBackground => write!(f, "AppEnteredBackground"),
+            Event::AppWillEnterForeground => write!(f, "AppWillEnterForeground"),
+            Event::AppEnteredForeground => write!(f, "AppEnteredForeground"),
+            Event::SessionEndRequested => write!(f, "SessionEndRequested"),
+            Event::SessionEnding => write!(f, "SessionEnding"),
+            Event::SystemResumed => write!(f, "SystemResumed"),
+            Event::AccessibilityScaleFactorChanged { new_scale, } => write!(f, "AccessibilityScaleFactorChanged scale={}", new_scale),
+            Event::EventQueueOverflowed { dropped, } => write!(f, "EventQueueOverflowed dropped={}", dropped),
+            Event::WindowShown          { window, } => write!(f, "WindowShown win={}", window),
+            Event::WindowHidden         { window, } => write!(f, "WindowHidden win={}", window),
+            Event::WindowNeedsRedraw    { window, zone, more_to_follow, } => write!(f, "WindowNeedsRedraw win={} zone=({}, {}, {}x{}) more={}", window, zone.x, zone.y, zone.w, zone.h, more_to_follow),
+            Event::WindowMoved          { window, position, by_user, } => write!(f, "WindowMoved win={} pos=({}, {}) by_user={}", window, position.x, position.y, by_user),
+            Event::WindowResized        { window, size, canvas_size, by_user, } => write!(f, "WindowResized win={} size={}x{} canvas_size={}x{} by_user={}", window, size.w, size.h, canvas_size.w, canvas_size.h, by_user),
+            Event::WindowMinimized      { window, } => write!(f, "WindowMinimized win={}", window),
+            Event::WindowMaximized      { window, } => write!(f, "WindowMaximized win={}", window),
+            Event::WindowUnminized      { window, } => write!(f, "WindowUnminized win={}", window),
+            Event::WindowCloseRequested { window, } => write!(f, "WindowCloseRequested win={}", window),
+            Event::WindowScaleFactorChanged { window, old, new, suggested_size, } => write!(f, "WindowScaleFactorChanged win={} old={} new={} suggested_size={}x{}", window, old, new, suggested_size.w, suggested_size.h),
+            Event::WindowRefreshRateChanged { window, refresh_rate, } => write!(f, "WindowRefreshRateChanged win={} refresh_rate={}", window, refresh_rate),
+            Event::DeviceConnected      { device, instant, ref info, initial, } => write!(f, "DeviceConnected dev={} info=[{}] t={} initial={}", device, info.summary(), instant, initial),
+            Event::DeviceInfoChanged    { device, instant, ref info, } => write!(f, "DeviceInfoChanged dev={} info=[{}] t={}", device, info.summary(), instant),
+            Event::DeviceRemapped       { device, instant, } => write!(f, "DeviceRemapped dev={} t={}", device, instant),
+            Event::DeviceDisconnected   { device, instant, } => write!(f, "DeviceDisconnected dev={} t={}", device, instant),
+            Event::AudioOutputDeviceAdded    { ref device_node, } => write!(f, "AudioOutputDeviceAdded node={}", device_node.display()),
+            Event::AudioOutputDeviceRemoved  { ref device_node, } => write!(f, "AudioOutputDeviceRemoved node={}", device_node.display()),
+            Event::AudioCaptureDeviceAdded   { ref device_node, } => write!(f, "AudioCaptureDeviceAdded node={}", device_node.display()),
+            Event::AudioCaptureDeviceRemoved { ref device_node, } => write!(f, "AudioCaptureDeviceRemoved node={}", device_node.display()),
+            Event::MouseEnter             { mouse, instant, window, is_grabbed,  is_focused, } => write!(f, "MouseEnter dev={} win={} grabbed={} focused={} t={}", mouse, window, is_grabbed, is_focused, instant),
+            Event::MouseLeave             { mouse, instant, window, was_grabbed, was_focused, } => write!(f, "MouseLeave dev={} win={} grabbed={} focused={} t={}", mouse, window, was_grabbed, was_focused, instant),
+            Event::MouseMotion            { mouse, instant, window, position, root_position, } => write!(f, "MouseMotion dev={} win={} pos=({}, {}) root_pos=({}, {}) t={}", mouse, window, position.x, position.y, root_position.x, root_position.y, instant),
+            Event::MouseButtonPressed     { mouse, instant, window, button, clicks, position, root_position: _, outside, } => match clicks {
+                Some(clicks) => write!(f, "MouseButtonPressed dev={} win={} button={:?} clicks={} pos=({}, {}) outside={} t={}", mouse, window, button, clicks, position.x, position.y, outside, instant),
+                None => write!(f, "MouseButtonPressed dev={} win={} button={:?} pos=({}, {}) outside={} t={}", mouse, window, button, position.x, position.y, outside, instant),
+            },
+            Event::MouseButtonReleased    { mouse, instant, window, button, position, root_position: _, } => write!(f, "MouseButtonReleased dev={} win={} button={:?} pos=({}, {}) t={}", mouse, window, button, position.x, position.y, instant),
+            Event::MouseScroll            { mouse, instant, window, scroll, } => write!(f, "MouseScroll dev={} win={} scroll=({}, {}) t={}", mouse, window, scroll.x, scroll.y, instant),
+            Event::MouseButtonPressedRaw  { mouse, instant, button, } => write!(f, "MouseButtonPressedRaw dev={} button={:?} t={}", mouse, button, instant),
+            Event::MouseButtonReleasedRaw { mouse, instant, button, } => write!(f, "MouseButtonReleasedRaw dev={} button={:?} t={}", mouse, button, instant),
+            Event::MouseScrollRaw         { mouse, instant, scroll, } => write!(f, "MouseScrollRaw dev={} scroll=({}, {}) t={}", mouse, scroll.x, scroll.y, instant),
+            Event::MouseMotionRaw         { mouse, instant, displacement, } => write!(f, "MouseMotionRaw dev={} displacement=({}, {}) t={}", mouse, displacement.x, displacement.y, instant),
+            Event::PointerBarrierHit      { mouse, instant, barrier, root_position, delta, } => write!(f, "PointerBarrierHit dev={} barrier={:?} root_pos=({}, {}) delta=({}, {}) t={}", mouse, barrier.0, root_position.x, root_position.y, delta.x, delta.y, instant),
+            Event::KeyboardFocusGained    { keyboard, window, } => write!(f, "KeyboardFocusGained dev={} win={}", keyboard, window),
+            Event::KeyboardFocusLost      { keyboard, window, } => write!(f, "KeyboardFocusLost dev={} win={}", keyboard, window),
+            Event::KeyboardTextChar       { keyboard, window, instant, char, is_repeat, repeat_count, } => write!(f, "KeyboardTextChar dev={} win={} char={:?} repeat={}x{} t={}", keyboard, window, char, is_repeat, repeat_count, instant),
+            Event::KeyboardTextString     { keyboard, window, instant, ref text, is_repeat, repeat_count, } => write!(f, "KeyboardTextString dev={} win={} text={:?} repeat={}x{} t={}", keyboard, window, text, is_repeat, repeat_count, instant),
+            Event::KeyboardTextComposition { window, ref text, } => write!(f, "KeyboardTextComposition win={} text={:?}", window, text),
+            Event::KeyboardKeyPressed     { keyboard, window, instant, key: ref k, is_repeat, repeat_count, } => write!(f, "KeyboardKeyPressed dev={} win={} key={} repeat={}x{} t={}", keyboard, window, key(k), is_repeat, repeat_count, instant),
+            Event::KeyboardKeyReleased    { keyboard, window, instant, key: ref k, } => write!(f, "KeyboardKeyReleased dev={} win={} key={} t={}", keyboard, window, key(k), instant),
+            Event::KeyboardKeyPressedRaw  { keyboard, instant, key: ref k, } => write!(f, "KeyboardKeyPressedRaw dev={} key={} t={}", keyboard, key(k), instant),
+            Event::KeyboardKeyReleasedRaw { keyboard, instant, key: ref k, } => write!(f, "KeyboardKeyReleasedRaw dev={} key={} t={}", keyboard, key(k), instant),
+            Event::KeyboardLayoutGroupChanged { keyboard, group, } => write!(f, "KeyboardLayoutGroupChanged dev={} group={}", keyboard, group),
+            Event::TouchFingerPressed  { touch, instant, finger, pressure, normalized_position, } => write!(f, "TouchFingerPressed dev={} finger={} pressure={} pos=({}, {}) t={}", touch, finger, pressure, normalized_position.x, normalized_position.y, instant),
+            Event::TouchFingerReleased { touch, instant, finger, pressure, normalized_position, } => write!(f, "TouchFingerReleased dev={} finger={} pressure={} pos=({}, {}) t={}", touch, finger, pressure, normalized_position.x, normalized_position.y, instant),
+            Event::TouchFingerMotion   { touch, instant, finger, pressure, normalized_motion, } => write!(f, "TouchFingerMotion dev={} finger={} pressure={} motion=({}, {}) t={}", touch, finger, pressure, normalized_motion.x, normalized_motion.y, instant),
+            Event::TouchMultiGesture   { touch, instant, nb_fingers, rotation_radians, pinch, normalized_center, } => write!(f, "TouchMultiGesture dev={} fingers={} rotation={} pinch={} center=({}, {}) t={}", touch, nb_fingers, rotation_radians, pinch, normalized_center.x, normalized_center.y, instant),
+            Event::TabletToolProximity           { tablet, instant, tool_serial, tool, entering, } => match tool_serial {
+                Some(serial) => write!(f, "TabletToolProximity dev={} tool={:?} serial={:#x} entering={} t={}", tablet, tool, serial, entering, instant),
+                None => write!(f, "TabletToolProximity dev={} tool={:?} entering={} t={}", tablet, tool, entering, instant),
+            },
+            Event::TabletPadButtonPressed        { tablet, instant, window, button, } => write!(f, "TabletPadButtonPressed dev={} win={} button={} t={}", tablet, window, button, instant),
+            Event::TabletPadButtonReleased       { tablet, instant, window, button, } => write!(f, "TabletPadButtonReleased dev={} win={} button={} t={}", tablet, window, button, instant),
+            Event::TabletStylusButtonPressed     { tablet, instant, window, button, } => write!(f, "TabletStylusButtonPressed dev={} win={} button={:?} t={}", tablet, window, button, instant),
+            Event::TabletStylusButtonReleased    { tablet, instant, window, button, } => write!(f, "TabletStylusButtonReleased dev={} win={} button={:?} t={}", tablet, window, button, instant),
+            Event::TabletStylusToolType          { tablet, instant, window, tool_type, } => write!(f, "TabletStylusToolType dev={} win={} tool={:?} t={}", tablet, window, tool_type, instant),
+            Event::TabletStylusMotion            { tablet, instant, window, position, root_position, physical_position, } => write!(f, "TabletStylusMotion dev={} win={} pos=({}, {}) root_pos=({}, {}) physical_pos=({}, {}) t={}", tablet, window, position.x, position.y, root_position.x, root_position.y, physical_position.x, physical_position.y, instant),
+            Event::TabletStylusPressure          { tablet, instant, window, pressure } => write!(f, "TabletStylusPressure dev={} win={} pressure={} t={}", tablet, window, pressure, instant),
+            Event::TabletStylusTilt              { tablet, instant, window, tilt, } => write!(f, "TabletStylusTilt dev={} win={} tilt=({}, {}) t={}", tablet, window, tilt.x, tilt.y, instant),
+            Event::TabletPadButtonPressedRaw     { tablet, instant, button, } => write!(f, "TabletPadButtonPressedRaw dev={} button={} t={}", tablet, button, instant),
+            Event::TabletPadButtonReleasedRaw    { tablet, instant, button, } => write!(f, "TabletPadButtonReleasedRaw dev={} button={} t={}", tablet, button, instant),
+            Event::TabletStylusButtonPressedRaw  { tablet, instant, button, } => write!(f, "TabletStylusButtonPressedRaw dev={} button={:?} t={}", tablet, button, instant),
+            Event::TabletStylusButtonReleasedRaw { tablet, instant, button, } => write!(f, "TabletStylusButtonReleasedRaw dev={} button={:?} t={}", tablet, button, instant),
+            Event::TabletStylusToolTypeRaw       { tablet, instant, tool_type, } => write!(f, "TabletStylusToolTypeRaw dev={} tool={:?} t={}", tablet, tool_type, instant),
+            Event::TabletStylusMotionRaw         { tablet, instant, physical_position, } => write!(f, "TabletStylusMotionRaw dev={} physical_pos=({}, {}) t={}", tablet, physical_position.x, physical_position.y, instant),
+            Event::TabletStylusPressureRaw       { tablet, instant, pressure } => write!(f, "TabletStylusPressureRaw dev={} pressure={} t={}", tablet, pressure, instant),
+            Event::TabletStylusTiltRaw           { tablet, instant, tilt, } => write!(f, "TabletStylusTiltRaw dev={} tilt=({}, {}) t={}", tablet, tilt.x, tilt.y, instant),
+            Event::ControllerButtonPressed  { controller, instant, button, } => write!(f, "ControllerButtonPressed dev={} button={:?} t={}", controller, button, instant),
+            Event::ControllerButtonReleased { controller, instant, button, } => write!(f, "ControllerButtonReleased dev={} button={:?} t={}", controller, button, instant),
+            Event::ControllerAxisMotion     { controller, instant, axis, value, } => write!(f, "ControllerAxisMotion dev={} axis={:?} value={} t={}", controller, axis, value, instant),
+            Event::ControllerPlayerIndexChanged { controller, instant, player_index, } => write!(f, "ControllerPlayerIndexChanged dev={} player_index={:?} t={}", controller, player_index, instant),
+            Event::ControllerEventsDropped { controller, instant, } => write!(f, "ControllerEventsDropped dev={} t={}", controller, instant),
         }
     }
 }
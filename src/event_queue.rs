@@ -0,0 +1,212 @@
+//! Bounding the internal event queue, see `Context::set_event_queue_policy()`.
+
+use std::collections::VecDeque;
+use context::Context;
+use event::Event;
+use error::{Result, invalid_arg};
+
+/// What to do when an event is pushed to a full queue, see `QueuePolicy`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum QueueOverflow {
+    /// Discard the oldest event in the queue.
+    DropOldest,
+    /// Discard the incoming event.
+    DropNewest,
+    /// Discard the oldest event that only reports a new value of some continuous state
+    /// (see `Event::is_coalescible()`), or else the incoming event if it is one.
+    ///
+    /// Other events (e.g button presses or device connections) are never discarded, so
+    /// the queue may grow beyond its maximum length if they are all there is.
+    CoalesceMotion,
+}
+
+/// How large the internal event queue of a `Context` may grow, see `Context::set_event_queue_policy()`.
+///
+/// The queue only grows while you're not polling: e.g a stalled application (breakpoint hit,
+/// long load) would otherwise accumulate a huge backlog of mouse motion events, and then
+/// spend seconds catching up.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct QueuePolicy {
+    /// The number of events above which `overflow` applies. Must be non-zero.
+    pub max_len: usize,
+    /// What to discard when the queue is full.
+    pub overflow: QueueOverflow,
+}
+
+impl Default for QueuePolicy {
+    /// A generous cap of 65536 events, with `QueueOverflow::CoalesceMotion`.
+    fn default() -> Self {
+        Self {
+            max_len: 1 << 16,
+            overflow: QueueOverflow::CoalesceMotion,
+        }
+    }
+}
+
+impl Event {
+    /// Does this event only report a new value of some continuous state (e.g a position or
+    /// an axis), such that discarding it only loses intermediate values?
+    ///
+    /// Scrolling and raw mouse motion are not included, since they report deltas.
+    pub fn is_coalescible(&self) -> bool {
+        match *self {
+            Event::MouseMotion { .. }
+            | Event::TabletStylusMotion { .. }
+            | Event::TabletStylusPressure { .. }
+            | Event::TabletStylusTilt { .. }
+            | Event::TabletStylusMotionRaw { .. }
+            | Event::TabletStylusPressureRaw { .. }
+            | Event::TabletStylusTiltRaw { .. }
+            | Event::ControllerAxisMotion { .. }
+            => true,
+            _ => false,
+        }
+    }
+}
+
+/// The queue of translated events that backends push to, which enforces a `QueuePolicy`.
+///
+/// Discarded events are replaced by an `Event::EventQueueOverflowed` where they were,
+/// which adjacent discards are merged into, so that the events that precede a gap are
+/// still reported before it.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct EventQueue {
+    events: VecDeque<Event>,
+    policy: QueuePolicy,
+    /// The number of `Event::EventQueueOverflowed` in `events`, which don't count towards `policy.max_len`.
+    nb_markers: usize,
+    /// With `QueueOverflow::CoalesceMotion`, no event before this index is coalescible,
+    /// so that `drop_one()` doesn't scan the same events again on every push.
+    first_coalescible: usize,
+}
+
+impl EventQueue {
+    pub fn policy(&self) -> QueuePolicy {
+        self.policy
+    }
+    /// Sets the policy, returning the events that were discarded to enforce it right away.
+    pub fn set_policy(&mut self, policy: QueuePolicy) -> Vec<Event> {
+        self.policy = policy;
+        let mut discarded = Vec::new();
+        while self.nb_events() > policy.max_len {
+            match self.drop_one() {
+                Some(ev) => discarded.push(ev),
+                None => break,
+            }
+        }
+        discarded
+    }
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+    fn nb_events(&self) -> usize {
+        self.events.len() - self.nb_markers
+    }
+    /// Pushes `ev`, returning the event that was discarded to make room, if any (which may be `ev` itself).
+    pub fn push_back(&mut self, ev: Event) -> Option<Event> {
+        if self.nb_events() >= self.policy.max_len {
+            match self.policy.overflow {
+                QueueOverflow::DropNewest => {
+                    let i = self.events.len();
+                    self.mark_dropped(i);
+                    return Some(ev);
+                },
+                QueueOverflow::DropOldest => {
+                    let discarded = self.drop_one();
+                    self.events.push_back(ev);
+                    return discarded;
+                },
+                QueueOverflow::CoalesceMotion => match self.drop_one() {
+                    Some(discarded) => {
+                        self.events.push_back(ev);
+                        return Some(discarded);
+                    },
+                    None if ev.is_coalescible() => {
+                        let i = self.events.len();
+                        self.mark_dropped(i);
+                        return Some(ev);
+                    },
+                    None => (),
+                },
+            }
+        }
+        self.events.push_back(ev);
+        None
+    }
+    /// Inserts `ev` at index `i`, regardless of the policy.
+    pub fn insert(&mut self, i: usize, ev: Event) {
+        if i <= self.first_coalescible {
+            self.first_coalescible = if ev.is_coalescible() { i } else { self.first_coalescible + 1 };
+        }
+        self.events.insert(i, ev);
+    }
+    pub fn pop_front(&mut self) -> Option<Event> {
+        let ev = self.events.pop_front()?;
+        if let Event::EventQueueOverflowed { .. } = ev {
+            self.nb_markers -= 1;
+        }
+        self.first_coalescible = self.first_coalescible.saturating_sub(1);
+        Some(ev)
+    }
+    /// Discards one event according to the policy, returning `None` if there was none to discard.
+    fn drop_one(&mut self) -> Option<Event> {
+        let i = match self.policy.overflow {
+            QueueOverflow::DropOldest => self.events.iter().position(|ev| !is_marker(ev)),
+            QueueOverflow::DropNewest => self.events.iter().rposition(|ev| !is_marker(ev)),
+            QueueOverflow::CoalesceMotion => {
+                let start = self.first_coalescible.min(self.events.len());
+                let i = self.events.iter().skip(start).position(Event::is_coalescible).map(|i| start + i);
+                self.first_coalescible = i.unwrap_or(self.events.len());
+                i
+            },
+        }?;
+        let discarded = self.events.remove(i)?;
+        self.mark_dropped(i);
+        Some(discarded)
+    }
+    /// Records that an event was discarded at index `i`, by merging it into an adjacent
+    /// `Event::EventQueueOverflowed`, or else inserting a new one there.
+    fn mark_dropped(&mut self, i: usize) {
+        for j in i.checked_sub(1).into_iter().chain(Some(i)) {
+            if let Some(&mut Event::EventQueueOverflowed { ref mut dropped }) = self.events.get_mut(j) {
+                *dropped += 1;
+                return;
+            }
+        }
+        self.events.insert(i, Event::EventQueueOverflowed { dropped: 1 });
+        self.nb_markers += 1;
+        if i <= self.first_coalescible {
+            self.first_coalescible += 1;
+        }
+    }
+}
+
+fn is_marker(ev: &Event) -> bool {
+    matches!(*ev, Event::EventQueueOverflowed { .. })
+}
+
+impl Context {
+    /// Sets how large the internal event queue may grow, and what is discarded when it is full.
+    ///
+    /// When events are discarded, an `Event::EventQueueOverflowed` is reported in their place,
+    /// so that you know where there is a gap in the input timeline.
+    /// If the queue is currently larger than `policy.max_len`, events are discarded right away.
+    ///
+    /// The default is `QueuePolicy::default()`.
+    /// On Linux, this applies to the X11 queue, and separately to this `Context`'s queue of
+    /// controller events (which other `Context`s of the same thread would keep polling).
+    pub fn set_event_queue_policy(&self, policy: QueuePolicy) -> Result<()> {
+        if policy.max_len == 0 {
+            return invalid_arg("The maximum length of the event queue must be non-zero");
+        }
+        self.os.set_event_queue_policy(policy);
+        Ok(())
+    }
+    /// Gets the policy set by `set_event_queue_policy()`.
+    pub fn event_queue_policy(&self) -> QueuePolicy {
+        self.os.event_queue_policy()
+    }
+}
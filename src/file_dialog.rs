@@ -0,0 +1,73 @@
+//! Native file open/save dialogs.
+
+use std::path::PathBuf;
+use context::Context;
+use error::{Result, invalid_arg};
+
+/// A named set of file extensions for `FileDialogOptions`, e.g `"Images"` with `["png", "jpg"]`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct FileDialogFilter {
+    /// The name shown to the user.
+    pub name: String,
+    /// Extensions without the leading dot. Must not be empty.
+    pub extensions: Vec<String>,
+}
+
+/// Settings for `Context::show_open_file_dialog()` and `Context::show_save_file_dialog()`.
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct FileDialogOptions {
+    /// The dialog's title. `None` lets the platform choose.
+    pub title: Option<String>,
+    /// If not empty, only files matching one of these filters are shown; The first one is selected initially.
+    pub filters: Vec<FileDialogFilter>,
+    /// The directory that is shown initially.
+    pub default_directory: Option<PathBuf>,
+    /// The file name that is initially filled in (mostly useful for save dialogs).
+    pub default_file_name: Option<String>,
+    /// Allow selecting several files. Ignored by save dialogs.
+    pub multiple: bool,
+}
+
+impl FileDialogOptions {
+    fn validate(&self) -> Result<()> {
+        for filter in self.filters.iter() {
+            if filter.extensions.is_empty() {
+                return invalid_arg(format!("The `{}` filter has no extensions", filter.name));
+            }
+            if let Some(ext) = filter.extensions.iter().find(|ext| ext.is_empty() || ext.starts_with('.')) {
+                return invalid_arg(format!("The `{}` filter has an invalid extension: {:?} (it must be non-empty and without the leading dot)", filter.name, ext));
+            }
+        }
+        if let Some(ref name) = self.default_file_name {
+            if name.contains('/') || name.contains('\\') {
+                return invalid_arg("default_file_name must not contain path separators (see default_directory)");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Context {
+    /// Shows a native dialog for picking one or more existing files, and blocks until it is closed.
+    ///
+    /// Returns `None` if the user cancelled.  
+    /// While the dialog runs, this context keeps processing its own event queue so that its
+    /// windows still respond to the window manager (they can't repaint, since that's your job,
+    /// but they won't be reported as frozen). Events that arrive in the meantime are kept
+    /// for you to poll afterwards.
+    ///
+    /// On Windows, this uses `IFileOpenDialog`.  
+    /// On Linux, this uses the XDG Desktop Portal over D-Bus. If there's no portal, this
+    /// runs `zenity` or, failing that, `kdialog`; If neither is installed, this returns `Unsupported`.
+    pub fn show_open_file_dialog(&self, options: &FileDialogOptions) -> Result<Option<Vec<PathBuf>>> {
+        options.validate()?;
+        self.os.show_open_file_dialog(options)
+    }
+    /// Shows a native dialog for choosing where to save a file, and blocks until it is closed.
+    ///
+    /// Returns `None` if the user cancelled. See `show_open_file_dialog()` for details.
+    pub fn show_save_file_dialog(&self, options: &FileDialogOptions) -> Result<Option<PathBuf>> {
+        options.validate()?;
+        self.os.show_save_file_dialog(options)
+    }
+}
@@ -1,7 +1,9 @@
 //! OpenGL-related structures and abstractions.
 
 use std::os::raw::{c_void, c_char};
+use std::time::{Duration, Instant};
 use os::{OsGLPixelFormat, OsGLContext};
+use context::Context;
 use window::Window;
 use error::Result;
 
@@ -62,6 +64,12 @@ pub struct GLPixelFormatSettings {
     /// See [The relevant section on the OpenGL
     /// wiki](https://www.khronos.org/opengl/wiki/Default_Framebuffer#Removed_buffer_images).
     pub aux_buffers: u8,
+    /// Requests an sRGB-capable default framebuffer.
+    ///
+    /// On Windows, this requires `WGL_ARB_framebuffer_sRGB` or `WGL_EXT_framebuffer_sRGB`.  
+    /// On X11, this requires `GLX_ARB_framebuffer_sRGB` or `GLX_EXT_framebuffer_sRGB` with GLX,
+    /// and EGL 1.5 or `EGL_KHR_gl_colorspace` with EGL; Otherwise, this is reported as unsupported.
+    pub srgb: bool,
 }
 
 impl Default for GLPixelFormatSettings {
@@ -82,6 +90,7 @@ impl Default for GLPixelFormatSettings {
             accum_blue_bits: 0,
             accum_alpha_bits: 0,
             aux_buffers: 0,
+            srgb: false,
         }
     }
 }
@@ -186,6 +195,58 @@ pub struct GLContextSettings {
     /// Enables the "robust access" bit in context flags, if the backend
     /// supports the extension.
     pub robust_access: Option<GLContextResetNotificationStrategy>,
+    /// Which API should create the context, on platforms that have several.
+    /// Use `GLContext::api()` to know which one was actually used.
+    pub api_preference: GLApiPreference,
+}
+
+impl Default for GLContextSettings {
+    /// A desktop OpenGL 2.1 context (so the profile doesn't matter) without any flags, created
+    /// via the platform's preferred API.
+    fn default() -> Self {
+        Self {
+            version: GLVersion::new_desktop(2, 1),
+            profile: GLProfile::default(),
+            debug: false,
+            forward_compatible: false,
+            robust_access: None,
+            api_preference: GLApiPreference::default(),
+        }
+    }
+}
+
+/// Which API should create an OpenGL context, on platforms that have several.
+///
+/// This is only meaningful on X11, where a context can be created either via GLX or EGL
+/// (with `EGL_KHR_platform_x11`). Some drivers (and XWayland) behave better with EGL, and
+/// EGL handles OpenGL ES contexts more naturally than the GLX ES extensions do.  
+/// On other platforms, the `Prefer*` variants are ignored and the `Only*` variants fail
+/// with an "unsupported" error.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum GLApiPreference {
+    /// Try GLX first, then EGL. This is the default.
+    PreferGLX,
+    /// Try EGL first, then GLX.
+    PreferEGL,
+    /// Only use GLX.
+    OnlyGLX,
+    /// Only use EGL.
+    OnlyEGL,
+}
+
+impl Default for GLApiPreference {
+    fn default() -> Self {
+        GLApiPreference::PreferGLX
+    }
+}
+
+/// The API that actually created an OpenGL context. See `GLContext::api()`.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum GLApi {
+    GLX,
+    EGL,
+    WGL,
 }
 
 /// Wrapper around a platform-specific OpenGL Context.
@@ -238,7 +299,82 @@ impl Default for GLSwapInterval {
     }
 }
 
+/// Timing statistics about `Window::gl_swap_buffers()`, see `Window::present_stats()`.
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct PresentStats {
+    /// The number of successful calls to `gl_swap_buffers()`.
+    pub presents: u64,
+    /// The estimated number of vertical blanks that were missed.
+    ///
+    /// With `GLX_OML_sync_control`, this is derived from jumps in the media stream counter (MSC).
+    /// Otherwise, it is estimated by comparing the wall-clock time between two presents with the
+    /// refresh interval of the window's monitor, or if it is unknown, with the shortest time
+    /// observed so far (which approximates the refresh interval when VSync is on).
+    pub estimated_misses: u64,
+    /// The exponentially-weighted moving average of the time between two presents, if there
+    /// were at least two of them.
+    pub average_interval: Option<Duration>,
+}
+
+/// Backends own one of these per window, and call `record()` after each present.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct PresentStatsCollector {
+    stats: PresentStats,
+    last_present: Option<Instant>,
+    last_msc: Option<i64>,
+    min_interval: Option<Duration>,
+}
+
+impl PresentStatsCollector {
+    pub fn stats(&self) -> PresentStats {
+        self.stats
+    }
+    /// `msc` is the media stream counter after the present, if the backend knows it.
+    /// Otherwise, `refresh_rate` (in Hz) is the one of the window's monitor, if known.
+    pub fn record(&mut self, msc: Option<i64>, refresh_rate: Option<f64>) {
+        let now = Instant::now();
+        self.stats.presents += 1;
+
+        if let (Some(msc), Some(last_msc)) = (msc, self.last_msc) {
+            if msc - last_msc > 1 {
+                self.stats.estimated_misses += (msc - last_msc - 1) as u64;
+            }
+        }
+        self.last_msc = msc;
+
+        if let Some(last_present) = self.last_present {
+            let interval = now.duration_since(last_present);
+            let refresh_interval = refresh_rate.filter(|r| *r > 0.).map(|r| 1. / r)
+                .or_else(|| self.min_interval.map(duration_to_secs));
+            if msc.is_none() {
+                if let Some(refresh_interval) = refresh_interval {
+                    // Anything above 1.5 refresh intervals means at least one vblank went by.
+                    let ratio = duration_to_secs(interval) / refresh_interval;
+                    if ratio >= 1.5 {
+                        self.stats.estimated_misses += (ratio - 0.5) as u64;
+                    }
+                }
+            }
+            self.min_interval = Some(self.min_interval.map_or(interval, |min| min.min(interval)));
+            self.stats.average_interval = Some(match self.stats.average_interval {
+                None => interval,
+                Some(average) => average * 7 / 8 + interval / 8,
+            });
+        }
+        self.last_present = Some(now);
+    }
+}
+
+fn duration_to_secs(d: Duration) -> f64 {
+    d.as_secs() as f64 + d.subsec_nanos() as f64 / 1_000_000_000.
+}
+
 impl GLContext {
+    /// Gets the API that created this context, which depends on `GLContextSettings::api_preference`
+    /// and on what the platform supports. It is worth mentioning in bug reports.
+    pub fn api(&self) -> GLApi {
+        self.0.api()
+    }
     /// Retrieves the OpenGL function pointer for the given name.
     pub unsafe fn get_proc_address(&self, name: *const c_char) -> *const c_void {
         self.0.get_proc_address(name)
@@ -253,6 +389,26 @@ impl GLContext {
             self.get_proc_address(cstr.as_ptr() as _)
         }
     }
+    /// Makes this context current for this thread, without any window.
+    ///
+    /// This context must have been created by `Context::create_offscreen_gl_context()`.
+    /// There is no default framebuffer worth rendering to, so you should render to
+    /// framebuffer objects instead.  
+    /// To make it non-current, call `make_gl_context_current(None)` on any window.
+    pub fn make_current_offscreen(&self) -> Result<()> {
+        self.0.make_current_offscreen()
+    }
+}
+
+impl Context {
+    /// Creates an OpenGL context that isn't tied to any visible window, for headless rendering.
+    ///
+    /// Make it current with `GLContext::make_current_offscreen()`.  
+    /// On X11, this is backed by a 1x1 GLX Pbuffer and requires GLX 1.3. On Windows, it
+    /// is backed by a hidden window.
+    pub fn create_offscreen_gl_context(&self, settings: &GLContextSettings) -> Result<GLContext> {
+        self.os.create_offscreen_gl_context(settings).map(GLContext)
+    }
 }
 
 impl Window {
@@ -271,15 +427,43 @@ impl Window {
     }
     /// Presents an OpenGL frame (commonly referred to as "swapping buffers").
     ///
-    /// There must be a current `GLContext` which targets this window.
+    /// There must be a current `GLContext` which targets this window.  
+    /// On X11, if `make_gl_context_current()` was called (for any window) since a `GLContext` was
+    /// made current to this window, this returns an error instead of presenting to
+    /// the wrong drawable.
     pub fn gl_swap_buffers(&self) -> Result<()> {
         self.0.gl_swap_buffers()
     }
     /// Sets the OpenGL swap interval for this window.
     /// 
-    /// There must be a current `GLContext` which targets this window.
+    /// There must be a current `GLContext` which targets this window; The same
+    /// staleness check as for `gl_swap_buffers()` applies.
     pub fn gl_set_swap_interval(&self, interval: GLSwapInterval) -> Result<()> {
         self.0.gl_set_swap_interval(interval)
     }
+    /// Gets the swap interval that was last successfully set via `gl_set_swap_interval()`
+    /// for this window, exactly as it was passed.
+    ///
+    /// This is useful after trying several intervals in turn (e.g `LateSwapTearing`, then
+    /// `VSync`), since only the last one that succeeded is in effect.  
+    /// Returns `None` if it was never set, in which case the driver's default applies
+    /// (usually `VSync`, but users may override it in their driver settings).
+    pub fn gl_swap_interval(&self) -> Option<GLSwapInterval> {
+        self.0.gl_swap_interval()
+    }
+    /// Enables or disables the collection of `PresentStats` for this window.
+    ///
+    /// This is disabled by default; When disabled, `gl_swap_buffers()` has no extra overhead.
+    /// Enabling it again after disabling it starts over from zero.
+    pub fn set_present_stats_collection(&self, enable: bool) {
+        self.0.set_present_stats_collection(enable)
+    }
+    /// Gets timing statistics about this window's presents, as collected since
+    /// `set_present_stats_collection(true)` was called.
+    ///
+    /// All fields are zero (or `None`) if collection is disabled.
+    pub fn present_stats(&self) -> PresentStats {
+        self.0.present_stats()
+    }
 }
 
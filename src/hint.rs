@@ -1,6 +1,9 @@
 //! Hints affect this crate's behaviour globally.
 
+use std::time::Duration;
+use log::LevelFilter;
 use error::Result;
+use log_verbosity;
 use os;
 
 /// Sets a global hint for this crate.
@@ -16,7 +19,13 @@ use os;
 /// the hint is indeed supported and setting it has "succeeded", whatever it means for that particular case.  
 /// If a hint does not make sense for the current platform, expect this function to fail with `ErrorKind::Unsupported`.
 pub fn set_hint(hint: Hint) -> Result<()> {
-    os::set_hint(hint)
+    match hint {
+        Hint::LogLevel(level) => {
+            log_verbosity::set_level(level);
+            Ok(())
+        },
+        hint => os::set_hint(hint),
+    }
 }
 
 /// A global hint for use by this crate.
@@ -29,4 +38,34 @@ pub enum Hint {
     /// If `false`, this crate's Xlib error handlers are used. Otherwise, they aren't. Defaults to
     /// `false` and can be called any time, but only from the main thread.
     XlibDefaultErrorHandlers(bool),
+    /// If `Some`, operations that wait for a reply from the X server first make a roundtrip
+    /// of their own (via XCB, which Xlib is built on), and fail with `ErrorKind::DisplayTimeout`
+    /// if the server doesn't reply within the given duration. Defaults to `None` (wait forever)
+    /// and can be set any time, but only from the main thread.
+    ///
+    /// Xlib itself can't be interrupted, so this is a best-effort watchdog: it catches a dead
+    /// peer (e.g a collapsed SSH tunnel) or a frozen server, but not a server that goes
+    /// unresponsive right after our own roundtrip. If libxcb can't be loaded, only a peer that
+    /// stopped reading requests is caught.
+    /// It covers operations that check for X errors, which include window creation and
+    /// property reads.
+    XlibRoundtripTimeout(Option<Duration>),
+    /// If `true`, controller capabilities (the set of buttons and axes, along with their
+    /// `AxisInfo`) are not queried when a controller is connected, but the first time one of its
+    /// `DeviceInfo` or `ControllerInfo` is requested, then cached. Defaults to `false`.
+    ///
+    /// This trades some latency on the first such query for lower memory usage for controllers
+    /// that are never queried, which may matter on embedded or handheld targets.  
+    /// This only affects controllers that are connected after the hint is set, so set it
+    /// before creating any `Context`. It is only supported on Linux.
+    LazyDeviceInfo(bool),
+    /// The most verbose level at which this crate logs, independently of the global logger's
+    /// level. Defaults to `Info` in debug builds and `Warn` in release builds.
+    ///
+    /// This is useful if you don't use the `log` crate for your own logs, and don't want
+    /// to set up a `log::Log` implementation only to filter out this crate's messages.  
+    /// This can be set at any time, and is supported on all platforms.
+    /// See also `Context::set_log_verbosity()`, which is a coarser version of this.  
+    /// Events logged because of `Context::log_events()` are not filtered by this.
+    LogLevel(LevelFilter),
 }
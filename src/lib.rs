@@ -13,11 +13,17 @@
 extern crate log;
 extern crate vek;
 extern crate uuid;
+#[cfg(feature = "raw-window-handle")]
+extern crate raw_window_handle;
 
 #[cfg(unix)]
 #[macro_use]
 extern crate nix;
 
+// Must come before other modules, so that its macros are in scope for them.
+#[macro_use]
+mod log_verbosity;
+
 
 /// Convenience shortcut for creating a `Context`.
 pub fn init() -> error::Result<Context> {
@@ -34,18 +40,34 @@ pub use error::{ErrorKind, Error};
 pub mod timeout;
 pub use timeout::Timeout;
 pub mod context;
-pub use context::Context;
+pub use context::{Context, ContextOptions, BackendKind, BackendInfo, Verbosity};
 pub mod desktop;
 pub use desktop::Desktop;
 pub mod cursor;
 pub use cursor::{Cursor, SystemCursor, RgbaCursorData, RgbaCursorAnimFrame};
+pub mod pointer_barrier;
+pub use pointer_barrier::{PointerBarrierID, PointerBarrierDirections};
+pub mod monitor;
+pub use monitor::{MonitorID, GammaRamps, DisplayMode, DisplayModeGuard};
 pub mod window;
-pub use window::{Window, WindowSettings, WindowTypeHint, NetWMWindowType};
+pub use window::{Window, NativeWindowHandle, WindowSettings, WindowTypeHint, NetWMWindowType, ImageMask, RgbaImage, WindowGeometry, HitTestResult, HitTestArea, GrabOptions, InputGrab, GrabHolder, WindowStateFlags};
 pub mod device;
 pub mod event;
-pub use event::{Event, EventInstant};
+pub use event::{Event, EventInstant, DeliveredEvent};
+mod event_queue;
+pub use event_queue::{QueuePolicy, QueueOverflow};
+pub mod stats;
+pub use stats::ContextStats;
 pub mod gl;
 pub use gl::*;
+pub mod file_dialog;
+pub use file_dialog::{FileDialogOptions, FileDialogFilter};
+mod master_volume;
+mod process_name;
+mod app_dirs;
+pub use app_dirs::{app_data_dir, app_cache_dir, app_config_dir};
+#[cfg(feature = "synthetic-input")]
+pub mod synthetic_input;
 // pub mod battery;
 
 mod version_cmp;
@@ -57,8 +79,11 @@ macro_rules! os_mod {
             pub mod $os;
             pub use self::$os::{
                 set_hint,
-                OsContext, OsWindow, OsWindowHandle, OsWindowFromHandleParams,
+                app_dir_base,
+                OsContext, OsWindow, OsInputGrab, OsWindowHandle, OsWindowFromHandleParams,
                 OsCursor,
+                OsPointerBarrierID,
+                OsMonitorID, OsDisplayModeGuard,
                 OsGLPixelFormat, OsGLContext,
                 OsEventInstant, OsUnprocessedEvent,
                 device_consts,
@@ -74,6 +99,8 @@ macro_rules! os_mod {
 
 #[cfg(x11)]
 mod x11;
+#[cfg(x11)]
+pub use x11::atoms::PreloadedAtoms as X11Atoms;
 
 #[cfg(target_os="linux")]
 os_mod!{linux}
@@ -0,0 +1,91 @@
+//! Gating of this crate's own log output (see `Hint::LogLevel` and `Context::set_log_verbosity()`).
+//!
+//! The macros below shadow the ones from the `log` crate for the whole crate, which is why
+//! this module has to be declared before any other.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use log::{Level, LevelFilter};
+use context::Verbosity;
+
+#[cfg(debug_assertions)]
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+#[cfg(not(debug_assertions))]
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Warn;
+
+// Log messages aren't tied to a Context, so this has to be a global.
+static LEVEL: AtomicUsize = AtomicUsize::new(DEFAULT_LEVEL as usize);
+
+pub fn set_level(level: LevelFilter) {
+    LEVEL.store(level as usize, Ordering::Relaxed);
+}
+
+pub fn level() -> LevelFilter {
+    match LEVEL.load(Ordering::Relaxed) {
+        x if x == LevelFilter::Off as usize => LevelFilter::Off,
+        x if x == LevelFilter::Error as usize => LevelFilter::Error,
+        x if x == LevelFilter::Warn as usize => LevelFilter::Warn,
+        x if x == LevelFilter::Info as usize => LevelFilter::Info,
+        x if x == LevelFilter::Debug as usize => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+pub fn set(verbosity: Verbosity) {
+    set_level(match verbosity {
+        Verbosity::Quiet => LevelFilter::Warn,
+        Verbosity::Normal => LevelFilter::Info,
+        Verbosity::Verbose => LevelFilter::Trace,
+    })
+}
+
+pub fn get() -> Verbosity {
+    match level() {
+        LevelFilter::Off | LevelFilter::Error | LevelFilter::Warn => Verbosity::Quiet,
+        LevelFilter::Info => Verbosity::Normal,
+        LevelFilter::Debug | LevelFilter::Trace => Verbosity::Verbose,
+    }
+}
+
+pub fn is_enabled(level: Level) -> bool {
+    level <= self::level()
+}
+
+macro_rules! error {
+    ($($arg:tt)+) => {
+        if $crate::log_verbosity::is_enabled(::log::Level::Error) {
+            log!(::log::Level::Error, $($arg)+);
+        }
+    };
+}
+
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        if $crate::log_verbosity::is_enabled(::log::Level::Warn) {
+            log!(::log::Level::Warn, $($arg)+);
+        }
+    };
+}
+
+macro_rules! info {
+    ($($arg:tt)+) => {
+        if $crate::log_verbosity::is_enabled(::log::Level::Info) {
+            log!(::log::Level::Info, $($arg)+);
+        }
+    };
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        if $crate::log_verbosity::is_enabled(::log::Level::Debug) {
+            log!(::log::Level::Debug, $($arg)+);
+        }
+    };
+}
+
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        if $crate::log_verbosity::is_enabled(::log::Level::Trace) {
+            log!(::log::Level::Trace, $($arg)+);
+        }
+    };
+}
@@ -0,0 +1,31 @@
+//! The system's master audio volume.
+
+use context::Context;
+use error::{Result, invalid_arg};
+
+impl Context {
+    /// Sets the volume of the system's default audio output, from `0.` (muted) to `1.` (full).
+    ///
+    /// Out-of-range values (including NaN) are an `InvalidArgument` error.
+    ///
+    /// On Linux, this sets the default PulseAudio sink's volume via the `pactl` tool; If it is
+    /// not installed, this returns `Unsupported`.  
+    /// On Windows, this sets the default render endpoint's volume via `IAudioEndpointVolume`.
+    /// (`ISimpleAudioVolume` would only affect our own audio session).  
+    /// On macOS, this would set the default output device's volume via `AudioObjectSetPropertyData`.
+    pub fn set_master_volume(&self, volume: f32) -> Result<()> {
+        if !(volume >= 0. && volume <= 1.) {
+            return invalid_arg(format!("The volume must be between 0 and 1 (got {})", volume));
+        }
+        self.os.set_master_volume(volume)
+    }
+    /// Gets the volume of the system's default audio output, from `0.` (muted) to `1.` (full).
+    ///
+    /// PulseAudio allows amplifying beyond 100%; This is reported as `1.`.  
+    /// On Linux, this requires `pactl` from PulseAudio 15 or later; With older versions, this
+    /// returns `Unsupported`.
+    /// See `set_master_volume()` for details.
+    pub fn master_volume(&self) -> Result<f32> {
+        self.os.master_volume().map(|volume| volume.max(0.).min(1.))
+    }
+}
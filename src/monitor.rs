@@ -0,0 +1,240 @@
+//! Monitors, their display modes, and their gamma ramps.
+//!
+//! Display modes can be switched for exclusive full-screen, see `Context::set_display_mode()`.
+//!
+//! Gamma ramps are look-up tables applied by the display hardware to every pixel before it is
+//! sent to the monitor. They are typically used for brightness/contrast adjustments, or to warm
+//! up colors at night.
+//!
+//! Any ramp that is modified via a `Context` is snapshotted before its first modification, and
+//! automatically restored when the `Context` is dropped.
+//!
+//! # Interaction with other color management clients
+//!
+//! Programs such as `redshift`, GNOME's Night Light, or Windows' Night light also modify gamma
+//! ramps, and there is no way to detect them. Whoever writes last wins; in particular:
+//!
+//! - If one of these programs changes the ramps while we have modified them, restoring the
+//!   snapshot on drop may undo its changes (until it applies them again, which they usually do periodically);
+//! - `set_monitor_brightness_multiplier()` is relative to the snapshot, so it preserves the
+//!   color temperature that was in effect at the time of the first modification, but not
+//!   later changes.
+
+use context::Context;
+use error::{self, Result};
+use Rect;
+use os::{OsMonitorID, OsDisplayModeGuard};
+
+/// A lightweight ID for a monitor, as returned by `Context::monitors()`.
+///
+/// On X11, this is actually an XRandR CRTC.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MonitorID(pub(crate) OsMonitorID);
+
+/// A resolution, refresh rate and color depth that a monitor supports,
+/// as returned by `Context::enumerate_display_modes()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct DisplayMode {
+    /// The width, in pixels.
+    pub width: u32,
+    /// The height, in pixels.
+    pub height: u32,
+    /// The refresh rate, in millihertz (e.g `59940` for 59.94 Hz), or zero if unknown.
+    pub refresh_rate_mhz: u32,
+    /// The number of bits per pixel.
+    ///
+    /// On X11, this is the depth of the default screen, which is the same for all modes.
+    pub bits_per_pixel: u8,
+}
+
+/// Restores a monitor's original display mode when dropped, as returned by `Context::set_display_mode()`.
+#[derive(Debug)]
+pub struct DisplayModeGuard(pub(crate) OsDisplayModeGuard);
+
+/// The red, green and blue gamma ramps of a monitor.
+///
+/// All three ramps have the same length, which is imposed by the monitor
+/// (this is usually 256, but some X11 drivers report 1024 or more).
+/// Each entry maps an input intensity (its index) to an output intensity
+/// in the `0..=65535` range.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct GammaRamps {
+    pub red: Vec<u16>,
+    pub green: Vec<u16>,
+    pub blue: Vec<u16>,
+}
+
+impl GammaRamps {
+    /// Creates linear ramps of the given size, i.e ramps that don't change anything.
+    pub fn linear(size: usize) -> Self {
+        let ramp: Vec<u16> = (0..size).map(|i| {
+            if size <= 1 {
+                return 0xffff;
+            }
+            (i as u64 * 0xffff / (size as u64 - 1)) as u16
+        }).collect();
+        Self {
+            red: ramp.clone(),
+            green: ramp.clone(),
+            blue: ramp,
+        }
+    }
+    /// Gets the size of these ramps, or `None` if the red, green and blue ramps don't have the same length.
+    pub fn size(&self) -> Option<usize> {
+        let size = self.red.len();
+        if self.green.len() != size || self.blue.len() != size {
+            return None;
+        }
+        Some(size)
+    }
+    /// Returns a copy of these ramps with `size` entries each, linearly interpolated from these ones.
+    ///
+    /// This is useful for applying the same ramps to monitors that have different ramp sizes.
+    pub fn resampled(&self, size: usize) -> Self {
+        let resample = |ramp: &[u16]| -> Vec<u16> {
+            if ramp.is_empty() {
+                return GammaRamps::linear(size).red;
+            }
+            (0..size).map(|i| {
+                if size <= 1 || ramp.len() == 1 {
+                    return ramp[ramp.len() - 1];
+                }
+                let x = i as f64 * (ramp.len() - 1) as f64 / (size - 1) as f64;
+                let lo = x.floor() as usize;
+                let hi = (lo + 1).min(ramp.len() - 1);
+                let t = x - lo as f64;
+                (ramp[lo] as f64 * (1. - t) + ramp[hi] as f64 * t).round() as u16
+            }).collect()
+        };
+        Self {
+            red: resample(&self.red),
+            green: resample(&self.green),
+            blue: resample(&self.blue),
+        }
+    }
+    /// Returns a copy of these ramps where each entry is multiplied by `multiplier`, saturating at 65535.
+    pub fn multiplied(&self, multiplier: f32) -> Self {
+        let mul = |ramp: &[u16]| ramp.iter().map(|v| {
+            (*v as f32 * multiplier).round().max(0.).min(65535.) as u16
+        }).collect();
+        Self {
+            red: mul(&self.red),
+            green: mul(&self.green),
+            blue: mul(&self.blue),
+        }
+    }
+}
+
+impl Context {
+    /// Gets the monitors that are currently active.
+    pub fn monitors(&self) -> Result<Vec<MonitorID>> {
+        self.os.monitors().map(|v| v.into_iter().map(MonitorID).collect())
+    }
+    /// Gets the position and size of a monitor, in desktop coordinates.
+    pub fn monitor_rect(&self, monitor: MonitorID) -> Result<Rect<i32, u32>> {
+        self.os.monitor_rect(monitor.0)
+    }
+    /// Gets the primary monitor, if any.
+    ///
+    /// On X11, this is the monitor of XRandR's primary output, which may not be set.  
+    /// On Windows, this is the monitor whose top-left corner is at the origin of the desktop.
+    pub fn primary_monitor(&self) -> Result<Option<MonitorID>> {
+        self.os.primary_monitor().map(|m| m.map(MonitorID))
+    }
+    /// Lists the display modes that a monitor supports, without duplicates.
+    ///
+    /// On X11, these are the modes of the first output of the monitor's CRTC.  
+    /// On Windows, these come from `EnumDisplaySettingsW()`.
+    pub fn enumerate_display_modes(&self, monitor: MonitorID) -> Result<Vec<DisplayMode>> {
+        self.os.display_modes(monitor.0)
+    }
+    /// Gets the display mode that a monitor is currently using.
+    pub fn current_display_mode(&self, monitor: MonitorID) -> Result<DisplayMode> {
+        self.os.current_display_mode(monitor.0)
+    }
+    /// Switches a monitor to another display mode, which must be one of those returned by
+    /// `enumerate_display_modes()`.
+    ///
+    /// The original mode is restored when the returned guard is dropped, so keep it alive
+    /// for as long as your application is in exclusive full-screen.
+    ///
+    /// On X11, switching to a mode that doesn't fit in the X screen (which spans all monitors)
+    /// returns `Unsupported`. Windows are not moved or resized on your behalf.  
+    /// On Windows, this calls `ChangeDisplaySettingsExW()` with `CDS_FULLSCREEN`, which means
+    /// that the change is temporary and Windows reverts it if the application quits.
+    pub fn set_display_mode(&self, monitor: MonitorID, mode: &DisplayMode) -> Result<DisplayModeGuard> {
+        self.os.set_display_mode(monitor.0, mode).map(DisplayModeGuard)
+    }
+    /// Gets the current gamma ramps of a monitor.
+    pub fn monitor_gamma(&self, monitor: MonitorID) -> Result<GammaRamps> {
+        self.os.monitor_gamma(monitor.0)
+    }
+    /// Sets the gamma ramps of a monitor.
+    ///
+    /// The ramps must have the same size as the ones returned by `monitor_gamma()`.
+    /// The original ramps are restored when this context is dropped.
+    pub fn set_monitor_gamma(&self, monitor: MonitorID, ramps: &GammaRamps) -> Result<()> {
+        if ramps.size().is_none() {
+            return error::invalid_arg("The red, green and blue gamma ramps must have the same length");
+        }
+        self.os.set_monitor_gamma(monitor.0, ramps)
+    }
+    /// Gets linear ramps of 256 entries, i.e ramps that don't change anything.
+    ///
+    /// These can be given to `set_gamma_ramp()` to undo the effects of other color management clients.
+    pub fn identity_gamma_ramp() -> GammaRamps {
+        GammaRamps::linear(256)
+    }
+    /// Gets the gamma ramps of the first monitor returned by `monitors()`, resampled to 256 entries.
+    ///
+    /// This is a convenience for the common case where all monitors share the same ramps,
+    /// e.g when they were set via `set_gamma_ramp()`.
+    pub fn gamma_ramp(&self) -> Result<GammaRamps> {
+        let monitor = match self.monitors()?.into_iter().next() {
+            Some(monitor) => monitor,
+            None => return error::failed("There is no active monitor"),
+        };
+        self.monitor_gamma(monitor).map(|ramps| ramps.resampled(256))
+    }
+    /// Sets the gamma ramps of all active monitors at once, e.g for night-mode software.
+    ///
+    /// The ramps may have any size, as they are resampled to the size of each monitor's ramps.
+    /// As with `set_monitor_gamma()`, the original ramps are restored when this context is dropped.  
+    /// This is all-or-nothing: if the ramps of a monitor can't be set, the monitors that were
+    /// already changed get their previous ramps back.
+    pub fn set_gamma_ramp(&self, ramps: &GammaRamps) -> Result<()> {
+        match ramps.size() {
+            None => return error::invalid_arg("The red, green and blue gamma ramps must have the same length"),
+            Some(0) => return error::invalid_arg("The gamma ramps must not be empty"),
+            Some(_) => (),
+        }
+        let mut previous = Vec::new();
+        for monitor in self.monitors()? {
+            previous.push((monitor, self.monitor_gamma(monitor)?));
+        }
+        for (i, &(monitor, ref previous_ramps)) in previous.iter().enumerate() {
+            if let Err(e) = self.set_monitor_gamma(monitor, &ramps.resampled(previous_ramps.red.len())) {
+                for &(monitor, ref previous_ramps) in previous[..i].iter().rev() {
+                    if let Err(e) = self.set_monitor_gamma(monitor, previous_ramps) {
+                        warn!("Could not restore the gamma ramps of {:?}: {}", monitor, e);
+                    }
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    /// Convenience for scaling the brightness of a monitor, by synthesizing gamma ramps.
+    ///
+    /// The multiplier is relative to the monitor's original ramps (as they were before this
+    /// context first modified them), so `1.` restores them and the colors' balance is preserved
+    /// (e.g a night-light tint stays as it was).
+    pub fn set_monitor_brightness_multiplier(&self, monitor: MonitorID, multiplier: f32) -> Result<()> {
+        if !multiplier.is_finite() || multiplier < 0. {
+            return error::invalid_arg(format!("Invalid brightness multiplier: {}", multiplier));
+        }
+        let original = self.os.original_monitor_gamma(monitor.0)?;
+        self.set_monitor_gamma(monitor, &original.multiplied(multiplier))
+    }
+}
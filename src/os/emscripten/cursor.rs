@@ -1,9 +1,11 @@
 use cursor::{SystemCursor, RgbaCursorData, RgbaCursorAnimFrame};
 use error::Result;
 use super::{OsContext, OsWindow};
-use Extent2;
+use pointer_barrier::PointerBarrierDirections;
+use {Vec2, Extent2};
 
 pub type OsCursor = ();
+pub type OsPointerBarrierID = ();
 
 impl OsContext {
     pub fn create_system_cursor(&self, s: SystemCursor) -> Result<OsCursor> {
@@ -43,3 +45,12 @@ impl OsWindow {
         unimplemented!()
     }
 }
+
+impl OsContext {
+    pub fn create_pointer_barrier(&self, from: Vec2<i32>, to: Vec2<i32>, directions: PointerBarrierDirections) -> Result<OsPointerBarrierID> {
+        unimplemented!()
+    }
+    pub fn destroy_pointer_barrier(&self, barrier: OsPointerBarrierID) -> Result<()> {
+        unimplemented!()
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::ops::Range;
 use std::path::Path;
 use std::collections::HashMap;
@@ -24,6 +25,12 @@ pub mod consts {
 } 
 
 pub type OsDeviceID = ();
+
+impl fmt::Display for DeviceID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        unimplemented!()
+    }
+}
 #[derive(Debug, Clone, PartialEq)]
 pub struct OsAxisInfo;
 #[derive(Debug, Clone, PartialEq)]
@@ -50,8 +57,10 @@ impl OsDeviceInfo {
     pub fn bus(&self) -> Option<Bus> { unimplemented!() }
     pub fn driver_name(&self) -> Option<&str> { unimplemented!() }
     pub fn driver_version(&self) -> Option<&str> { unimplemented!() }
+    pub fn physical_location(&self) -> Option<&str> { unimplemented!() }
     pub fn is_physical(&self) -> Option<bool> { unimplemented!() }
     pub fn controller(&self) -> Option<&ControllerInfo> { unimplemented!() }
+    pub fn controller_mut(&mut self) -> Option<&mut ControllerInfo> { unimplemented!() }
     pub fn mouse(&self) -> Option<&MouseInfo> { unimplemented!() }
     pub fn keyboard(&self) -> Option<&KeyboardInfo> { unimplemented!() }
     pub fn touch(&self) -> Option<&TouchInfo> { unimplemented!() }
@@ -65,4 +74,7 @@ impl OsContext {
     pub fn ping_device(&self, id: DeviceID) -> device::Result<()> {
         unimplemented!()
     }
+    pub fn is_device_id_current(&self, id: DeviceID) -> bool {
+        unimplemented!()
+    }
 }
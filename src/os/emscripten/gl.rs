@@ -1,4 +1,7 @@
 use std::os::raw::c_char;
+use gl::{GLContextSettings, GLApi};
+use error::Result;
+use super::OsContext;
 
 #[derive(Debug)]
 pub struct OsGLContext;
@@ -6,7 +9,19 @@ pub type OsGLPixelFormat = ();
 pub type OsGLProc = ();
 
 impl OsGLContext {
+    pub fn api(&self) -> GLApi {
+        unimplemented!()
+    }
     pub unsafe fn get_proc_address(&self, name: *const c_char) -> Option<OsGLProc> {
         unimplemented!()
     }
+    pub fn make_current_offscreen(&self) -> Result<()> {
+        unimplemented!()
+    }
+}
+
+impl OsContext {
+    pub fn create_offscreen_gl_context(&self, settings: &GLContextSettings) -> Result<OsGLContext> {
+        unimplemented!()
+    }
 }
@@ -5,5 +5,8 @@ pub fn set_hint(hint: Hint) -> Result<()> {
     match hint {
         Hint::XlibDefaultErrorHandlers(_) => unsupported_unexplained(),
         Hint::XlibXInitThreads => unsupported_unexplained(),
+        Hint::XlibRoundtripTimeout(_) => unsupported_unexplained(),
+        Hint::LazyDeviceInfo(_) => unsupported_unexplained(),
+        Hint::LogLevel(_) => unreachable!("Handled by hint::set_hint()"),
     }
 }
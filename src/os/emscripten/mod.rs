@@ -1,12 +1,16 @@
 pub mod hint;
 pub use self::hint::set_hint;
+pub mod app_dirs;
+pub use self::app_dirs::app_dir_base;
 pub mod context;
 pub use self::context::OsContext;
 pub mod window;
-pub use self::window::{OsWindow, OsWindowHandle, OsWindowFromHandleParams};
+pub use self::window::{OsWindow, OsInputGrab, OsWindowHandle, OsWindowFromHandleParams};
 pub mod desktop;
 pub mod cursor;
-pub use self::cursor::OsCursor;
+pub use self::cursor::{OsCursor, OsPointerBarrierID};
+pub mod monitor;
+pub use self::monitor::{OsMonitorID, OsDisplayModeGuard};
 pub mod gl;
 pub use self::gl::{OsGLContext, OsGLPixelFormat, OsGLProc};
 pub mod event_instant;
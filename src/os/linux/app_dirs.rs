@@ -0,0 +1,25 @@
+//! XDG base directories (https://specifications.freedesktop.org/basedir-spec/latest/).
+
+use std::env;
+use std::path::PathBuf;
+use app_dirs::AppDirKind;
+use error::{Result, failed};
+
+pub fn app_dir_base(kind: AppDirKind) -> Result<PathBuf> {
+    let (var, fallback) = match kind {
+        AppDirKind::Data => ("XDG_DATA_HOME", ".local/share"),
+        AppDirKind::Cache => ("XDG_CACHE_HOME", ".cache"),
+        AppDirKind::Config => ("XDG_CONFIG_HOME", ".config"),
+    };
+    // The spec says that relative paths are invalid and should be ignored.
+    if let Some(dir) = env::var_os(var).map(PathBuf::from) {
+        if dir.is_absolute() {
+            return Ok(dir);
+        }
+        warn!("Ignoring ${} because it is not an absolute path: `{}`", var, dir.display());
+    }
+    match env::var_os("HOME").map(PathBuf::from) {
+        Some(ref home) if home.is_absolute() => Ok(home.join(fallback)),
+        _ => failed(format!("Neither ${} nor $HOME are set to an absolute path", var)),
+    }
+}
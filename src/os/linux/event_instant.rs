@@ -2,6 +2,7 @@ use super::libc as c;
 use super::x11::xlib as x;
 
 use std::ops::{Add, Sub, AddAssign, SubAssign};
+use std::fmt::{self, Display, Formatter};
 use std::cmp::Ordering;
 use std::time::Duration;
 use time_utils;
@@ -17,6 +18,17 @@ pub enum OsEventInstant {
     // by not having the same timestamp format).
 }
 
+// These timestamps are relative to some unspecified origin (e.g X server start-up, or boot time).
+impl Display for OsEventInstant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            OsEventInstant::X11EventTimeMillis(ms) => write!(f, "+{}.{:03}s", ms / 1000, ms % 1000),
+            OsEventInstant::LinuxInputEventTimeval { tv_sec, tv_usec } => write!(f, "+{}.{:06}s", tv_sec, tv_usec),
+            OsEventInstant::UdevUsecs(us) => write!(f, "+{}.{:06}s", us / 1_000_000, us % 1_000_000),
+        }
+    }
+}
+
 impl PartialOrd for OsEventInstant {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (*self, *other) {
@@ -35,7 +47,40 @@ impl PartialOrd for OsEventInstant {
     }
 }
 
+// udev's USEC_INITIALIZED comes from CLOCK_MONOTONIC, and evdev timestamps come from
+// CLOCK_REALTIME (unless someone changed it via EVIOCSCLOCKID, which we don't).
+fn clock_now(clock: c::clockid_t) -> Option<c::timespec> {
+    unsafe {
+        let mut ts: c::timespec = ::std::mem::zeroed();
+        match c::clock_gettime(clock, &mut ts) {
+            0 => Some(ts),
+            _ => None,
+        }
+    }
+}
+
 impl OsEventInstant {
+    /// Gets the current instant from the same clock as `self`, if it can be read locally.
+    /// The X server's clock can't.
+    pub fn now_from_same_clock(&self) -> Option<Self> {
+        match *self {
+            OsEventInstant::X11EventTimeMillis(_) => None,
+            OsEventInstant::UdevUsecs(_) => clock_now(c::CLOCK_MONOTONIC).map(|ts| {
+                OsEventInstant::UdevUsecs(ts.tv_sec as u64 * 1_000_000 + ts.tv_nsec as u64 / 1_000)
+            }),
+            OsEventInstant::LinuxInputEventTimeval { .. } => clock_now(c::CLOCK_REALTIME).map(|ts| {
+                OsEventInstant::LinuxInputEventTimeval { tv_sec: ts.tv_sec, tv_usec: (ts.tv_nsec / 1_000) as _ }
+            }),
+        }
+    }
+    /// Is this clock guaranteed to never jump backwards?
+    pub fn is_monotonic(&self) -> bool {
+        match *self {
+            OsEventInstant::X11EventTimeMillis(_) | OsEventInstant::UdevUsecs(_) => true,
+            // CLOCK_REALTIME follows changes to the system time.
+            OsEventInstant::LinuxInputEventTimeval { .. } => false,
+        }
+    }
     pub fn duration_since(&self, earlier: Self) -> Option<Duration> {
         assert!(self >= &earlier); // Normally already checked by EventInstant::duration_since
         match (*self, earlier) {
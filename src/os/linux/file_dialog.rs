@@ -0,0 +1,302 @@
+//! File dialogs, via the XDG Desktop Portal's `FileChooser` interface over D-Bus.
+//!
+//! The portal works in sandboxes (Flatpak, Snap) and uses the desktop's own dialogs.
+//! If there's no portal (or no D-Bus at all), we fall back to the `zenity` or `kdialog` tools.
+
+use std::io::{self, Read};
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::process::{self, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+use file_dialog::FileDialogOptions;
+use error::{Result, ErrorKind, failed, unsupported};
+use x11::dbus::{SessionBus, DBUS_TYPE_ARRAY, DBUS_TYPE_STRUCT};
+use super::OsContext;
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_FILE_CHOOSER: &str = "org.freedesktop.portal.FileChooser";
+const PORTAL_REQUEST: &str = "org.freedesktop.portal.Request";
+const PORTAL_RESPONSE_MATCH_RULE: &str = "type='signal',interface='org.freedesktop.portal.Request',member='Response'";
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+enum DialogKind {
+    Open,
+    Save,
+}
+
+fn zenity_command(kind: DialogKind, options: &FileDialogOptions) -> Command {
+    let &FileDialogOptions { ref title, ref filters, ref default_directory, ref default_file_name, multiple } = options;
+    let mut cmd = Command::new("zenity");
+    cmd.arg("--file-selection");
+    if kind == DialogKind::Save {
+        cmd.arg("--save");
+    } else if multiple {
+        cmd.args(&["--multiple", "--separator=\n"]);
+    }
+    if let Some(ref title) = *title {
+        cmd.arg(format!("--title={}", title));
+    }
+    // A trailing slash selects the directory rather than a file named like it.
+    let mut filename = default_directory.as_ref().map(|dir| dir.join("").into_os_string()).unwrap_or_default();
+    if let Some(ref name) = *default_file_name {
+        filename.push(name);
+    }
+    if !filename.is_empty() {
+        let mut arg = OsStr::new("--filename=").to_os_string();
+        arg.push(filename);
+        cmd.arg(arg);
+    }
+    for filter in filters.iter() {
+        let patterns: Vec<_> = filter.extensions.iter().map(|ext| format!("*.{}", ext)).collect();
+        cmd.arg(format!("--file-filter={} | {}", filter.name, patterns.join(" ")));
+    }
+    cmd
+}
+
+fn kdialog_command(kind: DialogKind, options: &FileDialogOptions) -> Command {
+    let &FileDialogOptions { ref title, ref filters, ref default_directory, ref default_file_name, multiple } = options;
+    let mut cmd = Command::new("kdialog");
+    if let Some(ref title) = *title {
+        cmd.arg("--title").arg(title);
+    }
+    cmd.arg(match kind {
+        DialogKind::Open => "--getopenfilename",
+        DialogKind::Save => "--getsavefilename",
+    });
+    let mut start = default_directory.clone().unwrap_or_else(|| PathBuf::from("."));
+    if let Some(ref name) = *default_file_name {
+        start.push(name);
+    }
+    cmd.arg(start);
+    if !filters.is_empty() {
+        let filters: Vec<_> = filters.iter().map(|filter| {
+            let patterns: Vec<_> = filter.extensions.iter().map(|ext| format!("*.{}", ext)).collect();
+            format!("{} ({})", filter.name, patterns.join(" "))
+        }).collect();
+        cmd.arg(filters.join("|"));
+    }
+    if kind == DialogKind::Open && multiple {
+        cmd.args(&["--multiple", "--separate-output"]);
+    }
+    cmd
+}
+
+/// Decodes a `file://` URI, as returned by the portal. Paths are not necessarily UTF-8.
+fn path_from_file_uri(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.trim_start_matches("file://").as_bytes();
+    if encoded.len() == uri.len() {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let decoded = if encoded[i] == b'%' && i + 2 < encoded.len() {
+            ::std::str::from_utf8(&encoded[i + 1 .. i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match decoded {
+            Some(b) => {
+                bytes.push(b);
+                i += 3;
+            },
+            None => {
+                bytes.push(encoded[i]);
+                i += 1;
+            },
+        }
+    }
+    Some(PathBuf::from(OsStr::from_bytes(&bytes)))
+}
+
+/// Calls `OpenFile` or `SaveFile`, returning the handle of the request.
+fn request_portal_file_dialog(bus: &SessionBus, kind: DialogKind, options: &FileDialogOptions) -> Result<String> {
+    static NEXT_TOKEN: AtomicUsize = AtomicUsize::new(0);
+    let &FileDialogOptions { ref title, ref filters, ref default_directory, ref default_file_name, multiple } = options;
+    let handle_token = format!("dmc{}_{}", process::id(), NEXT_TOKEN.fetch_add(1, Ordering::SeqCst));
+    let method = match kind {
+        DialogKind::Open => "OpenFile",
+        DialogKind::Save => "SaveFile",
+    };
+    let mut msg = bus.new_method_call(PORTAL_DESTINATION, PORTAL_PATH, PORTAL_FILE_CHOOSER, method)?;
+    {
+        let mut args = msg.append();
+        // No parent window identifier; The dialog is not made transient for any of ours.
+        args.string("").string(title.as_ref().map_or("", String::as_str));
+        args.container(DBUS_TYPE_ARRAY, Some("{sv}"), |dict| {
+            dict.dict_entry("handle_token", "s", |v| { v.string(&handle_token); });
+            if kind == DialogKind::Open {
+                dict.dict_entry("multiple", "b", |v| { v.boolean(multiple); });
+            }
+            if !filters.is_empty() {
+                dict.dict_entry("filters", "a(sa(us))", |v| {
+                    v.container(DBUS_TYPE_ARRAY, Some("(sa(us))"), |array| for filter in filters.iter() {
+                        array.container(DBUS_TYPE_STRUCT, None, |f| {
+                            f.string(&filter.name).container(DBUS_TYPE_ARRAY, Some("(us)"), |patterns| for ext in filter.extensions.iter() {
+                                // 0 means a glob pattern, as opposed to a MIME type.
+                                patterns.container(DBUS_TYPE_STRUCT, None, |p| { p.uint32(0).string(&format!("*.{}", ext)); });
+                            });
+                        });
+                    });
+                });
+            }
+            if let Some(ref dir) = *default_directory {
+                // A NUL-terminated byte string, because paths are not necessarily UTF-8.
+                let mut bytes = dir.as_os_str().as_bytes().to_vec();
+                bytes.push(0);
+                dict.dict_entry("current_folder", "ay", |v| { v.byte_array(&bytes); });
+            }
+            if kind == DialogKind::Save {
+                if let Some(ref name) = *default_file_name {
+                    dict.dict_entry("current_name", "s", |v| { v.string(name); });
+                }
+            }
+        });
+        args.finish()?;
+    }
+    let reply = match bus.call(&msg, -1) {
+        Ok(reply) => reply,
+        Err(ref e) if e.is_unknown_service() => return unsupported(format!("The XDG Desktop Portal's FileChooser is unavailable: {}", e.message)),
+        Err(e) => return failed(format!("{}.{} failed: {} ({})", PORTAL_FILE_CHOOSER, method, e.message, e.name)),
+    };
+    // Older portals don't honor `handle_token`, so the handle in the reply is the one that counts.
+    match reply.read().string() {
+        Some(handle) => Ok(handle),
+        None => failed(format!("{}.{} replied without a request handle", PORTAL_FILE_CHOOSER, method)),
+    }
+}
+
+impl OsContext {
+    /// Shows a dialog via the XDG Desktop Portal, returning `Unsupported` if there's no portal.
+    fn run_portal_file_dialog(&self, kind: DialogKind, options: &FileDialogOptions) -> Result<Option<Vec<PathBuf>>> {
+        let bus = self.x11.session_bus()?;
+        // Subscribe before making the request, so that a quick response can't be missed.
+        if let Err(e) = bus.add_match(PORTAL_RESPONSE_MATCH_RULE) {
+            return failed(format!("Could not listen to portal responses: {}", e.message));
+        }
+        let result = request_portal_file_dialog(&bus, kind, options).and_then(|handle| self.wait_for_portal_response(&bus, &handle));
+        let _ = bus.remove_match(PORTAL_RESPONSE_MATCH_RULE);
+        result
+    }
+    fn wait_for_portal_response(&self, bus: &SessionBus, handle: &str) -> Result<Option<Vec<PathBuf>>> {
+        let response = loop {
+            match bus.next_message(10)? {
+                Some(msg) => if msg.is_signal(PORTAL_REQUEST, "Response") && msg.has_path(handle) {
+                    break msg;
+                },
+                // Keep our windows responsive; translated events stay queued for the user.
+                None => self.x11.pump_pending_x_events(),
+            }
+        };
+        // (u response, a{sv} results)
+        let mut args = response.read();
+        match args.uint32() {
+            Some(0) => (),
+            Some(1) => return Ok(None),
+            code => return failed(format!("The file chooser portal failed (response code {:?})", code)),
+        }
+        args.next();
+        let mut paths = Vec::new();
+        if let Some(mut results) = args.recurse() {
+            loop {
+                if let Some(mut entry) = results.recurse() {
+                    if entry.string() == Some("uris".to_owned()) && entry.next() {
+                        if let Some(mut uris) = entry.recurse().as_mut().and_then(|variant| variant.recurse()) {
+                            loop {
+                                if let Some(uri) = uris.string() {
+                                    match path_from_file_uri(&uri) {
+                                        Some(path) => paths.push(path),
+                                        None => warn!("The file chooser portal returned a non-file URI: {}", uri),
+                                    }
+                                }
+                                if !uris.next() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                if !results.next() {
+                    break;
+                }
+            }
+        }
+        if paths.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(paths))
+    }
+    /// Shows a dialog via the portal or, failing that, the first available dialog tool.
+    fn run_file_dialog(&self, kind: DialogKind, options: &FileDialogOptions) -> Result<Option<Vec<PathBuf>>> {
+        match self.run_portal_file_dialog(kind, options) {
+            Err(ref e) if e.kind == ErrorKind::Unsupported { requires_optin: false } => {
+                info!("Falling back to `zenity` or `kdialog` for the file dialog: {}", e);
+            },
+            result => return result,
+        }
+        let mut child = None;
+        for mut cmd in vec![zenity_command(kind, options), kdialog_command(kind, options)] {
+            match cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+                Ok(c) => {
+                    child = Some(c);
+                    break;
+                },
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return failed(format!("Could not run {:?}: {}", cmd, e)),
+            }
+        }
+        let mut child = match child {
+            Some(child) => child,
+            None => return unsupported("File dialogs require the `zenity` or `kdialog` tool, neither of which was found"),
+        };
+
+        // Read from another thread, so that the tool can't block on a full pipe while we wait for it.
+        let mut stdout = child.stdout.take().unwrap();
+        let reader = thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) => {
+                    // Keep our windows responsive; translated events stay queued for the user.
+                    self.x11.pump_pending_x_events();
+                    thread::sleep(Duration::from_millis(10));
+                },
+                Err(e) => return failed(format!("Waiting for the file dialog failed: {}", e)),
+            }
+        };
+        let output = match reader.join() {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return failed(format!("Reading the file dialog's output failed: {}", e)),
+            Err(_) => return failed("Reading the file dialog's output panicked"),
+        };
+        // Both tools exit with 1 when cancelled.
+        match status.code() {
+            Some(0) => (),
+            Some(1) => return Ok(None),
+            _ => return failed(format!("The file dialog exited with {}", status)),
+        }
+        // Paths are not necessarily UTF-8.
+        let paths: Vec<_> = output.split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| PathBuf::from(OsStr::from_bytes(line)))
+            .collect();
+        if paths.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(paths))
+    }
+    pub fn show_open_file_dialog(&self, options: &FileDialogOptions) -> Result<Option<Vec<PathBuf>>> {
+        self.run_file_dialog(DialogKind::Open, options)
+    }
+    pub fn show_save_file_dialog(&self, options: &FileDialogOptions) -> Result<Option<PathBuf>> {
+        self.run_file_dialog(DialogKind::Save, options).map(|paths| paths.and_then(|paths| paths.into_iter().next()))
+    }
+}
@@ -5,23 +5,30 @@ extern crate libevdev_sys;
 extern crate libudev_sys;
 extern crate libc as c;
 
-use std::ops::Range;
+use std::ops::{Range, Deref};
 use std::fmt::{self, Display, Formatter};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
 use std::ffi::{CStr, OsStr};
 use std::path::{Path, PathBuf};
+use std::fs;
+use std::borrow::Cow;
 use std::ptr;
 use std::mem;
 use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use event::{Event, EventInstant};
+use event_queue::{EventQueue, QueuePolicy};
 use os::{OsEventInstant, OsDeviceID};
-use device::{self, DeviceID, DeviceInfo, ControllerInfo, ControllerAxis, ControllerState, ControllerButton, ButtonState, Bus, VibrationState, AxisInfo, UsbIDs, MouseInfo, KeyboardInfo, TouchInfo, TabletInfo};
+use device::{self, DeviceID, DeviceInfo, ControllerInfo, ControllerAxis, ControllerState, ControllerButton, ButtonState, Bus, VibrationState, EventMode, AxisInfo, UsbIDs, MouseInfo, KeyboardInfo, TouchInfo, TabletInfo};
 
 use self::c::{c_int, c_uint, c_char};
 
 use uuid::Uuid as Guid;
-use nix::{self, errno::{self, Errno}};
+use nix::{self, errno::{self, Errno}, poll::{poll, PollFd, EventFlags}};
 
 use self::libevdev_sys::evdev;
 use self::libevdev_sys::evdev::libevdev_read_flag;
@@ -85,6 +92,10 @@ enum UdevDeviceAction {
     Move,
     Online,
     Offline,
+    /// A driver was bound to the device (kernel 4.14+).
+    Bind,
+    /// A driver was unbound from the device (kernel 4.14+).
+    Unbind,
     Other(String),
 }
 
@@ -102,6 +113,18 @@ pub struct LinuxdevContext {
     evdev_controllers: RefCell<HashMap<LinuxdevToken, Linuxdev>>,
     token_generator: RefCell<LinuxdevTokenGenerator>,
     pending_translated_events: RefCell<VecDeque<Event>>,
+    /// The event queue of each `LinuxdevHandle`, into which `pending_translated_events` are copied.
+    subscribers: RefCell<Vec<Weak<RefCell<EventQueue>>>>,
+    /// For each disconnected device, how many subscribers have yet to see its `DeviceDisconnected`
+    /// event. The device is only forgotten once all of them have.
+    pending_removals: RefCell<HashMap<LinuxdevToken, usize>>,
+    /// Controllers that don't have a player index are not in there.
+    player_indices: RefCell<HashMap<LinuxdevToken, u32>>,
+    /// Last known value of `time_spent_suspended_ns()`.
+    time_spent_suspended_ns: Cell<Option<i64>>,
+    // For `ContextStats`.
+    queue_high_water_mark: Cell<usize>,
+    udev_actions_seen: Cell<u64>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -120,6 +143,15 @@ struct Linuxdev {
     /// A libevdev handle is obtained from an open file descriptor, but this may fail for some
     /// reason.
     evdev: Option<LinuxdevEvdev>,
+    /// Set when udev reports that the device's driver was unbound, until it is bound again.
+    /// In the meantime, the evdev handle may not be usable.
+    is_driver_unbound: Cell<bool>,
+    /// With `EventMode::PollOnly`, events are still read (so that libevdev's state is up-to-date)
+    /// but button and axis events are not queued.
+    event_mode: Cell<EventMode>,
+    /// The value of each axis, as last reported by a `ControllerAxisMotion` event.
+    /// After a `SYN_DROPPED`, only axes whose value differs from this are reported again.
+    last_reported_axes: RefCell<HashMap<ControllerAxis, f64>>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -155,13 +187,19 @@ struct LinuxdevEvdev {
     /// The registered Force-Feedback ID for rumble effects, or -1.
     rumble_ff_id: Cell<i16>,
     props: EvdevProps,
-    buttons: HashSet<ControllerButton>,
-    axes: HashMap<ControllerAxis, AxisInfo>,
+    /// Cached capabilities; If `Hint::LazyDeviceInfo` was set, they are `None` until they are
+    /// first needed.
+    buttons: RefCell<Option<HashSet<ControllerButton>>>,
+    axes: RefCell<Option<HashMap<ControllerAxis, AxisInfo>>>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct EvdevProps {
-    name: String,
+    name: Option<String>,
+    /// Unique identifier (e.g serial number) as reported by the EVIOCGUNIQ ioctl, if any.
+    uniq: Option<String>,
+    /// Physical location (e.g `usb-0000:00:14.0-2/input0`) as reported by the EVIOCGPHYS ioctl, if any.
+    phys: Option<String>,
 	driver_version: (u16, u8, u8),
     id_bustype: c_int,
     id_product: u16,
@@ -219,6 +257,7 @@ pub struct LinuxdevDeviceInfo {
     bus: Option<Bus>,
     driver_name: Option<String>,
     driver_version: Option<String>,
+    physical_location: Option<String>,
     controller: ControllerInfo,
 }
 
@@ -230,6 +269,8 @@ pub struct OsControllerInfo {
     supports_rumble: bool,
     buttons: HashSet<ControllerButton>,
     axes: HashMap<ControllerAxis, AxisInfo>,
+    vendor_id: Option<u16>,
+    name: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -246,6 +287,12 @@ impl Drop for LinuxdevContext {
             evdev_controllers: _,
             token_generator: _,
             pending_translated_events: _,
+            subscribers: _,
+            pending_removals: _,
+            player_indices: _,
+            time_spent_suspended_ns: _,
+            queue_high_water_mark: _,
+            udev_actions_seen: _,
         } = self;
         unsafe {
             libudev_sys::udev_enumerate_unref(udev_enumerate);
@@ -260,7 +307,7 @@ impl Drop for Linuxdev {
         let &mut Self {
             udev_device, owns_udev_device, ref udev_props,
             fd, fd_has_write_access: _, event_api: _,
-            ref evdev,
+            ref evdev, is_driver_unbound: _, event_mode: _, last_reported_axes: _,
         } = self;
         unsafe {
             if owns_udev_device {
@@ -294,6 +341,13 @@ impl Default for LinuxdevContext {
             let udev_monitor = libudev_sys::udev_monitor_new_from_netlink(udev, b"udev\0".as_ptr() as _);
             assert!(!udev_monitor.is_null());
 
+            for subsystem in &[&b"input\0"[..], &b"sound\0"[..]] {
+                let status = libudev_sys::udev_monitor_filter_add_match_subsystem_devtype(udev_monitor, subsystem.as_ptr() as _, ptr::null());
+                if status < 0 {
+                    error!("udev_monitor_filter_add_match_subsystem_devtype() returned {}", status);
+                }
+            }
+
             let status = libudev_sys::udev_monitor_enable_receiving(udev_monitor);
             if status < 0 {
                 error!("udev_monitor_enable_receiving() returned {}", status);
@@ -310,6 +364,7 @@ impl Default for LinuxdevContext {
             let mut pending_translated_events = VecDeque::with_capacity(32);
             let mut token_generator = LinuxdevTokenGenerator::default();
             let mut evdev_controllers = HashMap::with_capacity(32);
+            let mut player_indices = HashMap::new();
 
             for entry in udev_enumerate::scan_devices_iter(udev_enumerate) {
                 let _entry_value = libudev_sys::udev_list_entry_get_value(entry);
@@ -341,6 +396,10 @@ impl Default for LinuxdevContext {
                     };
                     if worth_keeping {
                         debug!("Added {} to internal evdev_controllers list (token: {})", dev.display(), token);
+                        // udev_enumerate sorts devices by syspath, so this is deterministic.
+                        let player_index = lowest_free_player_index(&player_indices);
+                        player_indices.insert(token, player_index);
+                        dev.set_player_leds(Some(player_index));
                         evdev_controllers.insert(token, dev);
                     }
                 }
@@ -351,6 +410,12 @@ impl Default for LinuxdevContext {
                 evdev_controllers: RefCell::new(evdev_controllers),
                 token_generator: RefCell::new(token_generator),
                 pending_translated_events: RefCell::new(pending_translated_events),
+                subscribers: RefCell::new(Vec::new()),
+                pending_removals: RefCell::new(HashMap::new()),
+                player_indices: RefCell::new(player_indices),
+                time_spent_suspended_ns: Cell::new(time_spent_suspended_ns()),
+                queue_high_water_mark: Cell::new(0),
+                udev_actions_seen: Cell::new(0),
             }
         }
     }
@@ -403,8 +468,12 @@ impl LinuxdevDeviceInfo {
     pub fn driver_version(&self) -> Option<&str> {
         self.driver_version.as_ref().map(String::as_str)
     }
+    pub fn physical_location(&self) -> Option<&str> {
+        self.physical_location.as_ref().map(String::as_str)
+    }
     pub fn is_physical(&self) -> Option<bool> { None }
     pub fn controller(&self) -> Option<&ControllerInfo> { Some(&self.controller) }
+    pub fn controller_mut(&mut self) -> Option<&mut ControllerInfo> { Some(&mut self.controller) }
     pub fn mouse(&self) -> Option<&MouseInfo> { None }
     pub fn keyboard(&self) -> Option<&KeyboardInfo> { None }
     pub fn touch(&self) -> Option<&TouchInfo> { None }
@@ -453,23 +522,234 @@ impl UdevDeviceAction {
             b"change\0" => UdevDeviceAction::Change,
             b"online\0" => UdevDeviceAction::Online,
             b"offline\0" => UdevDeviceAction::Offline,
+            b"bind\0" => UdevDeviceAction::Bind,
+            b"unbind\0" => UdevDeviceAction::Unbind,
             _ => UdevDeviceAction::Other(action.to_string_lossy().into_owned()),
         }
     }
 }
 
 
-impl LinuxdevContext {
+/// Gets the total time the system has spent suspended since boot, in nanoseconds.
+///
+/// CLOCK_BOOTTIME keeps ticking while the system is suspended, but CLOCK_MONOTONIC doesn't.
+fn time_spent_suspended_ns() -> Option<i64> {
+    let now = |clock| unsafe {
+        let mut ts: c::timespec = mem::zeroed();
+        match c::clock_gettime(clock, &mut ts) {
+            0 => Some(ts.tv_sec as i64 * 1_000_000_000 + ts.tv_nsec as i64),
+            _ => None,
+        }
+    };
+    match (now(c::CLOCK_BOOTTIME), now(c::CLOCK_MONOTONIC)) {
+        (Some(boottime), Some(monotonic)) => Some(boottime - monotonic),
+        _ => None,
+    }
+}
+
+/// Player indices are assigned this way so that identical connection sequences give identical results.
+fn lowest_free_player_index(player_indices: &HashMap<LinuxdevToken, u32>) -> u32 {
+    (0..).find(|i| !player_indices.values().any(|held| held == i)).unwrap()
+}
+
+// Set by `Hint::LazyDeviceInfo`.
+static LAZY_DEVICE_INFO: AtomicBool = AtomicBool::new(false);
+
+pub fn set_lazy_device_info(lazy: bool) {
+    LAZY_DEVICE_INFO.store(lazy, Ordering::SeqCst);
+}
+fn is_device_info_lazy() -> bool {
+    LAZY_DEVICE_INFO.load(Ordering::SeqCst)
+}
+
+// Opening every controller once per `Context` would duplicate events and have contexts fight
+// over force feedback slots, so all contexts of a thread share the same backend.
+// `LinuxdevContext` is full of `Cell`s, so this can't be process-wide.
+thread_local! {
+    static SHARED_LINUXDEV_CONTEXT: RefCell<Weak<LinuxdevContext>> = RefCell::new(Weak::new());
+}
+
+/// A `Context`'s access to the `LinuxdevContext` of its thread, along with its own event queue.
+///
+/// Events are copied to the queue of each handle, so that each `Context` gets every event exactly once.
+#[derive(Debug)]
+pub struct LinuxdevHandle {
+    backend: Rc<LinuxdevContext>,
+    queue: Rc<RefCell<EventQueue>>,
+}
+
+impl Deref for LinuxdevHandle {
+    type Target = LinuxdevContext;
+    fn deref(&self) -> &LinuxdevContext {
+        &self.backend
+    }
+}
+
+impl Drop for LinuxdevHandle {
+    fn drop(&mut self) {
+        // Don't keep disconnected devices around on behalf of a queue that is going away.
+        let mut queue = mem::replace(&mut *self.queue.borrow_mut(), EventQueue::default());
+        while let Some(ev) = queue.pop_front() {
+            self.backend.on_event_discarded(&ev);
+        }
+    }
+}
+
+impl LinuxdevHandle {
+    pub fn new() -> Self {
+        let (backend, is_new) = SHARED_LINUXDEV_CONTEXT.with(|shared| {
+            if let Some(backend) = shared.borrow().upgrade() {
+                debug!("Sharing the linuxdev backend with another Context of this thread");
+                return (backend, false);
+            }
+            let backend = Rc::new(LinuxdevContext::default());
+            *shared.borrow_mut() = Rc::downgrade(&backend);
+            (backend, true)
+        });
+        // Events that were queued before we came in belong to the existing subscribers.
+        // Those of a new backend (e.g from enumerating devices) are ours.
+        if !is_new {
+            backend.fan_out_pending_events();
+        }
+        let queue = Rc::new(RefCell::new(EventQueue::default()));
+        backend.subscribers.borrow_mut().push(Rc::downgrade(&queue));
+        Self { backend, queue }
+    }
     pub fn poll_next_event(&self) -> Option<Event> {
-        self.pump_events();
+        self.backend.pump_events();
+        self.backend.fan_out_pending_events();
+
+        let nb_pending = self.queue.borrow().len();
+        if nb_pending > self.backend.queue_high_water_mark.get() {
+            self.backend.queue_high_water_mark.set(nb_pending);
+        }
 
-        let ev = self.pending_translated_events.borrow_mut().pop_front();
+        let ev = self.queue.borrow_mut().pop_front();
         if let Some(&Event::DeviceDisconnected { device: DeviceID(OsDeviceID::Linuxdev(token)), .. }) = ev.as_ref() {
-            let dev = self.evdev_controllers.borrow_mut().remove(&token).unwrap();
-            debug!("Removed disconnected {} from internal evdev_controllers list (token: {})", dev.display(), token);
+            self.backend.release_disconnected_device(token);
         }
         ev
     }
+    pub fn set_event_queue_policy(&self, policy: QueuePolicy) {
+        let discarded = self.queue.borrow_mut().set_policy(policy);
+        for ev in discarded.iter() {
+            self.backend.on_event_discarded(ev);
+        }
+    }
+    /// Queues a `DeviceConnected` event (with `initial: true`) for each controller that is currently
+    /// present, for this handle only. Must be called before the first `poll_next_event()`.
+    pub fn replay_initial_devices(&self) {
+        let pending_removals = self.backend.pending_removals.borrow();
+        let controllers = self.backend.evdev_controllers.borrow();
+        // Sorting tokens gives the enumeration order, which is deterministic.
+        let mut tokens = controllers.keys().filter(|token| !pending_removals.contains_key(token)).cloned().collect::<Vec<_>>();
+        tokens.sort_by_key(|token| token.0);
+        let mut queue = self.queue.borrow_mut();
+        for (i, token) in tokens.into_iter().enumerate() {
+            let dev = &controllers[&token];
+            queue.insert(i, Event::DeviceConnected {
+                device: DeviceID(OsDeviceID::Linuxdev(token)),
+                instant: dev.plug_instant(),
+                info: dev.device_info(),
+                initial: true,
+            });
+        }
+    }
+    /// Are there events to be returned by `poll_next_event()` ?
+    pub fn has_pending_events(&self) -> bool {
+        !self.queue.borrow().is_empty() || self.backend.has_pending_events()
+    }
+}
+
+impl LinuxdevContext {
+    /// Moves pending events to the queue of each live `LinuxdevHandle`.
+    fn fan_out_pending_events(&self) {
+        let mut pending = self.pending_translated_events.borrow_mut();
+        if pending.is_empty() {
+            return;
+        }
+        let queues = {
+            let mut subscribers = self.subscribers.borrow_mut();
+            subscribers.retain(|queue| queue.upgrade().is_some());
+            subscribers.iter().filter_map(Weak::upgrade).collect::<Vec<_>>()
+        };
+        for ev in pending.drain(..) {
+            if let Event::DeviceDisconnected { device: DeviceID(OsDeviceID::Linuxdev(token)), .. } = ev {
+                *self.pending_removals.borrow_mut().entry(token).or_insert(0) += queues.len();
+                if queues.is_empty() {
+                    self.remove_disconnected_device(token);
+                }
+            }
+            for queue in queues.iter() {
+                let discarded = queue.borrow_mut().push_back(ev.clone());
+                if let Some(discarded) = discarded {
+                    self.on_event_discarded(&discarded);
+                }
+            }
+        }
+    }
+    /// Called for events that a subscriber's queue will never return.
+    fn on_event_discarded(&self, ev: &Event) {
+        if let Event::DeviceDisconnected { device: DeviceID(OsDeviceID::Linuxdev(token)), .. } = *ev {
+            self.release_disconnected_device(token);
+        }
+    }
+    /// Called once per subscriber that has seen the `DeviceDisconnected` event for this device.
+    fn release_disconnected_device(&self, token: LinuxdevToken) {
+        let is_last = match self.pending_removals.borrow_mut().get_mut(&token) {
+            Some(remaining) => {
+                *remaining -= 1;
+                *remaining == 0
+            },
+            None => false,
+        };
+        if is_last {
+            self.remove_disconnected_device(token);
+        }
+    }
+    fn remove_disconnected_device(&self, token: LinuxdevToken) {
+        self.pending_removals.borrow_mut().remove(&token);
+        if let Some(dev) = self.evdev_controllers.borrow_mut().remove(&token) {
+            debug!("Removed disconnected {} from internal evdev_controllers list (token: {})", dev.display(), token);
+        }
+        self.player_indices.borrow_mut().remove(&token);
+    }
+    pub fn nb_controllers(&self) -> usize {
+        self.evdev_controllers.borrow().len()
+    }
+    pub fn queue_high_water_mark(&self) -> usize {
+        self.queue_high_water_mark.get()
+    }
+    pub fn udev_actions_seen(&self) -> u64 {
+        self.udev_actions_seen.get()
+    }
+    pub fn reset_stats(&self) {
+        self.queue_high_water_mark.set(0);
+        self.udev_actions_seen.set(0);
+    }
+    /// Are there events to be moved to the subscribers' queues?
+    ///
+    /// This doesn't read anything; It only checks whether the system was resumed (which no
+    /// file descriptor tells us), then our own queue, then `poll()`s the evdev and
+    /// udev monitor file descriptors with a zero timeout.
+    fn has_pending_events(&self) -> bool {
+        self.check_system_resumed();
+        if !self.pending_translated_events.borrow().is_empty() {
+            return true;
+        }
+        let mut poll_fds = vec![
+            PollFd::new(unsafe { libudev_sys::udev_monitor_get_fd(self.udev_monitor) }, EventFlags::POLLIN),
+        ];
+        poll_fds.extend(self.evdev_controllers.borrow().values().filter_map(|dev| dev.fd).map(|fd| PollFd::new(fd, EventFlags::POLLIN)));
+        match poll(&mut poll_fds, 0) {
+            Ok(nb_ready) => nb_ready > 0,
+            Err(e) => {
+                // Be conservative and let the caller pump events anyway.
+                warn!("poll() failed: {}", e);
+                true
+            },
+        }
+    }
     fn pump_events(&self) {
         for (token, dev) in self.evdev_controllers.borrow().iter() {
             let status = dev.pump_evdev(*token, &mut self.pending_translated_events.borrow_mut());
@@ -485,6 +765,23 @@ impl LinuxdevContext {
         // disconnected (all events matter), so pump the udev_monitor last. Any newly added device
         // will also be pumped immediately anyway.
         self.pump_udev_monitor();
+        self.check_system_resumed();
+    }
+    // udev does report "change" events for power_supply devices on resume, but also whenever a
+    // battery's charge changes, so it can't tell us reliably. Comparing clocks can.
+    fn check_system_resumed(&self) {
+        let now = match time_spent_suspended_ns() {
+            Some(now) => now,
+            None => return,
+        };
+        let before = self.time_spent_suspended_ns.replace(Some(now));
+        // Both clocks tick at the same rate otherwise, so anything above a second is not noise.
+        if let Some(before) = before {
+            if now - before > 1_000_000_000 {
+                info!("The system was suspended for {}s", (now - before) / 1_000_000_000);
+                self.pending_translated_events.borrow_mut().push_back(Event::SystemResumed);
+            }
+        }
     }
     fn pump_udev_monitor(&self) {
         loop {
@@ -494,6 +791,7 @@ impl LinuxdevContext {
             if udev_device.is_null() {
                 break;
             }
+            self.udev_actions_seen.set(self.udev_actions_seen.get() + 1);
             let action = unsafe {
                 cstr_or_none(libudev_sys::udev_device_get_action(udev_device))
             };
@@ -504,6 +802,16 @@ impl LinuxdevContext {
                 },
                 Some(action) => UdevDeviceAction::from_cstr(action),
             };
+            let subsystem = unsafe {
+                cstr_or_none(libudev_sys::udev_device_get_subsystem(udev_device))
+            };
+            if subsystem.map(CStr::to_bytes) == Some(b"sound") {
+                self.on_udev_sound_device_event(udev_device, &action);
+                unsafe {
+                    libudev_sys::udev_device_unref(udev_device);
+                }
+                continue;
+            }
             match action {
                 UdevDeviceAction::Add => self.add_linuxdev(unsafe {
                     // Increment refcount, because always decreased at the end
@@ -515,8 +823,10 @@ impl LinuxdevContext {
                     })
                 }),
                 UdevDeviceAction::Remove => self.on_udev_device_removed(udev_device),
+                UdevDeviceAction::Change => self.on_udev_device_changed(udev_device),
+                UdevDeviceAction::Bind => self.on_udev_device_bound(udev_device),
+                UdevDeviceAction::Unbind => self.on_udev_device_unbound(udev_device),
                   UdevDeviceAction::Move
-                | UdevDeviceAction::Change
                 | UdevDeviceAction::Online
                 | UdevDeviceAction::Offline
                 | UdevDeviceAction::Other(_) => {
@@ -528,6 +838,35 @@ impl LinuxdevContext {
             }
         }
     }
+    fn on_udev_sound_device_event(&self, udev_device: *mut libudev_sys::udev_device, action: &UdevDeviceAction) {
+        let sysname = unsafe {
+            cstr_or_none(libudev_sys::udev_device_get_sysname(udev_device))
+        };
+        let device_node = unsafe {
+            Linuxdev::device_node_pathbuf_of_udev_device(udev_device)
+        };
+        let (sysname, device_node) = match (sysname, device_node) {
+            (Some(sysname), Some(device_node)) => (sysname.to_bytes(), device_node),
+            _ => return, // Sound cards, controlC*, timer, seq... Not interesting.
+        };
+        // ALSA PCM nodes are named "pcmC<card>D<device><p|c>", with 'p' for playback
+        // and 'c' for capture.
+        if !sysname.starts_with(b"pcmC") {
+            return;
+        }
+        let ev = match (action, sysname.last()) {
+            (&UdevDeviceAction::Add, Some(&b'p')) => Event::AudioOutputDeviceAdded { device_node },
+            (&UdevDeviceAction::Remove, Some(&b'p')) => Event::AudioOutputDeviceRemoved { device_node },
+            (&UdevDeviceAction::Add, Some(&b'c')) => Event::AudioCaptureDeviceAdded { device_node },
+            (&UdevDeviceAction::Remove, Some(&b'c')) => Event::AudioCaptureDeviceRemoved { device_node },
+            _ => {
+                trace!("Ignoring {:?} for sound device {}", action, device_node.display());
+                return;
+            },
+        };
+        debug!("Sound device event: {:?}", ev);
+        self.pending_translated_events.borrow_mut().push_back(ev);
+    }
     fn add_linuxdev(&self, dev: Linuxdev) {
         if !dev.is_a_controller_and_evdev_node() {
             return;
@@ -537,9 +876,18 @@ impl LinuxdevContext {
             device: DeviceID(OsDeviceID::Linuxdev(token)),
             instant: dev.plug_instant(),
             info: dev.device_info(),
+            initial: false,
         };
         let mut queue = self.pending_translated_events.borrow_mut();
         queue.push_back(device_connected_event);
+        let player_index = lowest_free_player_index(&self.player_indices.borrow());
+        self.player_indices.borrow_mut().insert(token, player_index);
+        dev.set_player_leds(Some(player_index));
+        queue.push_back(Event::ControllerPlayerIndexChanged {
+            controller: DeviceID(OsDeviceID::Linuxdev(token)),
+            instant: dev.instant_now(),
+            player_index: Some(player_index),
+        });
         let status = dev.pump_evdev(token, &mut queue);
         match status {
             Err(device::Error::DeviceDisconnected(_)) | Ok(()) => (), 
@@ -551,15 +899,82 @@ impl LinuxdevContext {
         self.evdev_controllers.borrow_mut().insert(token, dev);
     }
     fn on_udev_device_removed(&self, udev_device: *mut libudev_sys::udev_device) {
+        if let Some(token) = self.token_of_udev_device(udev_device) {
+            self.queue_device_disconnected(token);
+        }
+        // Otherwise it's fine; the udev_device is not necessarily a controller!
+    }
+    // Fires when udev rules are reloaded, a driver re-binds (e.g xpad), etc.
+    fn on_udev_device_changed(&self, udev_device: *mut libudev_sys::udev_device) {
+        let token = match self.token_of_udev_device(udev_device) {
+            Some(token) => token,
+            None => return,
+        };
+        let mut evdev_controllers = self.evdev_controllers.borrow_mut();
+        let dev = evdev_controllers.get_mut(&token).unwrap();
+        let old_info = dev.device_info();
+        let status = unsafe {
+            dev.refresh_from_changed_udev_device(token, udev_device, &mut self.pending_translated_events.borrow_mut())
+        };
+        match status {
+            Ok(()) => {
+                let info = dev.device_info();
+                if info == old_info {
+                    trace!("{} changed, but nothing observable differs (token: {})", dev.display(), token);
+                    return;
+                }
+                debug!("{} changed (token: {})", dev.display(), token);
+                let device_info_changed_event = Event::DeviceInfoChanged {
+                    device: DeviceID(OsDeviceID::Linuxdev(token)),
+                    instant: dev.instant_now(),
+                    info,
+                };
+                self.pending_translated_events.borrow_mut().push_back(device_info_changed_event);
+            },
+            Err(device::Error::DeviceDisconnected(_)) => {
+                // The node went away between the notification and the re-read.
+                drop(evdev_controllers);
+                self.queue_device_disconnected(token);
+            },
+            Err(e) => warn!("Controller {}: refreshing after udev change failed: {}", dev.display(), e),
+        };
+    }
+    // A driver was just attached, so capabilities may have changed; Re-probe them like for a "change".
+    fn on_udev_device_bound(&self, udev_device: *mut libudev_sys::udev_device) {
+        let token = match self.token_of_udev_device(udev_device) {
+            Some(token) => token,
+            None => return,
+        };
+        debug!("A driver was bound to controller (token: {}); re-probing it", token);
+        self.on_udev_device_changed(udev_device);
+        if let Some(dev) = self.evdev_controllers.borrow().get(&token) {
+            dev.is_driver_unbound.set(false);
+        }
+    }
+    fn on_udev_device_unbound(&self, udev_device: *mut libudev_sys::udev_device) {
+        let token = match self.token_of_udev_device(udev_device) {
+            Some(token) => token,
+            None => return,
+        };
+        debug!("A driver was unbound from controller (token: {})", token);
+        let status = match self.evdev_controllers.borrow().get(&token) {
+            Some(dev) => {
+                dev.is_driver_unbound.set(true);
+                // Without a driver, the node may already be gone.
+                dev.pump_evdev(token, &mut self.pending_translated_events.borrow_mut())
+            },
+            None => return,
+        };
+        if let Err(device::Error::DeviceDisconnected(_)) = status {
+            self.queue_device_disconnected(token);
+        }
+    }
+    fn token_of_udev_device(&self, udev_device: *mut libudev_sys::udev_device) -> Option<LinuxdevToken> {
         // Reverse lookup
         let target_devnode = unsafe {
             Linuxdev::device_node_pathbuf_of_udev_device(udev_device)
-        };
-        if target_devnode.is_none() {
-            return; // Can't do anything about it
-        }
-        let target_devnode = target_devnode.unwrap();
-        let token = self.evdev_controllers.borrow().iter().filter_map(|(token, dev)| {
+        }?;
+        self.evdev_controllers.borrow().iter().filter_map(|(token, dev)| {
             let devnode = unsafe {
                 Linuxdev::device_node_pathbuf_of_udev_device(dev.udev_device)
             };
@@ -568,21 +983,28 @@ impl LinuxdevContext {
             } else {
                 None
             }).unwrap_or(None)
-        }).next();
-
-        if token.is_none() {
-            return; // It's fine; the udev_device is not necessarily a controller!
+        }).next()
+    }
+    fn queue_device_disconnected(&self, token: LinuxdevToken) {
+        let device = DeviceID(OsDeviceID::Linuxdev(token));
+        let mut queue = self.pending_translated_events.borrow_mut();
+        // A "change" may have already reported it; poll_next_event() expects only one.
+        let is_already_queued = queue.iter().any(|ev| match *ev {
+            Event::DeviceDisconnected { device: queued, .. } => queued == device,
+            _ => false,
+        });
+        if is_already_queued {
+            return;
         }
         // NOTE: Don't remove the device from our list, yet !
         // Wait until the DeviceDisconnected event is reported to the user to do it.
         // See self.poll_next_event()
-        let token = token.unwrap();
         let dev = &self.evdev_controllers.borrow()[&token];
         let device_disconnected_event = Event::DeviceDisconnected {
-            device: DeviceID(OsDeviceID::Linuxdev(token)),
+            device,
             instant: dev.instant_now(), // Looks like it's the closest we can get... ._.
         };
-        self.pending_translated_events.borrow_mut().push_back(device_disconnected_event);
+        queue.push_back(device_disconnected_event);
         debug!("{} disconnected but still kept in internal evdev_controllers list (token: {})", dev.display(), token);
     }
     pub fn controllers(&self) -> device::Result<HashMap<DeviceID, DeviceInfo>> {
@@ -594,15 +1016,42 @@ impl LinuxdevContext {
             (id, info)
         }).collect())
     }
+    /// Is this token in our table, and its `DeviceDisconnected` event not yet reported?
+    pub fn is_token_current(&self, token: LinuxdevToken) -> bool {
+        self.evdev_controllers.borrow().contains_key(&token) && !self.pending_removals.borrow().contains_key(&token)
+    }
     pub fn ping_controller(&self, token: LinuxdevToken) -> device::Result<()> {
         match self.evdev_controllers.borrow().get(&token) {
             None => device::disconnected(),
             Some(dev) => dev.pump_evdev(token, &mut self.pending_translated_events.borrow_mut()),
         }
     }
+    pub(crate) fn device_fd(&self, token: LinuxdevToken) -> Option<RawFd> {
+        self.evdev_controllers.borrow().get(&token).and_then(|dev| dev.fd)
+    }
+    pub fn controller_info(&self, controller: DeviceID) -> device::Result<ControllerInfo> {
+        self.with_controller(controller, |dev| Ok(dev.controller_info()))
+    }
     pub fn controller_state(&self, controller: DeviceID) -> device::Result<ControllerState> {
         self.with_controller(controller, |dev| dev.controller_state().map(ControllerState))
     }
+    pub fn all_controller_states(&self) -> device::Result<HashMap<DeviceID, ControllerState>> {
+        let evdev_controllers = self.evdev_controllers.borrow();
+        let mut pending_translated_events = self.pending_translated_events.borrow_mut();
+        let mut states = HashMap::with_capacity(evdev_controllers.len());
+        for (token, dev) in evdev_controllers.iter() {
+            // Pumping brings libevdev's state up to date. Events are queued as usual, not lost.
+            match dev.pump_evdev(*token, &mut pending_translated_events) {
+                Ok(()) => (),
+                // Same as in pump_events(): let pump_udev_monitor() deal with it.
+                Err(device::Error::DeviceDisconnected(_)) => continue,
+                Err(e) => warn!("Controller {}: pumping evdev failed: {}", dev.display(), e),
+            };
+            let id = DeviceID(OsDeviceID::Linuxdev(*token));
+            states.insert(id, ControllerState(dev.controller_state()?));
+        }
+        Ok(states)
+    }
     pub fn controller_button_state(&self, controller: DeviceID, button: ControllerButton) -> device::Result<ButtonState> {
         self.with_controller(controller, |dev| dev.controller_button_state(button))
     }
@@ -612,6 +1061,55 @@ impl LinuxdevContext {
     pub fn controller_set_vibration(&self, controller: DeviceID, vibration: &VibrationState) -> device::Result<()> {
         self.with_controller(controller, |dev| dev.controller_set_vibration(vibration))
     }
+    pub fn controller_rumble_pulse(&self, controller: DeviceID, vibration: &VibrationState, duration: Duration) -> device::Result<()> {
+        self.with_controller(controller, |dev| dev.controller_rumble_pulse(vibration, duration))
+    }
+    pub fn controller_player_index(&self, controller: DeviceID) -> device::Result<Option<u32>> {
+        let token = self.controller_token(controller)?;
+        Ok(self.player_indices.borrow().get(&token).cloned())
+    }
+    pub fn set_controller_player_index(&self, controller: DeviceID, player_index: Option<u32>) -> device::Result<()> {
+        let token = self.controller_token(controller)?;
+        let evdev_controllers = self.evdev_controllers.borrow();
+        let mut player_indices = self.player_indices.borrow_mut();
+        let previous = player_indices.get(&token).cloned();
+        if previous == player_index {
+            return Ok(());
+        }
+        let mut changes = vec![(token, player_index)];
+        let holder = player_index.and_then(|i| player_indices.iter().find(|&(_, &held)| held == i).map(|(&holder, _)| holder));
+        if let Some(holder) = holder {
+            changes.push((holder, previous));
+        }
+        let mut queue = self.pending_translated_events.borrow_mut();
+        for (token, player_index) in changes {
+            match player_index {
+                Some(i) => player_indices.insert(token, i),
+                None => player_indices.remove(&token),
+            };
+            let dev = &evdev_controllers[&token];
+            dev.set_player_leds(player_index);
+            queue.push_back(Event::ControllerPlayerIndexChanged {
+                controller: DeviceID(OsDeviceID::Linuxdev(token)),
+                instant: dev.instant_now(),
+                player_index,
+            });
+        }
+        Ok(())
+    }
+    fn controller_token(&self, controller: DeviceID) -> device::Result<LinuxdevToken> {
+        match controller.0 {
+            OsDeviceID::Linuxdev(token) if self.evdev_controllers.borrow().contains_key(&token) => Ok(token),
+            OsDeviceID::Linuxdev(_) => device::disconnected(),
+            _ => device::not_supported_by_device("This device does not refer to a controller"),
+        }
+    }
+    pub fn set_controller_event_mode(&self, controller: DeviceID, mode: EventMode) -> device::Result<()> {
+        self.with_controller(controller, |dev| {
+            dev.event_mode.set(mode);
+            Ok(())
+        })
+    }
     // We take a closure because we can't return a reference to the DeviceID (it outlives the
     // borrow() of self.evdev_controllers).
     fn with_controller<T, F: FnMut(&Linuxdev) -> device::Result<T>>(&self, controller: DeviceID, mut f: F) -> device::Result<T> {
@@ -654,6 +1152,12 @@ impl OsControllerInfo {
     pub fn axis(&self, axis: ControllerAxis) -> Option<&AxisInfo> {
         self.axes.get(&axis)
     }
+    pub fn vendor_id(&self) -> Option<u16> {
+        self.vendor_id
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(String::as_str)
+    }
 }
 impl OsControllerState {
     pub fn button(&self, button: ControllerButton) -> Option<ButtonState> {
@@ -662,37 +1166,81 @@ impl OsControllerState {
     pub fn axis(&self, axis: ControllerAxis) -> Option<f64> {
         self.axes.get(&axis).map(Clone::clone)
     }
+    pub fn axes(&self) -> Vec<ControllerAxis> {
+        self.axes.keys().cloned().collect()
+    }
 }
 
 
-// For logging. This is very simple right now.
+// For logging, and for naming devices that don't report a name.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct LinuxdevDisplay<'a> {
-    name: &'a str,
+    name: Option<&'a str>,
+    usb_ids: Option<UsbIDs>,
+    bus: Option<Bus>,
+}
+
+impl<'a> LinuxdevDisplay<'a> {
+    /// The device's name, or a stable name derived from its IDs (e.g "USB device 045e:028e").
+    pub fn name(&self) -> Cow<'a, str> {
+        let &Self { name, usb_ids, bus } = self;
+        if let Some(name) = name {
+            return Cow::Borrowed(name);
+        }
+        let kind = match bus {
+            Some(Bus::Usb) => "USB device",
+            Some(Bus::Bluetooth) => "Bluetooth device",
+            Some(Bus::Pci) => "PCI device",
+            Some(Bus::Virtual) => "Virtual device",
+            None => "Device",
+        };
+        match usb_ids {
+            Some(UsbIDs { vendor_id, product_id }) => Cow::Owned(format!("{} {:04x}:{:04x}", kind, vendor_id, product_id)),
+            None => Cow::Owned(format!("Unknown {}", kind.to_lowercase())),
+        }
+    }
 }
 
 impl<'a> Display for LinuxdevDisplay<'a> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let &Self { ref name } = self;
-        write!(f, "`{}`", name)
+        write!(f, "`{}`", self.name())
+    }
+}
+
+fn usb_ids(vendor_id: Option<u16>, product_id: Option<u16>) -> Option<UsbIDs> {
+    match (vendor_id, product_id) {
+        (Some(vendor_id), Some(product_id)) => Some(UsbIDs { vendor_id, product_id }),
+        _ => None,
     }
 }
 
 impl EvdevProps {
     pub fn display<'a>(&'a self) -> LinuxdevDisplay<'a> {
-        LinuxdevDisplay { name: self.name.as_str() }
+        LinuxdevDisplay {
+            name: self.name.as_ref().map(String::as_str),
+            usb_ids: usb_ids(Some(self.id_vendor), Some(self.id_product)),
+            bus: None,
+        }
     }
 }
 
 impl UdevProps {
     pub fn display<'a>(&'a self) -> LinuxdevDisplay<'a> {
-        LinuxdevDisplay { name: self.name().unwrap_or("???") }
+        LinuxdevDisplay {
+            name: self.name(),
+            usb_ids: usb_ids(self.id_vendor_id, self.id_model_id),
+            bus: None,
+        }
     }
 }
 
 impl Linuxdev {
     pub fn display<'a>(&'a self) -> LinuxdevDisplay<'a> {
-        self.evdev.as_ref().map(|e| e.props.display()).unwrap_or(self.udev_props.display())
+        LinuxdevDisplay {
+            name: self.name(),
+            usb_ids: usb_ids(self.vendor_id(), self.product_id()),
+            bus: self.bus(),
+        }
     }
 }
 
@@ -709,7 +1257,7 @@ impl UdevProps {
 }
 impl Linuxdev {
     pub fn name(&self) -> Option<&str> {
-        self.evdev.as_ref().map(|e| e.props.name.as_str()).or(self.udev_props.name())
+        self.evdev.as_ref().and_then(|e| e.props.name.as_ref().map(String::as_str)).or(self.udev_props.name())
     }
     pub fn product_id(&self) -> Option<u16> {
         self.evdev.as_ref().map(|e| e.props.id_product).or(self.udev_props.id_model_id)
@@ -717,6 +1265,12 @@ impl Linuxdev {
     pub fn vendor_id(&self) -> Option<u16> {
         self.evdev.as_ref().map(|e| e.props.id_vendor).or(self.udev_props.id_vendor_id)
     }
+    /// Prefers the evdev unique identifier, which tells apart two identical controllers
+    /// more reliably than udev's `ID_SERIAL` (which is often derived from vendor and product names).
+    pub fn serial(&self) -> Option<&str> {
+        self.evdev.as_ref().and_then(|e| e.props.uniq.as_ref().map(String::as_str))
+            .or(self.udev_props.id_serial.as_ref().map(String::as_str))
+    }
     pub fn driver_name(&self) -> Option<String> {
         self.udev_props.id_usb_driver.clone()
     }
@@ -791,31 +1345,33 @@ impl Linuxdev {
             device_node: unsafe {
                 Self::device_node_pathbuf_of_udev_device(self.udev_device)
 			},
-            name: self.name().map(|s| s.to_owned()),
-            serial: self.udev_props.id_serial.clone(),
-            usb_ids: match (self.vendor_id(), self.product_id()) {
-                (Some(vendor_id), Some(product_id)) => Some(UsbIDs {
-                    vendor_id,
-                    product_id,
-                }),
-                _ => None,
-            },
+            // Never leave it empty; UIs would show an anonymous controller.
+            name: Some(self.display().name().into_owned()),
+            serial: self.serial().map(|s| s.to_owned()),
+            usb_ids: usb_ids(self.vendor_id(), self.product_id()),
             vendor_name: self.udev_props.id_vendor.clone(),
             plug_instant: self.plug_instant(),
             bus: self.bus(),
             driver_name: self.driver_name(),
             driver_version: self.driver_version(),
-            controller: ControllerInfo(OsControllerInfo {
-                is_a_gamepad: self.is_a_gamepad(),
-                is_a_joystick: self.is_a_joystick(),
-                is_a_steering_wheel: self.is_a_steering_wheel(),
-                supports_rumble: self.supports_rumble(),
-                buttons: evdev.buttons.clone(),
-                axes: evdev.axes.clone(),
-            }),
+            physical_location: evdev.props.phys.clone(),
+            controller: self.controller_info(),
         };
         DeviceInfo(info.into())
     }
+    pub fn controller_info(&self) -> ControllerInfo {
+        let evdev = self.evdev.as_ref().unwrap();
+        ControllerInfo(OsControllerInfo {
+            is_a_gamepad: self.is_a_gamepad(),
+            is_a_joystick: self.is_a_joystick(),
+            is_a_steering_wheel: self.is_a_steering_wheel(),
+            supports_rumble: self.supports_rumble(),
+            buttons: evdev.buttons.borrow_mut().get_or_insert_with(|| self.evdev_all_controller_buttons_support()).clone(),
+            axes: evdev.axes.borrow_mut().get_or_insert_with(|| self.evdev_all_controller_axes_support()).clone(),
+            vendor_id: self.vendor_id(),
+            name: self.name().map(|s| s.to_owned()),
+        }, None)
+    }
 }
 
 struct FromUdevDevice {
@@ -843,50 +1399,7 @@ impl Linuxdev {
         // The drawback is that we're eagerly reserving some memory for stuff the caller
         // might never actually care about. But hey, system event queues already eat up a bunch of memory in any case.
 
-        let udev_prop_of = |udev_device, name: &[u8]| -> Option<&CStr> {
-            assert_eq!(b'\0', *name.last().unwrap());
-            cstr_or_none(libudev_sys::udev_device_get_property_value(udev_device, name.as_ptr() as _))
-        };
-        let udev_prop = |name: &[u8]| udev_prop_of(udev_device, name);
-        let udev_parent_prop = |name: &[u8]| -> Option<&CStr> {
-            // NOTE: Linked to child device, no need to free it.
-            let parent = libudev_sys::udev_device_get_parent(udev_device);
-            if parent.is_null() {
-                None
-            } else {
-                udev_prop_of(parent, name)
-            }
-        };
-        let udev_prop_bool = |name: &[u8]| udev_prop(name).map(|s| s.to_bytes()[0] == b'1').unwrap_or(false);
-        let udev_prop_string = |name: &[u8]| udev_prop(name).map(|s| s.to_string_lossy().into_owned());
-        let udev_parent_prop_string = |name: &[u8]| udev_parent_prop(name).map(|s| s.to_string_lossy().into_owned());
-
-        let udev_props = UdevProps {
-            usec_initialized: udev_prop_string(b"USEC_INITIALIZED\0").map(|s| s.parse().unwrap()),
-            id_usb_driver: udev_prop_string(b"ID_USB_DRIVER\0"),
-            id_bus: udev_prop_string(b"ID_BUS\0"),
-            id_serial: udev_prop_string(b"ID_SERIAL\0"),
-            id_model: udev_prop_string(b"ID_MODEL\0"), // "Controller" ??
-            id_vendor: udev_prop_string(b"ID_VENDOR\0"),
-            id_model_id : udev_prop_string(b"ID_MODEL_ID\0") .map(|s| u16::from_str_radix(&s, 16).unwrap()),
-            id_vendor_id: udev_prop_string(b"ID_VENDOR_ID\0").map(|s| u16::from_str_radix(&s, 16).unwrap()),
-            name: udev_prop_string(b"NAME\0").map(remove_quotes_if_any),
-            parent_name: udev_parent_prop_string(b"NAME\0").map(remove_quotes_if_any),
-            // NOTE: from udev source (https://github.com/systemd/systemd).
-            id_input              : udev_prop_bool(b"ID_INPUT\0"),
-            id_input_joystick     : udev_prop_bool(b"ID_INPUT_JOYSTICK\0"),
-            id_input_accelerometer: udev_prop_bool(b"ID_INPUT_ACCELEROMETER\0"),
-            id_input_key          : udev_prop_bool(b"ID_INPUT_KEY\0"),
-            id_input_keyboard     : udev_prop_bool(b"ID_INPUT_KEYBOARD\0"),
-            id_input_mouse        : udev_prop_bool(b"ID_INPUT_MOUSE\0"),
-            id_input_pointingstick: udev_prop_bool(b"ID_INPUT_POINTINGSTICK\0"),
-            id_input_switch       : udev_prop_bool(b"ID_INPUT_SWITCH\0"),
-            id_input_tablet       : udev_prop_bool(b"ID_INPUT_TABLET\0"),
-            id_input_tablet_pad   : udev_prop_bool(b"ID_INPUT_TABLET_PAD\0"),
-            id_input_touchpad     : udev_prop_bool(b"ID_INPUT_TOUCHPAD\0"),
-            id_input_touchscreen  : udev_prop_bool(b"ID_INPUT_TOUCHSCREEN\0"),
-            id_input_trackball    : udev_prop_bool(b"ID_INPUT_TRACKBALL\0"),
-        };
+        let udev_props = UdevProps::from_udev_device(udev_device);
 
         let devnode = cstr_or_none(libudev_sys::udev_device_get_devnode(udev_device));
 
@@ -949,7 +1462,8 @@ impl Linuxdev {
         let mut dev = Self {
             udev_device, owns_udev_device, udev_props,
             fd, fd_has_write_access, event_api,
-            evdev,
+            evdev, is_driver_unbound: Cell::new(false), event_mode: Cell::new(EventMode::default()),
+            last_reported_axes: RefCell::new(HashMap::new()),
         };
         if dev.evdev.is_some() {
             dev.evdev_refresh_all_controller_axes_support();
@@ -958,6 +1472,111 @@ impl Linuxdev {
         dev
     }
 
+    /// Re-reads udev properties and evdev capabilities after udev reported a "change" for
+    /// this device.
+    ///
+    /// The fd is kept open as-is. Whatever the current libevdev handle has buffered is pumped
+    /// into `pending_translated_events` before the handle is replaced, so no input is lost.
+    unsafe fn refresh_from_changed_udev_device(&mut self, with_token: LinuxdevToken, udev_device: *mut libudev_sys::udev_device, pending_translated_events: &mut VecDeque<Event>) -> device::Result<()> {
+        assert!(!udev_device.is_null());
+
+        self.pump_evdev(with_token, pending_translated_events)?;
+
+        let is_still_there = Self::device_node_pathbuf_of_udev_device(udev_device).map(|p| p.exists()).unwrap_or(false);
+        if !is_still_there {
+            return device::disconnected();
+        }
+
+        // Keep our own reference to the new udev_device, so later queries see the new properties.
+        let udev_device = libudev_sys::udev_device_ref(udev_device);
+        if self.owns_udev_device {
+            libudev_sys::udev_device_unref(self.udev_device);
+        }
+        self.udev_device = udev_device;
+        self.owns_udev_device = true;
+        self.udev_props = UdevProps::from_udev_device(udev_device);
+
+        if let (Some(fd), Some(rumble_ff_id)) = (self.fd, self.evdev.as_ref().map(|e| e.rumble_ff_id.get())) {
+            let mut libevdev = ptr::null_mut();
+            let status = evdev::libevdev_new_from_fd(fd, &mut libevdev);
+            if status == -c::ENODEV {
+                return device::disconnected();
+            }
+            if status < 0 {
+                warn!("Controller {}: libevdev_new_from_fd() returned {}; keeping previous capabilities", self.display(), status);
+            } else {
+                let new_evdev = LinuxdevEvdev::from_libevdev(libevdev);
+                // The rumble effect is registered with the fd, not the libevdev handle.
+                new_evdev.rumble_ff_id.set(rumble_ff_id);
+                let old_evdev = mem::replace(self.evdev.as_mut().unwrap(), new_evdev);
+                let _ignored_status = evdev::libevdev_free(old_evdev.libevdev);
+                self.evdev_refresh_all_controller_axes_support();
+                self.evdev_refresh_all_controller_buttons_support();
+            }
+        }
+
+        // We're only tracking controllers; from our point of view, it's gone.
+        if !self.is_a_controller_and_evdev_node() {
+            return device::disconnected();
+        }
+        Ok(())
+    }
+
+    /// Finds the player LEDs exposed by the kernel's LED class for this device, as
+    /// (player number, sysfs directory) pairs sorted by player number.
+    ///
+    /// They are `leds/` entries named `<device>:<color>:player-<N>`, usually found next to the
+    /// input device (i.e on the HID device), so we look a few levels up from the evdev node.
+    fn player_leds(&self) -> Vec<(u32, PathBuf)> {
+        let mut udev_device = self.udev_device;
+        for _ in 0..3 {
+            let syspath = unsafe {
+                cstr_or_none(libudev_sys::udev_device_get_syspath(udev_device))
+            };
+            if let Some(syspath) = syspath {
+                let leds_dir = Path::new(OsStr::from_bytes(syspath.to_bytes())).join("leds");
+                let mut leds: Vec<_> = fs::read_dir(&leds_dir).into_iter().flat_map(|entries| entries).filter_map(|entry| entry.ok()).filter_map(|entry| {
+                    let name = entry.file_name();
+                    let function = name.to_str()?.rsplit(':').next()?;
+                    if !function.starts_with("player") {
+                        return None;
+                    }
+                    let number = &function["player".len() ..];
+                    let number = if number.starts_with('-') { &number[1..] } else { number };
+                    Some((number.parse().ok()?, entry.path()))
+                }).collect();
+                if !leds.is_empty() {
+                    leds.sort();
+                    return leds;
+                }
+            }
+            // NOTE: Linked to child device, no need to free it.
+            udev_device = unsafe {
+                libudev_sys::udev_device_get_parent(udev_device)
+            };
+            if udev_device.is_null() {
+                break;
+            }
+        }
+        Vec::new()
+    }
+    /// Lights up the player LED matching `player_index`, and turns the other ones off.
+    /// Writing to sysfs often requires permissions we don't have, in which case this does nothing.
+    fn set_player_leds(&self, player_index: Option<u32>) {
+        for (number, led_dir) in self.player_leds() {
+            let is_on = player_index.map(|i| i + 1 == number).unwrap_or(false);
+            let brightness = if is_on {
+                fs::read_to_string(led_dir.join("max_brightness")).map(|s| s.trim().to_owned()).unwrap_or("1".to_owned())
+            } else {
+                "0".to_owned()
+            };
+            if let Err(e) = fs::write(led_dir.join("brightness"), brightness) {
+                debug!("Controller {}: could not set player LED {}: {}", self.display(), led_dir.display(), e);
+                return;
+            }
+        }
+    }
+
     fn parent(&self, try_open_fd_if_is_a_controller: bool) -> Option<Linuxdev> {
         let parent = unsafe {
             libudev_sys::udev_device_get_parent(self.udev_device)
@@ -998,6 +1617,31 @@ impl Linuxdev {
     }
 }
 
+/// Calls a string-returning evdev ioctl, such as EVIOCGUNIQ.
+unsafe fn evdev_ioctl_string(
+    libevdev: *mut evdev::libevdev,
+    ioctl: unsafe fn(c_int, &mut [c_char]) -> nix::Result<c_int>,
+    ioctl_name: &str,
+) -> Option<String>
+{
+    let fd = evdev::libevdev_get_fd(libevdev);
+    assert_ne!(fd, -1);
+    let mut buf = [0 as c_char; 256];
+    match ioctl(fd, &mut buf) {
+        Ok(_) => {
+            *buf.last_mut().unwrap() = 0;
+            let s = CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned();
+            Some(s).filter(|s| !s.is_empty())
+        },
+        // The device doesn't have this property.
+        Err(nix::Error::Sys(Errno::ENOENT)) => None,
+        Err(e) => {
+            warn!("{} ioctl() returned {}", ioctl_name, e);
+            None
+        },
+    }
+}
+
 impl LinuxdevEvdev {
     unsafe fn from_libevdev(libevdev: *mut evdev::libevdev) -> Self {
         assert!(!libevdev.is_null());
@@ -1005,8 +1649,10 @@ impl LinuxdevEvdev {
             name      : {
                 let cstr = cstr_or_none(evdev::libevdev_get_name(libevdev));
                 let name = cstr.map(|cstr| remove_quotes_if_any(cstr.to_string_lossy().into_owned()));
-                name.unwrap_or_else(|| "???".to_owned())
+                name.filter(|name| !name.is_empty())
             },
+            uniq: evdev_ioctl_string(libevdev, ev_ioctl::get_unique_identifier, "EVIOCGUNIQ"),
+            phys: evdev_ioctl_string(libevdev, ev_ioctl::get_physical_location, "EVIOCGPHYS"),
 			driver_version: {
 				let v = evdev::libevdev_get_driver_version(libevdev);
 				if v == -1 {
@@ -1067,6 +1713,55 @@ impl LinuxdevEvdev {
 }
 
 
+impl UdevProps {
+    unsafe fn from_udev_device(udev_device: *mut libudev_sys::udev_device) -> Self {
+        let udev_prop_of = |udev_device, name: &[u8]| -> Option<&CStr> {
+            assert_eq!(b'\0', *name.last().unwrap());
+            cstr_or_none(libudev_sys::udev_device_get_property_value(udev_device, name.as_ptr() as _))
+        };
+        let udev_prop = |name: &[u8]| udev_prop_of(udev_device, name);
+        let udev_parent_prop = |name: &[u8]| -> Option<&CStr> {
+            // NOTE: Linked to child device, no need to free it.
+            let parent = libudev_sys::udev_device_get_parent(udev_device);
+            if parent.is_null() {
+                None
+            } else {
+                udev_prop_of(parent, name)
+            }
+        };
+        let udev_prop_bool = |name: &[u8]| udev_prop(name).map(|s| s.to_bytes()[0] == b'1').unwrap_or(false);
+        let udev_prop_string = |name: &[u8]| udev_prop(name).map(|s| s.to_string_lossy().into_owned());
+        let udev_parent_prop_string = |name: &[u8]| udev_parent_prop(name).map(|s| s.to_string_lossy().into_owned());
+
+        UdevProps {
+            usec_initialized: udev_prop_string(b"USEC_INITIALIZED\0").map(|s| s.parse().unwrap()),
+            id_usb_driver: udev_prop_string(b"ID_USB_DRIVER\0"),
+            id_bus: udev_prop_string(b"ID_BUS\0"),
+            id_serial: udev_prop_string(b"ID_SERIAL\0"),
+            id_model: udev_prop_string(b"ID_MODEL\0"), // "Controller" ??
+            id_vendor: udev_prop_string(b"ID_VENDOR\0"),
+            id_model_id : udev_prop_string(b"ID_MODEL_ID\0") .map(|s| u16::from_str_radix(&s, 16).unwrap()),
+            id_vendor_id: udev_prop_string(b"ID_VENDOR_ID\0").map(|s| u16::from_str_radix(&s, 16).unwrap()),
+            name: udev_prop_string(b"NAME\0").map(remove_quotes_if_any),
+            parent_name: udev_parent_prop_string(b"NAME\0").map(remove_quotes_if_any),
+            // NOTE: from udev source (https://github.com/systemd/systemd).
+            id_input              : udev_prop_bool(b"ID_INPUT\0"),
+            id_input_joystick     : udev_prop_bool(b"ID_INPUT_JOYSTICK\0"),
+            id_input_accelerometer: udev_prop_bool(b"ID_INPUT_ACCELEROMETER\0"),
+            id_input_key          : udev_prop_bool(b"ID_INPUT_KEY\0"),
+            id_input_keyboard     : udev_prop_bool(b"ID_INPUT_KEYBOARD\0"),
+            id_input_mouse        : udev_prop_bool(b"ID_INPUT_MOUSE\0"),
+            id_input_pointingstick: udev_prop_bool(b"ID_INPUT_POINTINGSTICK\0"),
+            id_input_switch       : udev_prop_bool(b"ID_INPUT_SWITCH\0"),
+            id_input_tablet       : udev_prop_bool(b"ID_INPUT_TABLET\0"),
+            id_input_tablet_pad   : udev_prop_bool(b"ID_INPUT_TABLET_PAD\0"),
+            id_input_touchpad     : udev_prop_bool(b"ID_INPUT_TOUCHPAD\0"),
+            id_input_touchscreen  : udev_prop_bool(b"ID_INPUT_TOUCHSCREEN\0"),
+            id_input_trackball    : udev_prop_bool(b"ID_INPUT_TRACKBALL\0"),
+        }
+    }
+}
+
 impl Linuxdev {
     pub fn translate_ev_key(&self, code: u16) -> ControllerButton {
         controller_button_from_ev_key_code(code)
@@ -1090,12 +1785,12 @@ impl Linuxdev {
     }
 
     pub fn evdev_refresh_all_controller_buttons_support(&mut self) {
-        let buttons = self.evdev_all_controller_buttons_support();
-        self.evdev.as_mut().unwrap().buttons = buttons;
+        let buttons = if is_device_info_lazy() { None } else { Some(self.evdev_all_controller_buttons_support()) };
+        *self.evdev.as_ref().unwrap().buttons.borrow_mut() = buttons;
     }
     pub fn evdev_refresh_all_controller_axes_support(&mut self) {
-        let axes = self.evdev_all_controller_axes_support();
-        self.evdev.as_mut().unwrap().axes = axes;
+        let axes = if is_device_info_lazy() { None } else { Some(self.evdev_all_controller_axes_support()) };
+        *self.evdev.as_ref().unwrap().axes.borrow_mut() = axes;
     }
 
 
@@ -1486,12 +2181,9 @@ fn controller_axis_to_ev_abs_code_for_joysticks(axis: ControllerAxis) -> Option<
 impl Linuxdev {
     pub fn translate_linux_input_event(&self, with_token: LinuxdevToken, ev: &linux_input::input_event) -> Option<Event> {
         let &linux_input::input_event {
-            time, type_, code, value
+            time: _, type_, code, value
         } = ev;
-        let instant = {
-            let c::timeval { tv_sec, tv_usec } = time;
-            EventInstant(OsEventInstant::LinuxInputEventTimeval { tv_sec, tv_usec })
-        };
+        let instant = input_event_instant(ev);
         let controller = DeviceID(OsDeviceID::Linuxdev(with_token));
         match type_ {
             input_event_codes::EV_KEY => {
@@ -1533,20 +2225,37 @@ impl Linuxdev {
                 match status {
                     s if s == -c::EAGAIN || s == -c::EWOULDBLOCK => {
                         if read_flag as u32 == libevdev_read_flag::LIBEVDEV_READ_FLAG_SYNC as u32 {
+                            // The resync is complete; libevdev's state is now up-to-date.
                             read_flag = libevdev_read_flag::LIBEVDEV_READ_FLAG_NORMAL;
+                            // The resynced values are those of now, not of the drop.
+                            let instant = input_event_instant(&ev);
+                            let instant = instant.0.now_from_same_clock().map_or(instant, EventInstant);
+                            self.queue_resynced_axes(with_token, instant, pending_translated_events);
                         } else {
                             break Ok(());
                         }
                     },
                     s if s == libevdev_read_status::LIBEVDEV_READ_STATUS_SUCCESS as _ => {
                         if let Some(ev) = self.translate_linux_input_event(with_token, &ev) {
-                            pending_translated_events.push_back(ev);
+                            self.queue_translated_event(ev, pending_translated_events);
                         }
                     },
                     s if s == libevdev_read_status::LIBEVDEV_READ_STATUS_SYNC as _ => {
-                        read_flag = libevdev_read_flag::LIBEVDEV_READ_FLAG_SYNC;
-                        if let Some(ev) = self.translate_linux_input_event(with_token, &ev) {
-                            pending_translated_events.push_back(ev);
+                        if read_flag as u32 != libevdev_read_flag::LIBEVDEV_READ_FLAG_SYNC as u32 {
+                            // `ev` is the SYN_DROPPED itself. Let the user know before the
+                            // resync burst.
+                            read_flag = libevdev_read_flag::LIBEVDEV_READ_FLAG_SYNC;
+                            let controller = DeviceID(OsDeviceID::Linuxdev(with_token));
+                            let instant = input_event_instant(&ev);
+                            self.queue_translated_event(Event::ControllerEventsDropped { controller, instant }, pending_translated_events);
+                            continue;
+                        }
+                        // Axis events from the resync burst look stale (they carry the time of
+                        // the drop), so they are replaced by queue_resynced_axes(), stamped with
+                        // the current time, once the resync is complete. Button changes are reported as usual.
+                        match self.translate_linux_input_event(with_token, &ev) {
+                            Some(Event::ControllerAxisMotion { .. }) | None => (),
+                            Some(ev) => self.queue_translated_event(ev, pending_translated_events),
                         }
                     },
                     s if s == -c::ENODEV => break device::disconnected(),
@@ -1558,6 +2267,39 @@ impl Linuxdev {
         }
     }
 
+    fn queue_translated_event(&self, ev: Event, pending_translated_events: &mut VecDeque<Event>) {
+        if self.event_mode.get() == EventMode::PollOnly {
+            match ev {
+                Event::ControllerButtonPressed { .. }
+                | Event::ControllerButtonReleased { .. }
+                | Event::ControllerAxisMotion { .. }
+                | Event::ControllerEventsDropped { .. }
+                => return,
+                _ => (),
+            }
+        }
+        if let Event::ControllerAxisMotion { axis, value, .. } = ev {
+            self.last_reported_axes.borrow_mut().insert(axis, value);
+        }
+        pending_translated_events.push_back(ev);
+    }
+
+    /// After a resync, reports the axes whose value differs from what we last reported.
+    fn queue_resynced_axes(&self, with_token: LinuxdevToken, instant: EventInstant, pending_translated_events: &mut VecDeque<Event>) {
+        let controller = DeviceID(OsDeviceID::Linuxdev(with_token));
+        // Going through codes rather than evdev_all_controller_axes_state() keeps the order stable.
+        for code in ALL_ABS_CODES {
+            let axis = self.translate_ev_abs(*code);
+            let value = match self.evdev_controller_axis_state(axis) {
+                Some(value) => value,
+                None => continue,
+            };
+            if self.last_reported_axes.borrow().get(&axis) != Some(&value) {
+                self.queue_translated_event(Event::ControllerAxisMotion { controller, instant, axis, value }, pending_translated_events);
+            }
+        }
+    }
+
     fn controller_state(&self) -> device::Result<OsControllerState> {
         let state = OsControllerState {
             buttons: self.evdev_all_controller_buttons_state(),
@@ -1585,9 +2327,35 @@ impl Linuxdev {
     //
 
     fn controller_set_vibration(&self, vibration: &VibrationState) -> device::Result<()> {
+        self.ensure_can_rumble()?;
+        if vibration.is_zero() && self.evdev.as_ref().unwrap().rumble_ff_id.get() == -1 {
+            return Ok(());
+        }
+
+        let mut ff = vibration.to_ff_effect();
+        let number_of_times_to_play = ::std::i32::MAX * vibration.is_zero() as i32;
+        self.upload_rumble_ff_effect(&mut ff)?;
+        self.write_ff_event(ff.id as u16, number_of_times_to_play)
+    }
+    fn controller_rumble_pulse(&self, vibration: &VibrationState, duration: Duration) -> device::Result<()> {
+        self.ensure_can_rumble()?;
+        // The kernel stops the effect by itself once its replay length has elapsed.
+        let mut ff = vibration.to_ff_effect();
+        let ms = duration.as_secs().saturating_mul(1000).saturating_add(duration.subsec_nanos() as u64 / 1_000_000);
+        ff.replay.length = ::std::cmp::min(ms, 0x7fff) as u16;
+        if ff.replay.length == 0 {
+            return Ok(());
+        }
+        self.upload_rumble_ff_effect(&mut ff)?;
+        self.write_ff_event(ff.id as u16, 1)
+    }
+    fn ensure_can_rumble(&self) -> device::Result<()> {
         if self.evdev.is_none() {
             return device::not_supported_by_device_unexplained();
         }
+        if self.is_driver_unbound.get() {
+            return device::not_supported_by_device("The device's driver is currently unbound");
+        }
         assert!(self.fd.is_some());
         let evdev = self.evdev.as_ref().unwrap();
 
@@ -1597,40 +2365,52 @@ impl Linuxdev {
         if evdev.props.max_simultaneous_ff_effects < 1 {
             return device::not_supported_by_device("Device does not support playing at least one force feedback effect");
         }
-        if vibration.is_zero() && evdev.rumble_ff_id.get() == -1 {
-            return Ok(());
-        }
-
-        let mut ff = vibration.to_ff_effect();
-        let number_of_times_to_play = ::std::i32::MAX * vibration.is_zero() as i32;
-
+        Ok(())
+    }
+    /// Uploads `ff` as this device's rumble effect, replacing the previous one if any.
+    fn upload_rumble_ff_effect(&self, ff: &mut linux_input::ff_effect) -> device::Result<()> {
+        let evdev = self.evdev.as_ref().unwrap();
         match evdev.rumble_ff_id.get() {
-            -1 => {
-                assert!(!vibration.is_zero());
-                assert_eq!(ff.id, -1);
-                self.register_ff_effect(&mut ff)?;
-                assert_ne!(ff.id, -1);
-                evdev.rumble_ff_id.set(ff.id);
-                // Full power!! we want the vibration to reflect the full capabilities
-                // of the device; the VibrationState is already a percentage of the
-                // amount of vibration wanted by the user, so let's not be slowed
-                // down by some default gain value that is lower than 100%.
-                let max_ff_gain = 0xffff_i32;
-                let status = self.set_ff_gain(max_ff_gain);
-                match status {
-                    Err(device::Error::DeviceDisconnected(_)) => status?,
-                    Err(e) => warn!("Controller {}: Could not set FF_GAIN to {:x}: write() returned {}", self.display(), max_ff_gain, e),
-                    Ok(()) => (),
-                };
-                self.write_ff_event(ff.id as u16, number_of_times_to_play)
-            },
+            -1 => self.register_rumble_ff_effect(ff),
             id => {
                 ff.id = id;
-                self.register_ff_effect(&mut ff)?;
-                self.write_ff_event(id as u16, number_of_times_to_play)
+                let status = self.try_register_ff_effect(ff);
+                match status {
+                    // The kernel forgets uploaded effects when the device is reset, which notably
+                    // happens across a suspend/resume cycle; our cached ID is then rejected.
+                    // Upload the effect again under a fresh ID (only once; if this fails too,
+                    // something else is going on).
+                    Err(nix::Error::Sys(Errno::EINVAL)) => {
+                        info!("Controller {}: force feedback effect {} is gone (was the system suspended?); registering it again", self.display(), id);
+                        evdev.rumble_ff_id.set(-1);
+                        ff.id = -1;
+                        self.register_rumble_ff_effect(ff)
+                    },
+                    status => self.check_ff_effect_registration(status, ff),
+                }
             },
         }
     }
+    fn register_rumble_ff_effect(&self, ff: &mut linux_input::ff_effect) -> device::Result<()> {
+        assert_eq!(ff.id, -1);
+        self.register_ff_effect(ff)?;
+        assert_ne!(ff.id, -1);
+        self.evdev.as_ref().unwrap().rumble_ff_id.set(ff.id);
+        // Full power!! we want the vibration to reflect the full capabilities
+        // of the device; the VibrationState is already a percentage of the
+        // amount of vibration wanted by the user, so let's not be slowed
+        // down by some default gain value that is lower than 100%.
+        let max_ff_gain = 0xffff_i32;
+        let status = self.set_ff_gain(max_ff_gain);
+        match status {
+            Err(device::Error::DeviceDisconnected(_)) => status,
+            Err(e) => {
+                warn!("Controller {}: Could not set FF_GAIN to {:x}: write() returned {}", self.display(), max_ff_gain, e);
+                Ok(())
+            },
+            Ok(()) => Ok(()),
+        }
+    }
     // The FF_GAIN property defines a factor for the strength of force-feedback
     // effects. It ranges from 0% to 100% (0 to 0xffff).
     // The default value is not specified, but I've seen it set to something
@@ -1642,13 +2422,19 @@ impl Linuxdev {
         self.write_ff_event(ff::FF_GAIN as u16, gain)
     }
     fn register_ff_effect(&self, ff: &mut linux_input::ff_effect) -> device::Result<()> {
+        let status = self.try_register_ff_effect(ff);
+        self.check_ff_effect_registration(status, ff)
+    }
+    fn try_register_ff_effect(&self, ff: &mut linux_input::ff_effect) -> nix::Result<()> {
         let fd = self.fd.unwrap();
         assert!(self.fd_has_write_access);
 
         // Upload the effect. If its id is set to -1, the kernel or driver changes it to a valid value.
-        let status = unsafe {
-            ev_ioctl::register_ff_effect(fd, ff)
-        };
+        unsafe {
+            ev_ioctl::register_ff_effect(fd, ff).map(|_| ())
+        }
+    }
+    fn check_ff_effect_registration(&self, status: nix::Result<()>, ff: &linux_input::ff_effect) -> device::Result<()> {
         match status {
             Err(nix::Error::Sys(Errno::ENODEV)) => return device::disconnected(),
             Err(e) => return device::not_supported_by_device(format!("Controller {}: could not register force feedback effect: ioctl() generated {}", self.display(), e)),
@@ -1795,6 +2581,11 @@ mod linux_bus_id {
 }
 
 
+fn input_event_instant(ev: &linux_input::input_event) -> EventInstant {
+    let c::timeval { tv_sec, tv_usec } = ev.time;
+    EventInstant(OsEventInstant::LinuxInputEventTimeval { tv_sec, tv_usec })
+}
+
 #[allow(dead_code)]
 // I might have gotten some of these wrong.
 mod ev_ioctl {
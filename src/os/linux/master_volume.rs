@@ -0,0 +1,59 @@
+//! The master volume, via PulseAudio's `pactl` tool.
+//!
+//! This also works with PipeWire, which provides a PulseAudio-compatible server.
+//! Reading the volume requires `pactl get-sink-volume`, which appeared in PulseAudio 15.
+
+use std::io;
+use std::process::{Command, Output};
+use error::{Result, failed, unsupported};
+use super::OsContext;
+
+// PA_VOLUME_NORM, i.e 100%.
+const PA_VOLUME_NORM: f32 = 0x10000 as f32;
+
+fn pactl(args: &[&str]) -> Result<Output> {
+    let output = match Command::new("pactl").args(args).output() {
+        Ok(output) => output,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return unsupported("The master volume can only be accessed via PulseAudio's `pactl` tool, which was not found"),
+        Err(e) => return failed(format!("Could not run `pactl`: {}", e)),
+    };
+    if !output.status.success() {
+        return failed(format!("`pactl` failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output)
+}
+
+/// Parses the first line of `pactl --version`, e.g `pactl 14.2`.
+fn pactl_major_version() -> Option<u32> {
+    let output = pactl(&["--version"]).ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next()?.split_whitespace().nth(1)?.split('.').next()?.parse().ok()
+}
+
+impl OsContext {
+    pub fn set_master_volume(&self, volume: f32) -> Result<()> {
+        // Raw volumes are more precise than percentages, and apply to all channels.
+        let raw = (volume * PA_VOLUME_NORM).round() as u32;
+        pactl(&["set-sink-volume", "@DEFAULT_SINK@", &raw.to_string()]).map(|_| ())
+    }
+    pub fn master_volume(&self) -> Result<f32> {
+        // The output looks like: `Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB`.
+        // We report the loudest channel, which is also what PulseAudio does.
+        let output = match pactl(&["get-sink-volume", "@DEFAULT_SINK@"]) {
+            Ok(output) => output,
+            Err(e) => match pactl_major_version() {
+                Some(major) if major < 15 => return unsupported(format!("Reading the master volume requires `pactl` 15 or later (found version {})", major)),
+                _ => return Err(e),
+            },
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let raw = stdout.split(',')
+            .filter_map(|channel| channel.rsplit(':').next())
+            .filter_map(|value| value.split('/').next()?.trim().parse::<u32>().ok())
+            .max();
+        match raw {
+            Some(raw) => Ok(raw as f32 / PA_VOLUME_NORM),
+            None => failed(format!("Unexpected `pactl` output: `{}`", stdout.trim())),
+        }
+    }
+}
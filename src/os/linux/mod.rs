@@ -1,9 +1,17 @@
 mod linuxdev;
+mod termination_signals;
+mod app_dirs;
+mod file_dialog;
+mod master_volume;
+mod process_name;
 
 extern crate x11;
 extern crate libc;
 
-use std::time::Instant;
+use std::fmt;
+use std::cell::RefCell;
+use std::os::unix::io::RawFd;
+use std::time::{Duration, Instant};
 use std::ops::Range;
 use std::path::Path;
 use std::collections::HashMap;
@@ -13,11 +21,13 @@ use uuid::Uuid as Guid;
 use self::x11::xlib as x;
 use self::x11::xinput2 as xi2;
 
-use self::linuxdev::{LinuxdevContext, LinuxdevToken, LinuxdevAxisInfo, LinuxdevDeviceInfo, LinuxdevUnprocessedEvent};
+use self::termination_signals::TerminationSignals;
+use self::linuxdev::{LinuxdevHandle, LinuxdevToken, LinuxdevAxisInfo, LinuxdevDeviceInfo, LinuxdevUnprocessedEvent};
 pub use self::linuxdev::{OsControllerInfo, OsControllerState};
+pub use self::app_dirs::app_dir_base;
 use x11::{
     set_hint as set_hint_x11,
-    X11Context, X11Window, X11WindowHandle, X11WindowFromHandleParams, X11Cursor,
+    X11Context, X11Window, X11InputGrab, X11WindowHandle, X11WindowFromHandleParams, X11Cursor, X11PointerBarrierID, X11MonitorID, X11DisplayModeGuard,
     X11GLPixelFormat, X11GLContext,
     X11Keysym, X11Keycode,
     X11UnprocessedEvent,
@@ -29,39 +39,55 @@ use x11::{
     X11DeviceID,
 };
 use error::{Result};
+use context::{Context, ContextOptions, BackendInfo};
 use desktop::Desktop;
-use window::WindowSettings;
+use window::{WindowSettings, GrabHolder};
 use event::{Event, EventInstant, UnprocessedEvent};
+use event_queue::QueuePolicy;
 use timeout::Timeout;
 use device::{
     self,
     DeviceID, DeviceInfo, ButtonState, UsbIDs, Bus,
     ControllerButton, ControllerAxis, ControllerState, ControllerInfo,
-    VibrationState,
-    KeyboardInfo, KeyState, KeyboardState, Keysym, Keycode,
+    VibrationState, EventMode,
+    KeyboardInfo, KeyState, KeyboardState, Keysym, Keycode, ModifiersState,
     MouseInfo, MouseState,
     TabletInfo, TabletState, TabletPadButton, TabletStylusButton,
     TouchInfo,
 };
 use cursor::{SystemCursor, RgbaCursorData, RgbaCursorAnimFrame};
+use pointer_barrier::PointerBarrierDirections;
+use monitor::{GammaRamps, DisplayMode};
+use stats::ContextStats;
 use gl::{GLPixelFormatSettings, GLContextSettings};
-use Extent2;
+use {Vec2, Extent2, Rect};
 
 
 pub fn set_hint(hint: ::hint::Hint) -> Result<()> {
-    set_hint_x11(hint)
+    match hint {
+        ::hint::Hint::LazyDeviceInfo(lazy) => {
+            linuxdev::set_lazy_device_info(lazy);
+            Ok(())
+        },
+        hint => set_hint_x11(hint),
+    }
 }
 
 #[derive(Debug)]
 pub struct OsContext {
     pub x11: X11Context,
-    pub linuxdev: LinuxdevContext,
+    pub linuxdev: LinuxdevHandle,
+    pub termination_signals: RefCell<Option<TerminationSignals>>,
 }
 
 pub type OsWindow = X11Window;
+pub type OsInputGrab = X11InputGrab;
 pub type OsWindowHandle = X11WindowHandle;
 pub type OsWindowFromHandleParams = X11WindowFromHandleParams;
 pub type OsCursor = X11Cursor;
+pub type OsPointerBarrierID = X11PointerBarrierID;
+pub type OsMonitorID = X11MonitorID;
+pub type OsDisplayModeGuard = X11DisplayModeGuard;
 pub type OsGLPixelFormat = X11GLPixelFormat;
 pub type OsGLContext = X11GLContext;
 pub type OsKeycode = X11Keycode;
@@ -141,6 +167,15 @@ impl From<X11DeviceID> for OsDeviceID {
     }
 }
 
+impl fmt::Display for DeviceID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            OsDeviceID::X11(x11) => write!(f, "x11:{}", x11),
+            OsDeviceID::Linuxdev(token) => write!(f, "linuxdev:{}", token),
+        }
+    }
+}
+
 impl OsDeviceID {
     pub fn x11(self) -> device::Result<X11DeviceID> {
         match self {
@@ -193,8 +228,10 @@ impl OsDeviceInfo {
     pub fn bus(&self) -> Option<Bus> { self.linuxdev.bus() }
     pub fn driver_name(&self) -> Option<&str> { self.linuxdev.driver_name() }
     pub fn driver_version(&self) -> Option<&str> { self.linuxdev.driver_version() }
+    pub fn physical_location(&self) -> Option<&str> { self.linuxdev.physical_location() }
     pub fn is_physical(&self) -> Option<bool> { self.linuxdev.is_physical() }
     pub fn controller(&self) -> Option<&ControllerInfo> { self.linuxdev.controller() }
+    pub fn controller_mut(&mut self) -> Option<&mut ControllerInfo> { self.linuxdev.controller_mut() }
     pub fn mouse(&self) -> Option<&MouseInfo> { self.linuxdev.mouse() }
     pub fn keyboard(&self) -> Option<&KeyboardInfo> { self.linuxdev.keyboard() }
     pub fn touch(&self) -> Option<&TouchInfo> { self.linuxdev.touch() }
@@ -202,28 +239,88 @@ impl OsDeviceInfo {
 }
 
 
+impl Context {
+    /// (Linux-only) Gets the file descriptor that becomes readable when a termination signal is
+    /// caught, if `catch_termination_signals()` was called.
+    ///
+    /// This is meant for `poll()`-based event loops: when it is readable, the next call to
+    /// `poll_event()` returns `Event::Quit`. Don't read from it yourself.
+    pub fn termination_signal_fd(&self) -> Option<RawFd> {
+        self.os.termination_signals.borrow().as_ref().map(TerminationSignals::fd)
+    }
+    /// (Linux-only) Gets the evdev file descriptor of a controller, if it is one that was
+    /// opened via udev.
+    ///
+    /// This is an escape hatch for issuing `ioctl()`s that this crate doesn't support yet.
+    /// The file descriptor is owned by the `Context`, and is closed when the controller
+    /// is disconnected; Don't close it yourself.
+    ///
+    /// # Safety
+    ///
+    /// Reading from the file descriptor steals events from this crate. Writing to it, or issuing
+    /// `ioctl()`s that modify the device's state (e.g uploading or removing force-feedback effects,
+    /// or grabbing the device), can desynchronize the state this crate tracks for that device.
+    pub fn controller_raw_fd(&self, device: DeviceID) -> Option<RawFd> {
+        match device.0 {
+            OsDeviceID::Linuxdev(token) => self.os.linuxdev.device_fd(token),
+            _ => None,
+        }
+    }
+}
+
 impl From<X11Context> for OsContext {
     fn from(x11: X11Context) -> Self {
-        Self { x11, linuxdev: LinuxdevContext::default(), }
+        Self { x11, linuxdev: LinuxdevHandle::new(), termination_signals: RefCell::new(None), }
     }
 }
 
 impl OsContext {
-    pub fn new() -> Result<Self> {
-        X11Context::new().map(Self::from)
+    pub fn with_options(options: &ContextOptions) -> Result<Self> {
+        let os = Self::from(X11Context::with_options(options)?);
+        if options.replay_initial_devices {
+            os.linuxdev.replay_initial_devices();
+        }
+        Ok(os)
     }
     pub fn create_window(&self, window_settings: &WindowSettings) -> Result<OsWindow> {
         self.x11.create_window(window_settings)
     }
+    pub fn create_window_on_screen(&self, screen: usize, window_settings: &WindowSettings) -> Result<OsWindow> {
+        self.x11.create_window_on_screen(screen, window_settings)
+    }
+    pub fn create_offscreen_gl_context(&self, settings: &GLContextSettings) -> Result<OsGLContext> {
+        self.x11.create_offscreen_gl_context(settings)
+    }
+    pub fn screen_count(&self) -> Result<usize> {
+        self.x11.screen_count()
+    }
+    pub fn pointer_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        self.x11.pointer_grab_holder()
+    }
+    pub fn keyboard_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        self.x11.keyboard_grab_holder()
+    }
     pub fn window_from_handle(&self, handle: OsWindowHandle, params: Option<&OsWindowFromHandleParams>) -> Result<OsWindow> {
         self.x11.window_from_handle(handle, params)
     }
+    pub fn window_count(&self) -> usize {
+        self.x11.window_count()
+    }
+    pub fn windows(&self) -> Vec<OsWindowHandle> {
+        self.x11.windows()
+    }
+    pub fn window_by_handle(&self, handle: OsWindowHandle) -> Option<OsWindow> {
+        self.x11.window_by_handle(handle)
+    }
     pub fn desktops(&self) -> Result<Vec<Desktop>> {
         self.x11.desktops()
     }
     pub fn current_desktop(&self) -> Result<usize> {
         self.x11.current_desktop()
     }
+    pub fn accessibility_scale_factor(&self) -> Result<f64> {
+        self.x11.accessibility_scale_factor()
+    }
     pub fn create_system_cursor(&self, s: SystemCursor) -> Result<OsCursor> {
         self.x11.create_system_cursor(s)
     }
@@ -236,11 +333,87 @@ impl OsContext {
     pub fn create_animated_rgba_cursor(&self, frames: &[RgbaCursorAnimFrame]) -> Result<OsCursor> {
         self.x11.create_animated_rgba_cursor(frames)
     }
+    pub fn backend_info(&self) -> BackendInfo {
+        self.x11.backend_info()
+    }
     pub fn untrap_mouse(&self) -> Result<()> {
         self.x11.untrap_mouse()
     }
+    pub fn create_pointer_barrier(&self, from: Vec2<i32>, to: Vec2<i32>, directions: PointerBarrierDirections) -> Result<OsPointerBarrierID> {
+        self.x11.create_pointer_barrier(from, to, directions)
+    }
+    pub fn destroy_pointer_barrier(&self, barrier: OsPointerBarrierID) -> Result<()> {
+        self.x11.destroy_pointer_barrier(barrier)
+    }
+    pub fn monitors(&self) -> Result<Vec<OsMonitorID>> {
+        self.x11.monitors()
+    }
+    pub fn monitor_rect(&self, monitor: OsMonitorID) -> Result<Rect<i32, u32>> {
+        self.x11.monitor_rect(monitor)
+    }
+    pub fn primary_monitor(&self) -> Result<Option<OsMonitorID>> {
+        self.x11.primary_monitor()
+    }
+    pub fn display_modes(&self, monitor: OsMonitorID) -> Result<Vec<DisplayMode>> {
+        self.x11.display_modes(monitor)
+    }
+    pub fn current_display_mode(&self, monitor: OsMonitorID) -> Result<DisplayMode> {
+        self.x11.current_display_mode(monitor)
+    }
+    pub fn set_display_mode(&self, monitor: OsMonitorID, mode: &DisplayMode) -> Result<OsDisplayModeGuard> {
+        self.x11.set_display_mode(monitor, mode)
+    }
+    pub fn monitor_gamma(&self, monitor: OsMonitorID) -> Result<GammaRamps> {
+        self.x11.monitor_gamma(monitor)
+    }
+    pub fn original_monitor_gamma(&self, monitor: OsMonitorID) -> Result<GammaRamps> {
+        self.x11.original_monitor_gamma(monitor)
+    }
+    pub fn set_monitor_gamma(&self, monitor: OsMonitorID, ramps: &GammaRamps) -> Result<()> {
+        self.x11.set_monitor_gamma(monitor, ramps)
+    }
     fn poll_next_event(&self) -> Option<Event> {
-        self.linuxdev.poll_next_event().or_else(|| self.x11.poll_next_event())
+        if self.poll_termination_signals() {
+            return Some(Event::Quit);
+        }
+        self.poll_controller_event().or_else(|| self.x11.poll_next_event())
+    }
+    fn poll_termination_signals(&self) -> bool {
+        match *self.termination_signals.borrow() {
+            Some(ref signals) => signals.poll(),
+            None => false,
+        }
+    }
+    pub fn catch_termination_signals(&self) -> Result<()> {
+        if self.termination_signals.borrow().is_some() {
+            return Ok(());
+        }
+        *self.termination_signals.borrow_mut() = Some(TerminationSignals::catch()?);
+        Ok(())
+    }
+    pub fn fill_stats(&self, stats: &mut ContextStats) {
+        stats.devices_tracked = (self.x11.xi2_devices.borrow().len() + self.linuxdev.nb_controllers()) as _;
+        stats.queue_high_water_mark = ::std::cmp::max(self.x11.queue_high_water_mark.get(), self.linuxdev.queue_high_water_mark()) as _;
+        stats.x_errors_trapped = unsafe { ::x11::xlib_error::TRAPPED_ERROR_COUNT };
+        stats.udev_actions_seen = self.linuxdev.udev_actions_seen();
+    }
+    pub fn reset_stats(&self) {
+        self.x11.queue_high_water_mark.set(0);
+        self.linuxdev.reset_stats();
+        unsafe {
+            ::x11::xlib_error::TRAPPED_ERROR_COUNT = 0;
+        }
+    }
+    pub fn poll_controller_event(&self) -> Option<Event> {
+        if self.linuxdev.has_pending_events() {
+            self.linuxdev.poll_next_event()
+        } else {
+            None
+        }
+    }
+    /// The earliest moment at which events need to be pumped, regardless of incoming events.
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        self.x11.next_window_flash_deadline()
     }
     pub fn next_event(&self, timeout: Timeout) -> Option<Event> {
         match timeout.duration() {
@@ -267,6 +440,13 @@ impl OsContext {
     pub fn supports_raw_device_events(&self) -> Result<bool> {
         self.x11.supports_raw_device_events()
     }
+    pub fn set_event_queue_policy(&self, policy: QueuePolicy) {
+        self.x11.set_event_queue_policy(policy);
+        self.linuxdev.set_event_queue_policy(policy);
+    }
+    pub fn event_queue_policy(&self) -> QueuePolicy {
+        self.x11.event_queue_policy()
+    }
     pub fn devices(&self) -> device::Result<HashMap<DeviceID, DeviceInfo>> {
         match (self.x11.devices(), self.linuxdev.controllers()) {
             (Ok(mut devs), Ok(controllers)) => Ok({
@@ -287,15 +467,27 @@ impl OsContext {
             },
         }
     }
+    pub fn is_device_id_current(&self, id: DeviceID) -> bool {
+        match id.0 {
+            OsDeviceID::X11(x11) => self.x11.is_device_id_current(x11),
+            OsDeviceID::Linuxdev(token) => self.linuxdev.is_token_current(token),
+        }
+    }
     pub fn ping_device(&self, id: DeviceID) -> device::Result<()> {
         match id.0 {
             OsDeviceID::Linuxdev(token) => self.linuxdev.ping_controller(token),
             _ => unimplemented!{},
         }
     }
+    pub fn controller_info(&self, controller: DeviceID) -> device::Result<ControllerInfo> {
+        self.linuxdev.controller_info(controller)
+    }
     pub fn controller_state(&self, controller: DeviceID) -> device::Result<ControllerState> {
         self.linuxdev.controller_state(controller)
     }
+    pub fn all_controller_states(&self) -> device::Result<HashMap<DeviceID, ControllerState>> {
+        self.linuxdev.all_controller_states()
+    }
     pub fn controller_button_state(&self, controller: DeviceID, button: ControllerButton) -> device::Result<ButtonState> {
         self.linuxdev.controller_button_state(controller, button)
     }
@@ -305,6 +497,18 @@ impl OsContext {
     pub fn controller_set_vibration(&self, controller: DeviceID, vibration: &VibrationState) -> device::Result<()> {
         self.linuxdev.controller_set_vibration(controller, vibration)
     }
+    pub fn controller_rumble_pulse(&self, controller: DeviceID, vibration: &VibrationState, duration: Duration) -> device::Result<()> {
+        self.linuxdev.controller_rumble_pulse(controller, vibration, duration)
+    }
+    pub fn set_controller_event_mode(&self, controller: DeviceID, mode: EventMode) -> device::Result<()> {
+        self.linuxdev.set_controller_event_mode(controller, mode)
+    }
+    pub fn controller_player_index(&self, controller: DeviceID) -> device::Result<Option<u32>> {
+        self.linuxdev.controller_player_index(controller)
+    }
+    pub fn set_controller_player_index(&self, controller: DeviceID, player_index: Option<u32>) -> device::Result<()> {
+        self.linuxdev.set_controller_player_index(controller, player_index)
+    }
     pub fn main_mouse(&self) -> device::Result<DeviceID> {
         Ok(self.x11.core_x_mouse_deviceid())
     }
@@ -323,12 +527,27 @@ impl OsContext {
     pub fn keysym_name(&self, keysym: Keysym) -> device::Result<String> {
         self.x11.keysym_name(keysym)
     }
+    pub fn keysym_to_char(&self, keysym: Keysym, modifiers: ModifiersState) -> Option<char> {
+        self.x11.keysym_to_char(keysym, modifiers)
+    }
     pub fn keysym_from_keycode(&self, keyboard: DeviceID, keycode: Keycode) -> device::Result<Keysym> {
         self.x11.keysym_from_keycode(keyboard.0.x11()?, keycode)
     }
+    pub fn keyboard_current_group(&self, keyboard: DeviceID) -> device::Result<u32> {
+        self.x11.keyboard_current_group(keyboard.0.x11()?)
+    }
+    pub fn set_keyboard_current_group(&self, keyboard: DeviceID, group: u32) -> device::Result<()> {
+        self.x11.set_keyboard_current_group(keyboard.0.x11()?, group)
+    }
+    pub fn keyboard_group_names(&self, keyboard: DeviceID) -> device::Result<Vec<String>> {
+        self.x11.keyboard_group_names(keyboard.0.x11()?)
+    }
     pub fn keycode_from_keysym(&self, keyboard: DeviceID, keysym: Keysym) -> device::Result<Keycode> {
         self.x11.keycode_from_keysym(keyboard.0.x11()?, keysym)
     }
+    pub fn dump_keymap(&self) -> Vec<(Keycode, Keysym, String)> {
+        self.x11.dump_keymap()
+    }
     pub fn mouse_state(&self, mouse: DeviceID) -> device::Result<MouseState> {
         self.x11.mouse_state(mouse.0.x11()?)
     }
@@ -336,3 +555,16 @@ impl OsContext {
         self.x11.tablet_state(tablet.0.x11()?)
     }
 }
+
+#[cfg(feature = "synthetic-input")]
+impl OsContext {
+    pub fn synthesize_key(&self, keysym: Keysym, state: ButtonState) -> Result<()> {
+        self.x11.synthesize_key(keysym, state)
+    }
+    pub fn synthesize_mouse_move_to(&self, position: Vec2<i32>) -> Result<()> {
+        self.x11.synthesize_mouse_move_to(position)
+    }
+    pub fn synthesize_mouse_button(&self, button: ::device::MouseButton, state: ButtonState) -> Result<()> {
+        self.x11.synthesize_mouse_button(button, state)
+    }
+}
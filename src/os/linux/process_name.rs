@@ -0,0 +1,27 @@
+//! The process name, via `prctl(PR_SET_NAME)`.
+
+use std::ffi::CString;
+use error::{Result, failed};
+use super::OsContext;
+use super::libc as c;
+
+// TASK_COMM_LEN, including the NUL terminator.
+const MAX_NAME_LEN: usize = 16 - 1;
+
+impl OsContext {
+    pub fn set_process_name(&self, name: &str) -> Result<()> {
+        // The kernel truncates bytes, but we'd rather not split a UTF-8 sequence.
+        let mut len = ::std::cmp::min(name.len(), MAX_NAME_LEN);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        let name = CString::new(&name[..len]).unwrap();
+        let status = unsafe {
+            c::prctl(c::PR_SET_NAME, name.as_ptr() as c::c_ulong, 0 as c::c_ulong, 0 as c::c_ulong, 0 as c::c_ulong)
+        };
+        if status != 0 {
+            return failed(format!("prctl(PR_SET_NAME) failed: {}", ::std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+//! Catching SIGTERM and SIGINT, so that they can be reported as `Event::Quit`.
+//!
+//! This uses the self-pipe trick: the signal handler only writes a byte to a non-blocking pipe
+//! (which is async-signal-safe), and the read end of the pipe is checked when polling events.
+//! The read end is also suitable for `poll()`-based event loops.
+//!
+//! Each `Context` that catches termination signals has its own pipe, so that each of them gets
+//! the `Event::Quit`. The handlers are installed while at least one pipe is registered, and the
+//! previous ones are restored when the last one is dropped.
+
+use std::fmt;
+use std::os::unix::io::RawFd;
+use std::os::raw::c_int;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI32, Ordering};
+use nix::{self, errno::Errno, fcntl::OFlag, unistd};
+use nix::sys::signal::{self, Signal, SigAction, SigHandler, SaFlags, SigSet};
+use error::{Result, failed};
+use super::libc as c;
+
+/// How many `Context`s may catch termination signals at the same time.
+const MAX_PIPES: usize = 16;
+
+// The signal handler can't receive any context, so these have to be globals.
+// A slot is -1 when unused. Atomics are fine to access from a signal handler, unlike a Mutex.
+static WRITE_FDS: [AtomicI32; MAX_PIPES] = [
+    AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1),
+    AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1),
+    AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1),
+    AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1), AtomicI32::new(-1),
+];
+
+/// The handlers that were there before ours, along with the number of registered pipes.
+/// This is never touched by the signal handler.
+static INSTALLED: Mutex<Option<(SigAction, SigAction, usize)>> = Mutex::new(None);
+
+extern "C" fn on_termination_signal(_signum: c_int) {
+    unsafe {
+        // write() may clobber errno, which would be visible to the interrupted code.
+        let errno = *c::__errno_location();
+        for write_fd in WRITE_FDS.iter() {
+            let write_fd = write_fd.load(Ordering::SeqCst);
+            if write_fd != -1 {
+                // If the pipe is full, a Quit event is pending anyway.
+                c::write(write_fd, b"q".as_ptr() as _, 1);
+            }
+        }
+        *c::__errno_location() = errno;
+    }
+}
+
+pub struct TerminationSignals {
+    read_fd: RawFd,
+    write_fd: RawFd,
+    slot: usize,
+}
+
+impl fmt::Debug for TerminationSignals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TerminationSignals")
+            .field("read_fd", &self.read_fd)
+            .field("write_fd", &self.write_fd)
+            .field("slot", &self.slot)
+            .finish()
+    }
+}
+
+impl TerminationSignals {
+    pub fn catch() -> Result<Self> {
+        let (read_fd, write_fd) = match unistd::pipe2(OFlag::O_NONBLOCK | OFlag::O_CLOEXEC) {
+            Ok(fds) => fds,
+            Err(e) => return failed(format!("pipe2() failed: {}", e)),
+        };
+        let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = WRITE_FDS.iter().position(|fd| fd.compare_exchange(-1, write_fd, Ordering::SeqCst, Ordering::SeqCst).is_ok());
+        let slot = match slot {
+            Some(slot) => slot,
+            None => {
+                close_pipe(read_fd, write_fd);
+                return failed(format!("At most {} Contexts may catch termination signals at the same time", MAX_PIPES));
+            },
+        };
+        match installed.as_mut() {
+            Some(&mut (_, _, ref mut count)) => *count += 1,
+            None => match install_handlers() {
+                Ok((old_sigterm_action, old_sigint_action)) => *installed = Some((old_sigterm_action, old_sigint_action, 1)),
+                Err(e) => {
+                    WRITE_FDS[slot].store(-1, Ordering::SeqCst);
+                    close_pipe(read_fd, write_fd);
+                    return Err(e);
+                },
+            },
+        }
+        Ok(Self { read_fd, write_fd, slot })
+    }
+    pub fn fd(&self) -> RawFd {
+        self.read_fd
+    }
+    /// Drains the pipe, returning `true` if at least one signal was caught since the last call.
+    pub fn poll(&self) -> bool {
+        let mut buf = [0_u8; 64];
+        let mut was_caught = false;
+        loop {
+            match unistd::read(self.read_fd, &mut buf) {
+                Ok(0) => return was_caught,
+                Ok(_) => was_caught = true,
+                Err(nix::Error::Sys(Errno::EINTR)) => continue,
+                Err(nix::Error::Sys(Errno::EAGAIN)) => return was_caught,
+                Err(e) => {
+                    warn!("Reading the termination signals pipe failed: {}", e);
+                    return was_caught;
+                },
+            }
+        }
+    }
+}
+
+impl Drop for TerminationSignals {
+    fn drop(&mut self) {
+        let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+        WRITE_FDS[self.slot].store(-1, Ordering::SeqCst);
+        let is_last = match installed.as_mut() {
+            Some(&mut (_, _, ref mut count)) => {
+                *count -= 1;
+                *count == 0
+            },
+            None => false,
+        };
+        if is_last {
+            if let Some((old_sigterm_action, old_sigint_action, _)) = installed.take() {
+                unsafe {
+                    let _ = signal::sigaction(Signal::SIGTERM, &old_sigterm_action);
+                    let _ = signal::sigaction(Signal::SIGINT, &old_sigint_action);
+                }
+            }
+        }
+        close_pipe(self.read_fd, self.write_fd);
+    }
+}
+
+/// Installs our handler for SIGTERM and SIGINT, returning the previous ones.
+fn install_handlers() -> Result<(SigAction, SigAction)> {
+    let action = SigAction::new(SigHandler::Handler(on_termination_signal), SaFlags::SA_RESTART, SigSet::empty());
+    unsafe {
+        let old_sigterm_action = match signal::sigaction(Signal::SIGTERM, &action) {
+            Ok(old) => old,
+            Err(e) => return failed(format!("sigaction(SIGTERM) failed: {}", e)),
+        };
+        let old_sigint_action = match signal::sigaction(Signal::SIGINT, &action) {
+            Ok(old) => old,
+            Err(e) => {
+                let _ = signal::sigaction(Signal::SIGTERM, &old_sigterm_action);
+                return failed(format!("sigaction(SIGINT) failed: {}", e));
+            },
+        };
+        Ok((old_sigterm_action, old_sigint_action))
+    }
+}
+
+fn close_pipe(read_fd: RawFd, write_fd: RawFd) {
+    let _ = unistd::close(read_fd);
+    let _ = unistd::close(write_fd);
+}
@@ -0,0 +1,7 @@
+use std::path::PathBuf;
+use app_dirs::AppDirKind;
+use error::Result;
+
+pub fn app_dir_base(_kind: AppDirKind) -> Result<PathBuf> {
+    unimplemented!()
+}
@@ -1,14 +1,57 @@
 use error::Result;
+use std::path::PathBuf;
+use context::{ContextOptions, BackendInfo};
+use file_dialog::FileDialogOptions;
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasRawDisplayHandle, RawDisplayHandle, AppKitDisplayHandle};
 
 #[derive(Debug)]
 pub struct OsContext;
 
 impl OsContext {
-    pub fn new() -> Result<Self> {
+    pub fn with_options(_options: &ContextOptions) -> Result<Self> {
+        unimplemented!()
+    }
+    pub fn backend_info(&self) -> BackendInfo {
         unimplemented!()
     }
     pub fn untrap_mouse(&self) -> Result<()> {
         unimplemented!()
     }
+    pub fn show_open_file_dialog(&self, _options: &FileDialogOptions) -> Result<Option<Vec<PathBuf>>> {
+        unimplemented!()
+    }
+    pub fn show_save_file_dialog(&self, _options: &FileDialogOptions) -> Result<Option<PathBuf>> {
+        unimplemented!()
+    }
+    pub fn set_master_volume(&self, _volume: f32) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn master_volume(&self) -> Result<f32> {
+        unimplemented!()
+    }
+    pub fn set_process_name(&self, _name: &str) -> Result<()> {
+        unimplemented!()
+    }
 }
 
+
+#[cfg(feature = "synthetic-input")]
+impl OsContext {
+    pub fn synthesize_key(&self, keysym: ::device::Keysym, state: ::device::ButtonState) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn synthesize_mouse_move_to(&self, position: ::Vec2<i32>) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn synthesize_mouse_button(&self, button: ::device::MouseButton, state: ::device::ButtonState) -> Result<()> {
+        unimplemented!()
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+unsafe impl HasRawDisplayHandle for ::context::Context {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::AppKit(AppKitDisplayHandle::empty())
+    }
+}
@@ -9,4 +9,7 @@ impl OsContext {
     pub fn current_desktop(&self) -> Result<usize> {
         unimplemented!()
     }
+    pub fn accessibility_scale_factor(&self) -> Result<f64> {
+        unimplemented!()
+    }
 }
@@ -1,7 +1,10 @@
+use std::time::Instant;
 use super::OsContext;
 use timeout::Timeout;
 use error::Result;
 use event::Event;
+use event_queue::QueuePolicy;
+use stats::ContextStats;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct OsUnprocessedEvent;
@@ -14,8 +17,29 @@ impl OsContext {
     pub fn supports_raw_device_events(&self) -> Result<bool> {
         unimplemented!()
     }
+    pub fn catch_termination_signals(&self) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn set_event_queue_policy(&self, policy: QueuePolicy) {
+        unimplemented!()
+    }
+    pub fn event_queue_policy(&self) -> QueuePolicy {
+        unimplemented!()
+    }
+    pub fn poll_controller_event(&self) -> Option<Event> {
+        unimplemented!()
+    }
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        unimplemented!()
+    }
     pub fn next_event(&self, timeout: Timeout) -> Option<Event> {
         unimplemented!()
     }
+    pub fn fill_stats(&self, stats: &mut ContextStats) {
+        unimplemented!()
+    }
+    pub fn reset_stats(&self) {
+        unimplemented!()
+    }
 }
 
@@ -1,10 +1,17 @@
 use std::cmp::Ordering;
 use std::time::Duration;
 use std::ops::{Add, Sub, AddAssign, SubAssign};
+use std::fmt::{self, Display, Formatter};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct OsEventInstant;
 
+impl Display for OsEventInstant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        unimplemented!()
+    }
+}
+
 impl PartialOrd for OsEventInstant {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         unimplemented!()
@@ -12,6 +19,12 @@ impl PartialOrd for OsEventInstant {
 }
 
 impl OsEventInstant {
+    pub fn now_from_same_clock(&self) -> Option<Self> {
+        unimplemented!()
+    }
+    pub fn is_monotonic(&self) -> bool {
+        unimplemented!()
+    }
     pub fn duration_since(&self, earlier: Self) -> Option<Duration> {
         assert!(self >= &earlier); // Normally already checked by EventInstant::duration_since
         unimplemented!()
@@ -0,0 +1,39 @@
+use monitor::{GammaRamps, DisplayMode};
+use Rect;
+use error::Result;
+use super::OsContext;
+
+pub type OsMonitorID = ();
+
+#[derive(Debug)]
+pub struct OsDisplayModeGuard;
+
+impl OsContext {
+    pub fn monitors(&self) -> Result<Vec<OsMonitorID>> {
+        unimplemented!()
+    }
+    pub fn monitor_rect(&self, monitor: OsMonitorID) -> Result<Rect<i32, u32>> {
+        unimplemented!()
+    }
+    pub fn primary_monitor(&self) -> Result<Option<OsMonitorID>> {
+        unimplemented!()
+    }
+    pub fn display_modes(&self, monitor: OsMonitorID) -> Result<Vec<DisplayMode>> {
+        unimplemented!()
+    }
+    pub fn current_display_mode(&self, monitor: OsMonitorID) -> Result<DisplayMode> {
+        unimplemented!()
+    }
+    pub fn set_display_mode(&self, monitor: OsMonitorID, mode: &DisplayMode) -> Result<OsDisplayModeGuard> {
+        unimplemented!()
+    }
+    pub fn monitor_gamma(&self, monitor: OsMonitorID) -> Result<GammaRamps> {
+        unimplemented!()
+    }
+    pub fn original_monitor_gamma(&self, monitor: OsMonitorID) -> Result<GammaRamps> {
+        unimplemented!()
+    }
+    pub fn set_monitor_gamma(&self, monitor: OsMonitorID, ramps: &GammaRamps) -> Result<()> {
+        unimplemented!()
+    }
+}
@@ -1,18 +1,47 @@
+use std::fmt;
 use error::Result;
-use window::{Window, WindowSettings, WindowHandle, WindowStyleHint, WindowTypeHint};
+use window::{Window, HitTestFn, NativeWindowHandle, WindowSettings, WindowHandle, WindowStyleHint, WindowTypeHint, TitleBarFeatures, ResizeEdge, WindowOp, Capability, ImageMask, GrabOptions, GrabHolder, WindowStateFlags};
+use event::EventInstant;
+use device::MouseButton;
 use super::OsContext;
 use {Vec2, Extent2, Rect, Rgba};
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
 #[derive(Debug)]
 pub struct OsWindow;
 
+#[derive(Debug)]
+pub struct OsInputGrab;
+
 impl OsContext {
     pub fn create_window(&self, settings: &WindowSettings) -> Result<OsWindow> {
         unimplemented!()
     }
+    pub fn screen_count(&self) -> Result<usize> {
+        unimplemented!()
+    }
+    pub fn pointer_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        unimplemented!()
+    }
+    pub fn keyboard_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        unimplemented!()
+    }
+    pub fn create_window_on_screen(&self, screen: usize, settings: &WindowSettings) -> Result<OsWindow> {
+        unimplemented!()
+    }
     pub unsafe fn window_from_handle(&self, handle: OsWindowHandle, params: Option<&OsWindowFromHandleParams>) -> Result<OsWindow> {
         unimplemented!()
     }
+    pub fn window_count(&self) -> usize {
+        unimplemented!()
+    }
+    pub fn windows(&self) -> Vec<OsWindowHandle> {
+        unimplemented!()
+    }
+    pub fn window_by_handle(&self, handle: OsWindowHandle) -> Option<OsWindow> {
+        unimplemented!()
+    }
 }
 
 impl OsWindow {
@@ -22,9 +51,18 @@ impl OsWindow {
     pub fn set_title(&self, title: &str) -> Result<()> {
         unimplemented!()
     }
+    pub fn set_icon_name(&self, icon_name: &str) -> Result<()> {
+        unimplemented!()
+    }
     pub fn title(&self) -> Result<String> {
         unimplemented!()
     }
+    pub fn set_wm_class(&self, instance: &str, class: &str) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn wm_class(&self) -> Result<Option<(String, String)>> {
+        unimplemented!()
+    }
     pub fn set_icon(&self, size: Extent2<u32>, data: &[Rgba<u8>]) -> Result<()> {
         unimplemented!()
     }
@@ -40,9 +78,24 @@ impl OsWindow {
     pub fn set_style_hint(&self, style_hint: &WindowStyleHint) -> Result<()> {
         unimplemented!()
     }
+    pub fn set_title_bar_buttons(&self, buttons: TitleBarFeatures) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn title_bar_buttons(&self) -> TitleBarFeatures {
+        unimplemented!()
+    }
     pub fn raise(&self) -> Result<()> {
         unimplemented!()
     }
+    pub fn focus(&self) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn set_taskbar_progress(&self, progress: Option<f64>) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn focus_with_timestamp(&self, instant: EventInstant) -> Result<()> {
+        unimplemented!()
+    }
     pub fn set_min_size(&self, size: Extent2<u32>) -> Result<()> {
         unimplemented!()
     }
@@ -133,12 +186,18 @@ impl OsWindow {
     pub fn is_fullscreen(&self) -> Result<bool> {
         unimplemented!()
     }
+    pub fn state(&self) -> Result<WindowStateFlags> {
+        unimplemented!()
+    }
     pub fn demand_attention(&self) -> Result<()> {
         unimplemented!()
     }
     pub fn demand_urgent_attention(&self) -> Result<()> {
         unimplemented!()
     }
+    pub fn flash(&self, _count: Option<u32>) -> Result<()> {
+        unimplemented!()
+    }
     pub fn position(&self) -> Result<Vec2<i32>> {
         unimplemented!()
     }
@@ -148,6 +207,18 @@ impl OsWindow {
     pub fn canvas_size(&self) -> Result<Extent2<u32>> {
         unimplemented!()
     }
+    pub fn current_refresh_rate(&self) -> Result<f64> {
+        unimplemented!()
+    }
+    pub fn monitor_rects(&self) -> Result<Vec<Rect<i32, u32>>> {
+        unimplemented!()
+    }
+    pub fn normal_position_and_size(&self) -> Result<Option<Rect<i32, u32>>> {
+        unimplemented!()
+    }
+    pub fn scale_factor(&self) -> Result<f64> {
+        unimplemented!()
+    }
     pub fn size(&self) -> Result<Extent2<u32>> {
         unimplemented!()
     }
@@ -163,9 +234,30 @@ impl OsWindow {
     pub fn set_opacity(&self, alpha: f64) -> Result<()> {
         unimplemented!()
     }
+    pub fn set_shape_mask(&self, mask: Option<&ImageMask>) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn set_input_shape_mask(&self, mask: Option<&ImageMask>) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn set_content_protection(&self, enabled: bool) -> Result<()> {
+        unimplemented!()
+    }
+    pub(crate) fn set_cursor_hit_test(&self, hit_test: Option<HitTestFn>) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn begin_move_drag(&self, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn begin_resize_drag(&self, edge: ResizeEdge, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        unimplemented!()
+    }
     pub fn set_desktop(&self, i: usize) -> Result<()> {
         unimplemented!()
     }
+    pub fn set_visible_on_all_desktops(&self, sticky: bool) -> Result<()> {
+        unimplemented!()
+    }
     pub fn recenter_in_desktop(&self) -> Result<()> {
         unimplemented!()
     }
@@ -181,13 +273,50 @@ impl OsWindow {
     pub fn trap_mouse(&self) -> Result<()> {
         unimplemented!()
     }
+    pub fn grab_input(&self, options: GrabOptions) -> Result<OsInputGrab> {
+        unimplemented!()
+    }
     pub fn clear(&self) -> Result<()> {
         unimplemented!()
     }
     pub fn clear_rect(&self, r: Rect<i32, u32>) -> Result<()> {
         unimplemented!()
     }
+    pub fn text_input_active(&self) -> bool {
+        unimplemented!()
+    }
+    pub fn enable_text_input(&self) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn disable_text_input(&self) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn set_text_input_rect(&self, r: Rect<i32, u32>) -> Result<()> {
+        unimplemented!()
+    }
+    pub fn capability(&self, op: WindowOp) -> Capability {
+        unimplemented!()
+    }
 }
 
 pub type OsWindowHandle = ();
+
+impl<'a> From<&'a Window> for NativeWindowHandle {
+    fn from(w: &'a Window) -> Self {
+        unimplemented!()
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+unsafe impl HasRawWindowHandle for Window {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        unimplemented!()
+    }
+}
+
+impl fmt::Display for WindowHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        unimplemented!()
+    }
+}
 pub type OsWindowFromHandleParams = ();
@@ -0,0 +1,30 @@
+use std::ptr;
+use std::slice;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use app_dirs::AppDirKind;
+use error::Result;
+use super::winapi_utils::*;
+use super::winapi::um::{shlobj::SHGetKnownFolderPath, knownfolders, combaseapi::CoTaskMemFree};
+
+pub fn app_dir_base(kind: AppDirKind) -> Result<PathBuf> {
+    let folder_id = match kind {
+        AppDirKind::Data | AppDirKind::Config => &knownfolders::FOLDERID_RoamingAppData,
+        AppDirKind::Cache => &knownfolders::FOLDERID_LocalAppData,
+    };
+    unsafe {
+        let mut wide: *mut u16 = ptr::null_mut();
+        let hresult = SHGetKnownFolderPath(folder_id, 0, ptr::null_mut(), &mut wide);
+        // The buffer must be freed even on failure.
+        let result = hresult_to_result("SHGetKnownFolderPath", hresult).map(|()| {
+            let mut len = 0;
+            while *wide.offset(len as isize) != 0 {
+                len += 1;
+            }
+            // Not going through wide_string(), which is lossy.
+            PathBuf::from(OsString::from_wide(slice::from_raw_parts(wide, len)))
+        });
+        CoTaskMemFree(wide as _);
+        result
+    }
+}
@@ -0,0 +1,42 @@
+//! Initializing COM on the calling thread, for the few APIs that need it.
+
+use std::ptr;
+use error::Result;
+use super::winapi::shared::winerror::RPC_E_CHANGED_MODE;
+use super::winapi::um::combaseapi::{CoInitializeEx, CoUninitialize};
+use super::winapi::um::objbase::COINIT_APARTMENTTHREADED;
+use super::winapi_utils::*;
+
+/// Keeps COM initialized on the calling thread, and balances the initialization on drop.
+///
+/// COM objects must be released before this is dropped.
+#[derive(Debug)]
+pub struct ComGuard {
+    must_uninitialize: bool,
+}
+
+impl ComGuard {
+    pub fn new() -> Result<Self> {
+        let hresult = unsafe {
+            CoInitializeEx(ptr::null_mut(), COINIT_APARTMENTTHREADED)
+        };
+        // If COM was already initialized on this thread with another concurrency model, that's
+        // fine: the objects we use can be used from any apartment. That call must not be balanced, though.
+        if hresult == RPC_E_CHANGED_MODE {
+            return Ok(Self { must_uninitialize: false });
+        }
+        // S_FALSE (already initialized) must be balanced as well.
+        hresult_to_result("CoInitializeEx", hresult)?;
+        Ok(Self { must_uninitialize: true })
+    }
+}
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        if self.must_uninitialize {
+            unsafe {
+                CoUninitialize();
+            }
+        }
+    }
+}
@@ -1,13 +1,16 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::mem;
 use std::ptr;
 use std::collections::HashMap;
 use std::rc::{Rc, Weak};
 use std::ops::Deref;
-use std::collections::VecDeque;
-use super::{winapi_utils::*, OsSharedWindow, wgl::Wgl, wndproc};
+use super::{winapi_utils::*, OsSharedWindow, wgl::Wgl, wndproc, monitor};
 use error::Result;
-use event::Event;
+use context::{ContextOptions, BackendKind, BackendInfo};
+use event_queue::EventQueue;
+use monitor::GammaRamps;
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasRawDisplayHandle, RawDisplayHandle, WindowsDisplayHandle};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct ClassSettings {
@@ -21,11 +24,26 @@ pub struct OsSharedContext {
     class_atoms: RefCell<HashMap<ClassSettings, ATOM>>,
     pub weak_windows: RefCell<HashMap<HWND, Weak<OsSharedWindow>>>,
     pub wgl: Result<Wgl>,
-    pub pending_events: RefCell<VecDeque<Event>>,
+    pub(crate) pending_events: RefCell<EventQueue>,
+    // Gamma ramps of each monitor, as they were before we first modified them. Restored on drop.
+    pub original_gamma_ramps: RefCell<HashMap<HMONITOR, GammaRamps>>,
+    // Last value returned by `accessibility_scale_factor()`; Changes are only watched once it's set.
+    pub accessibility_scale_factor: Cell<Option<f64>>,
+    // Set on PBT_APMSUSPEND, so that we report a single SystemResumed even though every top-level window is notified.
+    pub is_suspended: Cell<bool>,
+    // For `ContextStats`.
+    pub queue_high_water_mark: Cell<usize>,
 }
 #[derive(Debug)]
 pub struct OsContext(pub(crate) Rc<OsSharedContext>);
 
+#[cfg(feature = "raw-window-handle")]
+unsafe impl HasRawDisplayHandle for ::context::Context {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Windows(WindowsDisplayHandle::empty())
+    }
+}
+
 impl Deref for OsContext {
     type Target = OsSharedContext;
     fn deref(&self) -> &OsSharedContext {
@@ -37,9 +55,15 @@ impl Drop for OsSharedContext {
     fn drop(&mut self) {
         let &mut Self {
             hinstance, ref class_atoms, weak_windows: _, wgl: _,
-            pending_events: _,
+            pending_events: _, ref original_gamma_ramps, accessibility_scale_factor: _,
+            is_suspended: _, queue_high_water_mark: _,
         } = self;
         unsafe {
+            for (hmonitor, ramps) in original_gamma_ramps.borrow().iter() {
+                if let Err(e) = monitor::set_monitor_gamma(*hmonitor, ramps) {
+                    warn!("Could not restore gamma ramps of monitor {:?}: {}", hmonitor, e);
+                }
+            }
             wndproc::CONTEXT = None;
             for class_atom in class_atoms.borrow().values() {
                 UnregisterClassW(MAKEINTATOM(*class_atom), hinstance);
@@ -134,7 +158,11 @@ impl OsSharedContext {
                 class_atoms: RefCell::new(HashMap::new()),
                 weak_windows: RefCell::new(HashMap::new()),
                 wgl: Wgl::new(),
-                pending_events: RefCell::new(VecDeque::new()),
+                pending_events: RefCell::new(EventQueue::default()),
+                original_gamma_ramps: RefCell::new(HashMap::new()),
+                accessibility_scale_factor: Cell::new(None),
+                is_suspended: Cell::new(false),
+                queue_high_water_mark: Cell::new(0),
             }
         };
         Ok(c)
@@ -142,7 +170,8 @@ impl OsSharedContext {
 }
 
 impl OsContext {
-    pub fn new() -> Result<Self> {
+    // NOTE: `x11_display_name` and `app_name` don't apply here, and there's no headless mode.
+    pub fn with_options(_options: &ContextOptions) -> Result<Self> {
         let rc = Rc::new(OsSharedContext::new()?);
         unsafe {
             assert!(wndproc::CONTEXT.is_none(), "Only one context at a time is supported!");
@@ -150,6 +179,9 @@ impl OsContext {
         }
         Ok(OsContext(rc))
     }
+    pub fn backend_info(&self) -> BackendInfo {
+        BackendInfo::new(BackendKind::Windows)
+    }
     pub fn untrap_mouse(&self) -> Result<()> {
         unimplemented!()
     }
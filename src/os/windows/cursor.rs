@@ -1,9 +1,13 @@
 use std::rc::Rc;
 use std::ptr;
 use cursor::{SystemCursor, RgbaCursorData, RgbaCursorAnimFrame};
-use error::{Result, failed};
+use error::{Result, failed, unsupported};
 use super::{OsSharedContext, OsSharedWindow, winapi_utils::{self as w32, *}};
-use Extent2;
+use pointer_barrier::PointerBarrierDirections;
+use {Vec2, Extent2};
+
+// Windows has no concept of pointer barriers (ClipCursor() only confines to a rectangle).
+pub type OsPointerBarrierID = ();
 
 #[derive(Debug, Hash)]
 pub struct HCursor(pub HCURSOR);
@@ -60,6 +64,12 @@ impl OsSharedContext {
     pub fn create_animated_rgba_cursor(&self, frames: &[RgbaCursorAnimFrame]) -> Result<OsCursor> {
         unimplemented!()
     }
+    pub fn create_pointer_barrier(&self, _from: Vec2<i32>, _to: Vec2<i32>, _directions: PointerBarrierDirections) -> Result<OsPointerBarrierID> {
+        unsupported("Pointer barriers are not supported on Windows")
+    }
+    pub fn destroy_pointer_barrier(&self, _barrier: OsPointerBarrierID) -> Result<()> {
+        unsupported("Pointer barriers are not supported on Windows")
+    }
 }
 
 impl OsSharedWindow {
@@ -1,6 +1,10 @@
+use std::mem;
+use std::ptr;
 use desktop::Desktop;
-use error::Result;
-use super::OsContext;
+use error::{Result, failed};
+use event::Event;
+use super::{OsContext, OsSharedContext, winapi_utils::*};
+use super::winapi::shared::winerror::{ERROR_SUCCESS, ERROR_FILE_NOT_FOUND};
 
 impl OsContext {
     pub fn desktops(&self) -> Result<Vec<Desktop>> {
@@ -9,4 +13,49 @@ impl OsContext {
     pub fn current_desktop(&self) -> Result<usize> {
         unimplemented!()
     }
+    pub fn accessibility_scale_factor(&self) -> Result<f64> {
+        let factor = text_scale_factor()?;
+        self.0.accessibility_scale_factor.set(Some(factor));
+        Ok(factor)
+    }
+}
+
+impl OsSharedContext {
+    /// Queries the scale factor again, returning an event if it has changed.
+    /// Does nothing unless `accessibility_scale_factor()` was called before.
+    pub fn refresh_accessibility_scale_factor(&self) -> Option<Event> {
+        let previous = self.accessibility_scale_factor.get()?;
+        let new_scale = match text_scale_factor() {
+            Ok(factor) => factor,
+            Err(e) => {
+                warn!("Could not refresh the accessibility scale factor: {}", e);
+                return None;
+            },
+        };
+        if new_scale == previous {
+            return None;
+        }
+        self.accessibility_scale_factor.set(Some(new_scale));
+        Some(Event::AccessibilityScaleFactorChanged { new_scale })
+    }
+}
+
+/// Reads the "Make text bigger" setting, which is a percentage between 100 and 225.
+fn text_scale_factor() -> Result<f64> {
+    let subkey = to_wide_with_nul("Software\\Microsoft\\Accessibility");
+    let value = to_wide_with_nul("TextScaleFactor");
+    let mut percent: DWORD = 0;
+    let mut size = mem::size_of::<DWORD>() as DWORD;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER, subkey.as_ptr(), value.as_ptr(), RRF_RT_REG_DWORD,
+            ptr::null_mut(), &mut percent as *mut _ as _, &mut size
+        )
+    };
+    match status as DWORD {
+        ERROR_SUCCESS => Ok(percent as f64 / 100.),
+        // The value only exists once the user has changed the setting.
+        ERROR_FILE_NOT_FOUND => Ok(1.),
+        err => failed(format!("RegGetValueW() failed: {}", winapi_errorcode_string(err))),
+    }
 }
@@ -1,7 +1,9 @@
+use std::collections::HashMap;
+use std::time::Duration;
 use device::{
     self,
     DeviceID, AxisInfo, ButtonState,
-    ControllerAxis, ControllerButton, ControllerState, VibrationState,
+    ControllerAxis, ControllerButton, ControllerState, ControllerInfo, VibrationState, EventMode,
 };
 use os::OsContext;
 
@@ -32,6 +34,12 @@ impl OsControllerInfo {
     pub fn axis(&self, axis: ControllerAxis) -> Option<&AxisInfo> {
         unimplemented!()
     }
+    pub fn vendor_id(&self) -> Option<u16> {
+        unimplemented!()
+    }
+    pub fn name(&self) -> Option<&str> {
+        unimplemented!()
+    }
 }
 
 impl OsControllerState {
@@ -41,12 +49,21 @@ impl OsControllerState {
     pub fn axis(&self, axis: ControllerAxis) -> Option<f64> {
         unimplemented!()
     }
+    pub fn axes(&self) -> Vec<ControllerAxis> {
+        unimplemented!()
+    }
 }
 
 impl OsContext {
+    pub fn controller_info(&self, controller: DeviceID) -> device::Result<ControllerInfo> {
+        unimplemented!()
+    }
     pub fn controller_state(&self, controller: DeviceID) -> device::Result<ControllerState> {
         unimplemented!()
     }
+    pub fn all_controller_states(&self) -> device::Result<HashMap<DeviceID, ControllerState>> {
+        unimplemented!()
+    }
     pub fn controller_button_state(&self, controller: DeviceID, button: ControllerButton) -> device::Result<ButtonState> {
         unimplemented!()
     }
@@ -56,5 +73,17 @@ impl OsContext {
     pub fn controller_set_vibration(&self, controller: DeviceID, vibration: &VibrationState) -> device::Result<()> {
         unimplemented!()
     }
+    pub fn controller_rumble_pulse(&self, controller: DeviceID, vibration: &VibrationState, duration: Duration) -> device::Result<()> {
+        unimplemented!()
+    }
+    pub fn set_controller_event_mode(&self, controller: DeviceID, mode: EventMode) -> device::Result<()> {
+        unimplemented!()
+    }
+    pub fn controller_player_index(&self, controller: DeviceID) -> device::Result<Option<u32>> {
+        unimplemented!()
+    }
+    pub fn set_controller_player_index(&self, controller: DeviceID, player_index: Option<u32>) -> device::Result<()> {
+        unimplemented!()
+    }
 }
 
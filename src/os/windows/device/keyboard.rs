@@ -1,7 +1,7 @@
 use device::{
     self,
     DeviceID, KeyState,
-    KeyboardState, Keysym, Keycode,
+    KeyboardState, Keysym, Keycode, ModifiersState,
 };
 use os::OsContext;
 use super::super::winapi_utils::*;
@@ -27,12 +27,27 @@ impl OsContext {
     pub fn keysym_name(&self, keysym: Keysym) -> device::Result<String> {
         unimplemented!()
     }
+    pub fn keysym_to_char(&self, keysym: Keysym, modifiers: ModifiersState) -> Option<char> {
+        unimplemented!()
+    }
     pub fn keysym_from_keycode(&self, keyboard: DeviceID, keycode: Keycode) -> device::Result<Keysym> {
         unimplemented!()
     }
+    pub fn keyboard_current_group(&self, keyboard: DeviceID) -> device::Result<u32> {
+        unimplemented!()
+    }
+    pub fn set_keyboard_current_group(&self, keyboard: DeviceID, group: u32) -> device::Result<()> {
+        unimplemented!()
+    }
+    pub fn keyboard_group_names(&self, keyboard: DeviceID) -> device::Result<Vec<String>> {
+        unimplemented!()
+    }
     pub fn keycode_from_keysym(&self, keyboard: DeviceID, keysym: Keysym) -> device::Result<Keycode> {
         unimplemented!()
     }
+    pub fn dump_keymap(&self) -> Vec<(Keycode, Keysym, String)> {
+        unimplemented!()
+    }
 }
 
 impl OsKeyboardState {
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::ops::Range;
 use std::path::Path;
 use std::collections::HashMap;
@@ -29,6 +30,15 @@ pub enum OsDeviceID {
     MainKeyboard,
 }
 
+impl fmt::Display for DeviceID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            OsDeviceID::MainMouse => write!(f, "main-mouse"),
+            OsDeviceID::MainKeyboard => write!(f, "main-keyboard"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct OsAxisInfo;
 #[derive(Debug, Clone, PartialEq)]
@@ -55,8 +65,10 @@ impl OsDeviceInfo {
     pub fn bus(&self) -> Option<Bus> { unimplemented!() }
     pub fn driver_name(&self) -> Option<&str> { unimplemented!() }
     pub fn driver_version(&self) -> Option<&str> { unimplemented!() }
+    pub fn physical_location(&self) -> Option<&str> { unimplemented!() }
     pub fn is_physical(&self) -> Option<bool> { unimplemented!() }
     pub fn controller(&self) -> Option<&ControllerInfo> { unimplemented!() }
+    pub fn controller_mut(&mut self) -> Option<&mut ControllerInfo> { unimplemented!() }
     pub fn mouse(&self) -> Option<&MouseInfo> { unimplemented!() }
     pub fn keyboard(&self) -> Option<&KeyboardInfo> { unimplemented!() }
     pub fn touch(&self) -> Option<&TouchInfo> { unimplemented!() }
@@ -70,4 +82,7 @@ impl OsContext {
     pub fn ping_device(&self, id: DeviceID) -> device::Result<()> {
         unimplemented!()
     }
+    pub fn is_device_id_current(&self, id: DeviceID) -> bool {
+        unimplemented!()
+    }
 }
@@ -1,8 +1,11 @@
 use std::mem;
 use std::ptr;
+use std::time::Instant;
 use timeout::Timeout;
-use error::Result;
+use error::{Result, unimplemented};
 use event::{Event, UnprocessedEvent};
+use event_queue::QueuePolicy;
+use stats::ContextStats;
 use super::winapi_utils::*;
 use super::{OsContext, OsSharedContext};
 
@@ -29,6 +32,22 @@ impl OsContext {
     pub fn supports_raw_device_events(&self) -> Result<bool> {
         unimplemented!()
     }
+    pub fn catch_termination_signals(&self) -> Result<()> {
+        unimplemented("Context::catch_termination_signals() is not implemented on Windows yet")
+    }
+    pub fn set_event_queue_policy(&self, policy: QueuePolicy) {
+        self.pending_events.borrow_mut().set_policy(policy);
+    }
+    pub fn event_queue_policy(&self) -> QueuePolicy {
+        self.pending_events.borrow().policy()
+    }
+    pub fn poll_controller_event(&self) -> Option<Event> {
+        unimplemented!()
+    }
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        // FlashWindowEx() stops by itself.
+        None
+    }
     pub fn next_event(&self, timeout: Timeout) -> Option<Event> {
         unsafe {
             let mut msg = mem::uninitialized();
@@ -60,10 +79,20 @@ impl OsContext {
         }
         self.pending_events.borrow_mut().pop_front()
     }
+    pub fn fill_stats(&self, stats: &mut ContextStats) {
+        stats.queue_high_water_mark = self.queue_high_water_mark.get() as _;
+    }
+    pub fn reset_stats(&self) {
+        self.queue_high_water_mark.set(0);
+    }
 }
 
 impl OsSharedContext {
     pub fn push_event(&self, ev: Event) {
-        self.pending_events.borrow_mut().push_back(ev);
+        let mut pending = self.pending_events.borrow_mut();
+        pending.push_back(ev);
+        if pending.len() > self.queue_high_water_mark.get() {
+            self.queue_high_water_mark.set(pending.len());
+        }
     }
 }
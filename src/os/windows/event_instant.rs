@@ -1,12 +1,25 @@
 use std::cmp::Ordering;
 use std::time::{Duration, Instant};
 use std::ops::{Add, Sub, AddAssign, SubAssign};
+use std::fmt::{self, Display, Formatter};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum OsEventInstant {
     Wndproc(Instant),
 }
 
+// `Instant`s have no meaningful origin, so display them relative to now (i.e as their age).
+impl Display for OsEventInstant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            OsEventInstant::Wndproc(i) => {
+                let age = i.elapsed();
+                write!(f, "-{}.{:03}s", age.as_secs(), age.subsec_nanos() / 1_000_000)
+            },
+        }
+    }
+}
+
 impl PartialOrd for OsEventInstant {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (*self, *other) {
@@ -16,6 +29,16 @@ impl PartialOrd for OsEventInstant {
 }
 
 impl OsEventInstant {
+    pub fn now_from_same_clock(&self) -> Option<Self> {
+        match *self {
+            OsEventInstant::Wndproc(_) => Some(OsEventInstant::Wndproc(Instant::now())),
+        }
+    }
+    pub fn is_monotonic(&self) -> bool {
+        match *self {
+            OsEventInstant::Wndproc(_) => true,
+        }
+    }
     pub fn duration_since(&self, earlier: Self) -> Option<Duration> {
         assert!(self >= &earlier); // Normally already checked by EventInstant::duration_since
         match (*self, earlier) {
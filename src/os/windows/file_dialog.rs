@@ -0,0 +1,149 @@
+//! File dialogs, via `IFileOpenDialog` and `IFileSaveDialog`.
+//!
+//! `Show()` runs a modal message loop which keeps dispatching messages to our windows, so
+//! their events are queued by the window procedure as usual.
+
+use std::ptr;
+use std::slice;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use file_dialog::FileDialogOptions;
+use error::Result;
+use super::winapi::Interface;
+use super::winapi::shared::winerror::{ERROR_CANCELLED, HRESULT_FROM_WIN32};
+use super::winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+use super::winapi::shared::shtypes::COMDLG_FILTERSPEC;
+use super::winapi::um::combaseapi::{CoCreateInstance, CoTaskMemFree};
+use super::winapi::um::shobjidl::{IFileDialog, IFileOpenDialog, IFileSaveDialog, CLSID_FileOpenDialog, CLSID_FileSaveDialog, FOS_ALLOWMULTISELECT};
+use super::winapi::um::shobjidl_core::{IShellItem, IShellItemArray, SHCreateItemFromParsingName, SIGDN_FILESYSPATH};
+use super::context::OsContext;
+use super::com::ComGuard;
+use super::winapi_utils::*;
+
+fn os_to_wide_with_nul(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(Some(0)).collect()
+}
+
+unsafe fn shell_item_path(item: *mut IShellItem) -> Result<PathBuf> {
+    let mut name: *mut u16 = ptr::null_mut();
+    hresult_to_result("IShellItem::GetDisplayName", (*item).GetDisplayName(SIGDN_FILESYSPATH, &mut name))?;
+    let len = (0..).take_while(|i| *name.offset(*i) != 0).count();
+    // Not `wide_string()`, which is lossy: paths may contain unpaired surrogates.
+    let path = OsString::from_wide(slice::from_raw_parts(name, len));
+    CoTaskMemFree(name as _);
+    Ok(path.into())
+}
+
+/// Applies the options that are common to open and save dialogs.
+unsafe fn setup_dialog(dialog: &IFileDialog, options: &FileDialogOptions) -> Result<()> {
+    let &FileDialogOptions { ref title, ref filters, ref default_directory, ref default_file_name, multiple: _ } = options;
+    if let Some(ref title) = *title {
+        hresult_to_result("IFileDialog::SetTitle", dialog.SetTitle(to_wide_with_nul(title).as_ptr()))?;
+    }
+    if !filters.is_empty() {
+        // The strings must outlive the `SetFileTypes()` call.
+        let names: Vec<_> = filters.iter().map(|f| to_wide_with_nul(&f.name)).collect();
+        let specs: Vec<_> = filters.iter().map(|f| {
+            let patterns: Vec<_> = f.extensions.iter().map(|ext| format!("*.{}", ext)).collect();
+            to_wide_with_nul(&patterns.join(";"))
+        }).collect();
+        let filterspecs: Vec<_> = names.iter().zip(specs.iter()).map(|(name, spec)| COMDLG_FILTERSPEC {
+            pszName: name.as_ptr(),
+            pszSpec: spec.as_ptr(),
+        }).collect();
+        hresult_to_result("IFileDialog::SetFileTypes", dialog.SetFileTypes(filterspecs.len() as _, filterspecs.as_ptr()))?;
+    }
+    if let Some(ref dir) = *default_directory {
+        let mut folder: *mut IShellItem = ptr::null_mut();
+        hresult_to_result("SHCreateItemFromParsingName", SHCreateItemFromParsingName(
+            os_to_wide_with_nul(dir.as_os_str()).as_ptr(), ptr::null_mut(),
+            &IShellItem::uuidof(), &mut folder as *mut _ as _
+        ))?;
+        let result = hresult_to_result("IFileDialog::SetFolder", dialog.SetFolder(folder));
+        (*folder).Release();
+        result?;
+    }
+    if let Some(ref name) = *default_file_name {
+        hresult_to_result("IFileDialog::SetFileName", dialog.SetFileName(to_wide_with_nul(name).as_ptr()))?;
+    }
+    Ok(())
+}
+
+/// Shows the dialog; Returns `false` if the user cancelled.
+unsafe fn show_dialog(dialog: &IFileDialog) -> Result<bool> {
+    let hresult = dialog.Show(ptr::null_mut());
+    if hresult == HRESULT_FROM_WIN32(ERROR_CANCELLED) {
+        return Ok(false);
+    }
+    hresult_to_result("IFileDialog::Show", hresult).map(|()| true)
+}
+
+impl OsContext {
+    pub fn show_open_file_dialog(&self, options: &FileDialogOptions) -> Result<Option<Vec<PathBuf>>> {
+        unsafe {
+            let _com = ComGuard::new()?;
+            let mut dialog: *mut IFileOpenDialog = ptr::null_mut();
+            hresult_to_result("CoCreateInstance", CoCreateInstance(
+                &CLSID_FileOpenDialog, ptr::null_mut(), CLSCTX_INPROC_SERVER,
+                &IFileOpenDialog::uuidof(), &mut dialog as *mut _ as _
+            ))?;
+            let result = (|| -> Result<Option<Vec<PathBuf>>> {
+                setup_dialog(&*dialog, options)?;
+                if options.multiple {
+                    let mut flags = 0;
+                    hresult_to_result("IFileDialog::GetOptions", (*dialog).GetOptions(&mut flags))?;
+                    hresult_to_result("IFileDialog::SetOptions", (*dialog).SetOptions(flags | FOS_ALLOWMULTISELECT))?;
+                }
+                if !show_dialog(&*dialog)? {
+                    return Ok(None);
+                }
+                let mut items: *mut IShellItemArray = ptr::null_mut();
+                hresult_to_result("IFileOpenDialog::GetResults", (*dialog).GetResults(&mut items))?;
+                let paths = (|| -> Result<Vec<PathBuf>> {
+                    let mut count = 0;
+                    hresult_to_result("IShellItemArray::GetCount", (*items).GetCount(&mut count))?;
+                    let mut paths = Vec::with_capacity(count as _);
+                    for i in 0..count {
+                        let mut item: *mut IShellItem = ptr::null_mut();
+                        hresult_to_result("IShellItemArray::GetItemAt", (*items).GetItemAt(i, &mut item))?;
+                        let path = shell_item_path(item);
+                        (*item).Release();
+                        paths.push(path?);
+                    }
+                    Ok(paths)
+                })();
+                (*items).Release();
+                paths.map(Some)
+            })();
+            (*dialog).Release();
+            result
+        }
+    }
+    pub fn show_save_file_dialog(&self, options: &FileDialogOptions) -> Result<Option<PathBuf>> {
+        unsafe {
+            let _com = ComGuard::new()?;
+            let mut dialog: *mut IFileSaveDialog = ptr::null_mut();
+            hresult_to_result("CoCreateInstance", CoCreateInstance(
+                &CLSID_FileSaveDialog, ptr::null_mut(), CLSCTX_INPROC_SERVER,
+                &IFileSaveDialog::uuidof(), &mut dialog as *mut _ as _
+            ))?;
+            let result = (|| -> Result<Option<PathBuf>> {
+                setup_dialog(&*dialog, options)?;
+                // Appends the first filter's extension when the user types a bare name.
+                if let Some(ext) = options.filters.first().map(|f| &f.extensions[0]) {
+                    hresult_to_result("IFileDialog::SetDefaultExtension", (*dialog).SetDefaultExtension(to_wide_with_nul(ext).as_ptr()))?;
+                }
+                if !show_dialog(&*dialog)? {
+                    return Ok(None);
+                }
+                let mut item: *mut IShellItem = ptr::null_mut();
+                hresult_to_result("IFileDialog::GetResult", (*dialog).GetResult(&mut item))?;
+                let path = shell_item_path(item);
+                (*item).Release();
+                path.map(Some)
+            })();
+            (*dialog).Release();
+            result
+        }
+    }
+}
@@ -2,26 +2,57 @@ use std::os::raw::*;
 use std::mem;
 use std::ptr;
 use std::rc::Rc;
-use gl::{GLPixelFormatSettings, GLPixelFormatChooser, GLContextSettings, GLSwapInterval, GLProfile, GLContextResetNotificationStrategy};
-use error::{Result, failed};
-use super::{OsWindow, OsSharedWindow, winapi_utils::*, wgl::consts::*};
+use gl::{GLPixelFormat, GLPixelFormatSettings, GLPixelFormatChooser, GLDefaultPixelFormatChooser, GLContextSettings, GLSwapInterval, GLProfile, GLContextResetNotificationStrategy, GLApiPreference, GLApi, PresentStats, PresentStatsCollector};
+use error::{Result, failed, unsupported, invalid_arg};
+use window::WindowSettings;
+use super::{OsContext, OsWindow, OsSharedWindow, winapi_utils::*, wgl::consts::*};
 
 #[derive(Debug)]
 pub struct OsGLContext {
     pub window: Rc<OsSharedWindow>,
     pub hglrc: HGLRC,
+    /// If `true`, `window` is a hidden window created only for this context.
+    pub is_offscreen: bool,
 }
 
 #[derive(Debug)]
 pub struct OsGLPixelFormat(c_int);
 
 impl OsGLContext {
+    pub fn api(&self) -> GLApi {
+        GLApi::WGL
+    }
     pub unsafe fn get_proc_address(&self, name: *const c_char) -> *const c_void {
         match wglGetProcAddress(name) as usize {
             0 => GetProcAddress(self.window.context.wgl().unwrap().opengl32_hmodule, name) as *const _, // wglGetProcAddress only works on extension functions
             f => f as *const _,
         }
     }
+    pub fn make_current_offscreen(&self) -> Result<()> {
+        if !self.is_offscreen {
+            return invalid_arg("This GL context was not created by `create_offscreen_gl_context()`");
+        }
+        self.window.make_gl_context_current(Some(self))
+    }
+}
+
+impl OsContext {
+    pub fn create_offscreen_gl_context(&self, settings: &GLContextSettings) -> Result<OsGLContext> {
+        // WGL has no window-less contexts; The hidden window is kept alive by the context.
+        let pixel_format = GLPixelFormatSettings::default();
+        let window = self.create_window(&WindowSettings {
+            opengl: Some(&GLDefaultPixelFormatChooser::from(&pixel_format)),
+            high_dpi: false,
+            parent: None,
+            position: None,
+            centered: false,
+            override_redirect: false,
+            wm_class: None,
+        })?;
+        let mut gl_context = window.create_gl_context(settings)?;
+        gl_context.is_offscreen = true;
+        Ok(gl_context)
+    }
 }
 
 impl OsWindow {
@@ -34,8 +65,15 @@ impl OsWindow {
             debug,
             forward_compatible,
             robust_access,
+            api_preference,
         } = settings;
 
+        match api_preference {
+            GLApiPreference::PreferGLX | GLApiPreference::PreferEGL => (),
+            GLApiPreference::OnlyGLX => return unsupported("GLX is not available on Windows"),
+            GLApiPreference::OnlyEGL => return unsupported("EGL is not available on Windows"),
+        }
+
         let mut context_attribs = [
             WGL_CONTEXT_MAJOR_VERSION_ARB, version.major as _,
             WGL_CONTEXT_MINOR_VERSION_ARB, version.minor as _,
@@ -85,14 +123,18 @@ impl OsWindow {
         }
 
         assert_eq!(&0, context_attribs.last().unwrap());
+        let create_context_attribs = match wgl.fns.wglCreateContextAttribsARB {
+            Some(f) if wgl.WGL_ARB_create_context => f,
+            _ => return failed("Missing extension `WGL_ARB_create_context`"),
+        };
         let hglrc_share: HGLRC = ptr::null_mut();
         let hglrc = unsafe {
-            (wgl.fns.wglCreateContextAttribsARB.unwrap())(self.own_dc()?, hglrc_share, context_attribs.as_ptr())
+            create_context_attribs(self.own_dc()?, hglrc_share, context_attribs.as_ptr())
         };
         if hglrc.is_null() {
             winapi_fail("wglCreateContextAttribsARB returned NULL")
         } else {
-            Ok(OsGLContext { window: Rc::clone(&self.0), hglrc })
+            Ok(OsGLContext { window: Rc::clone(&self.0), hglrc, is_offscreen: false, })
         }
     }
 }
@@ -110,8 +152,23 @@ impl OsSharedWindow {
             accum_alpha_bits,
             aux_buffers,
             transparent,
+            srgb,
         } = chooser.settings();
 
+        // The classic ChoosePixelFormat() ignores MSAA and sRGB, so the ARB chooser is mandatory.
+        // Its function pointer was loaded by Wgl::new() via a dummy window and context.
+        let choose_pixel_format = match wgl.fns.wglChoosePixelFormatARB {
+            Some(f) if wgl.WGL_ARB_pixel_format => f,
+            _ => return failed("Missing extension `WGL_ARB_pixel_format`"),
+        };
+        let wants_msaa = msaa.buffer_count > 0;
+        if wants_msaa && !(wgl.WGL_ARB_multisample || wgl.WGL_EXT_multisample) {
+            return failed("Missing extension `WGL_ARB_multisample` (or `WGL_EXT_multisample`), required for MSAA");
+        }
+        if srgb && !(wgl.WGL_ARB_framebuffer_sRGB || wgl.WGL_EXT_framebuffer_sRGB) {
+            return failed("Missing extension `WGL_ARB_framebuffer_sRGB` (or `WGL_EXT_framebuffer_sRGB`), required for sRGB");
+        }
+
         let mut attribs_i = [
             WGL_DRAW_TO_WINDOW_ARB, TRUE,
             WGL_SUPPORT_OPENGL_ARB, TRUE,
@@ -133,12 +190,14 @@ impl OsSharedWindow {
             WGL_AUX_BUFFERS_ARB, aux_buffers as _,
             0, 0, // WGL_SAMPLE_BUFFERS_ARB, value,
             0, 0, // WGL_SAMPLES_ARB, value,
+            0, 0, // WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB, value,
             0, // End
         ];
 
-        let mut i = attribs_i.len() - 5;
+        let mut i = attribs_i.len() - 7;
         assert_eq!(0, attribs_i[i]);
-        if wgl.WGL_ARB_multisample && msaa.buffer_count > 0 {
+        // The EXT variants of these extensions use the same values as the ARB ones.
+        if wants_msaa {
             attribs_i[i] = WGL_SAMPLE_BUFFERS_ARB;
             i += 1;
             attribs_i[i] = msaa.buffer_count as _;
@@ -148,6 +207,12 @@ impl OsSharedWindow {
             attribs_i[i] = msaa.sample_count as _;
             i += 1;
         }
+        if srgb {
+            attribs_i[i] = WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB;
+            i += 1;
+            attribs_i[i] = TRUE;
+            i += 1;
+        }
 
         assert_eq!(&0, attribs_i.last().unwrap());
         let attribs_f = &[
@@ -158,7 +223,7 @@ impl OsSharedWindow {
         let mut candidate_pixel_formats = [0; 32];
         let mut num_formats = 0;
         let is_ok = unsafe {
-            (self.context.wgl()?.fns.wglChoosePixelFormatARB.unwrap())(
+            choose_pixel_format(
                 self.own_dc()?,
                 attribs_i.as_ptr(),
                 attribs_f.as_ptr(),
@@ -170,14 +235,32 @@ impl OsSharedWindow {
         if is_ok == FALSE {
             return winapi_fail("wglChoosePixelFormatARB");
         }
-        let candidate_pixel_formats = &candidate_pixel_formats[..num_formats as _];
-        let i_pixel_format = candidate_pixel_formats[0]; // FIXME: Use chooser
-        assert_ne!(i_pixel_format, 0);
-        Ok(OsGLPixelFormat(i_pixel_format))
+        let candidate_pixel_formats: Vec<_> = candidate_pixel_formats[..num_formats as _].iter()
+            .map(|i| GLPixelFormat(OsGLPixelFormat(*i)))
+            .collect();
+        if candidate_pixel_formats.is_empty() {
+            return failed("wglChoosePixelFormatARB() found no pixel format matching the requested settings");
+        }
+        let i = chooser.choose(&candidate_pixel_formats);
+        match candidate_pixel_formats.into_iter().nth(i) {
+            Some(GLPixelFormat(pf)) => {
+                assert_ne!(pf.0, 0);
+                Ok(pf)
+            },
+            None => failed(format!("The pixel format chooser returned {}, which is out of bounds", i)),
+        }
     }
     pub fn set_pixel_format(&self, pf: &OsGLPixelFormat) -> Result<()> {
         let i_pixel_format = pf.0;
         assert_ne!(i_pixel_format, 0);
+        // SetPixelFormat() can only be called once per HWND.
+        let current = unsafe { GetPixelFormat(self.own_dc()?) };
+        if current == i_pixel_format {
+            return Ok(());
+        }
+        if current != 0 {
+            return failed(format!("This window already has pixel format {}, which can't be changed", current));
+        }
         let pfd_kludge = unsafe {
             let mut pfd = PIXELFORMATDESCRIPTOR {
                 nSize: mem::size_of::<PIXELFORMATDESCRIPTOR>() as _,
@@ -211,12 +294,40 @@ impl OsSharedWindow {
     pub fn gl_swap_buffers(&self) -> Result<()> {
         let is_ok = unsafe { SwapBuffers(self.own_dc()?) };
         if is_ok == FALSE {
-            winapi_fail("SwapBuffers")
-        } else {
-            Ok(())
+            return winapi_fail("SwapBuffers");
         }
+        // There's no WGL equivalent to GLX_OML_sync_control, so misses are estimated from wall-clock time.
+        if let Some(collector) = self.present_stats.borrow_mut().as_mut() {
+            collector.record(None, self.refresh_rate.get());
+        }
+        Ok(())
+    }
+    pub fn set_present_stats_collection(&self, enable: bool) {
+        // This caches it; monitor changes keep it up to date, so presents never need a roundtrip.
+        if enable && self.refresh_rate.get().is_none() {
+            if let Err(e) = self.current_refresh_rate() {
+                debug!("The refresh rate is unknown, so missed vblanks will be estimated from the shortest interval between presents: {}", e);
+            }
+        }
+        let mut present_stats = self.present_stats.borrow_mut();
+        match (enable, present_stats.is_some()) {
+            (true, false) => *present_stats = Some(PresentStatsCollector::default()),
+            (false, true) => *present_stats = None,
+            _ => (),
+        }
+    }
+    pub fn present_stats(&self) -> PresentStats {
+        self.present_stats.borrow().as_ref().map(PresentStatsCollector::stats).unwrap_or_default()
+    }
+    pub fn gl_swap_interval(&self) -> Option<GLSwapInterval> {
+        self.gl_swap_interval.get()
     }
     pub fn gl_set_swap_interval(&self, interval: GLSwapInterval) -> Result<()> {
+        self.gl_set_swap_interval_raw(interval)?;
+        self.gl_swap_interval.set(Some(interval));
+        Ok(())
+    }
+    fn gl_set_swap_interval_raw(&self, interval: GLSwapInterval) -> Result<()> {
         let wgl = self.context.wgl()?;
 
         let interval = match interval {
@@ -0,0 +1,51 @@
+//! The master volume, via the default render endpoint's `IAudioEndpointVolume`.
+
+use std::ptr;
+use error::Result;
+use super::winapi::Interface;
+use super::winapi::um::combaseapi::{CoCreateInstance, CLSCTX_ALL};
+use super::winapi::um::mmdeviceapi::{IMMDeviceEnumerator, IMMDevice, CLSID_MMDeviceEnumerator, eRender, eConsole};
+use super::winapi::um::endpointvolume::IAudioEndpointVolume;
+use super::context::OsContext;
+use super::com::ComGuard;
+use super::winapi_utils::*;
+
+/// Calls `f` with the default render endpoint's volume interface.
+unsafe fn with_endpoint_volume<T, F: FnOnce(&IAudioEndpointVolume) -> Result<T>>(f: F) -> Result<T> {
+    let _com = ComGuard::new()?;
+    let mut enumerator: *mut IMMDeviceEnumerator = ptr::null_mut();
+    hresult_to_result("CoCreateInstance", CoCreateInstance(
+        &CLSID_MMDeviceEnumerator, ptr::null_mut(), CLSCTX_ALL,
+        &IMMDeviceEnumerator::uuidof(), &mut enumerator as *mut _ as _
+    ))?;
+    let mut device: *mut IMMDevice = ptr::null_mut();
+    let hresult = (*enumerator).GetDefaultAudioEndpoint(eRender, eConsole, &mut device);
+    (*enumerator).Release();
+    hresult_to_result("IMMDeviceEnumerator::GetDefaultAudioEndpoint", hresult)?;
+    let mut volume: *mut IAudioEndpointVolume = ptr::null_mut();
+    let hresult = (*device).Activate(&IAudioEndpointVolume::uuidof(), CLSCTX_ALL, ptr::null_mut(), &mut volume as *mut _ as _);
+    (*device).Release();
+    hresult_to_result("IMMDevice::Activate", hresult)?;
+    let result = f(&*volume);
+    (*volume).Release();
+    result
+}
+
+impl OsContext {
+    pub fn set_master_volume(&self, volume: f32) -> Result<()> {
+        unsafe {
+            with_endpoint_volume(|endpoint| {
+                hresult_to_result("IAudioEndpointVolume::SetMasterVolumeLevelScalar", endpoint.SetMasterVolumeLevelScalar(volume, ptr::null()))
+            })
+        }
+    }
+    pub fn master_volume(&self) -> Result<f32> {
+        unsafe {
+            with_endpoint_volume(|endpoint| {
+                let mut volume = 0.;
+                hresult_to_result("IAudioEndpointVolume::GetMasterVolumeLevelScalar", endpoint.GetMasterVolumeLevelScalar(&mut volume))?;
+                Ok(volume)
+            })
+        }
+    }
+}
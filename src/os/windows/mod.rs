@@ -2,13 +2,22 @@ extern crate winapi;
 
 pub mod hint;
 pub use self::hint::set_hint;
+pub mod app_dirs;
+pub use self::app_dirs::app_dir_base;
 pub mod context;
 pub use self::context::{OsContext, OsSharedContext};
 pub mod window;
-pub use self::window::{OsWindow, OsSharedWindow, OsWindowHandle, OsWindowFromHandleParams};
+pub use self::window::{OsWindow, OsSharedWindow, OsInputGrab, OsWindowHandle, OsWindowFromHandleParams};
 pub mod desktop;
+pub mod com;
+pub mod taskbar_progress;
+pub mod file_dialog;
+pub mod master_volume;
+pub mod process_name;
 pub mod cursor;
-pub use self::cursor::{OsCursor, HCursor};
+pub use self::cursor::{OsCursor, OsPointerBarrierID, HCursor};
+pub mod monitor;
+pub use self::monitor::{OsMonitorID, OsDisplayModeGuard};
 pub mod gl;
 pub use self::gl::{OsGLContext, OsGLPixelFormat};
 pub mod event_instant;
@@ -24,6 +33,8 @@ pub use self::device::{
     mouse::{OsMouseButtonsState},
     tablet::{OsTabletInfo, OsTabletPadButtonsState, OsTabletStylusButtonsState},
 };
+#[cfg(feature = "synthetic-input")]
+pub mod synthetic_input;
 pub mod wgl;
 pub mod wndproc;
 
@@ -31,7 +42,7 @@ pub mod wndproc;
 pub mod winapi_utils {
     pub use super::winapi::{
         shared::{windef::*, minwindef::*, ntdef::*, winerror::SUCCEEDED, windowsx::*,},
-        um::{winuser::*, libloaderapi::*, winbase::*, errhandlingapi::*, wingdi::*,},
+        um::{winuser::*, libloaderapi::*, winbase::*, errhandlingapi::*, wingdi::*, imm::*, winreg::*,},
     };
     pub use std::os::windows::ffi::{OsStringExt, OsStrExt};
 
@@ -0,0 +1,235 @@
+use std::mem;
+use std::ptr;
+use monitor::{GammaRamps, DisplayMode};
+use Rect;
+use error::{Result, failed, invalid_arg};
+use super::{OsContext, winapi_utils::*};
+
+pub type OsMonitorID = HMONITOR;
+
+/// Restores the monitor's registry display mode on drop.
+#[derive(Debug)]
+pub struct OsDisplayModeGuard {
+    device_name: [u16; 32],
+}
+
+impl Drop for OsDisplayModeGuard {
+    fn drop(&mut self) {
+        // A NULL DEVMODE means "go back to the mode stored in the registry".
+        let status = unsafe {
+            ChangeDisplaySettingsExW(self.device_name.as_ptr(), ptr::null_mut(), ptr::null_mut(), 0, ptr::null_mut())
+        };
+        if status != DISP_CHANGE_SUCCESSFUL {
+            warn!("Could not restore the original display mode: ChangeDisplaySettingsExW() returned {}", status);
+        }
+    }
+}
+
+// GetDeviceGammaRamp() and SetDeviceGammaRamp() always deal with 256 entries per channel.
+const GAMMA_RAMP_SIZE: usize = 256;
+
+unsafe extern "system" fn push_monitor(hmonitor: HMONITOR, _hdc: HDC, _rect: LPRECT, lparam: LPARAM) -> BOOL {
+    let monitors = &mut *(lparam as *mut Vec<HMONITOR>);
+    monitors.push(hmonitor);
+    TRUE
+}
+
+/// Gets all monitors, in `EnumDisplayMonitors()` order.
+pub fn enum_monitors() -> Result<Vec<HMONITOR>> {
+    let mut monitors = Vec::<HMONITOR>::new();
+    let success = unsafe {
+        EnumDisplayMonitors(ptr::null_mut(), ptr::null(), Some(push_monitor), &mut monitors as *mut _ as LPARAM)
+    };
+    if success == FALSE {
+        return winapi_fail("EnumDisplayMonitors");
+    }
+    Ok(monitors)
+}
+
+/// Gets the position and size of the monitor, in virtual screen coordinates.
+pub unsafe fn monitor_rect(hmonitor: HMONITOR) -> Result<Rect<i32, u32>> {
+    let mut info: MONITORINFO = mem::zeroed();
+    info.cbSize = mem::size_of::<MONITORINFO>() as _;
+    if GetMonitorInfoW(hmonitor, &mut info) == FALSE {
+        return winapi_fail("GetMonitorInfoW");
+    }
+    let RECT { left, top, right, bottom } = info.rcMonitor;
+    Ok(Rect::new(left, top, (right - left) as _, (bottom - top) as _))
+}
+
+unsafe fn monitor_device_name(hmonitor: HMONITOR) -> Result<[u16; 32]> {
+    let mut info: MONITORINFOEXW = mem::zeroed();
+    info.cbSize = mem::size_of::<MONITORINFOEXW>() as _;
+    if GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut MONITORINFO) == FALSE {
+        return winapi_fail("GetMonitorInfoW");
+    }
+    Ok(info.szDevice)
+}
+
+/// Gets the refresh rate of the monitor's current mode, in Hz.
+///
+/// `EnumDisplaySettingsW()` only reports integers, so e.g 59.94 Hz is reported as 59 or 60, depending on the driver.
+pub unsafe fn monitor_refresh_rate(hmonitor: HMONITOR) -> Result<f64> {
+    let device_name = monitor_device_name(hmonitor)?;
+    let mut devmode: DEVMODEW = mem::zeroed();
+    devmode.dmSize = mem::size_of::<DEVMODEW>() as _;
+    if EnumDisplaySettingsW(device_name.as_ptr(), ENUM_CURRENT_SETTINGS, &mut devmode) == FALSE {
+        return winapi_fail("EnumDisplaySettingsW");
+    }
+    match devmode.dmDisplayFrequency {
+        // Both mean "the hardware's default rate", which is unknown to us.
+        0 | 1 => failed("EnumDisplaySettingsW() reported the hardware's default refresh rate"),
+        hz => Ok(hz as f64),
+    }
+}
+
+/// Calls `f` for each of the monitor's modes, in `EnumDisplaySettingsW()` order, until it returns `Some`.
+unsafe fn find_devmode<T, F: FnMut(&DEVMODEW) -> Option<T>>(device_name: &[u16; 32], mut f: F) -> Option<T> {
+    for i in 0.. {
+        let mut devmode: DEVMODEW = mem::zeroed();
+        devmode.dmSize = mem::size_of::<DEVMODEW>() as _;
+        if EnumDisplaySettingsW(device_name.as_ptr(), i, &mut devmode) == FALSE {
+            return None;
+        }
+        if let Some(x) = f(&devmode) {
+            return Some(x);
+        }
+    }
+    unreachable!()
+}
+
+fn display_mode_from_devmode(devmode: &DEVMODEW) -> DisplayMode {
+    // Frequencies of 0 or 1 mean "the hardware's default".
+    let hz = devmode.dmDisplayFrequency;
+    DisplayMode {
+        width: devmode.dmPelsWidth,
+        height: devmode.dmPelsHeight,
+        refresh_rate_mhz: if hz <= 1 { 0 } else { hz * 1000 },
+        bits_per_pixel: devmode.dmBitsPerPel as _,
+    }
+}
+
+unsafe fn with_monitor_dc<T, F: FnOnce(HDC) -> Result<T>>(hmonitor: HMONITOR, f: F) -> Result<T> {
+    let mut info: MONITORINFOEXW = mem::zeroed();
+    info.cbSize = mem::size_of::<MONITORINFOEXW>() as _;
+    if GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut MONITORINFO) == FALSE {
+        return winapi_fail("GetMonitorInfoW");
+    }
+    let hdc = CreateDCW(info.szDevice.as_ptr(), info.szDevice.as_ptr(), ptr::null(), ptr::null());
+    if hdc.is_null() {
+        return winapi_fail("CreateDCW");
+    }
+    let result = f(hdc);
+    DeleteDC(hdc);
+    result
+}
+
+pub unsafe fn get_monitor_gamma(hmonitor: HMONITOR) -> Result<GammaRamps> {
+    with_monitor_dc(hmonitor, |hdc| {
+        let mut ramp = [[0_u16; GAMMA_RAMP_SIZE]; 3];
+        if GetDeviceGammaRamp(hdc, ramp.as_mut_ptr() as *mut _) == FALSE {
+            return winapi_fail("GetDeviceGammaRamp");
+        }
+        Ok(GammaRamps {
+            red: ramp[0].to_vec(),
+            green: ramp[1].to_vec(),
+            blue: ramp[2].to_vec(),
+        })
+    })
+}
+
+pub unsafe fn set_monitor_gamma(hmonitor: HMONITOR, ramps: &GammaRamps) -> Result<()> {
+    with_monitor_dc(hmonitor, |hdc| {
+        let mut ramp = [[0_u16; GAMMA_RAMP_SIZE]; 3];
+        ramp[0].copy_from_slice(&ramps.red);
+        ramp[1].copy_from_slice(&ramps.green);
+        ramp[2].copy_from_slice(&ramps.blue);
+        // NOTE: Windows rejects ramps that deviate "too much" from the identity.
+        if SetDeviceGammaRamp(hdc, ramp.as_mut_ptr() as *mut _) == FALSE {
+            return failed("SetDeviceGammaRamp() returned FALSE (the ramps may be too far from the identity)");
+        }
+        Ok(())
+    })
+}
+
+impl OsContext {
+    pub fn monitors(&self) -> Result<Vec<OsMonitorID>> {
+        enum_monitors()
+    }
+    pub fn monitor_rect(&self, monitor: OsMonitorID) -> Result<Rect<i32, u32>> {
+        unsafe {
+            monitor_rect(monitor)
+        }
+    }
+    pub fn primary_monitor(&self) -> Result<Option<OsMonitorID>> {
+        // The primary monitor is the one whose top-left corner is at the origin.
+        let hmonitor = unsafe {
+            MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY)
+        };
+        Ok(Some(hmonitor).filter(|hmonitor| !hmonitor.is_null()))
+    }
+    pub fn display_modes(&self, monitor: OsMonitorID) -> Result<Vec<DisplayMode>> {
+        unsafe {
+            let device_name = monitor_device_name(monitor)?;
+            let mut modes = Vec::new();
+            find_devmode(&device_name, |devmode| {
+                let mode = display_mode_from_devmode(devmode);
+                // Modes that only differ by e.g their scaling look the same to us.
+                if !modes.contains(&mode) {
+                    modes.push(mode);
+                }
+                None::<()>
+            });
+            Ok(modes)
+        }
+    }
+    pub fn current_display_mode(&self, monitor: OsMonitorID) -> Result<DisplayMode> {
+        unsafe {
+            let device_name = monitor_device_name(monitor)?;
+            let mut devmode: DEVMODEW = mem::zeroed();
+            devmode.dmSize = mem::size_of::<DEVMODEW>() as _;
+            if EnumDisplaySettingsW(device_name.as_ptr(), ENUM_CURRENT_SETTINGS, &mut devmode) == FALSE {
+                return winapi_fail("EnumDisplaySettingsW");
+            }
+            Ok(display_mode_from_devmode(&devmode))
+        }
+    }
+    pub fn set_display_mode(&self, monitor: OsMonitorID, mode: &DisplayMode) -> Result<OsDisplayModeGuard> {
+        unsafe {
+            let device_name = monitor_device_name(monitor)?;
+            let mut devmode = match find_devmode(&device_name, |devmode| {
+                if display_mode_from_devmode(devmode) == *mode { Some(*devmode) } else { None }
+            }) {
+                Some(devmode) => devmode,
+                None => return invalid_arg(format!("{:?} is not a mode of this monitor", mode)),
+            };
+            devmode.dmFields = DM_PELSWIDTH | DM_PELSHEIGHT | DM_BITSPERPEL | DM_DISPLAYFREQUENCY;
+            let status = ChangeDisplaySettingsExW(device_name.as_ptr(), &mut devmode, ptr::null_mut(), CDS_FULLSCREEN, ptr::null_mut());
+            if status != DISP_CHANGE_SUCCESSFUL {
+                return failed(format!("ChangeDisplaySettingsExW() returned {}", status));
+            }
+            Ok(OsDisplayModeGuard { device_name })
+        }
+    }
+    pub fn monitor_gamma(&self, monitor: OsMonitorID) -> Result<GammaRamps> {
+        unsafe {
+            get_monitor_gamma(monitor)
+        }
+    }
+    pub fn original_monitor_gamma(&self, monitor: OsMonitorID) -> Result<GammaRamps> {
+        if let Some(ramps) = self.original_gamma_ramps.borrow().get(&monitor) {
+            return Ok(ramps.clone());
+        }
+        self.monitor_gamma(monitor)
+    }
+    pub fn set_monitor_gamma(&self, monitor: OsMonitorID, ramps: &GammaRamps) -> Result<()> {
+        if ramps.size() != Some(GAMMA_RAMP_SIZE) {
+            return invalid_arg(format!("The gamma ramps of this monitor must have {} entries", GAMMA_RAMP_SIZE));
+        }
+        let original = self.original_monitor_gamma(monitor)?;
+        self.original_gamma_ramps.borrow_mut().entry(monitor).or_insert(original);
+        unsafe {
+            set_monitor_gamma(monitor, ramps)
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! The process name, via `SetThreadDescription()`.
+
+use std::mem;
+use error::{Result, unsupported};
+use super::winapi::um::processthreadsapi::GetCurrentThread;
+use super::context::OsContext;
+use super::winapi_utils::*;
+
+impl OsContext {
+    pub fn set_process_name(&self, name: &str) -> Result<()> {
+        // Only available since Windows 10 version 1607, so we have to load it dynamically.
+        type SetThreadDescriptionFn = unsafe extern "system" fn(HANDLE, PCWSTR) -> HRESULT;
+        unsafe {
+            let kernel32 = GetModuleHandleW(to_wide_with_nul("kernel32.dll").as_ptr());
+            if kernel32.is_null() {
+                return unsupported("Could not get a handle to kernel32.dll");
+            }
+            let f = match GetProcAddress(kernel32, b"SetThreadDescription\0".as_ptr() as _) as usize {
+                0 => return unsupported("SetThreadDescription() requires Windows 10 version 1607 or later"),
+                f => mem::transmute::<usize, SetThreadDescriptionFn>(f),
+            };
+            hresult_to_result("SetThreadDescription", f(GetCurrentThread(), to_wide_with_nul(name).as_ptr()))
+        }
+    }
+}
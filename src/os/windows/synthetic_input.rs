@@ -0,0 +1,84 @@
+use std::mem;
+use error::{Result, invalid_arg};
+use device::{Keysym, MouseButton, ButtonState};
+use super::{OsSharedContext, winapi_utils::*};
+use super::device::keyboard::vkey_from_keysym;
+use Vec2;
+
+fn send_input(input: &mut INPUT) -> Result<()> {
+    let nb_inserted = unsafe {
+        SendInput(1, input, mem::size_of::<INPUT>() as _)
+    };
+    match nb_inserted {
+        // SendInput() doesn't tell when it's blocked by UIPI.
+        0 => winapi_fail("SendInput"),
+        _ => Ok(()),
+    }
+}
+
+impl OsSharedContext {
+    pub fn synthesize_key(&self, keysym: Keysym, state: ButtonState) -> Result<()> {
+        let vkey = match vkey_from_keysym(keysym) {
+            Some(vkey) => vkey,
+            None => return invalid_arg(format!("{:?} has no virtual-key code", keysym)),
+        };
+        unsafe {
+            let mut input: INPUT = mem::zeroed();
+            input.type_ = INPUT_KEYBOARD;
+            *input.u.ki_mut() = KEYBDINPUT {
+                wVk: vkey as _,
+                wScan: 0,
+                dwFlags: match state {
+                    ButtonState::Down => 0,
+                    ButtonState::Up => KEYEVENTF_KEYUP,
+                },
+                time: 0,
+                dwExtraInfo: 0,
+            };
+            send_input(&mut input)
+        }
+    }
+    pub fn synthesize_mouse_move_to(&self, position: Vec2<i32>) -> Result<()> {
+        unsafe {
+            // Absolute coordinates are normalized to [0, 65535] over the virtual desktop.
+            let origin = Vec2::new(GetSystemMetrics(SM_XVIRTUALSCREEN), GetSystemMetrics(SM_YVIRTUALSCREEN));
+            let size = Vec2::new(GetSystemMetrics(SM_CXVIRTUALSCREEN), GetSystemMetrics(SM_CYVIRTUALSCREEN));
+            let normalized = (position - origin) * 65535 / (size - 1).map(|x| x.max(1));
+            let mut input: INPUT = mem::zeroed();
+            input.type_ = INPUT_MOUSE;
+            *input.u.mi_mut() = MOUSEINPUT {
+                dx: normalized.x,
+                dy: normalized.y,
+                mouseData: 0,
+                dwFlags: MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK,
+                time: 0,
+                dwExtraInfo: 0,
+            };
+            send_input(&mut input)
+        }
+    }
+    pub fn synthesize_mouse_button(&self, button: MouseButton, state: ButtonState) -> Result<()> {
+        let is_down = state == ButtonState::Down;
+        let (flags, mouse_data) = match button {
+            MouseButton::Left    => (if is_down { MOUSEEVENTF_LEFTDOWN   } else { MOUSEEVENTF_LEFTUP   }, 0),
+            MouseButton::Middle  => (if is_down { MOUSEEVENTF_MIDDLEDOWN } else { MOUSEEVENTF_MIDDLEUP }, 0),
+            MouseButton::Right   => (if is_down { MOUSEEVENTF_RIGHTDOWN  } else { MOUSEEVENTF_RIGHTUP  }, 0),
+            MouseButton::Back    => (if is_down { MOUSEEVENTF_XDOWN      } else { MOUSEEVENTF_XUP      }, XBUTTON1),
+            MouseButton::Forward => (if is_down { MOUSEEVENTF_XDOWN      } else { MOUSEEVENTF_XUP      }, XBUTTON2),
+            _ => return invalid_arg(format!("{:?} can't be synthesized on Windows", button)),
+        };
+        unsafe {
+            let mut input: INPUT = mem::zeroed();
+            input.type_ = INPUT_MOUSE;
+            *input.u.mi_mut() = MOUSEINPUT {
+                dx: 0,
+                dy: 0,
+                mouseData: mouse_data as _,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            };
+            send_input(&mut input)
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Taskbar progress, via `ITaskbarList3`.
+
+use std::ptr;
+use error::Result;
+use super::winapi::Interface;
+use super::winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+use super::winapi::um::combaseapi::CoCreateInstance;
+use super::winapi::um::shobjidl_core::{ITaskbarList3, CLSID_TaskbarList, TBPF_NORMAL, TBPF_NOPROGRESS};
+use super::window::OsSharedWindow;
+use super::com::ComGuard;
+use super::winapi_utils::*;
+
+// The resolution of the progress value; ITaskbarList3 takes it as a fraction of integers.
+const PROGRESS_TOTAL: u64 = 10000;
+
+impl OsSharedWindow {
+    pub fn set_taskbar_progress(&self, progress: Option<f64>) -> Result<()> {
+        unsafe {
+            let _com = ComGuard::new()?;
+            let mut taskbar: *mut ITaskbarList3 = ptr::null_mut();
+            hresult_to_result("CoCreateInstance", CoCreateInstance(
+                &CLSID_TaskbarList, ptr::null_mut(), CLSCTX_INPROC_SERVER,
+                &ITaskbarList3::uuidof(), &mut taskbar as *mut _ as _
+            ))?;
+            let result = hresult_to_result("ITaskbarList3::HrInit", (*taskbar).HrInit()).and_then(|()| match progress {
+                Some(progress) => {
+                    hresult_to_result("ITaskbarList3::SetProgressState", (*taskbar).SetProgressState(self.hwnd, TBPF_NORMAL))?;
+                    let completed = (progress * PROGRESS_TOTAL as f64).round() as u64;
+                    hresult_to_result("ITaskbarList3::SetProgressValue", (*taskbar).SetProgressValue(self.hwnd, completed, PROGRESS_TOTAL))
+                },
+                None => hresult_to_result("ITaskbarList3::SetProgressState", (*taskbar).SetProgressState(self.hwnd, TBPF_NOPROGRESS)),
+            });
+            (*taskbar).Release();
+            result
+        }
+    }
+}
@@ -1,16 +1,72 @@
 use std::ptr;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::cell::{Cell, RefCell};
 use std::ops::Deref;
 use std::mem;
-use error::{Result, failed};
-use window::{Window, WindowSettings, WindowHandle, WindowStyleHint, WindowTypeHint, TitleBarFeatures, Borders};
+use std::fmt;
+use error::{Result, failed, invalid_arg, unsupported, unimplemented, busy};
+use event::{Event, EventInstant};
+use device::MouseButton;
+use window::{Window, HitTestFn, NativeWindowHandle, WindowSettings, WindowHandle, WindowStyleHint, WindowTypeHint, TitleBarFeatures, Borders, ResizeEdge, WindowOp, Capability, ImageMask, GrabOptions, GrabHolder, WindowStateFlags};
 use super::{OsContext, OsSharedContext, HCursor, winapi_utils::*};
+use gl::{PresentStatsCollector, GLSwapInterval};
 use {Vec2, Extent2, Rect, Rgba};
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle, Win32WindowHandle};
+#[cfg(feature = "synthetic-input")]
+use std::time::Instant;
+#[cfg(feature = "synthetic-input")]
+use device::DeviceID;
+#[cfg(feature = "synthetic-input")]
+use super::{OsDeviceID, OsEventInstant};
 
 
 pub type OsWindowHandle = HWND;
 
+/// The DPI that corresponds to a scale factor of 1.
+pub const DEFAULT_DPI: UINT = 96;
+
+/// Calls `GetDpiForWindow()` if available (Windows 10 1607 and later), otherwise returns
+/// the default DPI, which is what non-DPI-aware processes get anyway.
+pub fn dpi_for_window(hwnd: HWND) -> UINT {
+    type GetDpiForWindowFn = unsafe extern "system" fn(HWND) -> UINT;
+    unsafe {
+        let user32 = GetModuleHandleW(to_wide_with_nul("user32.dll").as_ptr());
+        if user32.is_null() {
+            return DEFAULT_DPI;
+        }
+        match GetProcAddress(user32, b"GetDpiForWindow\0".as_ptr() as _) as usize {
+            0 => DEFAULT_DPI,
+            f => mem::transmute::<usize, GetDpiForWindowFn>(f)(hwnd),
+        }
+    }
+}
+
+impl<'a> From<&'a Window> for NativeWindowHandle {
+    fn from(w: &'a Window) -> Self {
+        NativeWindowHandle::Win32 {
+            hwnd: w.0.hwnd as _,
+            hinstance: w.0.context.hinstance() as _,
+        }
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+unsafe impl HasRawWindowHandle for Window {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = Win32WindowHandle::empty();
+        handle.hwnd = self.0.hwnd as _;
+        handle.hinstance = self.0.context.hinstance() as _;
+        RawWindowHandle::Win32(handle)
+    }
+}
+
+impl fmt::Display for WindowHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "hwnd:{:p}", self.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct OsWindowFromHandleParams {
     pub class_atom: ATOM,
@@ -33,11 +89,39 @@ pub struct OsSharedWindow {
     pub is_mouse_outside: Cell<bool>,
     pub cursor: RefCell<Rc<HCursor>>,
     pub is_cursor_visible: Cell<bool>,
+    pub is_text_input_active: Cell<bool>,
+    pub title_bar_buttons: Cell<TitleBarFeatures>,
+    /// `None` unless enabled via `set_present_stats_collection()`.
+    pub(crate) present_stats: RefCell<Option<PresentStatsCollector>>,
+    /// The last swap interval that was successfully set via `gl_set_swap_interval()`.
+    pub gl_swap_interval: Cell<Option<GLSwapInterval>>,
+    /// The scale factor the window was last known to have, if it was ever queried.
+    pub scale_factor: Cell<Option<f64>>,
+    /// The refresh rate of the window's monitor, as last reported; `None` until first queried.
+    pub refresh_rate: Cell<Option<f64>>,
+    /// Used when handling `WM_NCHITTEST`, see `Window::set_cursor_hit_test()`.
+    pub(crate) hit_test: RefCell<Option<HitTestFn>>,
 }
 
 #[derive(Debug)]
 pub struct OsWindow(pub(crate) Rc<OsSharedWindow>);
 
+/// Releases the mouse capture on drop.
+#[derive(Debug)]
+pub struct OsInputGrab {
+    hwnd: HWND,
+}
+
+impl Drop for OsInputGrab {
+    fn drop(&mut self) {
+        unsafe {
+            if GetCapture() == self.hwnd {
+                ReleaseCapture();
+            }
+        }
+    }
+}
+
 impl Deref for OsWindow {
     type Target = OsSharedWindow;
     fn deref(&self) -> &OsSharedWindow {
@@ -52,7 +136,8 @@ impl Drop for OsSharedWindow {
             own_dc: _, // Destroyed with the window. DO NOT destroy it manually because it will fail.
             ref hicon,
             min_size: _, max_size: _, is_movable: _, is_mouse_outside: _,
-            cursor: _, is_cursor_visible: _,
+            cursor: _, is_cursor_visible: _, is_text_input_active: _,
+            title_bar_buttons: _, present_stats: _, gl_swap_interval: _, scale_factor: _, refresh_rate: _, hit_test: _,
         } = self;
 
         match context.weak_windows.borrow_mut().remove(&hwnd) {
@@ -71,13 +156,52 @@ impl Drop for OsSharedWindow {
 }
 
 impl OsContext {
+    pub fn screen_count(&self) -> Result<usize> {
+        Ok(1)
+    }
+    pub fn create_window_on_screen(&self, screen: usize, settings: &WindowSettings) -> Result<OsWindow> {
+        match screen {
+            0 => self.create_window(settings),
+            _ => invalid_arg(format!("There is no screen {} (Windows only has one)", screen)),
+        }
+    }
+    pub fn pointer_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        let hwnd = unsafe { GetCapture() };
+        if hwnd.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(GrabHolder::Window(WindowHandle(hwnd))))
+    }
+    pub fn keyboard_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        Ok(None)
+    }
     pub fn create_window(&self, settings: &WindowSettings) -> Result<OsWindow> {
         let &WindowSettings {
-            ref opengl, high_dpi,
+            ref opengl, high_dpi, parent, position, centered, override_redirect,
+            wm_class: _, // X11-only
         } = settings;
         unsafe {
-            let ex_style = WS_EX_ACCEPTFILES | WS_EX_OVERLAPPEDWINDOW;
-            let style = WS_OVERLAPPEDWINDOW;
+            // CW_USEDEFAULT is only valid for overlapped windows.
+            let (ex_style, style, parent_hwnd, (x, y, w, h)) = match parent {
+                None if override_redirect => (
+                    WS_EX_ACCEPTFILES | WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW,
+                    WS_POPUP,
+                    ptr::null_mut(),
+                    (0, 0, 600, 480),
+                ),
+                None => (
+                    WS_EX_ACCEPTFILES | WS_EX_OVERLAPPEDWINDOW,
+                    WS_OVERLAPPEDWINDOW,
+                    ptr::null_mut(),
+                    (CW_USEDEFAULT, CW_USEDEFAULT, CW_USEDEFAULT, CW_USEDEFAULT),
+                ),
+                Some(WindowHandle(parent_hwnd)) => (
+                    WS_EX_ACCEPTFILES,
+                    WS_CHILD | WS_CLIPSIBLINGS | WS_CLIPCHILDREN,
+                    parent_hwnd,
+                    (0, 0, 600, 480),
+                ),
+            };
             let class_settings = super::context::ClassSettings {
                 owndc: true, noclose: false,
             };
@@ -90,11 +214,8 @@ impl OsContext {
                 MAKEINTATOM(class_atom),
                 ptr::null(), // No title (yet)
                 style,
-                CW_USEDEFAULT, // x
-                CW_USEDEFAULT, // y
-                CW_USEDEFAULT, // w
-                CW_USEDEFAULT, // h
-                ptr::null_mut(), // No parent
+                x, y, w, h,
+                parent_hwnd,
                 ptr::null_mut(), // No menu
                 self.hinstance(),
                 ptr::null_mut(), // No custom data pointer
@@ -125,18 +246,44 @@ impl OsContext {
                 is_mouse_outside: Cell::new(true), // XXX not correct?
                 cursor: RefCell::new(self.create_default_system_cursor().unwrap().0),
                 is_cursor_visible: Cell::new(true),
+                is_text_input_active: Cell::new(true),
+                title_bar_buttons: Cell::new(Default::default()),
+                present_stats: RefCell::new(None),
+                gl_swap_interval: Cell::new(None),
+                scale_factor: Cell::new(None),
+                refresh_rate: Cell::new(None),
+                hit_test: RefCell::new(None),
             };
+            // This has to happen before anything else gets a chance to set the pixel format,
+            // since SetPixelFormat() can only be called once per HWND.
             if let Some(opengl) = opengl.as_ref() {
                 let pf = os_window.choose_gl_pixel_format(*opengl)?;
                 os_window.set_pixel_format(&pf)?;
             }
 
+            if parent.is_none() {
+                if let Some(position) = position {
+                    os_window.set_initial_position(position)?;
+                } else if centered {
+                    os_window.set_initial_position(os_window.centered_position()?)?;
+                }
+            }
+
             let _ = os_window.call_track_mouse_event();
             let os_window = Rc::new(os_window);
             self.weak_windows.borrow_mut().insert(hwnd, Rc::downgrade(&os_window));
             Ok(OsWindow(os_window))
         }
     }
+    pub fn window_count(&self) -> usize {
+        self.weak_windows.borrow().len()
+    }
+    pub fn windows(&self) -> Vec<OsWindowHandle> {
+        self.weak_windows.borrow().keys().cloned().collect()
+    }
+    pub fn window_by_handle(&self, hwnd: OsWindowHandle) -> Option<OsWindow> {
+        self.weak_windows.borrow().get(&hwnd).and_then(Weak::upgrade).map(OsWindow)
+    }
     pub unsafe fn window_from_handle(&self, hwnd: OsWindowHandle, params: Option<&OsWindowFromHandleParams>) -> Result<OsWindow> {
         if IsWindow(hwnd) == FALSE {
             return failed("HWND doesn't refer to a window");
@@ -167,6 +314,13 @@ impl OsContext {
                     is_mouse_outside: Cell::new(true),
                     cursor: RefCell::new(self.create_default_system_cursor().unwrap().0),
                     is_cursor_visible: Cell::new(true),
+                    is_text_input_active: Cell::new(true),
+                    title_bar_buttons: Cell::new(Default::default()),
+                    present_stats: RefCell::new(None),
+                    gl_swap_interval: Cell::new(None),
+                    scale_factor: Cell::new(None),
+                    refresh_rate: Cell::new(None),
+                    hit_test: RefCell::new(None),
                 };
                 let _ = os_window.call_track_mouse_event();
                 Ok(OsWindow(Rc::new(os_window)))
@@ -191,7 +345,68 @@ pub fn call_track_mouse_event(hwnd: HWND) -> Result<()> {
     }
 }
 
+/// Builds a region out of the horizontal runs of `true` pixels of each row.
+/// Note that window regions are relative to the window's frame, not its client area.
+unsafe fn mask_to_hrgn(mask: &ImageMask) -> Result<HRGN> {
+    let hrgn = CreateRectRgn(0, 0, 0, 0);
+    if hrgn.is_null() {
+        return winapi_fail("CreateRectRgn");
+    }
+    let (w, h) = (mask.width as usize, mask.height as usize);
+    for y in 0..h {
+        let row = &mask.pixels[y * w .. (y + 1) * w];
+        let mut x = 0;
+        while x < w {
+            if !row[x] {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < w && row[x] {
+                x += 1;
+            }
+            let run = CreateRectRgn(start as _, y as _, x as _, y as i32 + 1);
+            if run.is_null() {
+                DeleteObject(hrgn as _);
+                return winapi_fail("CreateRectRgn");
+            }
+            CombineRgn(hrgn, hrgn, run, RGN_OR);
+            DeleteObject(run as _);
+        }
+    }
+    Ok(hrgn)
+}
+
 impl OsSharedWindow {
+    fn set_initial_position(&self, position: Vec2<i32>) -> Result<()> {
+        let flags = SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE;
+        let is_ok = unsafe {
+            SetWindowPos(self.hwnd, ptr::null_mut(), position.x, position.y, 0, 0, flags)
+        };
+        if is_ok == FALSE {
+            return winapi_fail("SetWindowPos");
+        }
+        Ok(())
+    }
+    /// Centers the window (including its borders) within the primary monitor's work area.
+    fn centered_position(&self) -> Result<Vec2<i32>> {
+        unsafe {
+            let mut work_area: RECT = mem::zeroed();
+            if SystemParametersInfoW(SPI_GETWORKAREA, 0, &mut work_area as *mut _ as _, 0) == FALSE {
+                return winapi_fail("SystemParametersInfoW");
+            }
+            let mut rect: RECT = mem::zeroed();
+            if GetWindowRect(self.hwnd, &mut rect) == FALSE {
+                return winapi_fail("GetWindowRect");
+            }
+            let (w, h) = (rect.right - rect.left, rect.bottom - rect.top);
+            Ok(Vec2::new(
+                work_area.left + (work_area.right - work_area.left - w) / 2,
+                work_area.top + (work_area.bottom - work_area.top - h) / 2,
+            ))
+        }
+    }
+
     pub fn own_dc(&self) -> Result<HDC> {
         self.own_dc.clone()
     }
@@ -201,6 +416,9 @@ impl OsSharedWindow {
     pub fn call_track_mouse_event(&self) -> Result<()> {
         call_track_mouse_event(self.hwnd)
     }
+    pub fn set_icon_name(&self, _icon_name: &str) -> Result<()> {
+        unsupported("Windows has no icon name distinct from the title")
+    }
     pub fn set_title(&self, title: &str) -> Result<()> {
         let is_ok = unsafe {
             SetWindowTextW(self.hwnd, to_wide_with_nul(title).as_ptr())
@@ -222,6 +440,12 @@ impl OsSharedWindow {
         assert!(nb_chars_without_nul < wide.len() as _);
         Ok(wide_string(&wide[..nb_chars_without_nul as usize]))
     }
+    pub fn set_wm_class(&self, _instance: &str, _class: &str) -> Result<()> {
+        unsupported("`WM_CLASS` is X11-specific")
+    }
+    pub fn wm_class(&self) -> Result<Option<(String, String)>> {
+        unsupported("`WM_CLASS` is X11-specific")
+    }
     pub fn set_icon(&self, size: Extent2<u32>, data: &[Rgba<u8>]) -> Result<()> {
         if let Some(hicon) = self.hicon.get().take() {
             unsafe {
@@ -259,7 +483,7 @@ impl OsSharedWindow {
             }
         }
         */
-        unimplemented!()
+        unimplemented("Window::icon() is not implemented on Windows yet")
     }
     pub fn reset_icon(&self) -> Result<()> {
         unsafe {
@@ -303,29 +527,46 @@ impl OsSharedWindow {
         unsafe {
             let mut style = GetWindowLongW(self.hwnd, GWL_STYLE) as u32;
             debug_assert_ne!(0, style); // This can't fail, it has no reason to
-            if let Some(_) = borders {
-                style |= WS_BORDER | WS_SIZEBOX;
-            } else {
-                style &= !(WS_BORDER | WS_SIZEBOX);
+            match borders {
+                Some(Borders { client_side: false, .. }) => style |= WS_BORDER | WS_SIZEBOX,
+                // Client-side borders are drawn by the app, see Borders::rects().
+                Some(Borders { client_side: true, .. }) | None => style &= !(WS_BORDER | WS_SIZEBOX),
             };
-            if let Some(TitleBarFeatures { minimize, maximize, close, }) = title_bar_features {
-                if minimize {
-                    style |= WS_MINIMIZEBOX;
-                } else {
-                    style &= !WS_MINIMIZEBOX;
-                }
-                if maximize {
-                    style |= WS_MAXIMIZEBOX;
-                } else {
-                    style &= !WS_MAXIMIZEBOX;
-                }
-                self.set_close_button_enabled(close);
+            if let Some(features) = title_bar_features {
+                style = self.apply_title_bar_buttons(style, features);
             } else {
                 style &= !WS_CAPTION;
             }
             self.set_window_long_ptr(GWL_STYLE, style as _)
         }
     }
+    pub fn set_title_bar_buttons(&self, buttons: TitleBarFeatures) -> Result<()> {
+        let style = unsafe {
+            GetWindowLongW(self.hwnd, GWL_STYLE) as u32
+        };
+        let style = self.apply_title_bar_buttons(style, buttons);
+        self.set_window_long_ptr(GWL_STYLE, style as _)
+    }
+    pub fn title_bar_buttons(&self) -> TitleBarFeatures {
+        self.title_bar_buttons.get()
+    }
+    // Returns the new window style. The close button isn't a style bit, so it's handled right away.
+    fn apply_title_bar_buttons(&self, mut style: u32, buttons: TitleBarFeatures) -> u32 {
+        let TitleBarFeatures { minimize, maximize, close, } = buttons;
+        self.title_bar_buttons.set(buttons);
+        if minimize {
+            style |= WS_MINIMIZEBOX;
+        } else {
+            style &= !WS_MINIMIZEBOX;
+        }
+        if maximize {
+            style |= WS_MAXIMIZEBOX;
+        } else {
+            style &= !WS_MAXIMIZEBOX;
+        }
+        self.set_close_button_enabled(close);
+        style
+    }
     fn set_window_long_ptr(&self, gwl: i32, val: isize) -> Result<()> {
         unsafe {
             SetLastError(0); // See doc for SetWindowLongW()
@@ -358,6 +599,18 @@ impl OsSharedWindow {
         }
         Ok(())
     }
+    pub fn focus(&self) -> Result<()> {
+        unsafe {
+            // When the request is denied, the taskbar button flashes instead.
+            if SetForegroundWindow(self.hwnd) == FALSE {
+                return failed("SetForegroundWindow() was denied");
+            }
+        }
+        Ok(())
+    }
+    pub fn focus_with_timestamp(&self, _instant: EventInstant) -> Result<()> {
+        self.focus()
+    }
     pub fn set_min_size(&self, size: Extent2<u32>) -> Result<()> {
         self.min_size.set(Some(size));
         Ok(())
@@ -411,7 +664,9 @@ impl OsSharedWindow {
         }
     }
     pub fn show(&self) -> Result<()> {
-        self.show_window(SW_SHOW)
+        // SW_SHOW would activate the window anyway.
+        let ex_style = unsafe { GetWindowLongW(self.hwnd, GWL_EXSTYLE) } as DWORD;
+        self.show_window(if ex_style & WS_EX_NOACTIVATE != 0 { SW_SHOWNA } else { SW_SHOW })
     }
     pub fn hide(&self) -> Result<()> {
         self.show_window(SW_HIDE)
@@ -463,41 +718,60 @@ impl OsSharedWindow {
 
     // Urgh, these are complicated
     pub fn maximize_width(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::maximize_width() is not implemented on Windows yet")
     }
     pub fn unmaximize_width(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::unmaximize_width() is not implemented on Windows yet")
     }
     pub fn toggle_maximize_width(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::toggle_maximize_width() is not implemented on Windows yet")
     }
     pub fn is_width_maximized(&self) -> Result<bool> {
-        unimplemented!()
+        unimplemented("Window::is_width_maximized() is not implemented on Windows yet")
     }
     pub fn maximize_height(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::maximize_height() is not implemented on Windows yet")
     }
     pub fn unmaximize_height(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::unmaximize_height() is not implemented on Windows yet")
     }
     pub fn toggle_maximize_height(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::toggle_maximize_height() is not implemented on Windows yet")
     }
     pub fn is_height_maximized(&self) -> Result<bool> {
-        unimplemented!()
+        unimplemented("Window::is_height_maximized() is not implemented on Windows yet")
     }
 
     pub fn enter_fullscreen(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::enter_fullscreen() is not implemented on Windows yet")
     }
     pub fn leave_fullscreen(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::leave_fullscreen() is not implemented on Windows yet")
     }
     pub fn toggle_fullscreen(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::toggle_fullscreen() is not implemented on Windows yet")
     }
     pub fn is_fullscreen(&self) -> Result<bool> {
-        unimplemented!()
+        unimplemented("Window::is_fullscreen() is not implemented on Windows yet")
+    }
+    pub fn state(&self) -> Result<WindowStateFlags> {
+        let show_cmd = self.show_cmd()? as i32;
+        let is_maximized = show_cmd == SW_MAXIMIZE;
+        let ex_style = unsafe { GetWindowLongW(self.hwnd, GWL_EXSTYLE) } as DWORD;
+        Ok(WindowStateFlags {
+            // We can't make a window full-screen on Windows yet.
+            fullscreen: false,
+            maximized_width: is_maximized,
+            maximized_height: is_maximized,
+            minimized: show_cmd == SW_MINIMIZE,
+            focused: unsafe { GetForegroundWindow() } == self.hwnd,
+            hidden: unsafe { IsWindowVisible(self.hwnd) } == FALSE,
+            above: (ex_style & WS_EX_TOPMOST) != 0,
+            // There's no such thing on Windows.
+            below: false,
+            // FlashWindowEx() has no getter.
+            demands_attention: false,
+        })
     }
     fn flash_window_ex(&self, flags: DWORD, ucount: u32) -> Result<()> {
         unsafe {
@@ -518,6 +792,14 @@ impl OsSharedWindow {
     pub fn demand_urgent_attention(&self) -> Result<()> {
         self.flash_window_ex(FLASHW_ALL | FLASHW_TIMERNOFG, 0xffffffff)
     }
+    pub fn flash(&self, count: Option<u32>) -> Result<()> {
+        match count {
+            Some(0) => self.flash_window_ex(FLASHW_STOP, 0),
+            // Bounded flashing is stopped on WM_SETFOCUS by the window proc.
+            Some(n) => self.flash_window_ex(FLASHW_ALL, n),
+            None => self.flash_window_ex(FLASHW_ALL | FLASHW_TIMERNOFG, 0),
+        }
+    }
     pub fn position_and_size(&self) -> Result<Rect<i32, u32>> {
         unsafe {
             let mut r: RECT = mem::zeroed();
@@ -534,6 +816,39 @@ impl OsSharedWindow {
             Ok(r)
         }
     }
+    /// The rect the window has when it is neither maximized nor minimized.
+    pub fn normal_position_and_size(&self) -> Result<Option<Rect<i32, u32>>> {
+        unsafe {
+            let mut windowplacement = WINDOWPLACEMENT {
+                length: mem::size_of::<WINDOWPLACEMENT>() as _,
+                .. mem::zeroed()
+            };
+            if GetWindowPlacement(self.hwnd, &mut windowplacement) == FALSE {
+                return winapi_fail("GetWindowPlacement");
+            }
+            let mut r = windowplacement.rcNormalPosition;
+            // This is in workspace coordinates (which exclude the taskbar), except for tool windows.
+            let ex_style = GetWindowLongW(self.hwnd, GWL_EXSTYLE) as DWORD;
+            if (ex_style & WS_EX_TOOLWINDOW) == 0 {
+                let mut info: MONITORINFO = mem::zeroed();
+                info.cbSize = mem::size_of::<MONITORINFO>() as _;
+                if GetMonitorInfoW(MonitorFromRect(&r, MONITOR_DEFAULTTONEAREST), &mut info) == FALSE {
+                    return winapi_fail("GetMonitorInfoW");
+                }
+                let (dx, dy) = (info.rcWork.left - info.rcMonitor.left, info.rcWork.top - info.rcMonitor.top);
+                r.left += dx;
+                r.right += dx;
+                r.top += dy;
+                r.bottom += dy;
+            }
+            Ok(Some(Rect {
+                x: r.left,
+                y: r.top,
+                w: (r.right + 1 - r.left) as _,
+                h: (r.bottom + 1 - r.top) as _,
+            }))
+        }
+    }
     pub fn position(&self) -> Result<Vec2<i32>> {
         self.position_and_size().map(|ps| ps.position())
     }
@@ -543,6 +858,34 @@ impl OsSharedWindow {
     pub fn canvas_size(&self) -> Result<Extent2<u32>> {
         self.size() // FIXME: not true!
     }
+    /// Gets the position and size of each monitor, in `Context::monitors()` order.
+    pub fn monitor_rects(&self) -> Result<Vec<Rect<i32, u32>>> {
+        super::monitor::enum_monitors()?.into_iter().map(|hmonitor| unsafe {
+            super::monitor::monitor_rect(hmonitor)
+        }).collect()
+    }
+    pub fn current_refresh_rate(&self) -> Result<f64> {
+        let refresh_rate = unsafe {
+            super::monitor::monitor_refresh_rate(MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTOPRIMARY))?
+        };
+        self.refresh_rate.set(Some(refresh_rate));
+        Ok(refresh_rate)
+    }
+    /// Gets a `WindowRefreshRateChanged` event if the window's monitor now has a different
+    /// refresh rate. Nothing is reported until the refresh rate is first known.
+    pub fn refresh_rate_change(&self) -> Option<Event> {
+        let old = self.refresh_rate.get()?;
+        let new = self.current_refresh_rate().ok()?;
+        if new == old {
+            return None;
+        }
+        Some(Event::WindowRefreshRateChanged { window: self.handle(), refresh_rate: new })
+    }
+    pub fn scale_factor(&self) -> Result<f64> {
+        let scale_factor = dpi_for_window(self.hwnd) as f64 / DEFAULT_DPI as f64;
+        self.scale_factor.set(Some(scale_factor));
+        Ok(scale_factor)
+    }
 
     fn set_window_pos(&self, r: Rect<i32, u32>, flags: u32) -> Result<()> {
         unsafe {
@@ -564,33 +907,213 @@ impl OsSharedWindow {
     }
     // See WS_EX_LAYERED and UpdateLayeredWindow ()
     pub fn set_opacity(&self, alpha: f64) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::set_opacity() is not implemented on Windows yet")
+    }
+    pub fn set_shape_mask(&self, mask: Option<&ImageMask>) -> Result<()> {
+        unsafe {
+            let hrgn = match mask {
+                None => ptr::null_mut(),
+                Some(mask) => mask_to_hrgn(mask)?,
+            };
+            // On success, the system owns the region.
+            if SetWindowRgn(self.hwnd, hrgn, TRUE) == 0 {
+                if !hrgn.is_null() {
+                    DeleteObject(hrgn as _);
+                }
+                return winapi_fail("SetWindowRgn");
+            }
+        }
+        Ok(())
+    }
+    pub fn set_input_shape_mask(&self, _mask: Option<&ImageMask>) -> Result<()> {
+        unsupported("Windows has no input shape that is distinct from the window region")
+    }
+    pub fn set_content_protection(&self, enabled: bool) -> Result<()> {
+        // Not in winapi; Only supported since Windows 10 version 2004.
+        const WDA_EXCLUDEFROMCAPTURE: DWORD = 0x11;
+        unsafe {
+            if !enabled {
+                if SetWindowDisplayAffinity(self.hwnd, WDA_NONE) == FALSE {
+                    return winapi_fail("SetWindowDisplayAffinity");
+                }
+                return Ok(());
+            }
+            if SetWindowDisplayAffinity(self.hwnd, WDA_EXCLUDEFROMCAPTURE) == FALSE {
+                // Older versions reject it, but still support showing a black rectangle instead.
+                if SetWindowDisplayAffinity(self.hwnd, WDA_MONITOR) == FALSE {
+                    return winapi_fail("SetWindowDisplayAffinity");
+                }
+            }
+        }
+        Ok(())
+    }
+    pub(crate) fn set_cursor_hit_test(&self, hit_test: Option<HitTestFn>) -> Result<()> {
+        *self.hit_test.borrow_mut() = hit_test;
+        Ok(())
+    }
+    pub fn begin_move_drag(&self, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        self.begin_nc_drag(HTCAPTION, root_position)
+    }
+    pub fn begin_resize_drag(&self, edge: ResizeEdge, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        let hit_test = match edge {
+            ResizeEdge::TopLeft     => HTTOPLEFT,
+            ResizeEdge::Top         => HTTOP,
+            ResizeEdge::TopRight    => HTTOPRIGHT,
+            ResizeEdge::Right       => HTRIGHT,
+            ResizeEdge::BottomRight => HTBOTTOMRIGHT,
+            ResizeEdge::Bottom      => HTBOTTOM,
+            ResizeEdge::BottomLeft  => HTBOTTOMLEFT,
+            ResizeEdge::Left        => HTLEFT,
+        };
+        self.begin_nc_drag(hit_test, root_position)
+    }
+    // Pretends that the user pressed the left button on the given non-client area,
+    // which starts the system's modal move/resize loop.
+    fn begin_nc_drag(&self, hit_test: isize, root_position: Vec2<i32>) -> Result<()> {
+        unsafe {
+            ReleaseCapture();
+            let lparam = MAKELPARAM(root_position.x as i16 as _, root_position.y as i16 as _);
+            SendMessageW(self.hwnd, WM_NCLBUTTONDOWN, hit_test as _, lparam as _);
+        }
+        Ok(())
     }
     pub fn set_desktop(&self, i: usize) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::set_desktop() is not implemented on Windows yet")
+    }
+    pub fn set_visible_on_all_desktops(&self, sticky: bool) -> Result<()> {
+        unsupported("Windows has no notion of windows being visible on all virtual desktops")
     }
     pub fn recenter_in_desktop(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::recenter_in_desktop() is not implemented on Windows yet")
     }
     pub fn recenter_in_work_area(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::recenter_in_work_area() is not implemented on Windows yet")
     }
     // SetCursorPos
     pub fn set_mouse_position(&self, pos: Vec2<i32>) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::set_mouse_position() is not implemented on Windows yet")
     }
     // GetCursorPos
     pub fn mouse_position(&self) -> Result<Vec2<i32>> {
-        unimplemented!()
+        unimplemented("Window::mouse_position() is not implemented on Windows yet")
     }
     pub fn trap_mouse(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::trap_mouse() is not implemented on Windows yet")
+    }
+    // NOTE: GetCapture() only knows about windows of the calling thread, so we can't tell if
+    // another application holds the capture.
+    pub fn grab_input(&self, options: GrabOptions) -> Result<OsInputGrab> {
+        let GrabOptions { owner_events: _, keyboard } = options;
+        unsafe {
+            if !GetCapture().is_null() {
+                return busy("Another window already has the mouse capture");
+            }
+            SetCapture(self.hwnd);
+            if keyboard {
+                // There's no such thing as a keyboard grab, but key events go to the focused window.
+                SetFocus(self.hwnd);
+            }
+        }
+        Ok(OsInputGrab { hwnd: self.hwnd })
     }
     // TODO: Use RedrawWindow()
     pub fn clear(&self) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::clear() is not implemented on Windows yet")
     }
     pub fn clear_rect(&self, r: Rect<i32, u32>) -> Result<()> {
-        unimplemented!()
+        unimplemented("Window::clear_rect() is not implemented on Windows yet")
+    }
+
+    pub fn text_input_active(&self) -> bool {
+        self.is_text_input_active.get()
+    }
+    pub fn enable_text_input(&self) -> Result<()> {
+        let is_ok = unsafe { ImmAssociateContextEx(self.hwnd, ptr::null_mut(), IACE_DEFAULT) };
+        if is_ok == FALSE {
+            return winapi_fail("ImmAssociateContextEx");
+        }
+        self.is_text_input_active.set(true);
+        Ok(())
+    }
+    pub fn disable_text_input(&self) -> Result<()> {
+        if !self.is_text_input_active.replace(false) {
+            return Ok(());
+        }
+        unsafe {
+            let himc = ImmGetContext(self.hwnd);
+            if !himc.is_null() {
+                ImmNotifyIME(himc, NI_COMPOSITIONSTR, CPS_CANCEL, 0);
+                ImmReleaseContext(self.hwnd, himc);
+            }
+            // Detaches the input context from the window, so that the IME doesn't kick in.
+            ImmAssociateContextEx(self.hwnd, ptr::null_mut(), 0);
+        }
+        self.context.push_event(Event::KeyboardTextComposition { window: self.handle(), text: String::new() });
+        Ok(())
+    }
+    #[cfg(feature = "synthetic-input")]
+    pub fn push_committed_text_event(&self, s: &str) -> Result<()> {
+        if !self.is_text_input_active.get() {
+            return failed("Text input is not enabled for this window");
+        }
+        // Windows reports IME results as a sequence of WM_CHAR; Reporting the string as a whole is fine.
+        self.context.push_event(Event::KeyboardTextString {
+            keyboard: DeviceID(OsDeviceID::MainKeyboard),
+            window: self.handle(),
+            instant: EventInstant(OsEventInstant::Wndproc(Instant::now())),
+            text: s.to_owned(),
+            is_repeat: false,
+            repeat_count: 1,
+        });
+        Ok(())
+    }
+    pub fn capability(&self, op: WindowOp) -> Capability {
+        match op {
+              WindowOp::MaximizeWidthOrHeight
+            | WindowOp::Fullscreen
+            | WindowOp::SetOpacity
+            | WindowOp::SetDesktop
+            | WindowOp::SetVisibleOnAllDesktops
+            | WindowOp::SetMousePosition
+            | WindowOp::TrapMouse
+            | WindowOp::Clear
+            | WindowOp::SetInputShapeMask => Capability::Unsupported,
+              WindowOp::SetTitle
+            | WindowOp::SetIcon
+            | WindowOp::SetTypeHint
+            | WindowOp::SetStyleHint
+            | WindowOp::SetTitleBarButtons
+            | WindowOp::Raise
+            | WindowOp::SetMinMaxSize
+            | WindowOp::SetResizable
+            | WindowOp::SetMovable
+            | WindowOp::Maximize
+            | WindowOp::Minimize
+            | WindowOp::DemandAttention
+            | WindowOp::SetPositionAndSize
+            | WindowOp::BeginMoveResizeDrag
+            | WindowOp::TextInput
+            | WindowOp::SetShapeMask => Capability::Supported,
+        }
+    }
+    pub fn set_text_input_rect(&self, r: Rect<i32, u32>) -> Result<()> {
+        unsafe {
+            let himc = ImmGetContext(self.hwnd);
+            if himc.is_null() {
+                return failed("ImmGetContext() returned NULL");
+            }
+            let mut candidate_form = CANDIDATEFORM {
+                dwIndex: 0,
+                dwStyle: CFS_EXCLUDE,
+                ptCurrentPos: POINT { x: r.x, y: r.y + r.h as i32 },
+                rcArea: RECT { left: r.x, top: r.y, right: r.x + r.w as i32, bottom: r.y + r.h as i32 },
+            };
+            let is_ok = ImmSetCandidateWindow(himc, &mut candidate_form);
+            ImmReleaseContext(self.hwnd, himc);
+            if is_ok == FALSE {
+                return winapi_fail("ImmSetCandidateWindow");
+            }
+        }
+        Ok(())
     }
 }
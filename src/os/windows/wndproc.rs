@@ -11,11 +11,26 @@ use self::w32::{
 };
 use event::{Event, EventInstant};
 use device::{DeviceID, MouseButton, Key, Keycode};
-use window::WindowHandle;
+use window::{WindowHandle, HitTestResult};
 use {Vec2, Extent2};
 
 pub static mut CONTEXT: Option<Weak<OsSharedContext>> = None;
 
+// While a window has the mouse capture (see `OsSharedWindow::grab_input()`), it receives button
+// messages for clicks that happen outside of it, with client coordinates outside its client area.
+fn is_outside_capture(hwnd: HWND, x: i32, y: i32) -> bool {
+    unsafe {
+        if w32::GetCapture() != hwnd {
+            return false;
+        }
+        let mut rect: RECT = ::std::mem::zeroed();
+        if w32::GetClientRect(hwnd, &mut rect) == w32::FALSE {
+            return false;
+        }
+        x < rect.left || y < rect.top || x >= rect.right || y >= rect.bottom
+    }
+}
+
 fn push_event(hwnd: HWND, ev: Event) {
     // hwnd might be used later to retrieve the Context via a global list of windows (I don't like this :/ )
     // Avoid panicking, because we might receive messages even though we have no context current. Windows can do whatever it wants with window procs.
@@ -87,7 +102,9 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LP
         w32::WM_SIZE => {
             let w = LOWORD(lparam as _) as i16;
             let h = HIWORD(lparam as _) as i16;
-            push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size: Extent2::new(w as _, h as _), by_user: false });
+            let size = Extent2::new(w as _, h as _);
+            // FIXME: High-DPI is not handled, so the canvas is the window's size. See `OsWindow::canvas_size()`.
+            push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size, canvas_size: size, by_user: false });
             0
         },
         w32::WM_SIZING => {
@@ -95,7 +112,8 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LP
                 &mut *(lparam as *mut RECT)
             };
             // NOTE: We are allowed to mutate the rect
-            push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size: Extent2::new((rect.right - rect.left) as _, (rect.bottom - rect.top) as _), by_user: false });
+            let size = Extent2::new((rect.right - rect.left) as _, (rect.bottom - rect.top) as _);
+            push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size, canvas_size: size, by_user: false });
             1
         },
         // Sent to a window whose size, position, or place in the Z order has changed as a result of a call to the SetWindowPos function or another window-management function.
@@ -105,9 +123,21 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LP
             };
             if (wpos.flags & SWP_NOMOVE) == 0 {
                 push_event(hwnd, Event::WindowMoved { window: WindowHandle(hwnd), position: Vec2::new(wpos.x as _, wpos.y as _), by_user: true });
+                // The window may now be on another monitor.
+                if let Some(ev) = retrieve_window(hwnd).and_then(|window| window.refresh_rate_change()) {
+                    push_event(hwnd, ev);
+                }
             }
             if (wpos.flags & SWP_NOSIZE) == 0 {
-                push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size: Extent2::new(wpos.cx as _, wpos.cy as _), by_user: true });
+                let size = Extent2::new(wpos.cx as _, wpos.cy as _);
+                push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size, canvas_size: size, by_user: true });
+            }
+            0
+        },
+        // Sent to all top-level windows when the display resolution (or refresh rate) has changed.
+        w32::WM_DISPLAYCHANGE => {
+            if let Some(ev) = retrieve_window(hwnd).and_then(|window| window.refresh_rate_change()) {
+                push_event(hwnd, ev);
             }
             0
         },
@@ -120,7 +150,45 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LP
                 push_event(hwnd, Event::WindowMoved { window: WindowHandle(hwnd), position: Vec2::new(wpos.x as _, wpos.y as _), by_user: true });
             }
             if (wpos.flags & SWP_NOSIZE) == 0 {
-                push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size: Extent2::new(wpos.cx as _, wpos.cy as _), by_user: true });
+                let size = Extent2::new(wpos.cx as _, wpos.cy as _);
+                push_event(hwnd, Event::WindowResized { window: WindowHandle(hwnd), size, canvas_size: size, by_user: true });
+            }
+            0
+        },
+        w32::WM_NCHITTEST => {
+            let hit_test = retrieve_window(hwnd).and_then(|window| window.hit_test.borrow().clone());
+            let hit_test = match hit_test {
+                Some(hit_test) => hit_test,
+                None => return default_window_proc(),
+            };
+            let mut point = POINT { x: GET_X_LPARAM(lparam), y: GET_Y_LPARAM(lparam) };
+            let mut client_rect: RECT = unsafe { ::std::mem::zeroed() };
+            unsafe {
+                w32::ScreenToClient(hwnd, &mut point);
+                w32::GetClientRect(hwnd, &mut client_rect);
+            }
+            let size = Extent2::new(client_rect.right as u32, client_rect.bottom as u32);
+            match hit_test.hit_test(size, Vec2::new(point.x, point.y)) {
+                HitTestResult::Client => w32::HTCLIENT,
+                HitTestResult::Caption => w32::HTCAPTION,
+                HitTestResult::Transparent => w32::HTTRANSPARENT,
+            }
+        },
+        // Only sent to windows of per-monitor DPI-aware processes.
+        w32::WM_DPICHANGED => {
+            if let Some(window) = retrieve_window(hwnd) {
+                let new = HIWORD(wparam as _) as f64 / super::window::DEFAULT_DPI as f64;
+                if let Some(old) = window.scale_factor.replace(Some(new)) {
+                    if old != new {
+                        if let Ok(size) = window.size() {
+                            let suggested_size = Extent2::new(
+                                (size.w as f64 * new / old).round() as u32,
+                                (size.h as f64 * new / old).round() as u32,
+                            );
+                            push_event(hwnd, Event::WindowScaleFactorChanged { window: WindowHandle(hwnd), old, new, suggested_size });
+                        }
+                    }
+                }
             }
             0
         },
@@ -222,12 +290,15 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LP
             let instant = EventInstant(OsEventInstant::Wndproc(Instant::now()));
             let root_position = get_root_position(x, y);
 
+            let position = Vec2::new(x as _, y as _);
+
             let window = WindowHandle(hwnd);
-            push_event(hwnd, Event::MouseMotion { mouse, window, instant, position: Vec2::new(x as _, y as _), root_position });
+            push_event(hwnd, Event::MouseMotion { mouse, window, instant, position, root_position });
             push_event(hwnd, if is_down {
-                Event::MouseButtonPressed { mouse, window, instant, button, clicks, }
+                let outside = is_outside_capture(hwnd, x, y);
+                Event::MouseButtonPressed { mouse, window, instant, button, clicks, position, root_position, outside, }
             } else {
-                Event::MouseButtonReleased { mouse, window, instant, button }
+                Event::MouseButtonReleased { mouse, window, instant, button, position, root_position, }
             });
             match msg {
                 w32::WM_XBUTTONDBLCLK | w32::WM_XBUTTONDOWN | w32::WM_XBUTTONUP => 1,
@@ -235,6 +306,19 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LP
             }
         },
         w32::WM_SETFOCUS | w32::WM_KILLFOCUS => {
+            if msg == w32::WM_SETFOCUS {
+                // Stops bounded flashing started by `Window::flash()`; FLASHW_TIMERNOFG stops by itself.
+                let mut flashwinfo = w32::FLASHWINFO {
+                    cbSize: ::std::mem::size_of::<w32::FLASHWINFO>() as _,
+                    hwnd,
+                    dwFlags: w32::FLASHW_STOP,
+                    uCount: 0,
+                    dwTimeout: 0,
+                };
+                unsafe {
+                    w32::FlashWindowEx(&mut flashwinfo);
+                }
+            }
             let keyboard = DeviceID(OsDeviceID::MainKeyboard);
             let window = WindowHandle(hwnd);
             push_event(hwnd, match msg {
@@ -277,6 +361,27 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LP
             }
             0
         },
+        w32::WM_SETTINGCHANGE => {
+            // This is sent to every top-level window, but only the first one sees a change.
+            if let Some(context) = unsafe { CONTEXT.as_ref() }.and_then(Weak::upgrade) {
+                if let Some(ev) = context.refresh_accessibility_scale_factor() {
+                    context.push_event(ev);
+                }
+            }
+            default_window_proc()
+        },
+        w32::WM_POWERBROADCAST => {
+            if let Some(context) = unsafe { CONTEXT.as_ref() }.and_then(Weak::upgrade) {
+                match wparam {
+                    w32::PBT_APMSUSPEND => context.is_suspended.set(true),
+                    w32::PBT_APMRESUMEAUTOMATIC => if context.is_suspended.replace(false) {
+                        context.push_event(Event::SystemResumed);
+                    },
+                    _ => (),
+                }
+            }
+            w32::TRUE as _
+        },
         w32::WM_SETCURSOR => {
             if w32::LOWORD(lparam as _) as isize == w32::HTCLIENT {
                 let window = retrieve_window(hwnd).unwrap();
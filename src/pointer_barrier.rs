@@ -0,0 +1,44 @@
+//! Pointer barriers, which stop the mouse cursor when it reaches a line.
+//!
+//! They are useful for implementing "sticky" screen edges in multi-monitor setups,
+//! or keeping the cursor within bounds in kiosk applications.
+//!
+//! For now, only X11 supports them (via XFixes 5.0 or later).
+
+use context::Context;
+use error::{self, Result};
+use os::OsPointerBarrierID;
+use Vec2;
+
+/// A lightweight ID for a pointer barrier created via `Context::create_pointer_barrier()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PointerBarrierID(pub(crate) OsPointerBarrierID);
+
+/// The directions in which the mouse cursor is allowed to cross a pointer barrier.
+///
+/// The default value allows no direction at all, i.e the barrier blocks the cursor both ways.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct PointerBarrierDirections {
+    pub positive_x: bool,
+    pub positive_y: bool,
+    pub negative_x: bool,
+    pub negative_y: bool,
+}
+
+impl Context {
+    /// Creates a pointer barrier along the line from `from` to `to`, in desktop coordinates.
+    ///
+    /// The line must be either horizontal or vertical.  
+    /// Each time the cursor hits the barrier, an `Event::PointerBarrierHit` is reported.
+    pub fn create_pointer_barrier(&self, from: Vec2<i32>, to: Vec2<i32>, directions: PointerBarrierDirections) -> Result<PointerBarrierID> {
+        if from.x != to.x && from.y != to.y {
+            return error::invalid_arg("Pointer barriers must be either horizontal or vertical");
+        }
+        self.os.create_pointer_barrier(from, to, directions).map(PointerBarrierID)
+    }
+    /// Destroys a pointer barrier that was created via `create_pointer_barrier()`.
+    pub fn destroy_pointer_barrier(&self, barrier: PointerBarrierID) -> Result<()> {
+        self.os.destroy_pointer_barrier(barrier.0)
+    }
+}
@@ -0,0 +1,27 @@
+//! The name of the process, as shown by process lists and activity monitors.
+
+use context::Context;
+use error::{Result, invalid_arg};
+
+impl Context {
+    /// Sets the name under which this process appears in `top`, `ps`, the Task Manager, etc.
+    ///
+    /// This is distinct from window titles. It should be called from the main thread,
+    /// since some platforms actually name the calling thread.
+    ///
+    /// Empty names and names that contain NUL characters are an `InvalidArgument` error.
+    ///
+    /// On Linux, this calls `prctl(PR_SET_NAME)`, which truncates the name to 15 bytes.  
+    /// On Windows, this calls `SetThreadDescription()` on the current thread (debuggers and
+    /// the Task Manager show it); This returns `Unsupported` before Windows 10 version 1607.  
+    /// On macOS, this would call `pthread_setname_np()` on the main thread.
+    pub fn set_process_name(&self, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return invalid_arg("The process name must not be empty");
+        }
+        if name.contains('\0') {
+            return invalid_arg("The process name must not contain NUL characters");
+        }
+        self.os.set_process_name(name)
+    }
+}
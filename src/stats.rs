@@ -0,0 +1,156 @@
+//! Counters of what a `Context` has seen so far, for diagnostics (e.g to be included in bug reports).
+
+use std::fmt::{self, Display, Formatter};
+use context::Context;
+use event::Event;
+
+/// Statistics about a `Context`, as returned by `Context::stats()`.
+///
+/// Unless stated otherwise, fields are counters that only increase until `Context::reset_stats()`
+/// is called. Counters that make no sense for the current platform stay at zero.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct ContextStats {
+    pub window_events: u64,
+    pub keyboard_events: u64,
+    pub mouse_events: u64,
+    pub touch_events: u64,
+    pub tablet_events: u64,
+    pub controller_events: u64,
+    /// Device and audio device (dis)connections.
+    pub device_events: u64,
+    /// Any event that doesn't fall in the categories above.
+    pub other_events: u64,
+    /// The number of input devices currently tracked. This is not a counter.
+    pub devices_tracked: u64,
+    /// The largest number of events that were waiting in an internal queue at once.
+    pub queue_high_water_mark: u64,
+    /// (X11) The number of X errors that were caught and turned into an `Error`.
+    pub x_errors_trapped: u64,
+    /// (Linux) The number of udev actions (devices being added, removed, etc).
+    pub udev_actions_seen: u64,
+}
+
+impl ContextStats {
+    pub(crate) fn count_event(&mut self, ev: &Event) {
+        let counter = match *ev {
+            Event::WindowShown { .. }
+            | Event::WindowHidden { .. }
+            | Event::WindowNeedsRedraw { .. }
+            | Event::WindowMoved { .. }
+            | Event::WindowResized { .. }
+            | Event::WindowMinimized { .. }
+            | Event::WindowMaximized { .. }
+            | Event::WindowUnminized { .. }
+            | Event::WindowCloseRequested { .. }
+            | Event::WindowScaleFactorChanged { .. }
+            | Event::WindowRefreshRateChanged { .. }
+            => &mut self.window_events,
+
+            Event::KeyboardFocusGained { .. }
+            | Event::KeyboardFocusLost { .. }
+            | Event::KeyboardTextChar { .. }
+            | Event::KeyboardTextString { .. }
+            | Event::KeyboardTextComposition { .. }
+            | Event::KeyboardKeyPressed { .. }
+            | Event::KeyboardKeyReleased { .. }
+            | Event::KeyboardKeyPressedRaw { .. }
+            | Event::KeyboardKeyReleasedRaw { .. }
+            => &mut self.keyboard_events,
+
+            Event::MouseEnter { .. }
+            | Event::MouseLeave { .. }
+            | Event::MouseButtonPressed { .. }
+            | Event::MouseButtonReleased { .. }
+            | Event::MouseScroll { .. }
+            | Event::MouseMotion { .. }
+            | Event::MouseButtonPressedRaw { .. }
+            | Event::MouseButtonReleasedRaw { .. }
+            | Event::MouseScrollRaw { .. }
+            | Event::MouseMotionRaw { .. }
+            | Event::PointerBarrierHit { .. }
+            => &mut self.mouse_events,
+
+            Event::TouchFingerPressed { .. }
+            | Event::TouchFingerReleased { .. }
+            | Event::TouchFingerMotion { .. }
+            | Event::TouchMultiGesture { .. }
+            => &mut self.touch_events,
+
+            Event::TabletToolProximity { .. }
+            | Event::TabletPadButtonPressed { .. }
+            | Event::TabletPadButtonReleased { .. }
+            | Event::TabletStylusButtonPressed { .. }
+            | Event::TabletStylusButtonReleased { .. }
+            | Event::TabletStylusToolType { .. }
+            | Event::TabletStylusMotion { .. }
+            | Event::TabletStylusPressure { .. }
+            | Event::TabletStylusTilt { .. }
+            | Event::TabletPadButtonPressedRaw { .. }
+            | Event::TabletPadButtonReleasedRaw { .. }
+            | Event::TabletStylusButtonPressedRaw { .. }
+            | Event::TabletStylusButtonReleasedRaw { .. }
+            | Event::TabletStylusToolTypeRaw { .. }
+            | Event::TabletStylusMotionRaw { .. }
+            | Event::TabletStylusPressureRaw { .. }
+            | Event::TabletStylusTiltRaw { .. }
+            => &mut self.tablet_events,
+
+            Event::ControllerButtonPressed { .. }
+            | Event::ControllerButtonReleased { .. }
+            | Event::ControllerAxisMotion { .. }
+            | Event::ControllerPlayerIndexChanged { .. }
+            | Event::ControllerEventsDropped { .. }
+            => &mut self.controller_events,
+
+            Event::DeviceConnected { .. }
+            | Event::DeviceInfoChanged { .. }
+            | Event::DeviceRemapped { .. }
+            | Event::DeviceDisconnected { .. }
+            | Event::AudioOutputDeviceAdded { .. }
+            | Event::AudioOutputDeviceRemoved { .. }
+            | Event::AudioCaptureDeviceAdded { .. }
+            | Event::AudioCaptureDeviceRemoved { .. }
+            => &mut self.device_events,
+
+            _ => &mut self.other_events,
+        };
+        *counter += 1;
+    }
+}
+
+impl Display for ContextStats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let &Self {
+            window_events, keyboard_events, mouse_events, touch_events, tablet_events,
+            controller_events, device_events, other_events,
+            devices_tracked, queue_high_water_mark, x_errors_trapped, udev_actions_seen,
+        } = self;
+        write!(f, "events: window={} keyboard={} mouse={} touch={} tablet={} controller={} device={} other={}; ",
+            window_events, keyboard_events, mouse_events, touch_events, tablet_events,
+            controller_events, device_events, other_events
+        )?;
+        write!(f, "devices_tracked={} queue_high_water_mark={} x_errors_trapped={} udev_actions_seen={}",
+            devices_tracked, queue_high_water_mark, x_errors_trapped, udev_actions_seen
+        )
+    }
+}
+
+impl Context {
+    /// Gets statistics about what this context has seen so far.
+    ///
+    /// Events are counted as they are returned to you (e.g by `poll_event()`).
+    pub fn stats(&self) -> ContextStats {
+        let mut stats = *self.stats.borrow();
+        self.os.fill_stats(&mut stats);
+        stats
+    }
+    /// Resets all counters returned by `stats()` to zero.
+    pub fn reset_stats(&self) {
+        *self.stats.borrow_mut() = ContextStats::default();
+        self.os.reset_stats();
+    }
+    pub(crate) fn count_event(&self, ev: &Event) {
+        self.stats.borrow_mut().count_event(ev);
+    }
+}
@@ -0,0 +1,48 @@
+//! Synthesizing user input at the OS level, mostly for UI test automation.
+//!
+//! Unlike pushing `Event`s into a queue, synthesized input goes through the whole platform
+//! input path, so your application observes it exactly like regular user input.  
+//! The exception is `Window::push_committed_text_event()`, which does push an event.
+//!
+//! **These functions affect the whole user session**, not only your application's windows:
+//! the mouse cursor really moves and keys really get pressed, for every application.
+//! Don't call them in a user's face!
+//!
+//! This module is only available with the `synthetic-input` Cargo feature.  
+//! On X11, this requires the XTest extension. On Windows, this uses `SendInput()`.
+
+use context::Context;
+use window::Window;
+use device::{Keysym, MouseButton, ButtonState};
+use error::Result;
+use Vec2;
+
+impl Context {
+    /// Synthesizes a key press or release.
+    ///
+    /// The input appears to come from a virtual keyboard, which might not be
+    /// the main keyboard.
+    pub fn synthesize_key(&self, keysym: Keysym, state: ButtonState) -> Result<()> {
+        self.os.synthesize_key(keysym, state)
+    }
+    /// Synthesizes a mouse motion to the given position, in desktop coordinates.
+    pub fn synthesize_mouse_move_to(&self, position: Vec2<i32>) -> Result<()> {
+        self.os.synthesize_mouse_move_to(position)
+    }
+    /// Synthesizes a mouse button press or release, at the current mouse position.
+    pub fn synthesize_mouse_button(&self, button: MouseButton, state: ButtonState) -> Result<()> {
+        self.os.synthesize_mouse_button(button, state)
+    }
+}
+
+impl Window {
+    /// Queues the `Event::KeyboardTextString` that would be reported if the user had just
+    /// committed `s` via an input method, for testing text input handling.
+    ///
+    /// Unlike the `synthesize_*()` functions, this does not go through the platform's input
+    /// path (there's no portable way to drive an input method), so it's only seen by this `Context`.  
+    /// Text input must have been enabled for this window via `enable_text_input()`.
+    pub fn push_committed_text_event(&self, s: &str) -> Result<()> {
+        self.0.push_committed_text_event(s)
+    }
+}
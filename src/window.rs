@@ -1,15 +1,110 @@
 //! Window management.
 
+use std::os::raw::c_void;
+use std::thread;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::fmt;
+use std::mem;
+use std::time::{Duration, Instant};
 use context::Context;
+use timeout::Timeout;
 use vek::{Vec2, Extent2, Rect, Rgba};
-use os::{OsWindow, OsWindowHandle, OsWindowFromHandleParams};
+use os::{OsWindow, OsWindowHandle, OsWindowFromHandleParams, OsInputGrab};
 use gl::{GLPixelFormatChooser};
 use error::{self, Result};
+use event::EventInstant;
+use device::MouseButton;
 
 impl Context {
     /// Attempts to create a new `Window` that satisfies given settings.
     pub fn create_window(&self, settings: &WindowSettings) -> Result<Window> {
-        self.0.create_window(settings).map(Window)
+        self.os.create_window(settings).map(Window)
+    }
+    /// Gets the number of screens.
+    ///
+    /// On X11, a display connection may have several "screens", each with their own root window
+    /// (this is not the same thing as monitors, which usually are all part of a single screen).
+    /// On other platforms, there is only one screen.
+    pub fn screen_count(&self) -> Result<usize> {
+        self.os.screen_count()
+    }
+    /// Like `create_window()`, but creates the window on the given screen, which must be less than
+    /// `screen_count()`.
+    ///
+    /// `create_window()` uses the default screen.
+    pub fn create_window_on_screen(&self, screen: usize, settings: &WindowSettings) -> Result<Window> {
+        self.os.create_window_on_screen(screen, settings).map(Window)
+    }
+    /// Creates a window, then restores the geometry that `Window::geometry()` returned,
+    /// e.g in a previous run of the application.
+    ///
+    /// `settings.position` and `settings.centered` are ignored.  
+    /// If the saved monitor no longer exists (e.g it was unplugged), the window is placed
+    /// relative to the primary monitor instead (or the first one, if there's no primary monitor).
+    /// Either way, the window's top-left corner is kept within the monitor, so that it can't
+    /// end up out of reach.
+    ///
+    /// The window is shown, because window managers only honor maximized and full-screen
+    /// states for visible windows.
+    pub fn create_window_with_geometry(&self, settings: &WindowSettings, geometry: &WindowGeometry) -> Result<Window> {
+        let monitors = self.monitors().unwrap_or_default();
+        let monitor = match geometry.monitor {
+            None => None,
+            Some(i) => match monitors.get(i) {
+                Some(&m) => Some(m),
+                None => {
+                    info!("Saved monitor #{} no longer exists; Falling back to the primary monitor", i);
+                    self.primary_monitor().ok().and_then(|m| m).or_else(|| monitors.first().cloned())
+                },
+            },
+        };
+        let position = match monitor.map(|m| self.monitor_rect(m)) {
+            None => geometry.position,
+            Some(Err(e)) => {
+                warn!("Could not get the monitor's position and size: {}", e);
+                geometry.position
+            },
+            Some(Ok(m)) => {
+                let max = m.position() + Vec2::new(m.w as i32 - 1, m.h as i32 - 1);
+                Vec2::partial_max(m.position(), Vec2::partial_min(m.position() + geometry.position, max))
+            },
+        };
+        let window = self.create_window(&WindowSettings {
+            opengl: settings.opengl,
+            high_dpi: settings.high_dpi,
+            parent: settings.parent,
+            position: Some(position),
+            centered: false,
+            override_redirect: settings.override_redirect,
+            wm_class: settings.wm_class.clone(),
+        })?;
+        window.set_geometry(position, geometry.size)?;
+        window.show()?;
+        if geometry.fullscreen {
+            window.enter_fullscreen()?;
+        } else if geometry.maximized {
+            window.maximize()?;
+        }
+        Ok(window)
+    }
+    /// Gets who currently holds a pointer grab, if anyone, e.g to find out why clicks
+    /// don't reach a window (a popup menu of some application may be active).
+    ///
+    /// On X11, grabs by other clients can only be detected by attempting to grab the pointer
+    /// on the root window and releasing it right away; this may cause windows to receive
+    /// crossing events in `NotifyGrab` mode (reported as `MouseEnter { is_grabbed: true, .. }`).  
+    /// On Windows, only the capture held by windows of the calling thread is known.
+    pub fn pointer_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        self.os.pointer_grab_holder()
+    }
+    /// Gets who currently holds a keyboard grab, if anyone.
+    ///
+    /// On X11, the same caveats as `pointer_grab_holder()` apply, except that the probe may
+    /// cause the focused window to briefly lose and regain focus.  
+    /// On Windows, there is no such thing as a keyboard grab, so this always returns `None`.
+    pub fn keyboard_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        self.os.keyboard_grab_holder()
     }
     /// Attempts to create a new `Window` from the given handle.
     ///
@@ -32,7 +127,23 @@ impl Context {
     /// by someone else (including yourself or 3rd parties), in which case you should
     /// call `mem::forget()` on the last window in order to keep the system resources alive.
     pub unsafe fn window_from_handle(&self, handle: WindowHandle, params: Option<&OsWindowFromHandleParams>) -> Result<Window> {
-        self.0.window_from_handle(handle.0, params).map(Window)
+        self.os.window_from_handle(handle.0, params).map(Window)
+    }
+    /// Gets the number of live windows known to this `Context`, i.e those that were created
+    /// via `create_window()` or `window_from_handle()`.
+    pub fn window_count(&self) -> usize {
+        self.os.window_count()
+    }
+    /// Gets the handles of all live windows known to this `Context`, in no particular order.
+    pub fn windows(&self) -> Vec<WindowHandle> {
+        self.os.windows().into_iter().map(WindowHandle).collect()
+    }
+    /// Gets the live `Window` which handle is given, if it is known to this `Context`.
+    ///
+    /// This is the safe counterpart to `window_from_handle()`, and is handy for retrieving the
+    /// window an `Event` refers to.
+    pub fn window_by_handle(&self, handle: WindowHandle) -> Option<Window> {
+        self.os.window_by_handle(handle.0).map(Window)
     }
 }
 
@@ -61,6 +172,33 @@ pub struct Window(pub(crate) OsWindow);
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct WindowHandle(pub(crate) OsWindowHandle);
 
+/// A platform-specific window handle, along with whatever else is needed to make use of it,
+/// for interop with other crates (e.g for creating a Vulkan surface).
+///
+/// Get one via `NativeWindowHandle::from(&window)`.  
+/// The handles remain owned by the `Window`, and become dangling once it is dropped.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NativeWindowHandle {
+    /// An X11 window, and the Xlib `Display*` it belongs to.
+    X11 {
+        #[allow(missing_docs)]
+        window: u64,
+        #[allow(missing_docs)]
+        display: *mut c_void,
+    },
+    /// A Win32 `HWND`, and the `HINSTANCE` its class was registered with.
+    Win32 {
+        #[allow(missing_docs)]
+        hwnd: *mut c_void,
+        #[allow(missing_docs)]
+        hinstance: *mut c_void,
+    },
+    /// The `NSView*` of a macOS window.
+    MacosNsView(*mut c_void),
+    /// The canvas of an Emscripten "window" (there is only one).
+    Emscripten,
+}
+
 impl From<OsWindowHandle> for WindowHandle {
     fn from(h: OsWindowHandle) -> Self {
         WindowHandle(h)
@@ -136,12 +274,37 @@ impl<'a> Default for WindowTypeHint<'a> {
 
 
 /// Actually a simple thickness-color pair.
+///
+/// Borders drawn by the window manager can't be recolored or resized, so `thickness` and `color`
+/// are currently ignored unless `client_side` is `true`.
 #[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct Borders {
-    /// Thickness, in pixels. If `None`, use the window manager's default.
+    /// Thickness, in pixels. If `None`, use the window manager's default
+    /// (or 1 pixel for client-side borders).
     pub thickness: Option<u16>,
     /// If `None`, use the window manager's default.
     pub color: Option<Rgba<u8>>,
+    /// If `true`, the window manager doesn't draw borders, and you are expected to draw them
+    /// yourself inside the window's canvas, at the locations given by `rects()`.
+    pub client_side: bool,
+}
+
+impl Borders {
+    /// Gets the top, bottom, left and right border rectangles (in that order) for a canvas
+    /// of the given size (see `Window::canvas_size()`), for drawing client-side borders.
+    ///
+    /// The left and right rectangles don't overlap the top and bottom ones.
+    pub fn rects(&self, canvas_size: Extent2<u32>) -> [Rect<i32, u32>; 4] {
+        let Extent2 { w, h } = canvas_size;
+        let t = (self.thickness.unwrap_or(1) as u32).min(w / 2).min(h / 2);
+        let side_h = h - 2 * t;
+        [
+            Rect { x: 0, y: 0, w, h: t },
+            Rect { x: 0, y: (h - t) as _, w, h: t },
+            Rect { x: 0, y: t as _, w: t, h: side_h },
+            Rect { x: (w - t) as _, y: t as _, w: t, h: side_h },
+        ]
+    }
 }
 
 #[allow(missing_docs)]
@@ -172,6 +335,7 @@ pub struct WindowStyleHint {
 }
 
 /// The absolute minimum information a window needs at creation time.
+#[derive(Default)]
 pub struct WindowSettings<'a> {
     /// Support OpenGL ? (defaults to `None`).
     /// The settings need to be known beforehand so that the window
@@ -184,8 +348,385 @@ pub struct WindowSettings<'a> {
     /// However this defaults to `false` because it might break some
     /// assumptions.
     pub high_dpi: bool,
+    /// A foreign window to embed the new window into (e.g a plugin host's window), if any.
+    ///
+    /// The new window then becomes a child of `parent`, and isn't managed by the window manager.
+    /// It is positioned at `(0, 0)` in `parent`'s coordinates.
+    pub parent: Option<WindowHandle>,
+    /// The initial position of the window, in desktop coordinates (defaults to `None`).
+    ///
+    /// If `None`, the window manager decides where to place the window.  
+    /// Window managers are allowed to ignore this, but most don't.
+    /// This is ignored if `parent` is `Some`.
+    pub position: Option<Vec2<i32>>,
+    /// Should the window initially be centered within the current desktop's work area ? (defaults to `false`).
+    ///
+    /// This is ignored if `position` or `parent` is `Some`.
+    pub centered: bool,
+    /// Should the window bypass the window manager entirely ? (defaults to `false`).
+    ///
+    /// This is meant for splash screens: such a window has no decorations, appears instantly
+    /// and stays exactly where it is put, so you probably want to set `position` or `centered`
+    /// as well.  
+    /// Since no window manager is involved, the window receives no WM-mediated events:
+    /// there is no `WindowCloseRequested`, and it is never given keyboard focus by itself.
+    /// It still receives mouse events.
+    ///
+    /// On X11, this sets the override-redirect attribute. On Windows, this creates a
+    /// `WS_POPUP` window that isn't activated when shown or clicked.  
+    /// This is ignored if `parent` is `Some`.
+    pub override_redirect: bool,
+    /// (X11-only) The `WM_CLASS` of the window, as `(instance, class)` (defaults to `None`).
+    ///
+    /// Window managers, taskbars and `.desktop` files use it to tell applications apart. If `None`,
+    /// both strings are the application name given via `ContextOptions`, or the executable's name.  
+    /// This is ignored on other platforms.
+    pub wm_class: Option<(String, String)>,
+}
+
+/// A 1-bit image, for use with `Window::set_shape_mask()` and `Window::set_input_shape_mask()`.
+///
+/// `pixels` are laid out row by row, starting from the top-left corner, and must contain
+/// exactly `width * height` elements. `true` means "inside the shape".
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ImageMask {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<bool>,
+}
+
+impl ImageMask {
+    fn validate(&self) -> Result<()> {
+        if self.width == 0 || self.height == 0 {
+            return error::invalid_arg("The mask's width and height must be non-zero");
+        }
+        if self.pixels.len() != self.width as usize * self.height as usize {
+            return error::invalid_arg(format!("The mask has {} pixels, but it should have {}x{} = {}", self.pixels.len(), self.width, self.height, self.width as usize * self.height as usize));
+        }
+        Ok(())
+    }
+}
+
+/// What a point of a window is, as far as the mouse is concerned. See `Window::set_cursor_hit_test()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum HitTestResult {
+    /// The window's contents; Mouse input is reported as usual.
+    Client,
+    /// Acts like a title bar: pressing the left mouse button there starts moving the window.
+    Caption,
+    /// Mouse input goes to whatever is below the window.
+    Transparent,
+}
+
+/// An area of a window, and what it is as far as the mouse is concerned. See `Window::set_cursor_hit_test()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct HitTestArea {
+    /// The area, relative to the window's top-left corner.
+    pub rect: Rect<i32, u32>,
+    /// What the area is.
+    pub result: HitTestResult,
+}
+
+// The areas for the window size that a `HitTestFn` was last called with.
+type CachedHitTestAreas = Option<(Extent2<u32>, Rc<Vec<HitTestArea>>)>;
+
+/// The hit test function that backends store, see `Window::set_cursor_hit_test()`.
+#[derive(Clone)]
+pub(crate) struct HitTestFn {
+    f: Rc<dyn Fn(Extent2<u32>) -> Vec<HitTestArea>>,
+    areas: Rc<RefCell<CachedHitTestAreas>>,
+}
+
+impl HitTestFn {
+    pub fn new<F>(f: F) -> Self where F: Fn(Extent2<u32>) -> Vec<HitTestArea> + 'static {
+        Self { f: Rc::new(f), areas: Rc::new(RefCell::new(None)) }
+    }
+    /// Gets the areas for a window of the given size. `f` is only called when the size changes.
+    pub fn areas(&self, size: Extent2<u32>) -> Rc<Vec<HitTestArea>> {
+        if let Some((cached_size, ref areas)) = *self.areas.borrow() {
+            if cached_size == size {
+                return Rc::clone(areas);
+            }
+        }
+        let areas = Rc::new((self.f)(size));
+        *self.areas.borrow_mut() = Some((size, Rc::clone(&areas)));
+        areas
+    }
+    pub fn hit_test(&self, size: Extent2<u32>, position: Vec2<i32>) -> HitTestResult {
+        let Vec2 { x, y } = position;
+        self.areas(size).iter().rev()
+            .find(|a| x >= a.rect.x && y >= a.rect.y && x < a.rect.x + a.rect.w as i32 && y < a.rect.y + a.rect.h as i32)
+            .map(|a| a.result)
+            .unwrap_or(HitTestResult::Client)
+    }
+}
+
+impl fmt::Debug for HitTestFn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("HitTestFn")
+    }
+}
+
+/// An RGBA image, for use as a window icon (see `Window::set_icon_image()`) or as a cursor
+/// (see `RgbaCursorData::from_image()`).
+///
+/// `pixels` are laid out row by row, starting from the top-left corner, and must contain
+/// exactly `size.w * size.h` elements.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub size: Extent2<u32>,
+    pub pixels: Vec<Rgba<u8>>,
+}
+
+impl RgbaImage {
+    /// Creates an image from tightly packed 8-bit R, G, B, A bytes, laid out like `pixels`,
+    /// such as what `image::RgbaImage::into_raw()` returns.
+    ///
+    /// `bytes.len()` must be exactly `size.w * size.h * 4`.  
+    /// The buffer is reused as-is, without copying, unless its capacity is not a multiple of 4.
+    ///
+    /// Window icons expect straight (i.e not premultiplied) alpha.
+    pub fn from_raw(size: Extent2<u32>, bytes: Vec<u8>) -> Result<Self> {
+        validate_raw_rgba_len(size, bytes.len())?;
+        let image = Self { size, pixels: rgba_vec_from_raw(bytes) };
+        image.validate()?;
+        Ok(image)
+    }
+    /// Borrowed version of `from_raw()`, for calls that only need to read the pixels,
+    /// such as `Window::set_icon()`.
+    ///
+    /// `bytes` may start at any address, since `Rgba<u8>` has no alignment requirement.
+    pub fn pixels_from_raw_slice(size: Extent2<u32>, bytes: &[u8]) -> Result<&[Rgba<u8>]> {
+        validate_raw_rgba_len(size, bytes.len())?;
+        if size.w == 0 || size.h == 0 {
+            return error::invalid_arg("The image's width and height must be non-zero");
+        }
+        Ok(rgba_slice_from_raw(bytes))
+    }
+    pub(crate) fn validate(&self) -> Result<()> {
+        let Extent2 { w, h } = self.size;
+        if w == 0 || h == 0 {
+            return error::invalid_arg("The image's width and height must be non-zero");
+        }
+        if self.pixels.len() != w as usize * h as usize {
+            return error::invalid_arg(format!("The image has {} pixels, but it should have {}x{} = {}", self.pixels.len(), w, h, w as usize * h as usize));
+        }
+        Ok(())
+    }
+}
+
+fn validate_raw_rgba_len(size: Extent2<u32>, len: usize) -> Result<()> {
+    let expected = (size.w as usize).checked_mul(size.h as usize).and_then(|n| n.checked_mul(4));
+    match expected {
+        Some(expected) if expected == len => Ok(()),
+        Some(expected) => error::invalid_arg(format!("Expected {}x{}x4 = {} bytes, but got {}", size.w, size.h, expected, len)),
+        None => error::invalid_arg(format!("A {}x{} image is too large", size.w, size.h)),
+    }
+}
+
+// `Rgba<u8>` is `#[repr(C)]` with four `u8` fields, so any 4 bytes are a valid pixel at any address.
+fn assert_rgba_u8_layout() {
+    assert_eq!(mem::size_of::<Rgba<u8>>(), 4);
+    assert_eq!(mem::align_of::<Rgba<u8>>(), 1);
+}
+
+/// Reinterprets the buffer in place if its capacity allows it, otherwise copies it.
+fn rgba_vec_from_raw(bytes: Vec<u8>) -> Vec<Rgba<u8>> {
+    assert_rgba_u8_layout();
+    debug_assert_eq!(bytes.len() % 4, 0);
+    if bytes.capacity() % 4 != 0 {
+        return rgba_slice_from_raw(&bytes).to_vec();
+    }
+    let mut bytes = mem::ManuallyDrop::new(bytes);
+    let (ptr, len, cap) = (bytes.as_mut_ptr(), bytes.len(), bytes.capacity());
+    unsafe {
+        Vec::from_raw_parts(ptr as *mut Rgba<u8>, len / 4, cap / 4)
+    }
+}
+
+fn rgba_slice_from_raw(bytes: &[u8]) -> &[Rgba<u8>] {
+    assert_rgba_u8_layout();
+    debug_assert_eq!(bytes.len() % 4, 0);
+    unsafe {
+        ::std::slice::from_raw_parts(bytes.as_ptr() as *const Rgba<u8>, bytes.len() / 4)
+    }
+}
+
+/// A window's position, size and state, as returned by `Window::geometry()`, for restoring it
+/// in a later run with `Context::create_window_with_geometry()`.
+///
+/// All fields are plain data, so that applications can save them in whatever format they like.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct WindowGeometry {
+    /// The index, within `Context::monitors()`, of the monitor which contains the window's center,
+    /// if any.
+    pub monitor: Option<usize>,
+    /// The window's top-left corner, relative to the top-left corner of `monitor`, or in desktop
+    /// coordinates if `monitor` is `None`.
+    pub position: Vec2<i32>,
+    /// The window's size, in desktop pixel coordinates.
+    pub size: Extent2<u32>,
+    /// Was the window maximized (both horizontally and vertically)?
+    pub maximized: bool,
+    /// Was the window full-screen?
+    pub fullscreen: bool,
+}
+
+/// Options for `Window::grab_input()`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct GrabOptions {
+    /// If `true`, mouse events that happen over any of our own windows are reported to that
+    /// window as usual; Otherwise, all of them are reported to the grabbing window.
+    ///
+    /// Ignored on Windows, where all mouse events go to the grabbing window.
+    pub owner_events: bool,
+    /// Also grab the keyboard, so that key events are reported to the grabbing window only.
+    pub keyboard: bool,
+}
+
+impl Default for GrabOptions {
+    fn default() -> Self {
+        Self {
+            owner_events: true,
+            keyboard: true,
+        }
+    }
+}
+
+/// A subset of a window's state, as managed by the window manager.
+///
+/// See `Window::state()` and `Window::wait_for_state()`.
+///
+/// More flags may be added in the future, so this can't be built with a struct literal;
+/// Start from `Default::default()` or from `Window::state()` instead.
+#[non_exhaustive]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct WindowStateFlags {
+    /// Is the window full-screen?
+    pub fullscreen: bool,
+    /// Is the window's width maximized?
+    pub maximized_width: bool,
+    /// Is the window's height maximized?
+    pub maximized_height: bool,
+    /// Is the window minimized?
+    pub minimized: bool,
+    /// Does the window have the keyboard focus?
+    pub focused: bool,
+    /// Is the window hidden (see `Window::hide()`), or not shown yet?
+    ///
+    /// Minimized windows are not hidden.
+    pub hidden: bool,
+    /// Is the window kept above others (e.g "always on top")?
+    pub above: bool,
+    /// Is the window kept below others?
+    pub below: bool,
+    /// Is the window demanding attention (see `Window::demand_attention()`)?
+    pub demands_attention: bool,
+}
+
+impl WindowStateFlags {
+    /// Flags that the window manager changes in response to `enter_fullscreen()`, `maximize()`,
+    /// `minimize()` and the like, as opposed to e.g `focused` which may change at any time.
+    pub fn wm_state_mask() -> Self {
+        Self {
+            fullscreen: true, maximized_width: true, maximized_height: true, minimized: true,
+            hidden: true, above: true, below: true,
+            .. Self::default()
+        }
+    }
+    /// Returns these flags, with those that are not set in `mask` cleared.
+    pub fn masked(&self, mask: Self) -> Self {
+        let &Self {
+            fullscreen, maximized_width, maximized_height, minimized, focused, hidden, above, below, demands_attention,
+        } = self;
+        Self {
+            fullscreen: fullscreen && mask.fullscreen,
+            maximized_width: maximized_width && mask.maximized_width,
+            maximized_height: maximized_height && mask.maximized_height,
+            minimized: minimized && mask.minimized,
+            focused: focused && mask.focused,
+            hidden: hidden && mask.hidden,
+            above: above && mask.above,
+            below: below && mask.below,
+            demands_attention: demands_attention && mask.demands_attention,
+        }
+    }
 }
 
+/// Who holds an input grab, as returned by `Context::pointer_grab_holder()` and `Context::keyboard_grab_holder()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GrabHolder {
+    /// One of our own windows (e.g via `Window::grab_input()`).
+    Window(WindowHandle),
+    /// Another application; the platform doesn't tell which of its windows holds the grab.
+    OtherClient,
+}
+
+/// An active input grab, as returned by `Window::grab_input()`. The grab is released when this is dropped.
+#[derive(Debug)]
+pub struct InputGrab(pub(crate) OsInputGrab);
+
+/// An edge or corner of a window, for use with `Window::begin_resize_drag()`.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ResizeEdge {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+}
+
+/// A window operation, for use with `Window::capability()`.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum WindowOp {
+    SetTitle,
+    SetIcon,
+    SetTypeHint,
+    SetStyleHint,
+    SetTitleBarButtons,
+    Raise,
+    SetMinMaxSize,
+    SetResizable,
+    SetMovable,
+    Maximize,
+    MaximizeWidthOrHeight,
+    Minimize,
+    Fullscreen,
+    DemandAttention,
+    SetPositionAndSize,
+    BeginMoveResizeDrag,
+    SetOpacity,
+    SetDesktop,
+    SetVisibleOnAllDesktops,
+    SetMousePosition,
+    TrapMouse,
+    Clear,
+    TextInput,
+    SetShapeMask,
+    SetInputShapeMask,
+}
+
+/// Whether or not a `WindowOp` is expected to succeed on a given `Window`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Capability {
+    /// The operation is supported, and would fail only for unexpected reasons.
+    Supported,
+    /// The operation is known to fail with either `ErrorKind::Unsupported` or
+    /// `ErrorKind::Unimplemented`.
+    Unsupported,
+    /// There's no way to tell without actually performing the operation.
+    ///
+    /// For instance, on X11, some window managers don't advertise the
+    /// features they support.
+    Unknown,
+}
 
 impl Window {
     /// Gets this window's handle, which is an opaque wrapper around
@@ -201,6 +742,29 @@ impl Window {
     pub fn title(&self) -> Result<String> {
         self.0.title()
     }
+    /// (X11-only) Sets the window's `WM_CLASS`, as `(instance, class)`.
+    ///
+    /// Prefer `WindowSettings::wm_class`: window managers usually only read it when the window is mapped.  
+    /// On other platforms, this returns `Unsupported`.
+    pub fn set_wm_class(&self, instance: &str, class: &str) -> Result<()> {
+        self.0.set_wm_class(instance, class)
+    }
+    /// (X11-only) Gets the window's `WM_CLASS`, as `(instance, class)`, or `None` if it has none.
+    ///
+    /// On other platforms, this returns `Unsupported`.
+    pub fn wm_class(&self) -> Result<Option<(String, String)>> {
+        self.0.wm_class()
+    }
+    /// Sets the short name shown when the window is minimized (e.g in taskbars, or on icons
+    /// of iconified windows), which defaults to the title.
+    ///
+    /// This is useful when the title is long, e.g `"main.rs (~/src/project) - Editor"` versus `"main.rs"`.
+    /// Once this is called, `set_title()` no longer changes the icon name.  
+    /// On X11, this sets `WM_ICON_NAME` and `_NET_WM_ICON_NAME`; Whether they are shown depends on the desktop environment.  
+    /// On Windows, this returns `Unsupported`, since the taskbar always shows the title.
+    pub fn set_icon_name(&self, icon_name: &str) -> Result<()> {
+        self.0.set_icon_name(icon_name)
+    }
     /// FIXME: Use the `imgref` crate instead!
     /// Sets the window's icon via RGBA data.
     pub fn set_icon(&self, size: Extent2<u32>, data: &[Rgba<u8>]) -> Result<()> {
@@ -212,6 +776,15 @@ impl Window {
     pub fn icon(&self) -> Result<(Extent2<u32>, Vec<Rgba<u8>>)> {
         self.0.icon()
     }
+    /// Same as `set_icon()`, but takes an `RgbaImage`, which is also what cursors can be created from.
+    pub fn set_icon_image(&self, image: &RgbaImage) -> Result<()> {
+        image.validate()?;
+        self.0.set_icon(image.size, &image.pixels)
+    }
+    /// Same as `icon()`, but returns an `RgbaImage`.
+    pub fn icon_image(&self) -> Result<RgbaImage> {
+        self.0.icon().map(|(size, pixels)| RgbaImage { size, pixels })
+    }
     /// Resets the window's icon to the system's default.
     pub fn reset_icon(&self) -> Result<()> {
         self.0.reset_icon()
@@ -232,11 +805,66 @@ impl Window {
     pub fn set_style_hint(&self, style_hint: &WindowStyleHint) -> Result<()> {
         self.0.set_style_hint(style_hint)
     }
+    /// Enables or disables the title bar's minimize, maximize and close buttons, individually.
+    ///
+    /// Disabling `close` only prevents the user from closing the window via the title bar
+    /// or system menu. Closing it programmatically still works, and on X11 the window
+    /// keeps receiving `WM_DELETE_WINDOW` (i.e `WindowCloseRequested`) as usual.
+    ///
+    /// On X11, this sets the relevant `_MOTIF_WM_HINTS` functions and decorations,
+    /// and updates `_NET_WM_ALLOWED_ACTIONS` for window managers that respect
+    /// client-set values. The effect is window-manager-dependant.  
+    /// On Windows, this toggles `WS_MINIMIZEBOX` and `WS_MAXIMIZEBOX`, and disables
+    /// `SC_CLOSE` in the system menu.
+    pub fn set_title_bar_buttons(&self, buttons: TitleBarFeatures) -> Result<()> {
+        self.0.set_title_bar_buttons(buttons)
+    }
+    /// Gets the title bar buttons configuration last requested via `set_title_bar_buttons()`
+    /// or `set_style_hint()`.
+    ///
+    /// This is what was asked for, not necessarily what the window manager honored.
+    pub fn title_bar_buttons(&self) -> TitleBarFeatures {
+        self.0.title_bar_buttons()
+    }
     /// Raises the window on top of the stack.  
     /// This should work even when the window is hidden.
     pub fn raise(&self) -> Result<()> {
         self.0.raise()
     }
+    /// Shows progress (between 0 and 1 inclusive) on this window's taskbar entry, or clears
+    /// it with `None`.
+    ///
+    /// On Windows, this uses `ITaskbarList3`.  
+    /// On Linux and BSDs, this emits the Unity `LauncherEntry` D-Bus signal over the session bus,
+    /// which KDE Plasma and some GNOME docks understand. The progress is then associated to the
+    /// `<app_name>.desktop` file (see `ContextOptions::app_name`; defaults to the executable's name)
+    /// and is shared by all windows of the application. Whether anything listens can't be known.  
+    /// Returns `Unsupported` if libdbus or the session bus is unavailable.
+    pub fn set_taskbar_progress(&self, progress: Option<f64>) -> Result<()> {
+        if let Some(progress) = progress {
+            if !(progress >= 0. && progress <= 1.) {
+                return error::invalid_arg(format!("The progress must be between 0 and 1 (got {})", progress));
+            }
+        }
+        self.0.set_taskbar_progress(progress)
+    }
+    /// Asks for this window to be activated (raised and given the keyboard focus).
+    ///
+    /// Window managers may deny focus requests that don't follow user input, to prevent focus
+    /// stealing. On X11, the request carries the timestamp of the last key or mouse button press
+    /// that any of our windows received, which is what window managers such as GNOME's check.  
+    /// On Windows, this uses `SetForegroundWindow()`, which is subject to similar restrictions.
+    pub fn focus(&self) -> Result<()> {
+        self.0.focus()
+    }
+    /// Like `focus()`, but on behalf of the user input that happened at `instant`
+    /// (e.g a global hotkey that was reported via another channel).
+    ///
+    /// On X11, this fails with `InvalidArgument` if `instant` doesn't come from an X event.
+    /// On Windows, `instant` is ignored.
+    pub fn focus_with_timestamp(&self, instant: EventInstant) -> Result<()> {
+        self.0.focus_with_timestamp(instant)
+    }
     /// Constrains this window to have a size of at least the specified value (inclusive).
     pub fn set_min_size(&self, size: Extent2<u32>) -> Result<()> {
         self.0.set_min_size(size)
@@ -376,6 +1004,56 @@ impl Window {
     pub fn is_fullscreen(&self) -> Result<bool> {
         self.0.is_fullscreen()
     }
+    /// Does the window have the keyboard focus?
+    ///
+    /// This is a shorthand for `state()`'s `focused` flag.
+    pub fn is_focused(&self) -> Result<bool> {
+        self.state().map(|state| state.focused)
+    }
+    /// Is the window demanding attention (see `demand_attention()`)?
+    ///
+    /// This is a shorthand for `state()`'s `demands_attention` flag.
+    pub fn is_demanding_attention(&self) -> Result<bool> {
+        self.state().map(|state| state.demands_attention)
+    }
+    /// Gets the window's state, as last acknowledged by the window manager.
+    ///
+    /// This is queried each time, rather than tracked, so that it doesn't drift when the
+    /// window manager changes things on its own.
+    ///
+    /// On X11, this reads the `_NET_WM_STATE` and `WM_STATE` properties, and the input focus.  
+    /// On Windows, this uses `GetWindowPlacement()` and `GetForegroundWindow()`; `below` and
+    /// `demands_attention` are always `false`.
+    pub fn state(&self) -> Result<WindowStateFlags> {
+        self.0.state()
+    }
+    /// Blocks until the flags of the window's state (see `state()`) that are set in `mask` equal
+    /// those of `expected`, or the timeout expires.
+    ///
+    /// Requests such as `enter_fullscreen()` or `maximize()` only take effect once the window manager
+    /// has processed them; Use this if you need to wait for that (e.g before resizing your viewport).
+    /// Returns `Ok(false)` if the timeout expired.
+    ///
+    /// The usual way to build `expected` is to take the current `state()` and change the flags you're
+    /// interested in, before issuing the request. `WindowStateFlags::wm_state_mask()` is a sensible
+    /// `mask`, which ignores e.g focus changes that may happen in the meantime.
+    pub fn wait_for_state(&self, expected: WindowStateFlags, mask: WindowStateFlags, timeout: Timeout) -> Result<bool> {
+        let expected = expected.masked(mask);
+        let start = Instant::now();
+        loop {
+            if self.state()?.masked(mask) == expected {
+                return Ok(true);
+            }
+            if let Some(duration) = timeout.duration() {
+                if start.elapsed() >= duration {
+                    return Ok(false);
+                }
+            }
+            // The window manager updates the state asynchronously, and no event tells us
+            // reliably when it did, so polling is the simplest robust option.
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
     /// Softly demands the user's attention, in a platform-specific way.
     ///
     /// You should use this when you want your application to signal that,
@@ -395,6 +1073,18 @@ impl Window {
     pub fn demand_urgent_attention(&self) -> Result<()> {
         self.0.demand_urgent_attention()
     }
+    /// Flashes the window (or its taskbar entry) to get the user's attention.
+    ///
+    /// `None` flashes until the window gains focus. `Some(n)` flashes about `n` times, and stops
+    /// earlier if the window gains focus. `Some(0)` stops any ongoing flashing.
+    ///
+    /// On Windows, this is done via `FlashWindowEx()`.  
+    /// On X11, this adds `_NET_WM_STATE_DEMANDS_ATTENTION` to the window's `_NET_WM_STATE`,
+    /// and removes it once `n` flash periods (of 500 milliseconds) have elapsed. Since X11 has
+    /// no timers, this happens the next time events are polled or waited for after that.
+    pub fn flash(&self, count: Option<u32>) -> Result<()> {
+        self.0.flash(count)
+    }
     /// Retrieves the window's top-left corner position, in desktop pixel coordinates.
     pub fn position(&self) -> Result<Vec2<i32>> {
         self.0.position()
@@ -411,6 +1101,31 @@ impl Window {
     pub fn canvas_size(&self) -> Result<Extent2<u32>> {
         self.0.canvas_size()
     }
+    /// Gets the scale factor of the monitor this window is on (the one that contains its
+    /// center), where `1.` means 96 DPI. Applications should scale their UI by this.
+    ///
+    /// Once this is known, moving the window to a monitor with another scale factor is
+    /// reported via `Event::WindowScaleFactorChanged`.
+    ///
+    /// On X11, this is derived from the monitor's physical size as reported by XRandR,
+    /// rounded to the nearest quarter; It is `1.` if the size is unknown.  
+    /// On Windows, this is the window's DPI divided by 96; It is always `1.` unless the
+    /// process is per-monitor DPI-aware (via its manifest).
+    pub fn scale_factor(&self) -> Result<f64> {
+        self.0.scale_factor()
+    }
+    /// Gets the refresh rate, in Hz, of the monitor this window is on (the one that contains
+    /// its center, or else the primary monitor), e.g for frame pacing.
+    ///
+    /// Once this is known, changes (because the window moved to another monitor, or the
+    /// monitor's mode changed) are reported via `Event::WindowRefreshRateChanged`.
+    ///
+    /// On X11, this is computed from the XRandR mode of the monitor, so fractional rates
+    /// such as 59.94 Hz are preserved.  
+    /// On Windows, `EnumDisplaySettingsW()` only reports integer rates.
+    pub fn current_refresh_rate(&self) -> Result<f64> {
+        self.0.current_refresh_rate()
+    }
     /// Retrieves the window's size, in desktop pixel coordinates.
     /// 
     /// You should not rely on this being equal to its size
@@ -429,9 +1144,56 @@ impl Window {
         self.0.position_and_size()
     }
     /// Moves and resizes the window in desktop space.
+    ///
+    /// Unlike calling `set_position()` then `set_size()`, this is done in a single request,
+    /// so there's no visible intermediate state where the window has its new position but
+    /// its old size (or vice versa). This is what you want when restoring a saved window state.
+    ///
+    /// On X11, this uses `XMoveResizeWindow()`.  
+    /// On Windows, this uses a single `SetWindowPos()` call.
     pub fn set_position_and_size(&self, r: Rect<i32, u32>) -> Result<()> {
         self.0.set_position_and_size(r)
     }
+    /// Gets the window's position, size and state as a single value, for saving it and
+    /// restoring it later with `Context::create_window_with_geometry()`.
+    ///
+    /// If the window is maximized or full-screen, `position` and `size` are the ones it had
+    /// before (i.e the ones it gets back when restored), so that a window that is restored
+    /// after being re-created maximized doesn't cover the whole monitor.
+    ///
+    /// On X11, these are tracked from the `ConfigureNotify` events received while the window
+    /// is in normal state. If the window manager configures a window before updating
+    /// its `_NET_WM_STATE`, the maximized rect may be reported instead.  
+    /// On Windows, this uses `GetWindowPlacement()`.
+    pub fn geometry(&self) -> Result<WindowGeometry> {
+        let state = self.state()?;
+        let rect = if state.maximized_width || state.maximized_height || state.fullscreen {
+            match self.0.normal_position_and_size()? {
+                Some(rect) => rect,
+                None => self.position_and_size()?,
+            }
+        } else {
+            self.position_and_size()?
+        };
+        let center = Vec2::new(rect.x + (rect.w / 2) as i32, rect.y + (rect.h / 2) as i32);
+        let monitor = self.0.monitor_rects()?.into_iter().enumerate()
+            .find(|&(_, m)| m.map(|p| p, |e| e as i32).contains_point(center));
+        let (monitor, origin) = match monitor {
+            Some((i, m)) => (Some(i), m.position()),
+            None => (None, Vec2::zero()),
+        };
+        Ok(WindowGeometry {
+            monitor,
+            position: rect.position() - origin,
+            size: rect.extent(),
+            maximized: state.maximized_width && state.maximized_height,
+            fullscreen: state.fullscreen,
+        })
+    }
+    /// Same as `set_position_and_size()`, but takes the position and size separately.
+    pub fn set_geometry(&self, position: Vec2<i32>, size: Extent2<u32>) -> Result<()> {
+        self.set_position_and_size(Rect::new(position.x, position.y, size.w, size.h))
+    }
     /// Sets the window's overall opacity.
     ///
     /// The result is platform-specific and window-manager-specific.  
@@ -456,12 +1218,107 @@ impl Window {
         }
         self.0.set_opacity(alpha)
     }
+    /// Makes the window non-rectangular: only the parts of the window covered by `mask` are
+    /// visible (and receive input), the rest lets whatever is below show through.
+    ///
+    /// The mask's top-left corner is the window's top-left corner; the parts of the window
+    /// that are outside of the mask are hidden. Passing `None` restores the rectangular shape.  
+    /// This is meant for e.g desktop gadgets and overlays, which you probably want borderless.
+    ///
+    /// On X11, this requires the XShape extension.  
+    /// On Windows, this calls `SetWindowRgn()`.
+    pub fn set_shape_mask(&self, mask: Option<&ImageMask>) -> Result<()> {
+        if let Some(mask) = mask {
+            mask.validate()?;
+        }
+        self.0.set_shape_mask(mask)
+    }
+    /// Restricts the parts of the window that receive mouse input, independently of its
+    /// visible shape. Input on the other parts goes to whatever is below.
+    ///
+    /// Passing `None` restores the default, which is the visible shape.
+    ///
+    /// On X11, this requires XShape 1.1.  
+    /// On Windows, the input shape is always the visible shape, so this fails with `ErrorKind::Unsupported`.
+    pub fn set_input_shape_mask(&self, mask: Option<&ImageMask>) -> Result<()> {
+        if let Some(mask) = mask {
+            mask.validate()?;
+        }
+        self.0.set_input_shape_mask(mask)
+    }
+    /// Prevents (or allows again) the window's contents from appearing in screenshots and
+    /// screen recordings, e.g for DRM-protected video or sensitive information.
+    ///
+    /// On Windows, this calls `SetWindowDisplayAffinity()`. Windows 10 version 2004 and later
+    /// exclude the window from captures altogether; Older versions show it as a black rectangle instead.  
+    /// On X11, any client may read any window's contents, so this returns `Unsupported`.
+    pub fn set_content_protection(&self, enabled: bool) -> Result<()> {
+        self.0.set_content_protection(enabled)
+    }
+    /// Sets a function which, given the window's size, returns the areas of the window where
+    /// the mouse should interact with the window's contents, move the window (like a title bar
+    /// would), or go through the window.
+    ///
+    /// Points that are not in any area are `Client`. Where areas overlap, the last one wins.  
+    /// `f` is called again whenever the window's size changes.
+    ///
+    /// This is meant for frameless windows with custom chrome, and for overlays that should
+    /// let clicks through their transparent parts.  
+    /// Mouse events are still reported for `Caption` areas.
+    ///
+    /// On X11, the areas are combined into the window's input shape, which requires XShape 1.1.
+    /// `Caption` is implemented by calling `begin_move_drag()` when the left button is pressed there.  
+    /// On Windows, the areas are looked up when handling `WM_NCHITTEST`. Beware that clicks on
+    /// `Transparent` areas only go through to windows that belong to the same thread.
+    pub fn set_cursor_hit_test<F>(&self, f: F) -> Result<()> where F: Fn(Extent2<u32>) -> Vec<HitTestArea> + 'static {
+        self.0.set_cursor_hit_test(Some(HitTestFn::new(f)))
+    }
+    /// Removes the function set by `set_cursor_hit_test()`, so that the whole window is `Client` again.
+    pub fn clear_cursor_hit_test(&self) -> Result<()> {
+        self.0.set_cursor_hit_test(None)
+    }
+    /// Lets the window manager take over moving the window, as if the user had started
+    /// dragging its title bar.
+    ///
+    /// This is the proper way to make frameless windows (e.g with custom title bars) draggable,
+    /// without fighting the window manager.  
+    /// Call this in response to a mouse button press: `button` is the button being held,
+    /// `root_position` is the cursor's position in desktop coordinates, and `instant` is the
+    /// event's timestamp.
+    ///
+    /// On X11, this sends a `_NET_WM_MOVERESIZE` client message.  
+    /// On Windows, this sends `WM_NCLBUTTONDOWN` with `HTCAPTION`, so `button` is ignored.
+    pub fn begin_move_drag(&self, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        self.0.begin_move_drag(button, root_position, instant)
+    }
+    /// Lets the window manager take over resizing the window by the given edge or corner,
+    /// as if the user had started dragging it.
+    ///
+    /// See `begin_move_drag()` for the meaning of the other parameters.
+    pub fn begin_resize_drag(&self, edge: ResizeEdge, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        self.0.begin_resize_drag(edge, button, root_position, instant)
+    }
     /// Attempts to move the window to the desktop specified by index `i`.
     ///
     /// Some platforms might not support this.
     pub fn set_desktop(&self, i: usize) -> Result<()> {
         self.0.set_desktop(i)
     }
+    /// Makes the window "sticky", that is, visible on all desktops (workspaces) at once,
+    /// or undoes it.
+    ///
+    /// This is what you want for e.g notification bubbles and overlays.
+    /// It is **not** the same as "always on top", which keeps a window above others
+    /// but only on the desktop it belongs to. The two can be combined.
+    ///
+    /// On X11, this sends a `_NET_WM_STATE` client message with `_NET_WM_STATE_STICKY`.  
+    /// The effect is window-manager-dependant.  
+    /// On Windows, there is no such concept, so this fails with `ErrorKind::Unsupported`.  
+    /// On macOS, this would set `NSWindowCollectionBehaviorCanJoinAllSpaces` in the
+    /// window's `collectionBehavior`.
+    pub fn set_visible_on_all_desktops(&self, sticky: bool) -> Result<()> {
+        self.0.set_visible_on_all_desktops(sticky)
+    }
     /// Attempts to recenter the window in desktop space.
     pub fn recenter_in_desktop(&self) -> Result<()> {
         self.0.recenter_in_desktop()
@@ -497,6 +1354,21 @@ impl Window {
     pub fn trap_mouse(&self) -> Result<()> {
         self.0.trap_mouse()
     }
+    /// Grabs the mouse (and, optionally, the keyboard) so that their events are reported to
+    /// this window only, until the returned `InputGrab` is dropped.
+    ///
+    /// This is what popup menus need: while the grab is active, pressing a mouse button outside of
+    /// our windows is reported to this window as an `Event::MouseButtonPressed` with `outside`
+    /// set to `true`, so the menu can dismiss itself, and nothing underneath is activated.
+    ///
+    /// The window must be visible. This fails with `ErrorKind::Busy` if another grab is already
+    /// active, either from another application or from a previous `InputGrab`.
+    ///
+    /// On Windows, this uses `SetCapture()`, and the keyboard is "grabbed" by giving this window
+    /// the keyboard focus.
+    pub fn grab_input(&self, options: GrabOptions) -> Result<InputGrab> {
+        self.0.grab_input(options).map(InputGrab)
+    }
     /// Clears the window's contents. This is currently only used for testing
     /// (e.g when changing a window's size in any way).
     ///
@@ -508,4 +1380,41 @@ impl Window {
     pub fn clear_rect(&self, r: Rect<i32, u32>) -> Result<()> {
         self.0.clear_rect(r)
     }
+    /// Is text input currently enabled for this window?
+    ///
+    /// Text input is enabled by default, so that `KeyboardTextString` and `KeyboardTextChar`
+    /// events are reported as usual.
+    pub fn text_input_active(&self) -> bool {
+        self.0.text_input_active()
+    }
+    /// Enables text input for this window, giving focus to its input method, if any.
+    pub fn enable_text_input(&self) -> Result<()> {
+        self.0.enable_text_input()
+    }
+    /// Disables text input for this window.
+    ///
+    /// Any composition in progress is cancelled, and a final `KeyboardTextComposition` event
+    /// with empty text is reported so that you can clear your preedit display.
+    pub fn disable_text_input(&self) -> Result<()> {
+        self.0.disable_text_input()
+    }
+    /// Tells the input method where the text cursor is, in window coordinates, so that
+    /// its candidate window can be displayed next to it.
+    ///
+    /// This can be called at any time, including while a composition is in progress,
+    /// in which case the candidate window is moved accordingly.
+    ///
+    /// On X11, this sets the XIC's `XNSpotLocation`, which only works if the input method
+    /// supports the `XIMPreeditPosition` input style.  
+    /// On Windows, this calls `ImmSetCandidateWindow()`.
+    pub fn set_text_input_rect(&self, r: Rect<i32, u32>) -> Result<()> {
+        self.0.set_text_input_rect(r)
+    }
+    /// Tells whether the given operation is expected to succeed on this window,
+    /// without actually performing it.
+    ///
+    /// This is useful for e.g greying out menu items in your UI.
+    pub fn capability(&self, op: WindowOp) -> Capability {
+        self.0.capability(op)
+    }
 }
@@ -0,0 +1,126 @@
+//! The user's accessibility scaling preference.
+//!
+//! There's no X11 standard for this, so we read GNOME's `text-scaling-factor` setting.
+//! We don't link against GIO just for this, so the `gsettings` tool is used instead.
+//!
+//! Only the first query blocks. Afterwards, the value is cached, and refreshed by running
+//! `gsettings` in the background when the root window's RESOURCE_MANAGER property changes;
+//! Its output is collected the next time X events are pumped.
+
+use std::io::{self, Read};
+use std::process::{Command, Child, Output, Stdio};
+use error::{Error, Result, failed};
+use event::Event;
+use super::context::X11SharedContext;
+use super::x11::xlib as x;
+
+fn gsettings_command() -> Command {
+    let mut cmd = Command::new("gsettings");
+    cmd.args(&["get", "org.gnome.desktop.interface", "text-scaling-factor"]);
+    cmd
+}
+
+fn gsettings_error(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::NotFound => Error::unsupported("The accessibility scale factor can only be read via GNOME's `gsettings` tool, which was not found"),
+        _ => Error::failed(format!("Could not run `gsettings`: {}", e)),
+    }
+}
+
+fn parse_gsettings_output(output: &Output) -> Result<f64> {
+    if !output.status.success() {
+        return failed(format!("`gsettings` failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.trim().parse::<f64>() {
+        Ok(factor) if factor.is_finite() && factor > 0. => Ok(factor),
+        _ => failed(format!("Unexpected `gsettings` output: `{}`", stdout.trim())),
+    }
+}
+
+fn gnome_text_scaling_factor() -> Result<f64> {
+    let output = gsettings_command().output().map_err(gsettings_error)?;
+    parse_gsettings_output(&output)
+}
+
+/// A `gsettings` process that was started by `refresh_accessibility_scale_factor()`.
+#[derive(Debug)]
+pub struct AccessibilityScaleFactorQuery {
+    child: Child,
+    // The setting may have changed again after the process was started.
+    is_outdated: bool,
+}
+
+impl Drop for AccessibilityScaleFactorQuery {
+    fn drop(&mut self) {
+        // Don't leave a zombie process behind.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl X11SharedContext {
+    pub fn accessibility_scale_factor(&self) -> Result<f64> {
+        if let Some(factor) = self.accessibility_scale_factor.get() {
+            return Ok(factor);
+        }
+        let factor = gnome_text_scaling_factor()?;
+        self.accessibility_scale_factor.set(Some(factor));
+        // When the setting changes, gnome-settings-daemon also updates `Xft.dpi` in the
+        // RESOURCE_MANAGER property of the root window, so that's our cue to query it again.
+        let root = self.x_default_root_window();
+        unsafe {
+            x::XSelectInput(*self.lock_x_display(), root, x::PropertyChangeMask);
+        }
+        Ok(factor)
+    }
+    /// Starts querying the scale factor again in the background, see `poll_accessibility_scale_factor()`.
+    /// Does nothing unless `accessibility_scale_factor()` was called before.
+    pub fn refresh_accessibility_scale_factor(&self) {
+        if self.accessibility_scale_factor.get().is_none() {
+            return;
+        }
+        let mut query = self.accessibility_scale_factor_query.borrow_mut();
+        if let Some(ref mut query) = *query {
+            query.is_outdated = true;
+            return;
+        }
+        // The output is a single short line, which can't fill the pipe, so we can wait for
+        // the process to exit before reading it.
+        match gsettings_command().stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+            Ok(child) => *query = Some(AccessibilityScaleFactorQuery { child, is_outdated: false }),
+            Err(e) => warn!("Could not refresh the accessibility scale factor: {}", gsettings_error(e)),
+        }
+    }
+    /// Returns an event if a background query has finished and the scale factor has changed.
+    pub fn poll_accessibility_scale_factor(&self) -> Option<Event> {
+        let mut query = self.accessibility_scale_factor_query.borrow_mut();
+        let status = match query.as_mut()?.child.try_wait() {
+            Ok(None) => return None,
+            Ok(Some(status)) => Ok(status),
+            Err(e) => Err(e),
+        };
+        let mut finished = query.take().unwrap();
+        drop(query);
+        if finished.is_outdated {
+            self.refresh_accessibility_scale_factor();
+        }
+        let output = status.and_then(|status| {
+            let mut output = Output { status, stdout: Vec::new(), stderr: Vec::new() };
+            finished.child.stdout.take().unwrap().read_to_end(&mut output.stdout)?;
+            finished.child.stderr.take().unwrap().read_to_end(&mut output.stderr)?;
+            Ok(output)
+        });
+        let new_scale = match output.map_err(gsettings_error).and_then(|output| parse_gsettings_output(&output)) {
+            Ok(factor) => factor,
+            Err(e) => {
+                warn!("Could not refresh the accessibility scale factor: {}", e);
+                return None;
+            },
+        };
+        if self.accessibility_scale_factor.replace(Some(new_scale)) == Some(new_scale) {
+            return None;
+        }
+        Some(Event::AccessibilityScaleFactorChanged { new_scale })
+    }
+}
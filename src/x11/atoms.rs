@@ -8,6 +8,10 @@ use error::{Result, failed};
 /// once after opening a display.
 macro_rules! atoms {
     ($($atom:ident => $name:expr,)+) => {
+        /// (X11-only) The atoms that a `Context` interned when it was created, see `Context::atoms()`.
+        ///
+        /// Each one has an accessor named after it, which fails if the atom didn't exist on the
+        /// server at that time (e.g `_NET_WM_STATE()`, or `Rel_X()` for `Rel X`).
         #[repr(C)] // Passed directly as array of Atoms to XInternAtoms()
         #[allow(non_snake_case)]
         #[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
@@ -27,7 +31,7 @@ macro_rules! atoms {
                 failed(format!("`{}` atom was not registered", CStr::from_bytes_with_nul(name).unwrap().to_string_lossy()))
             }
             $(
-                #[allow(non_snake_case, dead_code)]
+                #[allow(non_snake_case, dead_code, missing_docs)]
                 pub fn $atom(&self) -> Result<x::Atom> {
                     match self.$atom {
                         0 => Self::err_atom_not_present($name),
@@ -35,7 +39,8 @@ macro_rules! atoms {
                     }
                 }
             )+
-            pub fn from_name_with_nul(&self, name: &[u8]) -> Result<x::Atom> {
+            #[allow(dead_code)]
+            pub(crate) fn from_name_with_nul(&self, name: &[u8]) -> Result<x::Atom> {
                 assert_eq!(&0, name.last().unwrap());
                 match name {
                     $($name => self.$atom(),)+
@@ -43,7 +48,7 @@ macro_rules! atoms {
                 }
             }
 
-            pub fn load(x_display: *mut x::Display) -> Result<Self> {
+            pub(crate) fn load(x_display: *mut x::Display) -> Result<Self> {
                 $(assert_eq!(&0, $name.last().unwrap());)+
                 let mut atoms = Self::default();
                 unsafe {
@@ -68,10 +73,10 @@ macro_rules! atoms {
 
                 Ok(atoms)
             }
-            pub fn interesting_xi2_props(&self) -> &[x::Atom] {
+            pub(crate) fn interesting_xi2_props(&self) -> &[x::Atom] {
                 &self.interesting_xi2_props
             }
-            pub fn is_interesting_xi2_prop(&self, prop: x::Atom) -> bool {
+            pub(crate) fn is_interesting_xi2_prop(&self, prop: x::Atom) -> bool {
                 self.interesting_xi2_props.contains(&prop)
             }
         }
@@ -263,6 +268,7 @@ atoms!{
     Wacom_Tool_Type   => b"Wacom Tool Type\0", // One of the atoms right below
         STYLUS            => b"STYLUS\0",
         ERASER            => b"ERASER\0",
+        CURSOR            => b"CURSOR\0",
         PAD               => b"PAD\0",
         TOUCH             => b"TOUCH\0",
 }
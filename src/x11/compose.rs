@@ -0,0 +1,559 @@
+//! Fallback support for dead keys and the Compose key, for when no X input method is available.
+//!
+//! Normally, the X input method (XIM) takes care of this, but it is not always available
+//! (e.g on minimal window manager setups). This implements the most common sequences of
+//! the `en_US.UTF-8/Compose` table, that is, Latin accents and a few symbols:
+//!
+//! - `<dead key> <letter>`, e.g `<dead_acute> <e>` gives `é`;
+//! - `<dead key> <space>` or `<dead key> <dead key>`, which give the accent itself;
+//! - `<Multi_key> <a> <b>`, e.g `<Multi_key> <o> <c>` gives `©`.
+//!
+//! Any key that doesn't belong to the pending sequence cancels it, and is then processed as usual.
+//! Modifier keys (Shift, etc) are ignored. Sequences are also cancelled if the user takes
+//! too long between two keys.
+//!
+//! Both tables MUST be kept sorted, because lookups are binary searches.
+
+use std::os::raw::c_uint;
+use super::x11::xlib as x;
+use super::x11::keysym::*;
+
+/// Pending sequences are cancelled if there is more than this delay between two keys.
+const SEQUENCE_TIMEOUT_MILLIS: x::Time = 5000;
+
+static DEAD_KEY_SEQUENCES: &'static [(c_uint, char, char)] = &[
+    (XK_dead_grave, ' ', '`'),
+    (XK_dead_grave, 'A', 'À'),
+    (XK_dead_grave, 'E', 'È'),
+    (XK_dead_grave, 'I', 'Ì'),
+    (XK_dead_grave, 'N', 'Ǹ'),
+    (XK_dead_grave, 'O', 'Ò'),
+    (XK_dead_grave, 'U', 'Ù'),
+    (XK_dead_grave, 'W', 'Ẁ'),
+    (XK_dead_grave, 'Y', 'Ỳ'),
+    (XK_dead_grave, 'a', 'à'),
+    (XK_dead_grave, 'e', 'è'),
+    (XK_dead_grave, 'i', 'ì'),
+    (XK_dead_grave, 'n', 'ǹ'),
+    (XK_dead_grave, 'o', 'ò'),
+    (XK_dead_grave, 'u', 'ù'),
+    (XK_dead_grave, 'w', 'ẁ'),
+    (XK_dead_grave, 'y', 'ỳ'),
+    (XK_dead_acute, ' ', '\''),
+    (XK_dead_acute, 'A', 'Á'),
+    (XK_dead_acute, 'C', 'Ć'),
+    (XK_dead_acute, 'E', 'É'),
+    (XK_dead_acute, 'G', 'Ǵ'),
+    (XK_dead_acute, 'I', 'Í'),
+    (XK_dead_acute, 'K', 'Ḱ'),
+    (XK_dead_acute, 'L', 'Ĺ'),
+    (XK_dead_acute, 'M', 'Ḿ'),
+    (XK_dead_acute, 'N', 'Ń'),
+    (XK_dead_acute, 'O', 'Ó'),
+    (XK_dead_acute, 'P', 'Ṕ'),
+    (XK_dead_acute, 'R', 'Ŕ'),
+    (XK_dead_acute, 'S', 'Ś'),
+    (XK_dead_acute, 'U', 'Ú'),
+    (XK_dead_acute, 'W', 'Ẃ'),
+    (XK_dead_acute, 'Y', 'Ý'),
+    (XK_dead_acute, 'Z', 'Ź'),
+    (XK_dead_acute, 'a', 'á'),
+    (XK_dead_acute, 'c', 'ć'),
+    (XK_dead_acute, 'e', 'é'),
+    (XK_dead_acute, 'g', 'ǵ'),
+    (XK_dead_acute, 'i', 'í'),
+    (XK_dead_acute, 'k', 'ḱ'),
+    (XK_dead_acute, 'l', 'ĺ'),
+    (XK_dead_acute, 'm', 'ḿ'),
+    (XK_dead_acute, 'n', 'ń'),
+    (XK_dead_acute, 'o', 'ó'),
+    (XK_dead_acute, 'p', 'ṕ'),
+    (XK_dead_acute, 'r', 'ŕ'),
+    (XK_dead_acute, 's', 'ś'),
+    (XK_dead_acute, 'u', 'ú'),
+    (XK_dead_acute, 'w', 'ẃ'),
+    (XK_dead_acute, 'y', 'ý'),
+    (XK_dead_acute, 'z', 'ź'),
+    (XK_dead_circumflex, ' ', '^'),
+    (XK_dead_circumflex, 'A', 'Â'),
+    (XK_dead_circumflex, 'C', 'Ĉ'),
+    (XK_dead_circumflex, 'E', 'Ê'),
+    (XK_dead_circumflex, 'G', 'Ĝ'),
+    (XK_dead_circumflex, 'H', 'Ĥ'),
+    (XK_dead_circumflex, 'I', 'Î'),
+    (XK_dead_circumflex, 'J', 'Ĵ'),
+    (XK_dead_circumflex, 'O', 'Ô'),
+    (XK_dead_circumflex, 'S', 'Ŝ'),
+    (XK_dead_circumflex, 'U', 'Û'),
+    (XK_dead_circumflex, 'W', 'Ŵ'),
+    (XK_dead_circumflex, 'Y', 'Ŷ'),
+    (XK_dead_circumflex, 'Z', 'Ẑ'),
+    (XK_dead_circumflex, 'a', 'â'),
+    (XK_dead_circumflex, 'c', 'ĉ'),
+    (XK_dead_circumflex, 'e', 'ê'),
+    (XK_dead_circumflex, 'g', 'ĝ'),
+    (XK_dead_circumflex, 'h', 'ĥ'),
+    (XK_dead_circumflex, 'i', 'î'),
+    (XK_dead_circumflex, 'j', 'ĵ'),
+    (XK_dead_circumflex, 'o', 'ô'),
+    (XK_dead_circumflex, 's', 'ŝ'),
+    (XK_dead_circumflex, 'u', 'û'),
+    (XK_dead_circumflex, 'w', 'ŵ'),
+    (XK_dead_circumflex, 'y', 'ŷ'),
+    (XK_dead_circumflex, 'z', 'ẑ'),
+    (XK_dead_tilde, ' ', '~'),
+    (XK_dead_tilde, 'A', 'Ã'),
+    (XK_dead_tilde, 'E', 'Ẽ'),
+    (XK_dead_tilde, 'I', 'Ĩ'),
+    (XK_dead_tilde, 'N', 'Ñ'),
+    (XK_dead_tilde, 'O', 'Õ'),
+    (XK_dead_tilde, 'U', 'Ũ'),
+    (XK_dead_tilde, 'V', 'Ṽ'),
+    (XK_dead_tilde, 'Y', 'Ỹ'),
+    (XK_dead_tilde, 'a', 'ã'),
+    (XK_dead_tilde, 'e', 'ẽ'),
+    (XK_dead_tilde, 'i', 'ĩ'),
+    (XK_dead_tilde, 'n', 'ñ'),
+    (XK_dead_tilde, 'o', 'õ'),
+    (XK_dead_tilde, 'u', 'ũ'),
+    (XK_dead_tilde, 'v', 'ṽ'),
+    (XK_dead_tilde, 'y', 'ỹ'),
+    (XK_dead_macron, ' ', '¯'),
+    (XK_dead_macron, 'A', 'Ā'),
+    (XK_dead_macron, 'E', 'Ē'),
+    (XK_dead_macron, 'G', 'Ḡ'),
+    (XK_dead_macron, 'I', 'Ī'),
+    (XK_dead_macron, 'O', 'Ō'),
+    (XK_dead_macron, 'U', 'Ū'),
+    (XK_dead_macron, 'Y', 'Ȳ'),
+    (XK_dead_macron, 'a', 'ā'),
+    (XK_dead_macron, 'e', 'ē'),
+    (XK_dead_macron, 'g', 'ḡ'),
+    (XK_dead_macron, 'i', 'ī'),
+    (XK_dead_macron, 'o', 'ō'),
+    (XK_dead_macron, 'u', 'ū'),
+    (XK_dead_macron, 'y', 'ȳ'),
+    (XK_dead_breve, ' ', '˘'),
+    (XK_dead_breve, 'A', 'Ă'),
+    (XK_dead_breve, 'E', 'Ĕ'),
+    (XK_dead_breve, 'G', 'Ğ'),
+    (XK_dead_breve, 'I', 'Ĭ'),
+    (XK_dead_breve, 'O', 'Ŏ'),
+    (XK_dead_breve, 'U', 'Ŭ'),
+    (XK_dead_breve, 'a', 'ă'),
+    (XK_dead_breve, 'e', 'ĕ'),
+    (XK_dead_breve, 'g', 'ğ'),
+    (XK_dead_breve, 'i', 'ĭ'),
+    (XK_dead_breve, 'o', 'ŏ'),
+    (XK_dead_breve, 'u', 'ŭ'),
+    (XK_dead_abovedot, ' ', '˙'),
+    (XK_dead_abovedot, 'A', 'Ȧ'),
+    (XK_dead_abovedot, 'B', 'Ḃ'),
+    (XK_dead_abovedot, 'C', 'Ċ'),
+    (XK_dead_abovedot, 'D', 'Ḋ'),
+    (XK_dead_abovedot, 'E', 'Ė'),
+    (XK_dead_abovedot, 'F', 'Ḟ'),
+    (XK_dead_abovedot, 'G', 'Ġ'),
+    (XK_dead_abovedot, 'H', 'Ḣ'),
+    (XK_dead_abovedot, 'I', 'İ'),
+    (XK_dead_abovedot, 'M', 'Ṁ'),
+    (XK_dead_abovedot, 'N', 'Ṅ'),
+    (XK_dead_abovedot, 'O', 'Ȯ'),
+    (XK_dead_abovedot, 'P', 'Ṗ'),
+    (XK_dead_abovedot, 'R', 'Ṙ'),
+    (XK_dead_abovedot, 'S', 'Ṡ'),
+    (XK_dead_abovedot, 'T', 'Ṫ'),
+    (XK_dead_abovedot, 'W', 'Ẇ'),
+    (XK_dead_abovedot, 'X', 'Ẋ'),
+    (XK_dead_abovedot, 'Y', 'Ẏ'),
+    (XK_dead_abovedot, 'Z', 'Ż'),
+    (XK_dead_abovedot, 'a', 'ȧ'),
+    (XK_dead_abovedot, 'b', 'ḃ'),
+    (XK_dead_abovedot, 'c', 'ċ'),
+    (XK_dead_abovedot, 'd', 'ḋ'),
+    (XK_dead_abovedot, 'e', 'ė'),
+    (XK_dead_abovedot, 'f', 'ḟ'),
+    (XK_dead_abovedot, 'g', 'ġ'),
+    (XK_dead_abovedot, 'h', 'ḣ'),
+    (XK_dead_abovedot, 'm', 'ṁ'),
+    (XK_dead_abovedot, 'n', 'ṅ'),
+    (XK_dead_abovedot, 'o', 'ȯ'),
+    (XK_dead_abovedot, 'p', 'ṗ'),
+    (XK_dead_abovedot, 'r', 'ṙ'),
+    (XK_dead_abovedot, 's', 'ṡ'),
+    (XK_dead_abovedot, 't', 'ṫ'),
+    (XK_dead_abovedot, 'w', 'ẇ'),
+    (XK_dead_abovedot, 'x', 'ẋ'),
+    (XK_dead_abovedot, 'y', 'ẏ'),
+    (XK_dead_abovedot, 'z', 'ż'),
+    (XK_dead_diaeresis, ' ', '"'),
+    (XK_dead_diaeresis, 'A', 'Ä'),
+    (XK_dead_diaeresis, 'E', 'Ë'),
+    (XK_dead_diaeresis, 'H', 'Ḧ'),
+    (XK_dead_diaeresis, 'I', 'Ï'),
+    (XK_dead_diaeresis, 'O', 'Ö'),
+    (XK_dead_diaeresis, 'U', 'Ü'),
+    (XK_dead_diaeresis, 'W', 'Ẅ'),
+    (XK_dead_diaeresis, 'X', 'Ẍ'),
+    (XK_dead_diaeresis, 'Y', 'Ÿ'),
+    (XK_dead_diaeresis, 'a', 'ä'),
+    (XK_dead_diaeresis, 'e', 'ë'),
+    (XK_dead_diaeresis, 'h', 'ḧ'),
+    (XK_dead_diaeresis, 'i', 'ï'),
+    (XK_dead_diaeresis, 'o', 'ö'),
+    (XK_dead_diaeresis, 't', 'ẗ'),
+    (XK_dead_diaeresis, 'u', 'ü'),
+    (XK_dead_diaeresis, 'w', 'ẅ'),
+    (XK_dead_diaeresis, 'x', 'ẍ'),
+    (XK_dead_diaeresis, 'y', 'ÿ'),
+    (XK_dead_abovering, ' ', '°'),
+    (XK_dead_abovering, 'A', 'Å'),
+    (XK_dead_abovering, 'U', 'Ů'),
+    (XK_dead_abovering, 'a', 'å'),
+    (XK_dead_abovering, 'u', 'ů'),
+    (XK_dead_abovering, 'w', 'ẘ'),
+    (XK_dead_abovering, 'y', 'ẙ'),
+    (XK_dead_doubleacute, ' ', '˝'),
+    (XK_dead_doubleacute, 'O', 'Ő'),
+    (XK_dead_doubleacute, 'U', 'Ű'),
+    (XK_dead_doubleacute, 'o', 'ő'),
+    (XK_dead_doubleacute, 'u', 'ű'),
+    (XK_dead_caron, ' ', 'ˇ'),
+    (XK_dead_caron, 'A', 'Ǎ'),
+    (XK_dead_caron, 'C', 'Č'),
+    (XK_dead_caron, 'D', 'Ď'),
+    (XK_dead_caron, 'E', 'Ě'),
+    (XK_dead_caron, 'G', 'Ǧ'),
+    (XK_dead_caron, 'H', 'Ȟ'),
+    (XK_dead_caron, 'I', 'Ǐ'),
+    (XK_dead_caron, 'K', 'Ǩ'),
+    (XK_dead_caron, 'L', 'Ľ'),
+    (XK_dead_caron, 'N', 'Ň'),
+    (XK_dead_caron, 'O', 'Ǒ'),
+    (XK_dead_caron, 'R', 'Ř'),
+    (XK_dead_caron, 'S', 'Š'),
+    (XK_dead_caron, 'T', 'Ť'),
+    (XK_dead_caron, 'U', 'Ǔ'),
+    (XK_dead_caron, 'Z', 'Ž'),
+    (XK_dead_caron, 'a', 'ǎ'),
+    (XK_dead_caron, 'c', 'č'),
+    (XK_dead_caron, 'd', 'ď'),
+    (XK_dead_caron, 'e', 'ě'),
+    (XK_dead_caron, 'g', 'ǧ'),
+    (XK_dead_caron, 'h', 'ȟ'),
+    (XK_dead_caron, 'i', 'ǐ'),
+    (XK_dead_caron, 'j', 'ǰ'),
+    (XK_dead_caron, 'k', 'ǩ'),
+    (XK_dead_caron, 'l', 'ľ'),
+    (XK_dead_caron, 'n', 'ň'),
+    (XK_dead_caron, 'o', 'ǒ'),
+    (XK_dead_caron, 'r', 'ř'),
+    (XK_dead_caron, 's', 'š'),
+    (XK_dead_caron, 't', 'ť'),
+    (XK_dead_caron, 'u', 'ǔ'),
+    (XK_dead_caron, 'z', 'ž'),
+    (XK_dead_cedilla, ' ', '¸'),
+    (XK_dead_cedilla, 'C', 'Ç'),
+    (XK_dead_cedilla, 'D', 'Ḑ'),
+    (XK_dead_cedilla, 'E', 'Ȩ'),
+    (XK_dead_cedilla, 'G', 'Ģ'),
+    (XK_dead_cedilla, 'H', 'Ḩ'),
+    (XK_dead_cedilla, 'K', 'Ķ'),
+    (XK_dead_cedilla, 'L', 'Ļ'),
+    (XK_dead_cedilla, 'N', 'Ņ'),
+    (XK_dead_cedilla, 'R', 'Ŗ'),
+    (XK_dead_cedilla, 'S', 'Ş'),
+    (XK_dead_cedilla, 'T', 'Ţ'),
+    (XK_dead_cedilla, 'c', 'ç'),
+    (XK_dead_cedilla, 'd', 'ḑ'),
+    (XK_dead_cedilla, 'e', 'ȩ'),
+    (XK_dead_cedilla, 'g', 'ģ'),
+    (XK_dead_cedilla, 'h', 'ḩ'),
+    (XK_dead_cedilla, 'k', 'ķ'),
+    (XK_dead_cedilla, 'l', 'ļ'),
+    (XK_dead_cedilla, 'n', 'ņ'),
+    (XK_dead_cedilla, 'r', 'ŗ'),
+    (XK_dead_cedilla, 's', 'ş'),
+    (XK_dead_cedilla, 't', 'ţ'),
+    (XK_dead_ogonek, ' ', '˛'),
+    (XK_dead_ogonek, 'A', 'Ą'),
+    (XK_dead_ogonek, 'E', 'Ę'),
+    (XK_dead_ogonek, 'I', 'Į'),
+    (XK_dead_ogonek, 'O', 'Ǫ'),
+    (XK_dead_ogonek, 'U', 'Ų'),
+    (XK_dead_ogonek, 'a', 'ą'),
+    (XK_dead_ogonek, 'e', 'ę'),
+    (XK_dead_ogonek, 'i', 'į'),
+    (XK_dead_ogonek, 'o', 'ǫ'),
+    (XK_dead_ogonek, 'u', 'ų'),
+];
+
+static MULTI_KEY_SEQUENCES: &'static [(char, char, char)] = &[
+    ('!', '!', '¡'),
+    ('"', 'A', 'Ä'),
+    ('"', 'E', 'Ë'),
+    ('"', 'H', 'Ḧ'),
+    ('"', 'I', 'Ï'),
+    ('"', 'O', 'Ö'),
+    ('"', 'U', 'Ü'),
+    ('"', 'W', 'Ẅ'),
+    ('"', 'X', 'Ẍ'),
+    ('"', 'Y', 'Ÿ'),
+    ('"', 'a', 'ä'),
+    ('"', 'e', 'ë'),
+    ('"', 'h', 'ḧ'),
+    ('"', 'i', 'ï'),
+    ('"', 'o', 'ö'),
+    ('"', 't', 'ẗ'),
+    ('"', 'u', 'ü'),
+    ('"', 'w', 'ẅ'),
+    ('"', 'x', 'ẍ'),
+    ('"', 'y', 'ÿ'),
+    ('\'', 'A', 'Á'),
+    ('\'', 'C', 'Ć'),
+    ('\'', 'E', 'É'),
+    ('\'', 'G', 'Ǵ'),
+    ('\'', 'I', 'Í'),
+    ('\'', 'K', 'Ḱ'),
+    ('\'', 'L', 'Ĺ'),
+    ('\'', 'M', 'Ḿ'),
+    ('\'', 'N', 'Ń'),
+    ('\'', 'O', 'Ó'),
+    ('\'', 'P', 'Ṕ'),
+    ('\'', 'R', 'Ŕ'),
+    ('\'', 'S', 'Ś'),
+    ('\'', 'U', 'Ú'),
+    ('\'', 'W', 'Ẃ'),
+    ('\'', 'Y', 'Ý'),
+    ('\'', 'Z', 'Ź'),
+    ('\'', 'a', 'á'),
+    ('\'', 'c', 'ć'),
+    ('\'', 'e', 'é'),
+    ('\'', 'g', 'ǵ'),
+    ('\'', 'i', 'í'),
+    ('\'', 'k', 'ḱ'),
+    ('\'', 'l', 'ĺ'),
+    ('\'', 'm', 'ḿ'),
+    ('\'', 'n', 'ń'),
+    ('\'', 'o', 'ó'),
+    ('\'', 'p', 'ṕ'),
+    ('\'', 'r', 'ŕ'),
+    ('\'', 's', 'ś'),
+    ('\'', 'u', 'ú'),
+    ('\'', 'w', 'ẃ'),
+    ('\'', 'y', 'ý'),
+    ('\'', 'z', 'ź'),
+    ('+', '-', '±'),
+    (',', 'C', 'Ç'),
+    (',', 'D', 'Ḑ'),
+    (',', 'E', 'Ȩ'),
+    (',', 'G', 'Ģ'),
+    (',', 'H', 'Ḩ'),
+    (',', 'K', 'Ķ'),
+    (',', 'L', 'Ļ'),
+    (',', 'N', 'Ņ'),
+    (',', 'R', 'Ŗ'),
+    (',', 'S', 'Ş'),
+    (',', 'T', 'Ţ'),
+    (',', 'c', 'ç'),
+    (',', 'd', 'ḑ'),
+    (',', 'e', 'ȩ'),
+    (',', 'g', 'ģ'),
+    (',', 'h', 'ḩ'),
+    (',', 'k', 'ķ'),
+    (',', 'l', 'ļ'),
+    (',', 'n', 'ņ'),
+    (',', 'r', 'ŗ'),
+    (',', 's', 'ş'),
+    (',', 't', 'ţ'),
+    ('-', '-', '–'),
+    ('-', ':', '÷'),
+    ('-', 'L', '£'),
+    ('.', '.', '…'),
+    ('/', 'O', 'Ø'),
+    ('/', 'o', 'ø'),
+    ('1', '2', '½'),
+    ('1', '4', '¼'),
+    ('3', '4', '¾'),
+    (':', '-', '÷'),
+    ('<', '<', '«'),
+    ('=', 'Y', '¥'),
+    ('=', 'e', '€'),
+    ('>', '>', '»'),
+    ('?', '?', '¿'),
+    ('A', 'E', 'Æ'),
+    ('L', '-', '£'),
+    ('O', 'C', '©'),
+    ('O', 'E', 'Œ'),
+    ('O', 'R', '®'),
+    ('Y', '=', '¥'),
+    ('^', '1', '¹'),
+    ('^', '2', '²'),
+    ('^', '3', '³'),
+    ('^', 'A', 'Â'),
+    ('^', 'C', 'Ĉ'),
+    ('^', 'E', 'Ê'),
+    ('^', 'G', 'Ĝ'),
+    ('^', 'H', 'Ĥ'),
+    ('^', 'I', 'Î'),
+    ('^', 'J', 'Ĵ'),
+    ('^', 'O', 'Ô'),
+    ('^', 'S', 'Ŝ'),
+    ('^', 'U', 'Û'),
+    ('^', 'W', 'Ŵ'),
+    ('^', 'Y', 'Ŷ'),
+    ('^', 'Z', 'Ẑ'),
+    ('^', 'a', 'â'),
+    ('^', 'c', 'ĉ'),
+    ('^', 'e', 'ê'),
+    ('^', 'g', 'ĝ'),
+    ('^', 'h', 'ĥ'),
+    ('^', 'i', 'î'),
+    ('^', 'j', 'ĵ'),
+    ('^', 'o', 'ô'),
+    ('^', 's', 'ŝ'),
+    ('^', 'u', 'û'),
+    ('^', 'w', 'ŵ'),
+    ('^', 'y', 'ŷ'),
+    ('^', 'z', 'ẑ'),
+    ('`', 'A', 'À'),
+    ('`', 'E', 'È'),
+    ('`', 'I', 'Ì'),
+    ('`', 'N', 'Ǹ'),
+    ('`', 'O', 'Ò'),
+    ('`', 'U', 'Ù'),
+    ('`', 'W', 'Ẁ'),
+    ('`', 'Y', 'Ỳ'),
+    ('`', 'a', 'à'),
+    ('`', 'e', 'è'),
+    ('`', 'i', 'ì'),
+    ('`', 'n', 'ǹ'),
+    ('`', 'o', 'ò'),
+    ('`', 'u', 'ù'),
+    ('`', 'w', 'ẁ'),
+    ('`', 'y', 'ỳ'),
+    ('a', 'e', 'æ'),
+    ('c', '|', '¢'),
+    ('e', '=', '€'),
+    ('m', 'u', 'µ'),
+    ('o', 'A', 'Å'),
+    ('o', 'a', 'å'),
+    ('o', 'c', '©'),
+    ('o', 'e', 'œ'),
+    ('o', 'o', '°'),
+    ('o', 'r', '®'),
+    ('p', '!', '¶'),
+    ('s', 'o', '§'),
+    ('s', 's', 'ß'),
+    ('t', 'm', '™'),
+    ('x', 'x', '×'),
+    ('|', 'c', '¢'),
+    ('~', 'A', 'Ã'),
+    ('~', 'E', 'Ẽ'),
+    ('~', 'I', 'Ĩ'),
+    ('~', 'N', 'Ñ'),
+    ('~', 'O', 'Õ'),
+    ('~', 'U', 'Ũ'),
+    ('~', 'V', 'Ṽ'),
+    ('~', 'Y', 'Ỹ'),
+    ('~', 'a', 'ã'),
+    ('~', 'e', 'ẽ'),
+    ('~', 'i', 'ĩ'),
+    ('~', 'n', 'ñ'),
+    ('~', 'o', 'õ'),
+    ('~', 'u', 'ũ'),
+    ('~', 'v', 'ṽ'),
+    ('~', 'y', 'ỹ'),
+];
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+enum Pending {
+    DeadKey(c_uint),
+    MultiKey,
+    MultiKeyThen(char),
+}
+
+/// What to do with a key press, once it went through the compose state machine.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Composed {
+    /// The key doesn't participate in any sequence; Its text should be reported as usual.
+    PassThrough,
+    /// The key was consumed by a sequence that isn't complete yet; No text should be reported.
+    Pending,
+    /// The key completed a sequence, which gives this character.
+    Char(char),
+}
+
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct ComposeState {
+    pending: Option<Pending>,
+    last_time: x::Time,
+}
+
+impl ComposeState {
+    /// Feeds the keysym of a key press (with modifiers applied) to the state machine.
+    pub fn feed(&mut self, keysym: x::KeySym, time: x::Time) -> Composed {
+        let keysym = keysym as c_uint;
+        if is_modifier_keysym(keysym) {
+            return Composed::PassThrough;
+        }
+        if self.pending.is_some() && time.wrapping_sub(self.last_time) > SEQUENCE_TIMEOUT_MILLIS {
+            trace!("Compose sequence timed out");
+            self.pending = None;
+        }
+        self.last_time = time;
+
+        let composed = match self.pending.take() {
+            None => None,
+            Some(Pending::DeadKey(dead)) => {
+                let base = if keysym == dead { Some(' ') } else { ascii_char(keysym) };
+                base.and_then(|base| lookup_dead_key(dead, base))
+            },
+            Some(Pending::MultiKey) => match ascii_char(keysym) {
+                Some(first) if MULTI_KEY_SEQUENCES.iter().any(|&(a, _, _)| a == first) => {
+                    self.pending = Some(Pending::MultiKeyThen(first));
+                    return Composed::Pending;
+                },
+                _ => None,
+            },
+            Some(Pending::MultiKeyThen(first)) => ascii_char(keysym).and_then(|second| lookup_multi_key(first, second)),
+        };
+        if let Some(c) = composed {
+            return Composed::Char(c);
+        }
+        // Either nothing was pending, or the sequence was just cancelled; See if this key starts a new one.
+        if keysym == XK_Multi_key {
+            self.pending = Some(Pending::MultiKey);
+            return Composed::Pending;
+        }
+        if lookup_dead_key(keysym, ' ').is_some() {
+            self.pending = Some(Pending::DeadKey(keysym));
+            return Composed::Pending;
+        }
+        Composed::PassThrough
+    }
+}
+
+fn ascii_char(keysym: c_uint) -> Option<char> {
+    // Keysyms for printable ASCII characters are the characters themselves.
+    match keysym {
+        0x20 ..= 0x7e => Some(keysym as u8 as char),
+        _ => None,
+    }
+}
+
+fn is_modifier_keysym(keysym: c_uint) -> bool {
+    // Same as Xlib's IsModifierKey() macro.
+    (keysym >= XK_Shift_L && keysym <= XK_Hyper_R)
+        || (keysym >= XK_ISO_Lock && keysym <= XK_ISO_Level5_Lock)
+        || keysym == XK_Mode_switch
+        || keysym == XK_Num_Lock
+}
+
+fn lookup_dead_key(dead: c_uint, base: char) -> Option<char> {
+    DEAD_KEY_SEQUENCES.binary_search_by(|&(d, b, _)| (d, b).cmp(&(dead, base))).ok().map(|i| DEAD_KEY_SEQUENCES[i].2)
+}
+
+fn lookup_multi_key(first: char, second: char) -> Option<char> {
+    MULTI_KEY_SEQUENCES.binary_search_by(|&(a, b, _)| (a, b).cmp(&(first, second))).ok().map(|i| MULTI_KEY_SEQUENCES[i].2)
+}
@@ -1,17 +1,18 @@
 use std::ptr;
 use std::mem;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::rc::{Rc, Weak};
 use std::cell::{RefCell, Cell};
 use std::slice;
 use std::ops::{Deref, Range};
 use std::os::raw::{c_int, c_long, c_ulong, c_uchar, c_char};
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 
-use context::Context;
+use context::{Context, ContextOptions, BackendKind, BackendInfo};
 use desktop::Desktop;
-use error::{Result, failed};
-use event::Event;
+use error::{Result, failed, unsupported, invalid_arg};
+use event_queue::EventQueue;
+use monitor::GammaRamps;
 use os::OsContext;
 use {Rect, Vec2};
 
@@ -20,25 +21,43 @@ use super::x11::xinput2 as xi2;
 use super::atoms;
 use super::prop::{self, PropType, PropElement, PropData};
 use super::xrender;
+use super::xfixes;
+use super::xshape;
+use super::xkb;
+use super::xrandr;
+#[cfg(feature = "synthetic-input")]
+use super::xtest;
 use super::device::{XI2DeviceCache};
 use super::xi;
+use super::xlib_error;
+use super::compose;
 use super::glx;
+use super::egl;
+use super::dbus;
+use super::accessibility;
 use super::X11SharedWindow;
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasRawDisplayHandle, RawDisplayHandle, XlibDisplayHandle};
 
 
 /// On X11-based targets, a `Context` **owns** an Xlib `Display` pointer.
 impl Context {
     /// X11-only specialization of `new()` where you can specify
     /// the name given to `XOpenDisplay()`.
+    ///
+    /// This is a shorthand for `with_options()` with only `x11_display_name` set.
     pub fn with_x11_display_name(name: Option<&CStr>) -> Result<Self> {
-        X11Context::with_x11_display_name(name).map(OsContext::from).map(Context)
+        Self::with_options(&ContextOptions {
+            x11_display_name: name.map(CStr::to_owned),
+            .. Default::default()
+        })
     }
     /// X11-only specialization of `new()` where you **transfer ownership**
     /// of an existing, valid Xlib `Display` pointer.
     ///
     /// This function is unsafe because there's no guarantee that the pointer is valid.
     pub unsafe fn from_xlib_display(dpy: *mut x::Display) -> Result<Self> {
-        X11Context::from_xlib_display(dpy).map(OsContext::from).map(Context)
+        X11Context::from_xlib_display(dpy).map(OsContext::from).map(Context::from_os)
     }
     /// (X11-only) Gets the `Display` pointer associated with this `Context`.
     ///
@@ -46,7 +65,7 @@ impl Context {
     /// Also, it is not locked via `XLockDisplay()`. It's up to you to call it
     /// if necessary and call `XUnlockDisplay()` as appropriate.
     pub fn xlib_display(&self) -> *mut x::Display {
-        self.0.x11.x11_owned_display.0
+        self.os.x11.x11_owned_display.0
     }
     /// (X11-only) Calls `XSynchronize` to enable or disable synchronous behaviour.
     ///
@@ -55,11 +74,61 @@ impl Context {
     /// [this page](https://tronche.com/gui/x/xlib/event-handling/protocol-errors/synchronization.html).
     pub fn xlib_xsynchronize(&self, enable: bool) {
         unsafe {
-            x::XSynchronize(*self.0.x11.lock_x_display(), enable as _);
+            x::XSynchronize(*self.os.x11.lock_x_display(), enable as _);
+        }
+    }
+    /// (X11-only) Gets the atoms that were interned when this `Context` was created,
+    /// so that they can be used along with `intern_atom()` and the `Window` property functions.
+    pub fn atoms(&self) -> &atoms::PreloadedAtoms {
+        &self.os.x11.atoms
+    }
+    /// (X11-only) Gets the atom for the given name via `XInternAtom()`.
+    ///
+    /// If `create` is `false` and the atom doesn't exist yet, this returns an error.
+    pub fn intern_atom(&self, name: &str, create: bool) -> Result<x::Atom> {
+        let c_name = match CString::new(name) {
+            Ok(s) => s,
+            Err(_) => return invalid_arg(format!("Atom names can't contain NUL bytes: {:?}", name)),
+        };
+        let x_display = self.os.x11.lock_x_display();
+        let only_if_exists = if create { x::False } else { x::True };
+        // BadAlloc, BadValue
+        let atom = unsafe {
+            xlib_error::sync_catch(*x_display, || {
+                x::XInternAtom(*x_display, c_name.as_ptr(), only_if_exists)
+            })?
+        };
+        match atom {
+            0 => failed(format!("The `{}` atom does not exist", name)),
+            atom => Ok(atom),
+        }
+    }
+    /// (X11-only) Gets the name of the given atom via `XGetAtomName()`.
+    pub fn atom_name(&self, atom: x::Atom) -> Result<String> {
+        let x_display = self.os.x11.lock_x_display();
+        // BadAtom
+        unsafe {
+            let name = xlib_error::sync_catch(*x_display, || x::XGetAtomName(*x_display, atom))?;
+            if name.is_null() {
+                return failed(format!("XGetAtomName() returned NULL for atom {}", atom));
+            }
+            let s = CStr::from_ptr(name).to_string_lossy().into_owned();
+            x::XFree(name as _);
+            Ok(s)
         }
     }
 }
 
+#[cfg(feature = "raw-window-handle")]
+unsafe impl HasRawDisplayHandle for Context {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        let mut handle = XlibDisplayHandle::empty();
+        handle.display = self.xlib_display() as _;
+        handle.screen = self.os.x11.x_default_screen_num();
+        RawDisplayHandle::Xlib(handle)
+    }
+}
+
 #[derive(Debug)]
 pub struct X11Context(pub Rc<X11SharedContext>);
 
@@ -120,15 +189,51 @@ pub struct X11SharedContext {
     pub atoms: atoms::PreloadedAtoms,
     pub xrender: Result<xrender::XRender>,
     pub xi: Result<xi::XI>,
+    pub xfixes: Result<xfixes::XFixes>,
+    pub xshape: Result<xshape::XShape>,
+    pub xrr: Result<xrandr::XRR>,
+    pub xkb: Result<xkb::Xkb>,
+    #[cfg(feature = "synthetic-input")]
+    pub xtest: Result<xtest::XTest>,
     pub glx: Result<glx::Glx>,
+    // Loaded the first time an EGL context is requested, see `egl()`.
+    pub egl: RefCell<Option<Result<Rc<egl::Egl>>>>,
+    // Connected the first time a D-Bus service is needed, see `session_bus()`.
+    pub session_bus: RefCell<Option<Result<Rc<dbus::SessionBus>>>>,
     pub invisible_x_cursor: x::Cursor,
     pub default_x_cursor: x::Cursor,
     pub weak_windows: RefCell<HashMap<x::Window, Weak<X11SharedWindow>>>,
-    pub pending_translated_events: RefCell<VecDeque<Event>>,
+    pub(crate) pending_translated_events: RefCell<EventQueue>,
     // These two fields are used to detect key repeat events.
     pub previous_mouse_position: Cell<Option<Vec2<f64>>>,
     pub previous_xi_raw_key_event: Cell<(c_int, x::Time, x::KeyCode)>,
     pub xi2_devices: RefCell<HashMap<c_int, XI2DeviceCache>>,
+    // Dead keys and Compose key handling, for windows that have no XIC.
+    pub compose_state: Cell<compose::ComposeState>,
+    // Bumped each time a GL context is made current (or un-made current), so that windows can
+    // tell whether the context they were last made current with is still the current one.
+    pub gl_make_current_generation: Cell<u64>,
+    // Gamma ramps of each CRTC, as they were before we first modified them. Restored on drop.
+    pub original_gamma_ramps: RefCell<HashMap<xrandr::X11MonitorID, GammaRamps>>,
+    // Built on demand, and invalidated by XRandR events.
+    pub monitor_cache: RefCell<Option<xrandr::MonitorCache>>,
+    // From `ContextOptions`; used as the default `WM_CLASS` of new windows.
+    pub app_name: Option<String>,
+    // Last value returned by `accessibility_scale_factor()`; Changes are only watched once it's set.
+    pub accessibility_scale_factor: Cell<Option<f64>>,
+    // The background `gsettings` process that refreshes `accessibility_scale_factor`, if any.
+    pub accessibility_scale_factor_query: RefCell<Option<accessibility::AccessibilityScaleFactorQuery>>,
+    // The window that holds the `X11InputGrab`, if any.
+    pub input_grab_window: Cell<Option<x::Window>>,
+    // Timestamp of the last key or button press on any of our windows, or `CurrentTime` if none yet.
+    // Window managers use it to tell focus requests that follow user input from focus stealing.
+    pub last_user_time: Cell<x::Time>,
+    // Whether the `X11InputGrab` also grabs the keyboard.
+    pub input_grab_has_keyboard: Cell<bool>,
+    // The core keyboard's active XKB group, kept up to date by `XkbStateNotify` events.
+    pub xkb_current_group: Cell<u32>,
+    // For `ContextStats`.
+    pub queue_high_water_mark: Cell<usize>,
 }
 
 impl Deref for X11Context {
@@ -141,15 +246,41 @@ impl Deref for X11Context {
 impl Drop for X11SharedContext {
     fn drop(&mut self) {
         let &mut Self {
-            x11_owned_display: _, xim, atoms: _, xrender: _, xi: _, glx: _,
+            x11_owned_display: _, xim, atoms: _, xrender: _, xi: _, xfixes: _, xshape: _, xrr: _, xkb: _,
+            #[cfg(feature = "synthetic-input")]
+            xtest: _,
+            glx: _,
+            ref egl,
+            session_bus: _,
             invisible_x_cursor, default_x_cursor, weak_windows: _,
             pending_translated_events: _,
             previous_mouse_position: _,
             previous_xi_raw_key_event: _,
             xi2_devices: _,
+            compose_state: _,
+            gl_make_current_generation: _,
+            ref original_gamma_ramps,
+            app_name: _,
+            accessibility_scale_factor: _,
+            accessibility_scale_factor_query: _,
+            monitor_cache: _,
+            input_grab_window: _,
+            last_user_time: _,
+            input_grab_has_keyboard: _,
+            xkb_current_group: _,
+            queue_high_water_mark: _,
         } = self;
+        // eglTerminate() needs the X display to still be open.
+        drop(egl.borrow_mut().take());
         let x_display = self.lock_x_display();
         unsafe {
+            for (crtc, ramps) in original_gamma_ramps.borrow().iter() {
+                let status = xlib_error::sync_catch(*x_display, || xrandr::xrr_set_crtc_gamma(*x_display, *crtc, ramps));
+                match status {
+                    Ok(Ok(())) => trace!("Restored gamma ramps of CRTC {}", crtc),
+                    Ok(Err(e)) | Err(e) => warn!("Could not restore gamma ramps of CRTC {}: {}", crtc, e),
+                }
+            }
             x::XSync(*x_display, x::False);
             x::XFreeCursor(*x_display, invisible_x_cursor);
             x::XFreeCursor(*x_display, default_x_cursor);
@@ -220,13 +351,9 @@ unsafe fn init_all_extensions(x_display: *mut x::Display) {
 
 
 impl X11Context {
-    pub fn new() -> Result<Self> {
-        Self::with_x11_display_name(None)
-    }
-
-    pub fn with_x11_display_name(x_display_name: Option<&::std::ffi::CStr>) -> Result<Self> {
-        let x_display_name_ptr = match x_display_name {
-            Some(s) => s.as_ptr(),
+    pub fn with_options(options: &ContextOptions) -> Result<Self> {
+        let x_display_name_ptr = match options.x11_display_name {
+            Some(ref s) => s.as_ptr(),
             None => ptr::null(),
         };
         let x_display = unsafe {
@@ -237,18 +364,21 @@ impl X11Context {
             CStr::from_ptr(x::XDisplayString(x_display)).to_string_lossy()
         };
         if x_display.is_null() {
+            if options.allow_headless {
+                return unsupported(format!("Failed to open X display `{}`, and headless contexts are not supported yet", actual_name));
+            }
             return failed(format!("Failed to open X display `{}`", actual_name));
         }
         trace!("Opened X Display `{}`", actual_name);
-        Self::from_x11_owned_display(X11OwnedDisplay(x_display))
+        Self::from_x11_owned_display(X11OwnedDisplay(x_display), options.app_name.clone())
     }
 
     pub unsafe fn from_xlib_display(x_display: *mut x::Display) -> Result<Self> {
         assert!(!x_display.is_null());
-        Self::from_x11_owned_display(X11OwnedDisplay(x_display))
+        Self::from_x11_owned_display(X11OwnedDisplay(x_display), None)
     }
 
-    pub fn from_x11_owned_display(x11_owned_display: X11OwnedDisplay) -> Result<Self> {
+    pub fn from_x11_owned_display(x11_owned_display: X11OwnedDisplay, app_name: Option<String>) -> Result<Self> {
         let mut c = unsafe {
             let x_display = x11_owned_display.lock();
             let screen_count      = x::XScreenCount(*x_display);
@@ -267,6 +397,12 @@ impl X11Context {
             let default_x_cursor = super::cursor::create_default_x_cursor(*x_display);
             let xrender = super::xrender::XRender::query(*x_display);
             let xi = super::xi::XI::query(*x_display);
+            let xfixes = super::xfixes::XFixes::query(*x_display);
+            let xshape = super::xshape::XShape::query(*x_display);
+            let xrr = super::xrandr::XRR::query(*x_display);
+            let xkb = super::xkb::Xkb::query(*x_display);
+            #[cfg(feature = "synthetic-input")]
+            let xtest = super::xtest::XTest::query(*x_display);
             let glx = super::glx::Glx::query(*x_display);
 
             let xim = {
@@ -283,7 +419,7 @@ impl X11Context {
 
             let previous_mouse_position = Cell::new(None);
             let previous_xi_raw_key_event = Cell::default();
-            let pending_translated_events = RefCell::new(VecDeque::new());
+            let pending_translated_events = RefCell::new(EventQueue::default());
             let weak_windows = RefCell::new(HashMap::new());
             let xi2_devices = RefCell::new(super::device::xi2_query_device_info(*x_display, xi2::XIAllDevices, &atoms)
                 .unwrap()
@@ -299,15 +435,35 @@ impl X11Context {
                 .collect());
 
             X11SharedContext {
-                xim, atoms, xrender, xi, glx, invisible_x_cursor, default_x_cursor,
+                xim, atoms, xrender, xi, xfixes, xshape, xrr, xkb,
+                #[cfg(feature = "synthetic-input")]
+                xtest,
+                glx, invisible_x_cursor, default_x_cursor,
+                egl: RefCell::new(None),
+                session_bus: RefCell::new(None),
                 weak_windows, pending_translated_events,
                 previous_mouse_position,
                 previous_xi_raw_key_event,
                 xi2_devices,
+                compose_state: Cell::default(),
+                gl_make_current_generation: Cell::new(0),
+                original_gamma_ramps: RefCell::new(HashMap::new()),
+                monitor_cache: RefCell::new(None),
+                app_name,
+                accessibility_scale_factor: Cell::new(None),
+                accessibility_scale_factor_query: RefCell::new(None),
+                input_grab_window: Cell::new(None),
+                last_user_time: Cell::new(x::CurrentTime),
+                input_grab_has_keyboard: Cell::new(false),
+                xkb_current_group: Cell::new(0),
+                queue_high_water_mark: Cell::new(0),
                 x11_owned_display: mem::zeroed(), // Can't move x11_owned_display because it is borrowed
             }
         };
         mem::forget(mem::replace(&mut c.x11_owned_display, x11_owned_display));
+        if let Ok(group) = c.xkb_query_current_group(super::missing_bits::xkb::XkbUseCoreKbd) {
+            c.xkb_current_group.set(group);
+        }
         Ok(X11Context(Rc::new(c)))
     }
 }
@@ -324,6 +480,31 @@ impl X11SharedContext {
             x::XDefaultScreen(*self.lock_x_display())
         }
     }
+    pub fn backend_info(&self) -> BackendInfo {
+        let mut info = BackendInfo::new(BackendKind::X11);
+        unsafe {
+            let x_display = self.lock_x_display();
+            let vendor = x::XServerVendor(*x_display);
+            if !vendor.is_null() {
+                info.server_vendor = Some(CStr::from_ptr(vendor).to_string_lossy().into_owned());
+            }
+            info.server_vendor_release = Some(x::XVendorRelease(*x_display) as _);
+            info.protocol_version = Some((x::XProtocolVersion(*x_display) as _, x::XProtocolRevision(*x_display) as _));
+        }
+        info.glx_version = self.glx().ok().map(|glx| (glx.major_version as _, glx.minor_version as _));
+        info.xinput_version = self.xi().ok().map(|xi| (xi.major_version as _, xi.minor_version as _));
+        info.xrender_version = self.xrender().ok().map(|xr| (xr.major_version as _, xr.minor_version as _));
+        info
+    }
+    pub fn screen_count(&self) -> Result<usize> {
+        let count = unsafe {
+            x::XScreenCount(*self.lock_x_display())
+        };
+        if count <= 0 {
+            return failed(format!("XScreenCount() returned {}", count));
+        }
+        Ok(count as _)
+    }
     pub fn x_default_root_window(&self) -> x::Window {
         unsafe {
             x::XDefaultRootWindow(*self.lock_x_display())
@@ -365,6 +546,10 @@ impl X11SharedContext {
     pub fn current_desktop(&self) -> Result<usize> {
         self.root_prop::<c_ulong>(self.atoms._NET_CURRENT_DESKTOP()?, PropType::Any, 0..1).map(|pd| pd.data[0] as _)
     }
+    /// The list of EWMH atoms the window manager claims to support.
+    pub fn net_supported(&self) -> Result<Vec<c_ulong>> {
+        self.root_prop::<c_ulong>(self.atoms._NET_SUPPORTED()?, PropType::Atom, 0..1024).map(|pd| pd.data)
+    }
     pub fn desktops(&self) -> Result<Vec<Desktop>> {
         let nb_desktops = self.number_of_desktops()?;
 
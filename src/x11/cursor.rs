@@ -33,7 +33,7 @@ impl Drop for X11SharedCursor {
 }
 
 impl X11SharedWindow {
-    fn refresh_cursor_internal(&self) -> Result<()> {
+    pub fn refresh_cursor_internal(&self) -> Result<()> {
         let x_display = self.context.lock_x_display();
         unsafe {
             if self.is_cursor_visible.get() {
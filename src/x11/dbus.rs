@@ -0,0 +1,491 @@
+//! A private connection to the D-Bus session bus, for the desktop services that X11 has no
+//! standard for (file dialogs via the XDG Desktop Portal, taskbar progress, ...).
+//!
+//! libdbus is loaded at runtime, the first time the session bus is needed, so that it is not
+//! a hard dependency. A missing libdbus or session bus is reported as "unsupported".
+//!
+//! Only the few parts of the API that we need are exposed, as thin wrappers around
+//! `DBusMessageIter` for writing and reading message arguments.
+
+#![allow(non_snake_case)]
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_void, c_char, c_int, c_uint};
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+use super::libc;
+use super::X11SharedContext;
+use error::{Result, failed, unsupported};
+
+pub enum DBusConnection {}
+pub enum DBusMessage {}
+#[allow(non_camel_case_types)]
+pub type dbus_bool_t = u32;
+
+#[repr(C)]
+pub struct DBusError {
+    pub name: *const c_char,
+    pub message: *const c_char,
+    dummy: c_uint,
+    padding1: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct DBusMessageIter {
+    dummy1: *mut c_void,
+    dummy2: *mut c_void,
+    dummy3: u32,
+    dummy4: c_int,
+    dummy5: c_int,
+    dummy6: c_int,
+    dummy7: c_int,
+    dummy8: c_int,
+    dummy9: c_int,
+    dummy10: c_int,
+    dummy11: c_int,
+    pad1: c_int,
+    pad2: *mut c_void,
+    pad3: *mut c_void,
+}
+
+pub const DBUS_BUS_SESSION: c_int = 0;
+pub const DBUS_TYPE_INVALID: c_int = 0;
+pub const DBUS_TYPE_BYTE: c_int = b'y' as _;
+pub const DBUS_TYPE_BOOLEAN: c_int = b'b' as _;
+pub const DBUS_TYPE_UINT32: c_int = b'u' as _;
+pub const DBUS_TYPE_DOUBLE: c_int = b'd' as _;
+pub const DBUS_TYPE_STRING: c_int = b's' as _;
+pub const DBUS_TYPE_OBJECT_PATH: c_int = b'o' as _;
+pub const DBUS_TYPE_ARRAY: c_int = b'a' as _;
+pub const DBUS_TYPE_VARIANT: c_int = b'v' as _;
+pub const DBUS_TYPE_STRUCT: c_int = b'r' as _;
+pub const DBUS_TYPE_DICT_ENTRY: c_int = b'e' as _;
+pub const DBUS_ERROR_SERVICE_UNKNOWN: &str = "org.freedesktop.DBus.Error.ServiceUnknown";
+pub const DBUS_ERROR_UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+pub const DBUS_ERROR_UNKNOWN_OBJECT: &str = "org.freedesktop.DBus.Error.UnknownObject";
+pub const DBUS_ERROR_UNKNOWN_INTERFACE: &str = "org.freedesktop.DBus.Error.UnknownInterface";
+
+pub mod fn_types {
+    #![allow(non_camel_case_types)]
+
+    use std::os::raw::{c_void, c_char, c_int};
+    use super::*;
+
+    pub type dbus_error_init = unsafe extern "C" fn(*mut DBusError);
+    pub type dbus_error_free = unsafe extern "C" fn(*mut DBusError);
+    pub type dbus_bus_get_private = unsafe extern "C" fn(c_int, *mut DBusError) -> *mut DBusConnection;
+    pub type dbus_bus_add_match = unsafe extern "C" fn(*mut DBusConnection, *const c_char, *mut DBusError);
+    pub type dbus_bus_remove_match = unsafe extern "C" fn(*mut DBusConnection, *const c_char, *mut DBusError);
+    pub type dbus_connection_set_exit_on_disconnect = unsafe extern "C" fn(*mut DBusConnection, dbus_bool_t);
+    pub type dbus_connection_close = unsafe extern "C" fn(*mut DBusConnection);
+    pub type dbus_connection_unref = unsafe extern "C" fn(*mut DBusConnection);
+    pub type dbus_connection_send = unsafe extern "C" fn(*mut DBusConnection, *mut DBusMessage, *mut u32) -> dbus_bool_t;
+    pub type dbus_connection_flush = unsafe extern "C" fn(*mut DBusConnection);
+    pub type dbus_connection_send_with_reply_and_block = unsafe extern "C" fn(*mut DBusConnection, *mut DBusMessage, c_int, *mut DBusError) -> *mut DBusMessage;
+    pub type dbus_connection_read_write = unsafe extern "C" fn(*mut DBusConnection, c_int) -> dbus_bool_t;
+    pub type dbus_connection_pop_message = unsafe extern "C" fn(*mut DBusConnection) -> *mut DBusMessage;
+    pub type dbus_message_new_method_call = unsafe extern "C" fn(*const c_char, *const c_char, *const c_char, *const c_char) -> *mut DBusMessage;
+    pub type dbus_message_new_signal = unsafe extern "C" fn(*const c_char, *const c_char, *const c_char) -> *mut DBusMessage;
+    pub type dbus_message_unref = unsafe extern "C" fn(*mut DBusMessage);
+    pub type dbus_message_is_signal = unsafe extern "C" fn(*mut DBusMessage, *const c_char, *const c_char) -> dbus_bool_t;
+    pub type dbus_message_has_path = unsafe extern "C" fn(*mut DBusMessage, *const c_char) -> dbus_bool_t;
+    pub type dbus_message_iter_init = unsafe extern "C" fn(*mut DBusMessage, *mut DBusMessageIter) -> dbus_bool_t;
+    pub type dbus_message_iter_init_append = unsafe extern "C" fn(*mut DBusMessage, *mut DBusMessageIter);
+    pub type dbus_message_iter_append_basic = unsafe extern "C" fn(*mut DBusMessageIter, c_int, *const c_void) -> dbus_bool_t;
+    pub type dbus_message_iter_open_container = unsafe extern "C" fn(*mut DBusMessageIter, c_int, *const c_char, *mut DBusMessageIter) -> dbus_bool_t;
+    pub type dbus_message_iter_close_container = unsafe extern "C" fn(*mut DBusMessageIter, *mut DBusMessageIter) -> dbus_bool_t;
+    pub type dbus_message_iter_get_arg_type = unsafe extern "C" fn(*mut DBusMessageIter) -> c_int;
+    pub type dbus_message_iter_get_basic = unsafe extern "C" fn(*mut DBusMessageIter, *mut c_void);
+    pub type dbus_message_iter_recurse = unsafe extern "C" fn(*mut DBusMessageIter, *mut DBusMessageIter);
+    pub type dbus_message_iter_next = unsafe extern "C" fn(*mut DBusMessageIter) -> dbus_bool_t;
+}
+
+macro_rules! dbus_fns {
+    ($($func:ident)+) => {
+        #[derive(Debug, Copy, Clone)]
+        pub struct DBusFns {
+            $(pub $func: fn_types::$func,)+
+        }
+        impl DBusFns {
+            unsafe fn load(lib: *mut c_void) -> Result<Self> {
+                $(
+                    let $func = libc::dlsym(lib, concat!(stringify!($func), "\0").as_ptr() as *const c_char);
+                    if $func.is_null() {
+                        return failed(concat!("libdbus doesn't export `", stringify!($func), "`"));
+                    }
+                )+
+                Ok(Self { $($func: mem::transmute::<*mut c_void, fn_types::$func>($func),)+ })
+            }
+        }
+    };
+}
+
+dbus_fns!(
+    dbus_error_init
+    dbus_error_free
+    dbus_bus_get_private
+    dbus_bus_add_match
+    dbus_bus_remove_match
+    dbus_connection_set_exit_on_disconnect
+    dbus_connection_close
+    dbus_connection_unref
+    dbus_connection_send
+    dbus_connection_flush
+    dbus_connection_send_with_reply_and_block
+    dbus_connection_read_write
+    dbus_connection_pop_message
+    dbus_message_new_method_call
+    dbus_message_new_signal
+    dbus_message_unref
+    dbus_message_is_signal
+    dbus_message_has_path
+    dbus_message_iter_init
+    dbus_message_iter_init_append
+    dbus_message_iter_append_basic
+    dbus_message_iter_open_container
+    dbus_message_iter_close_container
+    dbus_message_iter_get_arg_type
+    dbus_message_iter_get_basic
+    dbus_message_iter_recurse
+    dbus_message_iter_next
+);
+
+/// An error reported by libdbus, e.g in reply to a method call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DBusCallError {
+    pub name: String,
+    pub message: String,
+}
+
+impl DBusCallError {
+    /// Does this error mean that the service (or the requested part of it) doesn't exist?
+    pub fn is_unknown_service(&self) -> bool {
+        [DBUS_ERROR_SERVICE_UNKNOWN, DBUS_ERROR_UNKNOWN_METHOD, DBUS_ERROR_UNKNOWN_OBJECT, DBUS_ERROR_UNKNOWN_INTERFACE].contains(&self.name.as_str())
+    }
+}
+
+/// A private connection to the session bus, along with the functions of libdbus.
+#[derive(Debug)]
+pub struct SessionBus {
+    lib: *mut c_void,
+    connection: *mut DBusConnection,
+    pub fns: DBusFns,
+}
+
+impl Drop for SessionBus {
+    fn drop(&mut self) {
+        unsafe {
+            // Private connections must be closed before the last reference is dropped.
+            (self.fns.dbus_connection_close)(self.connection);
+            (self.fns.dbus_connection_unref)(self.connection);
+            libc::dlclose(self.lib);
+        }
+    }
+}
+
+impl X11SharedContext {
+    /// Connects to the session bus the first time it is called. Failure is remembered as well.
+    pub fn session_bus(&self) -> Result<Rc<SessionBus>> {
+        let mut session_bus = self.session_bus.borrow_mut();
+        if session_bus.is_none() {
+            *session_bus = Some(unsafe { SessionBus::connect() }.map(Rc::new));
+        }
+        session_bus.as_ref().unwrap().clone()
+    }
+}
+
+/// Runs `f` with an initialized `DBusError`, which is converted to a `DBusCallError` if set afterwards.
+unsafe fn with_error<T, F: FnOnce(*mut DBusError) -> T>(fns: &DBusFns, f: F) -> ::std::result::Result<T, DBusCallError> {
+    let mut err: DBusError = mem::zeroed();
+    (fns.dbus_error_init)(&mut err);
+    let value = f(&mut err);
+    if err.name.is_null() {
+        return Ok(value);
+    }
+    let call_error = DBusCallError {
+        name: CStr::from_ptr(err.name).to_string_lossy().into_owned(),
+        message: if err.message.is_null() { String::new() } else { CStr::from_ptr(err.message).to_string_lossy().into_owned() },
+    };
+    (fns.dbus_error_free)(&mut err);
+    Err(call_error)
+}
+
+impl SessionBus {
+    unsafe fn connect() -> Result<Self> {
+        let lib = [&b"libdbus-1.so.3\0"[..], &b"libdbus-1.so\0"[..]].iter()
+            .map(|name| libc::dlopen(name.as_ptr() as *const c_char, libc::RTLD_NOW | libc::RTLD_LOCAL))
+            .find(|lib| !lib.is_null());
+        let lib = match lib {
+            Some(lib) => lib,
+            None => return unsupported("Could not load libdbus"),
+        };
+        let fns = match DBusFns::load(lib) {
+            Ok(fns) => fns,
+            Err(e) => {
+                libc::dlclose(lib);
+                return Err(e);
+            },
+        };
+        let connection = match with_error(&fns, |err| (fns.dbus_bus_get_private)(DBUS_BUS_SESSION, err)) {
+            Ok(connection) if !connection.is_null() => connection,
+            Ok(_) => {
+                libc::dlclose(lib);
+                return unsupported("Could not connect to the D-Bus session bus");
+            },
+            Err(e) => {
+                libc::dlclose(lib);
+                return unsupported(format!("Could not connect to the D-Bus session bus: {}", e.message));
+            },
+        };
+        // By default, libdbus calls `_exit()` when the bus goes away.
+        (fns.dbus_connection_set_exit_on_disconnect)(connection, 0);
+        Ok(Self { lib, connection, fns })
+    }
+    pub fn new_method_call(&self, destination: &str, path: &str, interface: &str, method: &str) -> Result<Message> {
+        let (destination, path, interface, method) = (cstring(destination), cstring(path), cstring(interface), cstring(method));
+        let msg = unsafe {
+            (self.fns.dbus_message_new_method_call)(destination.as_ptr(), path.as_ptr(), interface.as_ptr(), method.as_ptr())
+        };
+        Message::from_raw(self, msg)
+    }
+    pub fn new_signal(&self, path: &str, interface: &str, name: &str) -> Result<Message> {
+        let (path, interface, name) = (cstring(path), cstring(interface), cstring(name));
+        let msg = unsafe {
+            (self.fns.dbus_message_new_signal)(path.as_ptr(), interface.as_ptr(), name.as_ptr())
+        };
+        Message::from_raw(self, msg)
+    }
+    /// Writes `msg` out without waiting for any reply.
+    pub fn send(&self, msg: &Message) -> Result<()> {
+        unsafe {
+            if (self.fns.dbus_connection_send)(self.connection, msg.msg, ptr::null_mut()) == 0 {
+                return failed("dbus_connection_send() failed (out of memory)");
+            }
+            (self.fns.dbus_connection_flush)(self.connection);
+        }
+        Ok(())
+    }
+    /// Sends a method call and blocks until its reply, or an error, is received.
+    pub fn call(&self, msg: &Message, timeout_ms: c_int) -> ::std::result::Result<Message, DBusCallError> {
+        unsafe {
+            let reply = with_error(&self.fns, |err| (self.fns.dbus_connection_send_with_reply_and_block)(self.connection, msg.msg, timeout_ms, err))?;
+            Ok(Message { fns: self.fns, msg: reply })
+        }
+    }
+    pub fn add_match(&self, rule: &str) -> ::std::result::Result<(), DBusCallError> {
+        let rule = cstring(rule);
+        unsafe {
+            with_error(&self.fns, |err| (self.fns.dbus_bus_add_match)(self.connection, rule.as_ptr(), err))
+        }
+    }
+    pub fn remove_match(&self, rule: &str) -> ::std::result::Result<(), DBusCallError> {
+        let rule = cstring(rule);
+        unsafe {
+            with_error(&self.fns, |err| (self.fns.dbus_bus_remove_match)(self.connection, rule.as_ptr(), err))
+        }
+    }
+    /// Reads and writes pending data for at most `timeout_ms`, then returns the next incoming
+    /// message, if any. Returns an error if the connection was closed.
+    pub fn next_message(&self, timeout_ms: c_int) -> Result<Option<Message>> {
+        unsafe {
+            let msg = (self.fns.dbus_connection_pop_message)(self.connection);
+            if !msg.is_null() {
+                return Ok(Some(Message { fns: self.fns, msg }));
+            }
+            if (self.fns.dbus_connection_read_write)(self.connection, timeout_ms) == 0 {
+                return failed("The D-Bus session bus connection was closed");
+            }
+            let msg = (self.fns.dbus_connection_pop_message)(self.connection);
+            Ok(if msg.is_null() { None } else { Some(Message { fns: self.fns, msg }) })
+        }
+    }
+}
+
+fn cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap()
+}
+
+/// An owned `DBusMessage`.
+#[derive(Debug)]
+pub struct Message {
+    fns: DBusFns,
+    msg: *mut DBusMessage,
+}
+
+impl Drop for Message {
+    fn drop(&mut self) {
+        unsafe {
+            (self.fns.dbus_message_unref)(self.msg);
+        }
+    }
+}
+
+impl Message {
+    fn from_raw(bus: &SessionBus, msg: *mut DBusMessage) -> Result<Self> {
+        if msg.is_null() {
+            return failed("Could not create a D-Bus message (out of memory)");
+        }
+        Ok(Message { fns: bus.fns, msg })
+    }
+    pub fn is_signal(&self, interface: &str, name: &str) -> bool {
+        let (interface, name) = (cstring(interface), cstring(name));
+        unsafe {
+            (self.fns.dbus_message_is_signal)(self.msg, interface.as_ptr(), name.as_ptr()) != 0
+        }
+    }
+    pub fn has_path(&self, path: &str) -> bool {
+        let path = cstring(path);
+        unsafe {
+            (self.fns.dbus_message_has_path)(self.msg, path.as_ptr()) != 0
+        }
+    }
+    /// Appends arguments to this message.
+    pub fn append<'a>(&'a mut self) -> Appender<'a> {
+        unsafe {
+            let mut iter = mem::zeroed();
+            (self.fns.dbus_message_iter_init_append)(self.msg, &mut iter);
+            Appender { fns: &self.fns, iter, failed: false }
+        }
+    }
+    /// Reads the arguments of this message.
+    pub fn read<'a>(&'a self) -> Reader<'a> {
+        unsafe {
+            let mut iter = mem::zeroed();
+            let has_args = (self.fns.dbus_message_iter_init)(self.msg, &mut iter) != 0;
+            Reader { fns: &self.fns, iter, has_args }
+        }
+    }
+}
+
+/// Writes arguments of a message, in order.
+///
+/// Failures (which only happen when out of memory) are reported by `finish()`.
+pub struct Appender<'a> {
+    fns: &'a DBusFns,
+    iter: DBusMessageIter,
+    failed: bool,
+}
+
+impl<'a> Appender<'a> {
+    fn basic(&mut self, type_: c_int, value: *const c_void) -> &mut Self {
+        unsafe {
+            if (self.fns.dbus_message_iter_append_basic)(&mut self.iter, type_, value) == 0 {
+                self.failed = true;
+            }
+        }
+        self
+    }
+    pub fn string(&mut self, s: &str) -> &mut Self {
+        let s = cstring(s);
+        let ptr = s.as_ptr();
+        self.basic(DBUS_TYPE_STRING, &ptr as *const _ as *const c_void)
+    }
+    pub fn boolean(&mut self, b: bool) -> &mut Self {
+        let b = b as dbus_bool_t;
+        self.basic(DBUS_TYPE_BOOLEAN, &b as *const _ as *const c_void)
+    }
+    pub fn uint32(&mut self, u: u32) -> &mut Self {
+        self.basic(DBUS_TYPE_UINT32, &u as *const _ as *const c_void)
+    }
+    pub fn double(&mut self, d: f64) -> &mut Self {
+        self.basic(DBUS_TYPE_DOUBLE, &d as *const _ as *const c_void)
+    }
+    pub fn byte_array(&mut self, bytes: &[u8]) -> &mut Self {
+        self.container(DBUS_TYPE_ARRAY, Some("y"), |a| {
+            for b in bytes.iter() {
+                a.basic(DBUS_TYPE_BYTE, b as *const _ as *const c_void);
+            }
+        })
+    }
+    /// Opens a container (`signature` is that of the contents, for arrays and variants),
+    /// lets `f` fill it, then closes it.
+    pub fn container<F: FnOnce(&mut Appender<'a>)>(&mut self, type_: c_int, signature: Option<&str>, f: F) -> &mut Self {
+        let signature = signature.map(cstring);
+        unsafe {
+            let mut sub = Appender { fns: self.fns, iter: mem::zeroed(), failed: false };
+            let signature = signature.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+            if (self.fns.dbus_message_iter_open_container)(&mut self.iter, type_, signature, &mut sub.iter) == 0 {
+                self.failed = true;
+                return self;
+            }
+            f(&mut sub);
+            self.failed |= sub.failed;
+            if (self.fns.dbus_message_iter_close_container)(&mut self.iter, &mut sub.iter) == 0 {
+                self.failed = true;
+            }
+        }
+        self
+    }
+    /// Appends an entry of an `a{sv}` dictionary, `f` being given the variant's contents.
+    pub fn dict_entry<F: FnOnce(&mut Appender<'a>)>(&mut self, key: &str, value_signature: &str, f: F) -> &mut Self {
+        self.container(DBUS_TYPE_DICT_ENTRY, None, |entry| {
+            entry.string(key).container(DBUS_TYPE_VARIANT, Some(value_signature), f);
+        })
+    }
+    pub fn finish(&self) -> Result<()> {
+        if self.failed {
+            return failed("Could not append D-Bus message arguments (out of memory)");
+        }
+        Ok(())
+    }
+}
+
+/// Reads arguments of a message, in order.
+pub struct Reader<'a> {
+    fns: &'a DBusFns,
+    iter: DBusMessageIter,
+    has_args: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// The type of the current argument, or `DBUS_TYPE_INVALID` past the end.
+    pub fn arg_type(&mut self) -> c_int {
+        if !self.has_args {
+            return DBUS_TYPE_INVALID;
+        }
+        unsafe {
+            (self.fns.dbus_message_iter_get_arg_type)(&mut self.iter)
+        }
+    }
+    /// Moves to the next argument, returning `false` if there's none.
+    pub fn next(&mut self) -> bool {
+        self.has_args = self.has_args && unsafe { (self.fns.dbus_message_iter_next)(&mut self.iter) != 0 };
+        self.has_args
+    }
+    pub fn uint32(&mut self) -> Option<u32> {
+        if self.arg_type() != DBUS_TYPE_UINT32 {
+            return None;
+        }
+        let mut u = 0_u32;
+        unsafe {
+            (self.fns.dbus_message_iter_get_basic)(&mut self.iter, &mut u as *mut _ as *mut c_void);
+        }
+        Some(u)
+    }
+    pub fn string(&mut self) -> Option<String> {
+        match self.arg_type() {
+            DBUS_TYPE_STRING | DBUS_TYPE_OBJECT_PATH => (),
+            _ => return None,
+        }
+        let mut s: *const c_char = ptr::null();
+        unsafe {
+            (self.fns.dbus_message_iter_get_basic)(&mut self.iter, &mut s as *mut _ as *mut c_void);
+            Some(CStr::from_ptr(s).to_string_lossy().into_owned())
+        }
+    }
+    /// Reads the contents of the current container argument (array, variant, struct, dict entry).
+    pub fn recurse(&mut self) -> Option<Reader<'a>> {
+        match self.arg_type() {
+            DBUS_TYPE_ARRAY | DBUS_TYPE_VARIANT | DBUS_TYPE_STRUCT | DBUS_TYPE_DICT_ENTRY => (),
+            _ => return None,
+        }
+        unsafe {
+            let mut sub = Reader { fns: self.fns, iter: mem::zeroed(), has_args: true };
+            (self.fns.dbus_message_iter_recurse)(&mut self.iter, &mut sub.iter);
+            sub.has_args = sub.arg_type() != DBUS_TYPE_INVALID;
+            Some(sub)
+        }
+    }
+}
@@ -1,12 +1,17 @@
 use std::slice;
 use std::ptr;
 use std::mem;
-use std::os::raw::c_int;
+use std::os::raw::{c_int, c_uint};
 use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt;
 use error::{Result, failed};
 use super::x11::xinput2 as xi2;
 use super::x11::xlib as x;
+use super::x11::keysym::{XK_KP_Space, XK_KP_Equal};
 use super::xlib_error;
+use super::keysym_ucs;
+use super::missing_bits::xkb;
 use super::X11SharedContext;
 use super::atoms::PreloadedAtoms;
 use device::{
@@ -14,9 +19,9 @@ use device::{
     DeviceID, DeviceInfo, ButtonState, UsbIDs, Bus, AxisInfo,
     ControllerButton, ControllerAxis, ControllerState, ControllerInfo,
     VibrationState,
-    KeyboardInfo, KeyState, KeyboardState, Keysym, Keycode,
+    KeyboardInfo, KeyState, KeyboardState, Keysym, Keycode, ModifiersState,
     MouseInfo, MouseState, MouseButton,
-    TabletInfo, TabletState, TabletPadButton, TabletStylusButton,
+    TabletInfo, TabletState, TabletPadButton, TabletStylusButton, TabletTool,
     TouchInfo,
 };
 use Vec2;
@@ -28,6 +33,16 @@ pub enum X11DeviceID {
     XISlave(c_int),
 }
 
+impl fmt::Display for X11DeviceID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            X11DeviceID::CoreKeyboard => write!(f, "core-keyboard"),
+            X11DeviceID::CorePointer => write!(f, "core-pointer"),
+            X11DeviceID::XISlave(x) => write!(f, "{}", x),
+        }
+    }
+}
+
 impl X11DeviceID {
     pub fn xi_device_id(&self) -> device::Result<c_int> {
         match *self {
@@ -79,7 +94,33 @@ impl X11TabletStylusButtonsState {
     }
 }
 
+fn x_keysym_name(x_keysym: x::KeySym) -> Option<String> {
+    // NOTE: Points to static data; must not be freed.
+    let name = unsafe { x::XKeysymToString(x_keysym) };
+    if name.is_null() {
+        return None;
+    }
+    Some(unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned())
+}
+
+/// XKB accepts XInput device IDs wherever it expects a keyboard.
+fn xkb_device_spec(keyboard: X11DeviceID) -> device::Result<c_uint> {
+    match keyboard {
+        X11DeviceID::CoreKeyboard => Ok(xkb::XkbUseCoreKbd),
+        X11DeviceID::XISlave(deviceid) => Ok(deviceid as _),
+        X11DeviceID::CorePointer => device::not_supported_by_device("The core pointer has no keyboard groups"),
+    }
+}
+
 impl X11SharedContext {
+    /// XI device IDs are reused by the server, so there's no token to check; The best we can
+    /// do is to check that the device is in our cache, which follows hierarchy changes.
+    pub fn is_device_id_current(&self, id: X11DeviceID) -> bool {
+        match id {
+            X11DeviceID::CoreKeyboard | X11DeviceID::CorePointer => true,
+            X11DeviceID::XISlave(deviceid) => self.xi2_devices.borrow().contains_key(&deviceid),
+        }
+    }
     pub fn keyboard_state(&self, keyboard: X11DeviceID) -> device::Result<KeyboardState> {
         /*
         let x_display = self.lock_x_display();
@@ -101,14 +142,70 @@ impl X11SharedContext {
         unimplemented!{}
     }
     pub fn keysym_name(&self, keysym: Keysym) -> device::Result<String> {
-        unimplemented!{}
+        match keysym.x_keysym().and_then(x_keysym_name) {
+            Some(name) => Ok(name),
+            None => device::failed(format!("XKeysymToString() has no name for {:?}", keysym)),
+        }
+    }
+    pub fn keysym_to_char(&self, keysym: Keysym, modifiers: ModifiersState) -> Option<char> {
+        let x_keysym = keysym.x_keysym()?;
+        let keycode = unsafe {
+            x::XKeysymToKeycode(*self.lock_x_display(), x_keysym)
+        };
+        if keycode == 0 {
+            // Not on the keyboard, so there are no levels to pick from.
+            return keysym_ucs::x_keysym_to_char(x_keysym);
+        }
+        let group = self.xkb_current_group.get();
+        let level_keysym = |level| self.xkb_keycode_to_keysym(keycode, group, level);
+        // Keypad keys give digits at level 1, and Num Lock inverts the effect of Shift on them.
+        let is_keypad = level_keysym(1).map_or(false, |sym| sym >= XK_KP_Space as _ && sym <= XK_KP_Equal as _);
+        let shift = if is_keypad { modifiers.shift != modifiers.num_lock } else { modifiers.shift };
+        let base_level = if modifiers.alt_gr { 2 } else { 0 };
+        let mut x_keysym = level_keysym(base_level + shift as c_int).or_else(|| level_keysym(base_level))?;
+        if modifiers.caps_lock && !is_keypad {
+            // Caps Lock inverts the effect of Shift on letters; Other keysyms have no case.
+            let (mut lower, mut upper) = (0, 0);
+            unsafe {
+                x::XConvertCase(x_keysym, &mut lower, &mut upper);
+            }
+            x_keysym = if modifiers.shift { lower } else { upper };
+        }
+        keysym_ucs::x_keysym_to_char(x_keysym)
     }
     pub fn keysym_from_keycode(&self, keyboard: X11DeviceID, keycode: Keycode) -> device::Result<Keysym> {
-        unimplemented!{}
+        // FIXME: We're ignoring the keyboard ID; XKB only tells us about the core keyboard.
+        let group = self.xkb_current_group.get();
+        match self.xkb_keycode_to_keysym(keycode.0, group, 0) {
+            Some(x_keysym) => Ok(Keysym::from_x_keysym(x_keysym)),
+            None => device::failed(format!("{:?} has no keysym in group {}", keycode, group)),
+        }
+    }
+    pub fn keyboard_current_group(&self, keyboard: X11DeviceID) -> device::Result<u32> {
+        // Ask the server rather than relying on `xkb_current_group`, which is only as recent
+        // as the last time events were polled (and only tracks the core keyboard).
+        self.xkb_query_current_group(xkb_device_spec(keyboard)?).map_err(device::Error::Other)
+    }
+    pub fn set_keyboard_current_group(&self, keyboard: X11DeviceID, group: u32) -> device::Result<()> {
+        self.xkb_lock_group(xkb_device_spec(keyboard)?, group).map_err(device::Error::Other)
+    }
+    pub fn keyboard_group_names(&self, keyboard: X11DeviceID) -> device::Result<Vec<String>> {
+        self.xkb_group_names(xkb_device_spec(keyboard)?).map_err(device::Error::Other)
     }
     pub fn keycode_from_keysym(&self, keyboard: X11DeviceID, keysym: Keysym) -> device::Result<Keycode> {
         unimplemented!{}
     }
+    pub fn dump_keymap(&self) -> Vec<(Keycode, Keysym, String)> {
+        let (mut min_keycode, mut max_keycode) = (0, 0);
+        unsafe {
+            x::XDisplayKeycodes(*self.lock_x_display(), &mut min_keycode, &mut max_keycode);
+        }
+        (min_keycode ..= max_keycode).filter_map(|keycode| {
+            let x_keysym = self.x_keycode_to_keysym(keycode as _, 0)?;
+            let name = x_keysym_name(x_keysym).unwrap_or_else(|| format!("{:#x}", x_keysym));
+            Some((Keycode(keycode as _), Keysym::from_x_keysym(x_keysym), name))
+        }).collect()
+    }
     pub fn mouse_state(&self, mouse: X11DeviceID) -> device::Result<MouseState> {
         unimplemented!{}
     }
@@ -139,6 +236,10 @@ pub struct XI2DeviceInfo {
 }
 
 impl XI2DeviceInfo {
+    /// The range and mode of each valuator, by index; Used to tell whether they changed.
+    pub fn axis_infos(&self) -> HashMap<usize, XI2AxisInfo> {
+        self.valuator_classes.iter().map(|(i, class)| (*i, class.axis_info)).collect()
+    }
     pub fn replace_classes<I: IntoIterator<Item=XI2DeviceAnyClassInfo>>(&mut self, classes: I, atoms: &PreloadedAtoms) {
         self.key_class = None;
         self.button_class = None;
@@ -600,6 +701,46 @@ impl XI2DeviceProperty {
     }
 }
 
+/// Parses the value of the "Wacom Serial IDs" property, returning the serial number and hardware
+/// ID of the tool that is in proximity. The serial number is 0 when no tool is in proximity.
+pub fn wacom_tool_in_proximity(serial_ids: &XI2DeviceProperty) -> Option<(u32, u32)> {
+    // [tablet ID, previous serial, previous hardware ID, serial, hardware ID]
+    match serial_ids.as_slice::<u32>() {
+        Some(ids) if ids.len() >= 5 => Some((ids[3], ids[4])),
+        _ => None,
+    }
+}
+
+/// Identifies a tool from its Wacom hardware ID (same table as the Linux kernel's
+/// `wacom_intuos_get_tool_type()`), falling back to the device's "Wacom Tool Type" property.
+pub fn wacom_tablet_tool(hw_tool_id: u32, wacom_tool_type: Option<x::Atom>, atoms: &PreloadedAtoms) -> TabletTool {
+    match hw_tool_id {
+        0x812 | 0x801 | 0x12802 | 0x012 => return TabletTool::Pencil,
+        0x832 | 0x032 => return TabletTool::Brush,
+        0x007 | 0x09c | 0x094 | 0x017 | 0x806 => return TabletTool::Mouse,
+        0x096 | 0x097 | 0x006 => return TabletTool::Lens,
+        0x82a | 0x85a | 0x91a | 0xd1a | 0x0fa | 0x82b | 0x81b | 0x91b | 0x80c | 0x80a | 0x90a |
+        0x1480a | 0x1090a | 0x1080c | 0x1680a | 0x1880a => return TabletTool::Eraser,
+        0xd12 | 0x912 | 0x112 | 0x913 | 0x902 | 0x10902 => return TabletTool::Airbrush,
+        0x822 | 0x842 | 0x852 | 0x823 | 0x813 | 0x802 | 0x022 |
+        0x10804 | 0x10842 | 0x14802 | 0x16802 | 0x18802 | 0x10802 => return TabletTool::Pen,
+        _ => (),
+    };
+    let wacom_tool_type = match wacom_tool_type {
+        Some(t) => t,
+        None => return TabletTool::Unknown,
+    };
+    if Ok(wacom_tool_type) == atoms.STYLUS() {
+        TabletTool::Pen
+    } else if Ok(wacom_tool_type) == atoms.ERASER() {
+        TabletTool::Eraser
+    } else if Ok(wacom_tool_type) == atoms.CURSOR() {
+        TabletTool::Mouse
+    } else {
+        TabletTool::Unknown
+    }
+}
+
 #[derive(Debug)]
 pub struct XI2DeviceCache {
     pub info: XI2DeviceInfo,
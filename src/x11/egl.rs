@@ -0,0 +1,460 @@
+//! EGL on X11, as an alternative to GLX (see `GLApiPreference`).
+//!
+//! libEGL is loaded at runtime, the first time a `GLContext` is created via EGL, so that it is not
+//! a hard dependency.
+//!
+//! - The display is obtained via `eglGetPlatformDisplay()` (EGL 1.5) or `eglGetPlatformDisplayEXT()`
+//!   with `EGL_PLATFORM_X11_KHR`, if `EGL_KHR_platform_x11` or `EGL_EXT_platform_x11` is
+//!   supported. Otherwise, we fall back to `eglGetDisplay()`;
+//! - Context attributes are the EGL 1.5 ones, or the `EGL_KHR_create_context` ones.
+//!   Without either, only the major version of ES contexts can be requested;
+//! - sRGB window surfaces require EGL 1.5 or `EGL_KHR_gl_colorspace`;
+//! - There's no equivalent of stereo, accumulation and auxiliary buffers, which are ignored.
+//!
+//! A missing libEGL, or an X display that EGL can't use, is reported as "unsupported".
+
+#![allow(non_snake_case)]
+
+use std::ffi::CStr;
+use std::os::raw::{c_void, c_char, c_uint};
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+use super::libc;
+use super::x11::xlib as x;
+use super::X11SharedContext;
+use gl::{GLPixelFormatSettings, GLContextSettings, GLVariant, GLProfile, GLContextResetNotificationStrategy};
+use error::{Result, failed, unsupported};
+use version_cmp;
+
+pub type EGLDisplay = *mut c_void;
+pub type EGLConfig = *mut c_void;
+pub type EGLContext = *mut c_void;
+pub type EGLSurface = *mut c_void;
+pub type EGLint = i32;
+pub type EGLBoolean = c_uint;
+pub type EGLenum = c_uint;
+
+pub const EGL_FALSE: EGLBoolean = 0;
+pub const EGL_NO_DISPLAY: EGLDisplay = 0 as _;
+pub const EGL_NO_CONTEXT: EGLContext = 0 as _;
+pub const EGL_NO_SURFACE: EGLSurface = 0 as _;
+pub const EGL_NONE: EGLint = 0x3038;
+pub const EGL_EXTENSIONS: EGLint = 0x3055;
+pub const EGL_VENDOR: EGLint = 0x3053;
+pub const EGL_VERSION: EGLint = 0x3054;
+pub const EGL_RED_SIZE: EGLint = 0x3024;
+pub const EGL_GREEN_SIZE: EGLint = 0x3023;
+pub const EGL_BLUE_SIZE: EGLint = 0x3022;
+pub const EGL_ALPHA_SIZE: EGLint = 0x3021;
+pub const EGL_DEPTH_SIZE: EGLint = 0x3025;
+pub const EGL_STENCIL_SIZE: EGLint = 0x3026;
+pub const EGL_SAMPLE_BUFFERS: EGLint = 0x3032;
+pub const EGL_SAMPLES: EGLint = 0x3031;
+pub const EGL_NATIVE_VISUAL_ID: EGLint = 0x302E;
+pub const EGL_SURFACE_TYPE: EGLint = 0x3033;
+pub const EGL_RENDERABLE_TYPE: EGLint = 0x3040;
+pub const EGL_WIDTH: EGLint = 0x3057;
+pub const EGL_HEIGHT: EGLint = 0x3056;
+pub const EGL_PBUFFER_BIT: EGLint = 0x0001;
+pub const EGL_WINDOW_BIT: EGLint = 0x0004;
+pub const EGL_OPENGL_ES_BIT: EGLint = 0x0001;
+pub const EGL_OPENGL_ES2_BIT: EGLint = 0x0004;
+pub const EGL_OPENGL_ES3_BIT: EGLint = 0x0040;
+pub const EGL_OPENGL_BIT: EGLint = 0x0008;
+pub const EGL_OPENGL_ES_API: EGLenum = 0x30A0;
+pub const EGL_OPENGL_API: EGLenum = 0x30A2;
+pub const EGL_PLATFORM_X11_KHR: EGLenum = 0x31D5;
+// EGL 1.5 context attributes. The first three have the same values in `EGL_KHR_create_context`.
+pub const EGL_CONTEXT_MAJOR_VERSION: EGLint = 0x3098; // Same as EGL_CONTEXT_CLIENT_VERSION
+pub const EGL_CONTEXT_MINOR_VERSION: EGLint = 0x30FB;
+pub const EGL_CONTEXT_OPENGL_PROFILE_MASK: EGLint = 0x30FD;
+pub const EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY: EGLint = 0x31BD;
+pub const EGL_CONTEXT_OPENGL_DEBUG: EGLint = 0x31B0;
+pub const EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE: EGLint = 0x31B1;
+pub const EGL_CONTEXT_OPENGL_ROBUST_ACCESS: EGLint = 0x31B2;
+pub const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: EGLint = 0x0001;
+pub const EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT: EGLint = 0x0002;
+pub const EGL_NO_RESET_NOTIFICATION: EGLint = 0x31BE;
+pub const EGL_LOSE_CONTEXT_ON_RESET: EGLint = 0x31BF;
+// EGL 1.5 surface attributes, which have the same values in `EGL_KHR_gl_colorspace`.
+pub const EGL_GL_COLORSPACE: EGLint = 0x309D;
+pub const EGL_GL_COLORSPACE_SRGB: EGLint = 0x3089;
+// EGL_KHR_create_context
+pub const EGL_CONTEXT_FLAGS_KHR: EGLint = 0x30FC;
+pub const EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR: EGLint = 0x0001;
+pub const EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE_BIT_KHR: EGLint = 0x0002;
+pub const EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR: EGLint = 0x0004;
+
+pub mod fn_types {
+    #![allow(non_camel_case_types)]
+
+    use std::os::raw::{c_void, c_char};
+    use super::*;
+
+    pub type eglGetProcAddress = unsafe extern "C" fn(*const c_char) -> *const c_void;
+    pub type eglGetError = unsafe extern "C" fn() -> EGLint;
+    pub type eglQueryString = unsafe extern "C" fn(EGLDisplay, EGLint) -> *const c_char;
+    pub type eglGetDisplay = unsafe extern "C" fn(*mut c_void) -> EGLDisplay;
+    pub type eglGetPlatformDisplay = unsafe extern "C" fn(EGLenum, *mut c_void, *const isize) -> EGLDisplay;
+    pub type eglGetPlatformDisplayEXT = unsafe extern "C" fn(EGLenum, *mut c_void, *const EGLint) -> EGLDisplay;
+    pub type eglInitialize = unsafe extern "C" fn(EGLDisplay, *mut EGLint, *mut EGLint) -> EGLBoolean;
+    pub type eglTerminate = unsafe extern "C" fn(EGLDisplay) -> EGLBoolean;
+    pub type eglBindAPI = unsafe extern "C" fn(EGLenum) -> EGLBoolean;
+    pub type eglChooseConfig = unsafe extern "C" fn(EGLDisplay, *const EGLint, *mut EGLConfig, EGLint, *mut EGLint) -> EGLBoolean;
+    pub type eglGetConfigAttrib = unsafe extern "C" fn(EGLDisplay, EGLConfig, EGLint, *mut EGLint) -> EGLBoolean;
+    pub type eglCreateContext = unsafe extern "C" fn(EGLDisplay, EGLConfig, EGLContext, *const EGLint) -> EGLContext;
+    pub type eglDestroyContext = unsafe extern "C" fn(EGLDisplay, EGLContext) -> EGLBoolean;
+    pub type eglCreateWindowSurface = unsafe extern "C" fn(EGLDisplay, EGLConfig, x::Window, *const EGLint) -> EGLSurface;
+    pub type eglCreatePbufferSurface = unsafe extern "C" fn(EGLDisplay, EGLConfig, *const EGLint) -> EGLSurface;
+    pub type eglDestroySurface = unsafe extern "C" fn(EGLDisplay, EGLSurface) -> EGLBoolean;
+    pub type eglMakeCurrent = unsafe extern "C" fn(EGLDisplay, EGLSurface, EGLSurface, EGLContext) -> EGLBoolean;
+    pub type eglSwapBuffers = unsafe extern "C" fn(EGLDisplay, EGLSurface) -> EGLBoolean;
+    pub type eglSwapInterval = unsafe extern "C" fn(EGLDisplay, EGLint) -> EGLBoolean;
+}
+
+macro_rules! egl_fns {
+    ($($func:ident)+) => {
+        #[derive(Debug, Copy, Clone)]
+        pub struct EglFns {
+            $(pub $func: fn_types::$func,)+
+        }
+        impl EglFns {
+            unsafe fn load(lib: *mut c_void) -> Result<Self> {
+                $(
+                    let $func = libc::dlsym(lib, concat!(stringify!($func), "\0").as_ptr() as *const c_char);
+                    if $func.is_null() {
+                        return failed(concat!("libEGL doesn't export `", stringify!($func), "`"));
+                    }
+                )+
+                Ok(Self { $($func: mem::transmute::<*mut c_void, fn_types::$func>($func),)+ })
+            }
+        }
+    };
+}
+
+egl_fns!(
+    eglGetProcAddress
+    eglGetError
+    eglQueryString
+    eglGetDisplay
+    eglInitialize
+    eglTerminate
+    eglBindAPI
+    eglChooseConfig
+    eglGetConfigAttrib
+    eglCreateContext
+    eglDestroyContext
+    eglCreateWindowSurface
+    eglCreatePbufferSurface
+    eglDestroySurface
+    eglMakeCurrent
+    eglSwapBuffers
+    eglSwapInterval
+);
+
+/// An initialized EGL display for our X display, along with the functions of libEGL.
+///
+/// This must be dropped before the X display is closed.
+#[derive(Debug)]
+pub struct Egl {
+    lib: *mut c_void,
+    pub display: EGLDisplay,
+    pub major_version: EGLint,
+    pub minor_version: EGLint,
+    pub EGL_KHR_create_context: bool,
+    pub EGL_KHR_gl_colorspace: bool,
+    pub fns: EglFns,
+}
+
+impl Drop for Egl {
+    fn drop(&mut self) {
+        unsafe {
+            (self.fns.eglTerminate)(self.display);
+            libc::dlclose(self.lib);
+        }
+    }
+}
+
+impl X11SharedContext {
+    /// Loads and initializes EGL the first time it is called. Failure is remembered as well.
+    pub fn egl(&self) -> Result<Rc<Egl>> {
+        let mut egl = self.egl.borrow_mut();
+        if egl.is_none() {
+            *egl = Some(unsafe { Egl::load(*self.lock_x_display()) }.map(Rc::new));
+        }
+        egl.as_ref().unwrap().clone()
+    }
+}
+
+fn has_extension(extensions: &str, name: &str) -> bool {
+    extensions.split_whitespace().any(|e| e == name)
+}
+
+impl Egl {
+    pub unsafe fn load(x_display: *mut x::Display) -> Result<Self> {
+        let lib = [&b"libEGL.so.1\0"[..], &b"libEGL.so\0"[..]].iter()
+            .map(|name| libc::dlopen(name.as_ptr() as *const c_char, libc::RTLD_NOW | libc::RTLD_LOCAL))
+            .find(|lib| !lib.is_null());
+        let lib = match lib {
+            Some(lib) => lib,
+            None => return unsupported("Could not load libEGL"),
+        };
+        let fns = match EglFns::load(lib) {
+            Ok(fns) => fns,
+            Err(e) => {
+                libc::dlclose(lib);
+                return Err(e);
+            },
+        };
+
+        // This is NULL if client extensions aren't supported at all.
+        let client_extensions = (fns.eglQueryString)(EGL_NO_DISPLAY, EGL_EXTENSIONS);
+        let client_extensions = if client_extensions.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(client_extensions).to_string_lossy().into_owned()
+        };
+        info!("EGL client extensions: {:?}", client_extensions);
+
+        let has_platform_x11 = has_extension(&client_extensions, "EGL_KHR_platform_x11")
+            || has_extension(&client_extensions, "EGL_EXT_platform_x11");
+        let get_platform_display = libc::dlsym(lib, b"eglGetPlatformDisplay\0".as_ptr() as *const c_char);
+        let get_platform_display_ext = (fns.eglGetProcAddress)(b"eglGetPlatformDisplayEXT\0".as_ptr() as *const c_char);
+
+        let display = if has_platform_x11 && !get_platform_display.is_null() {
+            let f = mem::transmute::<*mut c_void, fn_types::eglGetPlatformDisplay>(get_platform_display);
+            f(EGL_PLATFORM_X11_KHR, x_display as *mut c_void, ptr::null())
+        } else if has_platform_x11 && !get_platform_display_ext.is_null() {
+            let f = mem::transmute::<*const c_void, fn_types::eglGetPlatformDisplayEXT>(get_platform_display_ext);
+            f(EGL_PLATFORM_X11_KHR, x_display as *mut c_void, ptr::null())
+        } else {
+            (fns.eglGetDisplay)(x_display as *mut c_void)
+        };
+        if display == EGL_NO_DISPLAY {
+            libc::dlclose(lib);
+            return unsupported("Could not get an EGL display for the X display");
+        }
+
+        let (mut major_version, mut minor_version) = (0, 0);
+        if (fns.eglInitialize)(display, &mut major_version, &mut minor_version) == EGL_FALSE {
+            let err = (fns.eglGetError)();
+            libc::dlclose(lib);
+            return unsupported(format!("eglInitialize() failed (EGL error 0x{:x})", err));
+        }
+
+        let query_string = |name| {
+            let s = (fns.eglQueryString)(display, name);
+            if s.is_null() { String::new() } else { CStr::from_ptr(s).to_string_lossy().into_owned() }
+        };
+        let extensions = query_string(EGL_EXTENSIONS);
+        info!("EGL vendor    : {:?}", query_string(EGL_VENDOR));
+        info!("EGL version   : {:?}", query_string(EGL_VERSION));
+        info!("EGL extensions: {:?}", extensions);
+
+        Ok(Self {
+            lib, display, major_version, minor_version, fns,
+            EGL_KHR_create_context: has_extension(&extensions, "EGL_KHR_create_context"),
+            EGL_KHR_gl_colorspace: has_extension(&extensions, "EGL_KHR_gl_colorspace"),
+        })
+    }
+
+    fn fail<T>(&self, f: &str) -> Result<T> {
+        let err = unsafe { (self.fns.eglGetError)() };
+        failed(format!("{}() failed (EGL error 0x{:x})", f, err))
+    }
+
+    fn bind_api(&self, variant: GLVariant) -> Result<()> {
+        let api = match variant {
+            GLVariant::Desktop => EGL_OPENGL_API,
+            GLVariant::ES => EGL_OPENGL_ES_API,
+        };
+        match unsafe { (self.fns.eglBindAPI)(api) } {
+            EGL_FALSE => self.fail("eglBindAPI"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Chooses a config that supports `surface_type` and the context's version.
+    /// If `visual_id` is given, the config must also match that X visual.
+    pub fn choose_config(&self, settings: &GLPixelFormatSettings, context_settings: &GLContextSettings, surface_type: EGLint, visual_id: Option<x::VisualID>) -> Result<EGLConfig> {
+        let version = context_settings.version;
+        let renderable_type = match version.variant {
+            GLVariant::Desktop => EGL_OPENGL_BIT,
+            GLVariant::ES => match version.major {
+                0 | 1 => EGL_OPENGL_ES_BIT,
+                2 => EGL_OPENGL_ES2_BIT,
+                _ => EGL_OPENGL_ES3_BIT,
+            },
+        };
+        let attribs = [
+            EGL_SURFACE_TYPE, surface_type,
+            EGL_RENDERABLE_TYPE, renderable_type,
+            EGL_RED_SIZE, settings.red_bits as _,
+            EGL_GREEN_SIZE, settings.green_bits as _,
+            EGL_BLUE_SIZE, settings.blue_bits as _,
+            EGL_ALPHA_SIZE, settings.alpha_bits as _,
+            EGL_DEPTH_SIZE, settings.depth_bits as _,
+            EGL_STENCIL_SIZE, settings.stencil_bits as _,
+            EGL_SAMPLE_BUFFERS, settings.msaa.buffer_count as _,
+            EGL_SAMPLES, settings.msaa.sample_count as _,
+            EGL_NONE // keep last
+        ];
+        let configs = unsafe {
+            let mut count = 0;
+            if (self.fns.eglChooseConfig)(self.display, attribs.as_ptr(), ptr::null_mut(), 0, &mut count) == EGL_FALSE {
+                return self.fail("eglChooseConfig");
+            }
+            let mut configs = vec![ptr::null_mut(); count.max(0) as usize];
+            if (self.fns.eglChooseConfig)(self.display, attribs.as_ptr(), configs.as_mut_ptr(), count, &mut count) == EGL_FALSE {
+                return self.fail("eglChooseConfig");
+            }
+            configs.truncate(count.max(0) as usize);
+            configs
+        };
+        let visual_id = match visual_id {
+            None => return configs.first().cloned().map_or_else(|| unsupported("eglChooseConfig() found no config matching the requested settings"), Ok),
+            Some(visual_id) => visual_id,
+        };
+        // The window's visual was already chosen, so only a config that renders to it will do.
+        configs.into_iter().find(|config| self.native_visual_id(*config) == Some(visual_id))
+            .map_or_else(|| failed(format!("No EGLConfig matches the window's visual (0x{:x})", visual_id)), Ok)
+    }
+    /// Gets the X visual that windows must have to be rendered to with `config`, if any.
+    pub fn native_visual_id(&self, config: EGLConfig) -> Option<x::VisualID> {
+        let mut native_visual_id = 0;
+        let is_ok = unsafe {
+            (self.fns.eglGetConfigAttrib)(self.display, config, EGL_NATIVE_VISUAL_ID, &mut native_visual_id)
+        };
+        match (is_ok, native_visual_id) {
+            (EGL_FALSE, _) | (_, 0) => None,
+            _ => Some(native_visual_id as _),
+        }
+    }
+
+    fn gen_context_attribs(&self, settings: &GLContextSettings) -> Vec<EGLint> {
+        let &GLContextSettings {
+            version, profile, debug, forward_compatible, robust_access, ..
+        } = settings;
+
+        let mut attribs = vec![EGL_CONTEXT_MAJOR_VERSION, version.major as _];
+        let is_egl_1_5 = !version_cmp::lt((self.major_version, self.minor_version), (1, 5));
+
+        if is_egl_1_5 || self.EGL_KHR_create_context {
+            attribs.extend(&[EGL_CONTEXT_MINOR_VERSION, version.minor as _]);
+            if version.is_desktop() {
+                attribs.extend(&[EGL_CONTEXT_OPENGL_PROFILE_MASK, match profile {
+                    GLProfile::Core => EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                    GLProfile::Compatibility => EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT,
+                }]);
+            }
+            if let Some(robust_access) = robust_access {
+                attribs.extend(&[EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY, match robust_access {
+                    GLContextResetNotificationStrategy::NoResetNotification => EGL_NO_RESET_NOTIFICATION,
+                    GLContextResetNotificationStrategy::LoseContextOnReset => EGL_LOSE_CONTEXT_ON_RESET,
+                }]);
+            }
+        }
+        if is_egl_1_5 {
+            // EGL 1.5 rejects EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE for ES contexts.
+            if debug {
+                attribs.extend(&[EGL_CONTEXT_OPENGL_DEBUG, 1]);
+            }
+            if forward_compatible && version.is_desktop() {
+                attribs.extend(&[EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE, 1]);
+            }
+            if robust_access.is_some() {
+                attribs.extend(&[EGL_CONTEXT_OPENGL_ROBUST_ACCESS, 1]);
+            }
+        } else if self.EGL_KHR_create_context {
+            let flags = if debug { EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR } else { 0 }
+                | if forward_compatible && version.is_desktop() { EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE_BIT_KHR } else { 0 }
+                | if robust_access.is_some() { EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR } else { 0 };
+            attribs.extend(&[EGL_CONTEXT_FLAGS_KHR, flags]);
+        } else if version.is_desktop() {
+            // Only EGL_CONTEXT_CLIENT_VERSION is known, and it's only valid for ES.
+            attribs.clear();
+        }
+        attribs.push(EGL_NONE);
+        attribs
+    }
+
+    pub fn create_context(&self, config: EGLConfig, settings: &GLContextSettings) -> Result<EGLContext> {
+        self.bind_api(settings.version.variant)?;
+        let attribs = self.gen_context_attribs(settings);
+        let context = unsafe {
+            (self.fns.eglCreateContext)(self.display, config, EGL_NO_CONTEXT, attribs.as_ptr())
+        };
+        if context == EGL_NO_CONTEXT {
+            return self.fail("eglCreateContext");
+        }
+        Ok(context)
+    }
+    pub fn destroy_context(&self, context: EGLContext) {
+        unsafe {
+            (self.fns.eglDestroyContext)(self.display, context);
+        }
+    }
+    pub fn create_window_surface(&self, config: EGLConfig, x_window: x::Window, srgb: bool) -> Result<EGLSurface> {
+        let attribs = if srgb {
+            let is_egl_1_5 = !version_cmp::lt((self.major_version, self.minor_version), (1, 5));
+            if !(is_egl_1_5 || self.EGL_KHR_gl_colorspace) {
+                return unsupported("sRGB surfaces require EGL 1.5 or `EGL_KHR_gl_colorspace`");
+            }
+            [EGL_GL_COLORSPACE, EGL_GL_COLORSPACE_SRGB, EGL_NONE]
+        } else {
+            [EGL_NONE, 0, 0]
+        };
+        let surface = unsafe {
+            (self.fns.eglCreateWindowSurface)(self.display, config, x_window, attribs.as_ptr())
+        };
+        if surface == EGL_NO_SURFACE {
+            return self.fail("eglCreateWindowSurface");
+        }
+        Ok(surface)
+    }
+    pub fn create_pbuffer_surface(&self, config: EGLConfig, w: EGLint, h: EGLint) -> Result<EGLSurface> {
+        let attribs = [EGL_WIDTH, w, EGL_HEIGHT, h, EGL_NONE];
+        let surface = unsafe {
+            (self.fns.eglCreatePbufferSurface)(self.display, config, attribs.as_ptr())
+        };
+        if surface == EGL_NO_SURFACE {
+            return self.fail("eglCreatePbufferSurface");
+        }
+        Ok(surface)
+    }
+    pub fn destroy_surface(&self, surface: EGLSurface) {
+        unsafe {
+            (self.fns.eglDestroySurface)(self.display, surface);
+        }
+    }
+    /// `variant` is the context's; The API to which it belongs has to be bound first.
+    pub fn make_current(&self, surface: EGLSurface, context: EGLContext, variant: GLVariant) -> Result<()> {
+        self.bind_api(variant)?;
+        match unsafe { (self.fns.eglMakeCurrent)(self.display, surface, surface, context) } {
+            EGL_FALSE => self.fail("eglMakeCurrent"),
+            _ => Ok(()),
+        }
+    }
+    pub fn release_current(&self) {
+        unsafe {
+            (self.fns.eglMakeCurrent)(self.display, EGL_NO_SURFACE, EGL_NO_SURFACE, EGL_NO_CONTEXT);
+        }
+    }
+    pub fn swap_buffers(&self, surface: EGLSurface) -> Result<()> {
+        match unsafe { (self.fns.eglSwapBuffers)(self.display, surface) } {
+            EGL_FALSE => self.fail("eglSwapBuffers"),
+            _ => Ok(()),
+        }
+    }
+    /// Applies to the surface that is bound to the current context.
+    pub fn swap_interval(&self, interval: EGLint) -> Result<()> {
+        match unsafe { (self.fns.eglSwapInterval)(self.display, interval) } {
+            EGL_FALSE => self.fail("eglSwapInterval"),
+            _ => Ok(()),
+        }
+    }
+    pub unsafe fn get_proc_address(&self, name: *const c_char) -> *const c_void {
+        (self.fns.eglGetProcAddress)(name)
+    }
+}
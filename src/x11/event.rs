@@ -1,15 +1,19 @@
 use std::mem;
 use std::ptr;
 use std::slice;
-use std::rc::Rc;
-use std::os::raw::c_int;
+use std::rc::{Rc, Weak};
+use std::os::raw::{c_int, c_char};
 use std::collections::HashMap;
+use std::time::Instant;
 use super::context::{X11SharedContext};
 use super::x11::xlib as x;
 use super::x11::xinput2 as xi2;
+use super::x11::xrandr as xrr;
 use super::{X11SharedWindow, X11DeviceID};
+use super::compose;
+use super::xrandr;
 use super::device::{
-    XI2DeviceCache,
+    XI2DeviceCache, XI2DeviceProperty,
     XI2DeviceRole, XI2DeviceAnyClassInfo,
     XI2ButtonLabel, XI2AxisLabel,
     XI2ValuatorClassInfo,
@@ -17,8 +21,10 @@ use super::device::{
 use os::{OsEventInstant};
 use error::{Result, failed};
 use event::{Event, EventInstant, UnprocessedEvent};
+use event_queue::QueuePolicy;
 use device::{self, DeviceID, DeviceInfo, MouseButton, Key, Keysym, Keycode};
-use window::WindowHandle;
+use window::{WindowHandle, HitTestResult};
+use pointer_barrier::PointerBarrierID;
 use {Vec2, Extent2, Rect};
 
 macro_rules! define_x11_unprocessed_event_enum {
@@ -77,12 +83,18 @@ impl X11SharedContext {
         self.xi()?;
         Ok(true)
     }
+    pub fn set_event_queue_policy(&self, policy: QueuePolicy) {
+        self.pending_translated_events.borrow_mut().set_policy(policy);
+    }
+    pub fn event_queue_policy(&self) -> QueuePolicy {
+        self.pending_translated_events.borrow().policy()
+    }
 
     pub fn poll_next_event(&self) -> Option<Event> {
         let ev = self.poll_next_event_real();
         let q = self.pending_translated_events.borrow();
         if let Some(ev) = ev.as_ref() {
-            trace!("[EV] ---- ({} / {}): {:?}", q.len(), q.capacity(), ev);
+            trace!("[EV] ---- ({}): {:?}", q.len(), ev);
         }
         ev
     }
@@ -91,6 +103,14 @@ impl X11SharedContext {
         if let Some(ev) = ev {
             return Some(ev);
         }
+        self.pump_pending_x_events();
+        self.pending_translated_events.borrow_mut().pop_front()
+    }
+    /// Translates all X events that are available right now, without returning any.
+    ///
+    /// Among other things, this answers `_NET_WM_PING` requests, which is what window managers
+    /// use to decide that a window is frozen.
+    pub fn pump_pending_x_events(&self) {
         loop {
             let n = self.x_pending();
             if n <= 0 {
@@ -100,7 +120,10 @@ impl X11SharedContext {
                 self.pump_x_event(&mut self.x_next_event());
             }
         }
-        self.pending_translated_events.borrow_mut().pop_front()
+        self.stop_expired_window_flashes();
+        if let Some(ev) = self.poll_accessibility_scale_factor() {
+            self.push_event(ev);
+        }
     }
     fn x_pending(&self) -> c_int {
         let x_display = self.lock_x_display();
@@ -126,9 +149,13 @@ impl X11SharedContext {
         }
     }
 
-    fn push_event(&self, e: Event) {
-        trace!("[EV] ++++ ({} / {}): {:?}", self.pending_translated_events.borrow().len(), self.pending_translated_events.borrow().capacity(), e);
-        self.pending_translated_events.borrow_mut().push_back(e);
+    pub fn push_event(&self, e: Event) {
+        trace!("[EV] ++++ ({}): {:?}", self.pending_translated_events.borrow().len(), e);
+        let mut pending = self.pending_translated_events.borrow_mut();
+        pending.push_back(e);
+        if pending.len() > self.queue_high_water_mark.get() {
+            self.queue_high_water_mark.set(pending.len());
+        }
     }
     // FIXME: So what should be do about these?
     fn push_unhandled_x_event<T: Into<x::XEvent>>(&self, e: T) {
@@ -184,6 +211,7 @@ impl X11SharedContext {
             x::VisibilityNotify => self.pump_x_visibility_event(e.as_mut()),
             x::MapNotify => self.pump_x_map_event(e.as_mut()),
             x::UnmapNotify => self.pump_x_unmap_event(e.as_mut()),
+            x::PropertyNotify => self.pump_x_property_event(e.as_mut()),
             // ---
             // Events that we definitely want to ignore (AFAIK)
             x::GraphicsExpose
@@ -194,7 +222,6 @@ impl X11SharedContext {
             // ---
             // Events that we're ignoring today, but might be interesting later
             x::KeymapNotify 
-            | x::PropertyNotify  
             | x::CirculateRequest
             | x::ConfigureRequest
             | x::MapRequest
@@ -206,6 +233,10 @@ impl X11SharedContext {
             | x::SelectionRequest
                 => self.push_unhandled_x_event(*e),
             // ---
+            // Extension events, which have dynamic type numbers
+            t if self.xkb().map(|xkb| xkb.event_base == t).unwrap_or(false) => self.pump_xkb_event(e),
+            t if self.xrr().map(|xrr| t == xrr.event_base + xrr::RRScreenChangeNotify || t == xrr.event_base + xrr::RRNotify).unwrap_or(false) => self.pump_xrr_event(e),
+            // ---
             // Events that we seemingly don't know about
             _   => self.push_unhandled_x_event(*e),
         }
@@ -239,6 +270,7 @@ impl X11SharedContext {
             | xi2::XI_RawTouchUpdate  
             | xi2::XI_RawTouchEnd 
                 => self.pump_xi_raw_event(unsafe { mem::transmute(e) }),
+            xi2::XI_BarrierHit => self.pump_xi_barrier_event(unsafe { mem::transmute(e) }),
             _   => self.push_unhandled_xi2_event(*e),
         }
     }
@@ -289,6 +321,26 @@ impl X11SharedContext {
         self.push_handled_x_event(*e, 1);
         self.push_event(ev)
     }
+    fn pump_x_property_event(&self, e: &mut x::XPropertyEvent) {
+        let &mut x::XPropertyEvent {
+            type_: _, serial: _, send_event: _, display: _, window, atom, time: _, state: _,
+        } = e;
+        if let Ok(net_wm_state) = self.atoms._NET_WM_STATE() {
+            if atom == net_wm_state {
+                let w = self.weak_windows.borrow().get(&window).and_then(Weak::upgrade);
+                if let Some(w) = w {
+                    w.refresh_is_in_normal_state();
+                    return self.push_handled_x_event(*e, 0);
+                }
+            }
+        }
+        if window != self.x_default_root_window() || atom != x::XA_RESOURCE_MANAGER {
+            return self.push_unhandled_x_event(*e);
+        }
+        // The new value is reported by `pump_pending_x_events()` once it is known.
+        self.refresh_accessibility_scale_factor();
+        self.push_handled_x_event(*e, 0);
+    }
     fn pump_x_crossing_event(&self, e: &mut x::XCrossingEvent) {
         let &mut x::XCrossingEvent {
             type_, serial: _, send_event: _, display: _, window, root: _, subwindow: _,
@@ -314,6 +366,9 @@ impl X11SharedContext {
         };
         let motion = Event::MouseMotion { mouse, window, instant, position, root_position };
         self.previous_mouse_position.set(Some(position));
+        if type_ == x::EnterNotify {
+            self.reapply_cursor_for_x_window(window.0);
+        }
         let ev = match type_ {
             x::EnterNotify => Event::MouseEnter { mouse, window, instant, is_grabbed, is_focused },
             x::LeaveNotify => Event::MouseLeave { mouse, window, instant, was_grabbed, was_focused },
@@ -328,6 +383,10 @@ impl X11SharedContext {
             type_, serial: _, send_event: _, display: _, window, mode: _, detail: _,
         } = e;
         let keyboard = self.core_x_keyboard_deviceid();
+        if type_ == x::FocusIn {
+            self.reapply_cursor_for_x_window(window);
+            self.stop_flashing_x_window(window);
+        }
         let window = WindowHandle(window);
         let ev = match type_ {
             x::FocusIn => Event::KeyboardFocusGained { keyboard, window, },
@@ -399,18 +458,113 @@ impl X11SharedContext {
 
         let w = self.weak_windows.borrow()[&x_window].upgrade().unwrap();
 
-        if position != w.prev_pos.get() {
+        let has_moved = position != w.prev_pos.get();
+        let has_resized = size != w.prev_size.get();
+        if has_moved {
             if send_event != x::False {
                 self.push_event(Event::WindowMoved { window, position, by_user, });
             }
             w.prev_pos.set(position);
         }
-        if size != w.prev_size.get() {
-            self.push_event(Event::WindowResized { window, size, by_user, });
+        if has_resized {
+            // High-DPI is not handled (yet), so the canvas is the window's size.
+            let canvas_size = size;
+            self.push_event(Event::WindowResized { window, size, canvas_size, by_user, });
             w.prev_size.set(size);
         }
+        if has_resized {
+            if let Err(e) = w.refresh_hit_test_shape(size) {
+                warn!("Could not update the hit test shape of X Window {}: {}", x_window, e);
+            }
+        }
+        if w.is_in_normal_state.get() {
+            w.normal_rect.set(Some(Rect::new(position.x, position.y, size.w, size.h)));
+        }
+        if has_moved || has_resized {
+            self.check_monitor_change(&w);
+        }
         // self.push_handled_x_event(*e, 2); FIXME
     }
+    /// Starts moving the window if the left button was pressed on a `Caption` area, as
+    /// reported by the window's hit test function (see `Window::set_cursor_hit_test()`).
+    fn handle_hit_test_caption_press(&self, x_window: x::Window, button: MouseButton, position: Vec2<f64>, root_position: Vec2<f64>, instant: EventInstant) {
+        if button != MouseButton::Left {
+            return;
+        }
+        let w = self.weak_windows.borrow().get(&x_window).and_then(Weak::upgrade);
+        let w = match w {
+            Some(w) => w,
+            None => return,
+        };
+        let hit_test = match w.hit_test.borrow().clone() {
+            Some(hit_test) => hit_test,
+            None => return,
+        };
+        if hit_test.hit_test(w.prev_size.get(), position.map(|x| x as i32)) != HitTestResult::Caption {
+            return;
+        }
+        if let Err(e) = w.begin_move_drag(button, root_position.map(|x| x as i32), instant) {
+            warn!("Could not start moving X Window {} from its caption area: {}", x_window, e);
+        }
+    }
+    /// Checks the window's scale factor and refresh rate if its center has left the monitor
+    /// it was last known to be on (see `X11SharedWindow::monitor_rect`).
+    pub fn check_monitor_change(&self, w: &X11SharedWindow) {
+        let (position, size) = (w.prev_pos.get(), w.prev_size.get());
+        let center = position + Vec2::new(size.w as i32 / 2, size.h as i32 / 2);
+        if w.monitor_rect.get().map(|r| xrandr::rect_contains_point(r, center)).unwrap_or(false) {
+            return;
+        }
+        match self.monitor_rect_at(center) {
+            Ok(rect) => w.monitor_rect.set(rect),
+            Err(e) => trace!("Could not get the monitor of X Window {}: {}", w.x_window, e),
+        }
+        self.check_scale_factor_change(w, center, size);
+        self.check_refresh_rate_change(w, center);
+    }
+    /// Reports `WindowScaleFactorChanged` if the window's center is now on a monitor with a
+    /// different scale factor. Nothing is reported until the scale factor is first known, i.e
+    /// the first time this is called or `Window::scale_factor()` is.
+    fn check_scale_factor_change(&self, w: &X11SharedWindow, center: Vec2<i32>, size: Extent2<u32>) {
+        let new = match self.monitor_scale_factor_at(center) {
+            Ok(Some(new)) => new,
+            // The center is off-screen; Keep the previous scale factor.
+            Ok(None) => return,
+            Err(e) => {
+                trace!("Could not get the scale factor for X Window {}: {}", w.x_window, e);
+                return;
+            },
+        };
+        let old = match w.scale_factor.replace(Some(new)) {
+            Some(old) if old != new => old,
+            _ => return,
+        };
+        let suggested_size = Extent2::new(
+            (size.w as f64 * new / old).round() as u32,
+            (size.h as f64 * new / old).round() as u32,
+        );
+        let window = WindowHandle(w.x_window);
+        self.push_event(Event::WindowScaleFactorChanged { window, old, new, suggested_size, });
+    }
+    /// Reports `WindowRefreshRateChanged` if the monitor that contains the window's center now
+    /// has a different refresh rate. As with the scale factor, nothing is reported until the
+    /// refresh rate is first known.
+    fn check_refresh_rate_change(&self, w: &X11SharedWindow, center: Vec2<i32>) {
+        let new = match self.monitor_refresh_rate_at(center) {
+            Ok(Some(new)) => new,
+            Ok(None) => return,
+            Err(e) => {
+                trace!("Could not get the refresh rate for X Window {}: {}", w.x_window, e);
+                return;
+            },
+        };
+        match w.refresh_rate.replace(Some(new)) {
+            Some(old) if old != new => (),
+            _ => return,
+        };
+        let window = WindowHandle(w.x_window);
+        self.push_event(Event::WindowRefreshRateChanged { window, refresh_rate: new, });
+    }
     fn pump_x_resize_request_event(&self, e: &mut x::XResizeRequestEvent) {
         unimplemented!{} // They're evil, we never use them
         /*
@@ -423,7 +577,7 @@ impl X11SharedContext {
         let size = Extent2::new(width as _, height as _);
 
         self.push_handled_x_event(*e, 1);
-        self.push_event(Event::WindowResized { window, size, by_user, })
+        self.push_event(Event::WindowResized { window, size, canvas_size: size, by_user, })
         */
     }
 
@@ -501,7 +655,7 @@ impl X11SharedContext {
 
         let &mut x::XKeyEvent {
             type_, serial: _, send_event: _, display: _, window, root: _, subwindow: _,
-            time, x, y, x_root, y_root, state: _, keycode, same_screen: _,
+            time, x, y, x_root, y_root, state, keycode, same_screen: _,
         } = e;
 
         self.set_net_wm_user_time_for_x_window(window, time);
@@ -522,14 +676,17 @@ impl X11SharedContext {
         let instant = EventInstant(OsEventInstant::X11EventTimeMillis(time));
         let keycode = keycode as x::KeyCode;
 
-        let index_into_x_keysyms_list = 0;
+        // The primary keysym, in the group that was active at the time of the event.
+        let group = self.xkb_group_for_core_state(state);
+        let level = 0;
         let (keysym, text) = match type_ {
-            x::KeyRelease => (self.x_key_event_keysym(e, index_into_x_keysyms_list), None),
+            x::KeyRelease => (self.xkb_keycode_to_keysym(keycode, group, level), None),
             x::KeyPress => match self.retrieve_window(window.0) {
-                Err(_) => (self.x_key_event_keysym(e, index_into_x_keysyms_list), None),
+                Err(_) => (self.xkb_keycode_to_keysym(keycode, group, level), None),
                 Ok(w) => match w.xic {
-                    None => (self.x_key_event_keysym(e, index_into_x_keysyms_list), None),
-                    Some(xic) => self.x_utf8_lookup_string(xic, e),
+                    Some(xic) if w.is_text_input_active.get() => self.x_utf8_lookup_string(xic, e),
+                    None if w.is_text_input_active.get() => self.x_lookup_string_with_compose(e),
+                    _ => (self.xkb_keycode_to_keysym(keycode, group, level), None),
                 },
             },
             _ => unreachable!{}
@@ -611,6 +768,18 @@ impl X11SharedContext {
             b => (Some(MouseButton::Other(b as _)), None),
         }
     }
+    /// Reverse of `x11_button_to_mousebutton_or_scroll()`.
+    pub fn mousebutton_to_x11_button(button: MouseButton) -> Option<u32> {
+        match button {
+            MouseButton::Left => Some(1),
+            MouseButton::Middle => Some(2),
+            MouseButton::Right => Some(3),
+            MouseButton::Back => Some(8),
+            MouseButton::Forward => Some(9),
+            MouseButton::Other(b) if b > 0 => Some(b as _),
+            _ => None,
+        }
+    }
 
     fn xi2_button_label_to_mouse_button_or_scroll(detail: c_int, label: Option<XI2ButtonLabel>) -> (Option<MouseButton>, Option<Vec2<i32>>) {
         match label {
@@ -667,8 +836,12 @@ impl X11SharedContext {
             None => {
                 let button = button.unwrap();
                 let ev = match type_ {
-                    x::ButtonPress => Event::MouseButtonPressed { mouse, window, instant, button, clicks: None },
-                    x::ButtonRelease => Event::MouseButtonReleased { mouse, window, instant, button },
+                    x::ButtonPress => {
+                        self.handle_hit_test_caption_press(window.0, button, position, root_position, instant);
+                        let outside = self.is_outside_input_grab(window.0, position);
+                        Event::MouseButtonPressed { mouse, window, instant, button, clicks: None, position, root_position, outside }
+                    },
+                    x::ButtonRelease => Event::MouseButtonReleased { mouse, window, instant, button, position, root_position },
                     _ => unreachable!{},
                 };
                 Some(ev)
@@ -689,15 +862,25 @@ impl X11SharedContext {
     fn pump_xi_device_changed_event(&self, e: &mut xi2::XIDeviceChangedEvent) {
         let &mut xi2::XIDeviceChangedEvent {
             _type: _, serial: _, send_event: _, display: _, extension: _, evtype: _,
-            time: _, deviceid, sourceid,
+            time, deviceid, sourceid,
             reason, // SlaveSwitch, DeviceChange
             num_classes, classes,
         } = e;
 
+        let classes = || unsafe {
+            slice::from_raw_parts(classes, num_classes as _).iter()
+                .filter_map(|x| XI2DeviceAnyClassInfo::try_from_anyclassinfo(*x))
+        };
+
+        let mut remapped = None;
+
         match reason {
             xi2::XISlaveSwitch => {
-                // deviceid is a master, sourceid is the new slave
-                self.xi2_devices.borrow_mut().get_mut(&deviceid).unwrap().info.attachment = sourceid;
+                // deviceid is a master, sourceid is the new slave. The master now mirrors the slave's classes.
+                if let Some(dev) = self.xi2_devices.borrow_mut().get_mut(&deviceid) {
+                    dev.info.attachment = sourceid;
+                    dev.info.replace_classes(classes(), &self.atoms);
+                }
             },
             xi2::XIDeviceChange => {
                 // Use sourceid; deviceid is undefined
@@ -707,24 +890,44 @@ impl X11SharedContext {
                 let dev = unsafe {
                     super::device::refresh_xi2_device_cache(*self.lock_x_display(), sourceid, &self.atoms)
                 };
-                if let Ok(dev) = dev {
-                    self.xi2_devices.borrow_mut().insert(sourceid, dev);
+                let mut xi2_devices = self.xi2_devices.borrow_mut();
+                let old_axes = xi2_devices.get(&sourceid).map(|dev| dev.info.axis_infos());
+                match dev {
+                    Ok(dev) => { xi2_devices.insert(sourceid, dev); },
+                    // At least don't normalize against stale ranges; The event has the new classes.
+                    Err(e) => if let Some(dev) = xi2_devices.get_mut(&sourceid) {
+                        warn!("Could not re-query XI2 device {}, using the event's classes instead: {}", sourceid, e);
+                        dev.info.replace_classes(classes(), &self.atoms);
+                    },
+                }
+                let new_axes = xi2_devices.get(&sourceid).map(|dev| dev.info.axis_infos());
+                // A device we didn't know about yet wasn't remapped; It was just discovered.
+                if old_axes.is_some() && new_axes.is_some() && old_axes != new_axes {
+                    remapped = Some(Event::DeviceRemapped {
+                        device: DeviceID(X11DeviceID::XISlave(sourceid).into()),
+                        instant: EventInstant(OsEventInstant::X11EventTimeMillis(time)),
+                    });
                 }
             },
             _ => unreachable!(),
         }
 
-        self.push_handled_xi2_event(*e, 0);
+        self.push_handled_xi2_event(*e, remapped.is_some() as usize);
+        if let Some(remapped) = remapped {
+            self.push_event(remapped);
+        }
     }
     fn pump_xi_property_event(&self, e: &mut xi2::XIPropertyEvent) {
         let &mut xi2::XIPropertyEvent {
             _type: _, serial: _, send_event: _, display: _, extension: _, evtype: _,
-            time: _, 
+            time,
             deviceid,
             property, // Atom
             what, // PropertyCreated, PropertyDeleted, PropertyModified
         } = e;
 
+        let mut proximity = None;
+
         match what {
             xi2::XIPropertyDeleted => {
                 self.xi2_devices.borrow_mut().get_mut(&deviceid).unwrap().props.remove(&property);
@@ -735,7 +938,11 @@ impl X11SharedContext {
                         super::device::xi2_get_device_property(*self.lock_x_display(), deviceid, property)
                     };
                     if let Ok(Some(value)) = value {
-                        self.xi2_devices.borrow_mut().get_mut(&deviceid).unwrap().props.insert(property, value);
+                        let previous = self.xi2_devices.borrow_mut().get_mut(&deviceid).unwrap().props.insert(property, value.clone());
+                        // Wacom drivers update this property whenever a tool enters or leaves proximity.
+                        if Ok(property) == self.atoms.Wacom_Serial_IDs() {
+                            proximity = self.tablet_tool_proximity_event(deviceid, time, previous.as_ref(), &value);
+                        }
                     }
                 }
             },
@@ -743,6 +950,32 @@ impl X11SharedContext {
         };
 
         self.push_handled_xi2_event(*e, 0);
+        if let Some(proximity) = proximity {
+            self.push_event(proximity);
+        }
+    }
+    fn tablet_tool_proximity_event(&self, deviceid: c_int, time: x::Time, previous: Option<&XI2DeviceProperty>, current: &XI2DeviceProperty) -> Option<Event> {
+        let (previous_serial, previous_tool_id) = previous.and_then(super::device::wacom_tool_in_proximity).unwrap_or((0, 0));
+        let (serial, tool_id) = super::device::wacom_tool_in_proximity(current)?;
+        let (entering, serial, tool_id) = match (previous_serial, serial) {
+            (0, 0) => return None,
+            // When leaving, the driver resets the IDs, so the tool is only known from before.
+            (_, 0) => (false, previous_serial, previous_tool_id),
+            (p, s) if p == s => return None,
+            _ => (true, serial, tool_id),
+        };
+        let wacom_tool_type = self.atoms.Wacom_Tool_Type().ok().and_then(|atom| {
+            let xi2_devices = self.xi2_devices.borrow();
+            let prop = xi2_devices.get(&deviceid)?.props.get(&atom)?;
+            prop.as_slice::<u32>()?.first().map(|t| *t as x::Atom)
+        });
+        Some(Event::TabletToolProximity {
+            tablet: DeviceID(X11DeviceID::XISlave(deviceid).into()),
+            instant: EventInstant(OsEventInstant::X11EventTimeMillis(time)),
+            tool_serial: Some(serial as u64),
+            tool: super::device::wacom_tablet_tool(tool_id, wacom_tool_type, &self.atoms),
+            entering,
+        })
     }
 
     fn pump_xi_hierarchy_event(&self, e: &mut xi2::XIHierarchyEvent) {
@@ -814,6 +1047,12 @@ impl X11SharedContext {
             xi2::XINotifyUngrab => (false, true),
             _ => unreachable!{},
         };
+        if evtype == xi2::XI_Enter || evtype == xi2::XI_FocusIn {
+            self.reapply_cursor_for_x_window(x_window);
+        }
+        if evtype == xi2::XI_FocusIn {
+            self.stop_flashing_x_window(x_window);
+        }
         let ev = match evtype {
             xi2::XI_Enter => Event::MouseEnter { mouse, window, instant, is_grabbed, is_focused },
             xi2::XI_Leave => Event::MouseLeave { mouse, window, instant, was_grabbed, was_focused },
@@ -953,8 +1192,12 @@ impl X11SharedContext {
                 }
                 if let Some(button) = button {
                     let button_ev = match evtype {
-                        xi2::XI_ButtonPress   => Event::MouseButtonPressed  { mouse: slave_device_id, window, instant, button, clicks: None, },
-                        xi2::XI_ButtonRelease => Event::MouseButtonReleased { mouse: slave_device_id, window, instant, button },
+                        xi2::XI_ButtonPress   => {
+                            self.handle_hit_test_caption_press(x_window, button, position, root_position, instant);
+                            let outside = self.is_outside_input_grab(x_window, position);
+                            Event::MouseButtonPressed  { mouse: slave_device_id, window, instant, button, clicks: None, position, root_position, outside, }
+                        },
+                        xi2::XI_ButtonRelease => Event::MouseButtonReleased { mouse: slave_device_id, window, instant, button, position, root_position, },
                         _ => unreachable!(),
                     };
                     self.push_event(button_ev);
@@ -970,6 +1213,21 @@ impl X11SharedContext {
             _ => self.push_unhandled_xi2_event(*e),
         }
     }
+    fn pump_xi_barrier_event(&self, e: &mut xi2::XIBarrierEvent) {
+        let &mut xi2::XIBarrierEvent {
+            _type: _, serial: _, send_event: _, display: _, extension: _, evtype: _,
+            time, deviceid: _, sourceid, event: _, root: _, root_x, root_y, dx, dy, dtime: _,
+            flags: _, barrier, eventid: _,
+        } = e;
+        self.push_handled_xi2_event(*e, 1);
+        self.push_event(Event::PointerBarrierHit {
+            mouse: DeviceID(X11DeviceID::XISlave(sourceid).into()),
+            instant: EventInstant(OsEventInstant::X11EventTimeMillis(time)),
+            barrier: PointerBarrierID(barrier),
+            root_position: Vec2::new(root_x, root_y),
+            delta: Vec2::new(dx, dy),
+        })
+    }
     fn pump_xi_raw_event(&self, e: &mut xi2::XIRawEvent) {
         let &mut xi2::XIRawEvent {
             _type: _, serial: _, send_event: _, display: _, extension: _, evtype,
@@ -1014,9 +1272,11 @@ impl X11SharedContext {
                 let keycode = detail as x::KeyCode;
                 let key = Key {
                     code: Keycode(keycode),
-                    sym: self.x_keycode_to_keysym(keycode, 0).map(Keysym::from_x_keysym),
+                    sym: self.xkb_keycode_to_keysym(keycode, self.xkb_current_group.get(), 0).map(Keysym::from_x_keysym),
                     // The code => sym translation is supposedly keyboard-specific, but I found no API in X11
                     // that allows doing this (accepting an XInput2 device id).
+                    // Raw events carry no modifier state, so this is the core keyboard's group as of
+                    // the last XkbStateNotify we processed.
                 };
                 let ev = match evtype {
                     xi2::XI_RawKeyPress => Event::KeyboardKeyPressedRaw { keyboard, instant, key },
@@ -1119,11 +1379,31 @@ impl X11SharedContext {
         }
     }
 
-    fn x_key_event_keysym(&self, x_event: &mut x::XKeyEvent, index: c_int) -> Option<x::KeySym> {
-        match unsafe { x::XLookupKeysym(x_event, index) } {
-            x if x == x::NoSymbol as _ => None,
-            x => Some(x),
+    /// Fallback for when there's no XIC: XLookupString() only handles Latin-1, and knows nothing
+    /// about dead keys and the Compose key, so we handle these ourselves.
+    fn x_lookup_string_with_compose(&self, x_event: &mut x::XKeyEvent) -> (Option<x::KeySym>, Option<String>) {
+        assert_eq!(x_event.type_, x::KeyPress);
+        let mut buf = [0 as c_char; 32];
+        let mut keysym: x::KeySym = 0;
+        let len = unsafe {
+            x::XLookupString(x_event, buf.as_mut_ptr(), buf.len() as _, &mut keysym, ptr::null_mut())
+        };
+        if keysym == x::NoSymbol as x::KeySym {
+            return (None, None);
         }
+        let mut compose_state = self.compose_state.get();
+        let composed = compose_state.feed(keysym, x_event.time);
+        self.compose_state.set(compose_state);
+        let text = match composed {
+            compose::Composed::Char(c) => Some(c.to_string()),
+            compose::Composed::Pending => None,
+            compose::Composed::PassThrough => {
+                // The buffer is Latin-1 encoded, which maps directly to Unicode code points.
+                let text: String = buf[..len.max(0) as usize].iter().map(|&c| c as u8 as char).collect();
+                if text.is_empty() { None } else { Some(text) }
+            },
+        };
+        (Some(keysym), text)
     }
 
     fn x_utf8_lookup_string(&self, xic: x::XIC, x_event: &mut x::XKeyEvent) -> (Option<x::KeySym>, Option<String>) {
@@ -1173,7 +1453,44 @@ impl X11SharedContext {
         }
         result
     }
+    // Some WMs reset the window's cursor to the default arrow when it regains focus,
+    // so hidden or custom cursors would flicker back after e.g Alt-Tab.
+    fn reapply_cursor_for_x_window(&self, window: x::Window) {
+        let err = match self.retrieve_window(window) {
+            Ok(w) => w.refresh_cursor_internal().err(),
+            Err(e) => Some(e),
+        };
+        if let Some(e) = err {
+            trace!("Could not reapply cursor for X Window {}: {}", window, e);
+        }
+    }
+    fn stop_flashing_x_window(&self, window: x::Window) {
+        let w = match self.retrieve_window(window) {
+            Ok(w) => w,
+            Err(_) => return,
+        };
+        if w.is_flashing.get() {
+            if let Err(e) = w.stop_flashing() {
+                trace!("Could not stop flashing X Window {}: {}", window, e);
+            }
+        }
+    }
+    /// When the earliest bounded window flash should stop, if any.
+    pub fn next_window_flash_deadline(&self) -> Option<Instant> {
+        self.weak_windows.borrow().values().filter_map(Weak::upgrade).filter_map(|w| w.flash_deadline.get()).min()
+    }
+    fn stop_expired_window_flashes(&self) {
+        let now = Instant::now();
+        for w in self.weak_windows.borrow().values().filter_map(Weak::upgrade) {
+            if w.flash_deadline.get().map_or(false, |deadline| deadline <= now) {
+                if let Err(e) = w.stop_flashing() {
+                    trace!("Could not stop flashing X Window {}: {}", w.x_window, e);
+                }
+            }
+        }
+    }
     fn set_net_wm_user_time_for_x_window(&self, window: x::Window, time: x::Time) {
+        self.last_user_time.set(time);
         let err = match self.retrieve_window(window) {
             Ok(w) => w.set_net_wm_user_time(time).err(),
             Err(e) => Some(e),
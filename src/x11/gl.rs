@@ -18,23 +18,43 @@
 //   - GLX_CONTEXT_ROBUST_ACCESS_BIT_ARB
 //   - GLX_EXT_create_context_es_profile
 //   - GLX_EXT_create_context_es2_profile
+//
+// If `GLContextSettings::api_preference` asks for it, contexts are created via EGL
+// instead (see egl.rs).
 
-use std::os::raw::{c_void, c_char};
+use std::os::raw::{c_void, c_char, c_int};
 use std::rc::Rc;
 use std::ptr;
+use std::mem;
 use std::slice;
 use version_cmp;
 use super::x11::xlib as x;
 use super::x11::glx::*;
 use super::{X11Context, X11SharedContext, X11SharedWindow};
 use super::xlib_error;
-use gl::{GLPixelFormatChooser, GLContextSettings, GLSwapInterval};
-use error::{Result, failed};
+use super::egl::{self, Egl, EGLContext, EGLSurface};
+use gl::{GLPixelFormatChooser, GLPixelFormatSettings, GLContextSettings, GLSwapInterval, GLApiPreference, GLApi, GLVariant, PresentStats, PresentStatsCollector};
+use error::{Result, failed, unsupported, invalid_arg};
 
 #[derive(Debug)]
 pub struct X11GLContext {
     pub context: Rc<X11SharedContext>,
+    /// NULL for contexts created via EGL.
     pub glx_context: GLXContext,
+    /// Only for contexts created by `create_offscreen_gl_context()`.
+    pub pbuffer: Option<GLXPbuffer>,
+    /// Only for contexts created via EGL.
+    pub egl_context: Option<X11EGLContext>,
+}
+
+#[derive(Debug)]
+pub struct X11EGLContext {
+    pub egl: Rc<Egl>,
+    pub context: EGLContext,
+    /// The client API which has to be bound before making the context current.
+    pub variant: GLVariant,
+    /// Only for contexts created by `create_offscreen_gl_context()`.
+    pub pbuffer: Option<EGLSurface>,
 }
 
 #[derive(Debug)]
@@ -42,6 +62,8 @@ pub struct X11GLPixelFormat {
     pub context: Rc<X11SharedContext>,
     pub visual_info: *mut x::XVisualInfo,
     pub fbconfig: Option<GLXFBConfig>, // GLX >= 1.3
+    /// What was asked for; Used to choose a matching EGLConfig.
+    pub settings: GLPixelFormatSettings,
 }
 
 impl Drop for X11GLPixelFormat {
@@ -52,11 +74,28 @@ impl Drop for X11GLPixelFormat {
     }
 }
 
+impl Drop for X11EGLContext {
+    fn drop(&mut self) {
+        // Like glXDestroyContext(), this is deferred until it's not current to any thread.
+        self.egl.destroy_context(self.context);
+        if let Some(pbuffer) = self.pbuffer {
+            self.egl.destroy_surface(pbuffer);
+        }
+    }
+}
+
 impl Drop for X11GLContext {
     fn drop(&mut self) {
+        if self.egl_context.is_some() {
+            return;
+        }
         unsafe {
+            let x_display = self.context.lock_x_display();
             // Defers destruction until it's not current to any thread.
-            glXDestroyContext(*self.context.lock_x_display(), self.glx_context);
+            glXDestroyContext(*x_display, self.glx_context);
+            if let Some(pbuffer) = self.pbuffer {
+                glXDestroyPbuffer(*x_display, pbuffer);
+            }
         }
     }
 }
@@ -64,7 +103,17 @@ impl Drop for X11GLContext {
 
 
 impl X11GLContext {
+    pub fn api(&self) -> GLApi {
+        match self.egl_context {
+            Some(_) => GLApi::EGL,
+            None => GLApi::GLX,
+        }
+    }
     pub unsafe fn get_proc_address(&self, name: *const c_char) -> *const c_void {
+        if let Some(ref egl_context) = self.egl_context {
+            return egl_context.egl.get_proc_address(name);
+        }
+
         #[cfg(not(target_os = "linux"))]
         unimplemented!("We don't know how the situation is in OSes other than Linux! This could require moving to x11-dl.");
 
@@ -74,25 +123,63 @@ impl X11GLContext {
             Some(p) => p as _,
         }
     }
+    pub fn make_current_offscreen(&self) -> Result<()> {
+        if let Some(ref egl_context) = self.egl_context {
+            let pbuffer = match egl_context.pbuffer {
+                Some(pbuffer) => pbuffer,
+                None => return invalid_arg("This GL context was not created by `create_offscreen_gl_context()`"),
+            };
+            self.context.release_glx_context();
+            egl_context.egl.make_current(pbuffer, egl_context.context, egl_context.variant)?;
+            let generation = self.context.gl_make_current_generation.get().wrapping_add(1);
+            self.context.gl_make_current_generation.set(generation);
+            return Ok(());
+        }
+        let pbuffer = match self.pbuffer {
+            Some(pbuffer) => pbuffer,
+            None => return invalid_arg("This GL context was not created by `create_offscreen_gl_context()`"),
+        };
+        self.context.release_egl_context();
+        let is_ok = unsafe {
+            glXMakeContextCurrent(*self.context.lock_x_display(), pbuffer, pbuffer, self.glx_context)
+        };
+        if is_ok == x::False {
+            return failed("glXMakeContextCurrent() failed");
+        }
+        // Any window that was previously made current is now stale.
+        let generation = self.context.gl_make_current_generation.get().wrapping_add(1);
+        self.context.gl_make_current_generation.set(generation);
+        Ok(())
+    }
 }
 
 
 impl X11Context {
-    pub fn choose_gl_pixel_format(&self, chooser: &GLPixelFormatChooser) -> Result<X11GLPixelFormat> {
-        let glx = self.glx()?;
+    pub fn choose_gl_pixel_format_on_screen(&self, chooser: &dyn GLPixelFormatChooser, screen_num: c_int) -> Result<X11GLPixelFormat> {
+        let glx = match self.glx() {
+            Ok(glx) => glx,
+            Err(e) => {
+                info!("GLX is unavailable ({}); Choosing the window's visual via EGL", e);
+                return self.choose_egl_pixel_format_on_screen(chooser.settings(), screen_num).map_err(|egl_e| {
+                    warn!("Could not choose a visual via EGL either: {}", egl_e);
+                    e
+                });
+            },
+        };
         let x_display = self.lock_x_display();
         let settings = chooser.settings();
+        glx.check_pixel_format_support(settings)?;
 
         if version_cmp::lt((glx.major_version, glx.minor_version), (1, 3)) {
             // Not actually mutated, but glXChooseVisual wants *mut...
             let mut visual_attribs = glx.gen_visual_attribs(settings);
             let visual_info = unsafe {
-                glXChooseVisual(*x_display, self.x_default_screen_num(), visual_attribs.as_mut_ptr())
+                glXChooseVisual(*x_display, screen_num, visual_attribs.as_mut_ptr())
             };
             if visual_info.is_null() {
                 return failed("glXChooseVisual() returned NULL");
             }
-            return Ok(X11GLPixelFormat { context: Rc::clone(&self.0), visual_info, fbconfig: None });
+            return Ok(X11GLPixelFormat { context: Rc::clone(&self.0), visual_info, fbconfig: None, settings: *settings });
         }
 
         // If we're here, we have GLX >= 1.3.
@@ -101,7 +188,7 @@ impl X11Context {
         let fbconfigs = {
             let mut fbcount = 0;
             let fbcs = unsafe { 
-                glXChooseFBConfig(*x_display, self.x_default_screen_num(), visual_attribs.as_ptr(), &mut fbcount)
+                glXChooseFBConfig(*x_display, screen_num, visual_attribs.as_ptr(), &mut fbcount)
             };
             if fbcs.is_null() {
                 return failed("glXChooseFBConfig() returned NULL");
@@ -295,27 +382,47 @@ impl X11Context {
             let visual_info = glXGetVisualFromFBConfig(*x_display, best_fbc);
             assert!(!visual_info.is_null());
             x::XFree(fbconfigs.as_ptr() as *const _ as *mut _);
-            Ok(X11GLPixelFormat { context: Rc::clone(&self.0), visual_info, fbconfig: Some(best_fbc) })
+            Ok(X11GLPixelFormat { context: Rc::clone(&self.0), visual_info, fbconfig: Some(best_fbc), settings: *settings })
         }
     }
+    /// Chooses the visual of an `EGLConfig` matching `settings`, for servers that don't have GLX.
+    ///
+    /// The config that is actually used is chosen again by `create_egl_context()`, among those
+    /// that render to this visual, since the context's settings are only known then.
+    fn choose_egl_pixel_format_on_screen(&self, settings: &GLPixelFormatSettings, screen_num: c_int) -> Result<X11GLPixelFormat> {
+        let egl = self.egl()?;
+        let config = egl.choose_config(settings, &GLContextSettings::default(), egl::EGL_WINDOW_BIT, None)?;
+        let visual_id = match egl.native_visual_id(config) {
+            Some(visual_id) => visual_id,
+            None => return failed("The chosen EGLConfig has no native visual"),
+        };
+        let x_display = self.lock_x_display();
+        let visual_info = unsafe {
+            let mut template: x::XVisualInfo = mem::zeroed();
+            template.visualid = visual_id;
+            template.screen = screen_num;
+            let mut count = 0;
+            x::XGetVisualInfo(*x_display, x::VisualIDMask | x::VisualScreenMask, &mut template, &mut count)
+        };
+        if visual_info.is_null() {
+            return failed(format!("XGetVisualInfo() found no visual with ID 0x{:x} on screen {}", visual_id, screen_num));
+        }
+        Ok(X11GLPixelFormat { context: Rc::clone(&self.0), visual_info, fbconfig: None, settings: *settings })
+    }
 }
 
 
-impl X11SharedWindow {
-    pub fn x11_gl_pixel_format(&self) -> Result<&X11GLPixelFormat> {
-        self.x11_gl_pixel_format.as_ref().map_err(Clone::clone)
-    }
-    pub fn create_gl_context(&self, settings: &GLContextSettings) -> Result<X11GLContext> {
-        let glx = self.context.glx()?;
-        let x_display = self.context.lock_x_display();
-        let &X11GLPixelFormat { visual_info, fbconfig, context: _ } = &self.x11_gl_pixel_format()?;
+impl X11SharedContext {
+    fn create_glx_context(&self, visual_info: *mut x::XVisualInfo, fbconfig: Option<GLXFBConfig>, settings: &GLContextSettings) -> Result<GLXContext> {
+        let glx = self.glx()?;
+        let x_display = self.lock_x_display();
 
         let glx_lt_1_3 = version_cmp::lt((glx.major_version, glx.minor_version), (1, 3));
         let glx_lt_1_4 = version_cmp::lt((glx.major_version, glx.minor_version), (1, 4));
 
         let (f, glx_context) = unsafe {
             let get_glx_context = || if glx_lt_1_3 {
-                ("glXCreateContext", glXCreateContext(*x_display, *visual_info, ptr::null_mut(), x::True))
+                ("glXCreateContext", glXCreateContext(*x_display, visual_info, ptr::null_mut(), x::True))
             } else if glx_lt_1_4 || (!glx_lt_1_4 && !glx.ext.GLX_ARB_create_context) {
                 ("glXCreateNewContext", glXCreateNewContext(*x_display, fbconfig.unwrap(), GLX_RGBA_TYPE, ptr::null_mut(), x::True))
             } else {
@@ -329,34 +436,277 @@ impl X11SharedWindow {
         if glx_context.is_null() {
             return failed(format!("{}() returned NULL", f));
         }
-        Ok(X11GLContext { context: Rc::clone(&self.context), glx_context })
+        Ok(glx_context)
+    }
+    /// Makes no GLX context current, so that an EGL context can be made current instead.
+    fn release_glx_context(&self) {
+        if self.glx().is_err() {
+            return;
+        }
+        unsafe {
+            glXMakeCurrent(*self.lock_x_display(), 0, ptr::null_mut());
+        }
+    }
+    /// Makes no EGL context current, if EGL was ever loaded.
+    fn release_egl_context(&self) {
+        if let Some(Ok(ref egl)) = *self.egl.borrow() {
+            egl.release_current();
+        }
     }
+}
 
-    pub fn make_gl_context_current(&self, c: Option<&X11GLContext>) -> Result<()> {
-        let x_display = self.context.lock_x_display();
-        let glx_context = match c {
-            Some(c) => c.glx_context,
-            None => ptr::null_mut(),
+/// Calls `glx` and/or `egl` in the order given by `preference`.
+/// If both fail, the error of the preferred one is returned.
+fn create_with_preferred_api<T, G, E>(preference: GLApiPreference, glx: G, egl: E) -> Result<T>
+    where G: FnOnce() -> Result<T>, E: FnOnce() -> Result<T>
+{
+    match preference {
+        GLApiPreference::OnlyGLX => glx(),
+        GLApiPreference::OnlyEGL => egl(),
+        GLApiPreference::PreferGLX => glx().or_else(|e| {
+            info!("Could not create a GL context via GLX ({}); Trying EGL", e);
+            egl().map_err(|egl_e| { warn!("Could not create a GL context via EGL either: {}", egl_e); e })
+        }),
+        GLApiPreference::PreferEGL => egl().or_else(|e| {
+            info!("Could not create a GL context via EGL ({}); Trying GLX", e);
+            glx().map_err(|glx_e| { warn!("Could not create a GL context via GLX either: {}", glx_e); e })
+        }),
+    }
+}
+
+impl X11Context {
+    pub fn create_offscreen_gl_context(&self, settings: &GLContextSettings) -> Result<X11GLContext> {
+        let gl_context = create_with_preferred_api(
+            settings.api_preference,
+            || self.create_offscreen_glx_context(settings),
+            || self.create_offscreen_egl_context(settings),
+        )?;
+        info!("Created offscreen GL context via {:?}", gl_context.api());
+        Ok(gl_context)
+    }
+    fn create_offscreen_egl_context(&self, settings: &GLContextSettings) -> Result<X11GLContext> {
+        let egl = self.egl()?;
+        let pixel_format = GLPixelFormatSettings::default();
+        let config = egl.choose_config(&pixel_format, settings, egl::EGL_PBUFFER_BIT, None)?;
+        // Rendering is expected to go to framebuffer objects; The pbuffer only has to exist.
+        let pbuffer = egl.create_pbuffer_surface(config, 1, 1)?;
+        let context = match egl.create_context(config, settings) {
+            Ok(context) => context,
+            Err(e) => {
+                egl.destroy_surface(pbuffer);
+                return Err(e);
+            },
         };
-        unsafe {
-            match self.glx_window {
-                Some(w) => glXMakeContextCurrent(*x_display, w, w, glx_context),
-                None => glXMakeCurrent(*x_display, self.x_window, glx_context),
-            };
+        let egl_context = X11EGLContext { egl, context, variant: settings.version.variant, pbuffer: Some(pbuffer) };
+        Ok(X11GLContext { context: Rc::clone(&self.0), glx_context: ptr::null_mut(), pbuffer: None, egl_context: Some(egl_context), })
+    }
+    fn create_offscreen_glx_context(&self, settings: &GLContextSettings) -> Result<X11GLContext> {
+        let glx = self.glx()?;
+        if version_cmp::lt((glx.major_version, glx.minor_version), (1, 3)) {
+            return unsupported("Pbuffers require GLX 1.3");
+        }
+        let x_display = self.lock_x_display();
+        let fbconfig_attribs = [
+            GLX_DRAWABLE_TYPE, GLX_PBUFFER_BIT,
+            GLX_RENDER_TYPE, GLX_RGBA_BIT,
+            GLX_RED_SIZE, 8,
+            GLX_GREEN_SIZE, 8,
+            GLX_BLUE_SIZE, 8,
+            GLX_ALPHA_SIZE, 8,
+            GLX_DEPTH_SIZE, 24,
+            GLX_STENCIL_SIZE, 8,
+            0 // keep last
+        ];
+        let fbconfig = unsafe {
+            let mut fbcount = 0;
+            let fbcs = glXChooseFBConfig(*x_display, self.x_default_screen_num(), fbconfig_attribs.as_ptr(), &mut fbcount);
+            if fbcs.is_null() || fbcount <= 0 {
+                return failed("glXChooseFBConfig() found no FBConfig that supports pbuffers");
+            }
+            let fbconfig = *fbcs;
+            x::XFree(fbcs as *mut _);
+            fbconfig
+        };
+        // Rendering is expected to go to framebuffer objects; The pbuffer only has to exist.
+        let pbuffer_attribs = [
+            GLX_PBUFFER_WIDTH, 1,
+            GLX_PBUFFER_HEIGHT, 1,
+            0 // keep last
+        ];
+        let pbuffer = unsafe {
+            xlib_error::sync_catch(*x_display, || glXCreatePbuffer(*x_display, fbconfig, pbuffer_attribs.as_ptr()))?
+        };
+        if pbuffer == 0 {
+            return failed("glXCreatePbuffer() returned None");
         }
+        drop(x_display);
+        match self.create_glx_context(ptr::null_mut(), Some(fbconfig), settings) {
+            Ok(glx_context) => Ok(X11GLContext { context: Rc::clone(&self.0), glx_context, pbuffer: Some(pbuffer), egl_context: None, }),
+            Err(e) => {
+                unsafe {
+                    glXDestroyPbuffer(*self.lock_x_display(), pbuffer);
+                }
+                Err(e)
+            },
+        }
+    }
+}
+
+impl X11SharedWindow {
+    pub fn x11_gl_pixel_format(&self) -> Result<&X11GLPixelFormat> {
+        self.x11_gl_pixel_format.as_ref().map_err(Clone::clone)
+    }
+    pub fn create_gl_context(&self, settings: &GLContextSettings) -> Result<X11GLContext> {
+        // Fail early for windows without OpenGL support, rather than trying both APIs.
+        self.x11_gl_pixel_format()?;
+        let gl_context = create_with_preferred_api(
+            settings.api_preference,
+            || self.create_glx_context(settings),
+            || self.create_egl_context(settings),
+        )?;
+        info!("Created GL context for X Window {} via {:?}", self.x_window, gl_context.api());
+        Ok(gl_context)
+    }
+    fn create_glx_context(&self, settings: &GLContextSettings) -> Result<X11GLContext> {
+        let &X11GLPixelFormat { visual_info, fbconfig, .. } = self.x11_gl_pixel_format()?;
+        let glx_context = self.context.create_glx_context(visual_info, fbconfig, settings)?;
+        Ok(X11GLContext { context: Rc::clone(&self.context), glx_context, pbuffer: None, egl_context: None, })
+    }
+    fn create_egl_context(&self, settings: &GLContextSettings) -> Result<X11GLContext> {
+        let &X11GLPixelFormat { visual_info, settings: ref pixel_format, .. } = self.x11_gl_pixel_format()?;
+        let egl = self.context.egl()?;
+        // The window's visual was already chosen, so only configs for that visual will do.
+        let visual_id = unsafe { (*visual_info).visualid };
+        let config = egl.choose_config(pixel_format, settings, egl::EGL_WINDOW_BIT, Some(visual_id))?;
+        let context = egl.create_context(config, settings)?;
+        let egl_context = X11EGLContext { egl, context, variant: settings.version.variant, pbuffer: None };
+        // A window can only have one EGLSurface, which all of its EGL contexts share.
+        if self.egl_surface.get().is_none() {
+            let surface = egl_context.egl.create_window_surface(config, self.x_window, pixel_format.srgb)?;
+            self.egl_surface.set(Some(surface));
+        }
+        Ok(X11GLContext { context: Rc::clone(&self.context), glx_context: ptr::null_mut(), pbuffer: None, egl_context: Some(egl_context), })
+    }
+
+    pub fn make_gl_context_current(&self, c: Option<&X11GLContext>) -> Result<()> {
+        match c.and_then(|c| c.egl_context.as_ref()) {
+            Some(egl_context) => {
+                let surface = match self.egl_surface.get() {
+                    Some(surface) => surface,
+                    None => return invalid_arg("This GL context was created via EGL for another window"),
+                };
+                self.context.release_glx_context();
+                egl_context.egl.make_current(surface, egl_context.context, egl_context.variant)?;
+            },
+            None => {
+                self.context.release_egl_context();
+                let x_display = self.context.lock_x_display();
+                let glx_context = match c {
+                    Some(c) => c.glx_context,
+                    None => ptr::null_mut(),
+                };
+                unsafe {
+                    match self.glx_window {
+                        Some(w) => glXMakeContextCurrent(*x_display, w, w, glx_context),
+                        None => glXMakeCurrent(*x_display, self.x_window, glx_context),
+                    };
+                }
+            },
+        }
+        // Any other window that was previously made current is now stale.
+        let generation = self.context.gl_make_current_generation.get().wrapping_add(1);
+        self.context.gl_make_current_generation.set(generation);
+        self.gl_generation.set(c.map(|_| generation));
+        self.gl_api.set(c.map(X11GLContext::api));
         Ok(())
     }
 
+    fn ensure_gl_context_is_still_current(&self) -> Result<()> {
+        match self.gl_generation.get() {
+            None => failed("No GL context was made current to this window"),
+            Some(g) if g != self.context.gl_make_current_generation.get() => {
+                failed("The GL context was made current again since it was made current to this window")
+            },
+            Some(_) => Ok(()),
+        }
+    }
+
     pub fn gl_swap_buffers(&self) -> Result<()> {
+        self.ensure_gl_context_is_still_current()?;
+        if let (Some(GLApi::EGL), Some(surface)) = (self.gl_api.get(), self.egl_surface.get()) {
+            self.context.egl()?.swap_buffers(surface)?;
+            if let Some(collector) = self.present_stats.borrow_mut().as_mut() {
+                collector.record(None, self.refresh_rate.get());
+            }
+            return Ok(());
+        }
+        let drawable = match self.glx_window {
+            Some(w) => w,
+            None => self.x_window,
+        };
         unsafe {
-            glXSwapBuffers(*self.context.lock_x_display(), match self.glx_window {
-                Some(w) => w,
-                None => self.x_window,
-            });
+            glXSwapBuffers(*self.context.lock_x_display(), drawable);
+        }
+        if let Some(collector) = self.present_stats.borrow_mut().as_mut() {
+            collector.record(self.gl_media_stream_counter(drawable), self.refresh_rate.get());
         }
         Ok(())
     }
+    /// Gets the media stream counter (i.e the number of vertical retraces so far) via
+    /// `GLX_OML_sync_control`, if available.
+    fn gl_media_stream_counter(&self, drawable: GLXDrawable) -> Option<i64> {
+        let glx = self.context.glx().ok()?;
+        if !glx.ext.GLX_OML_sync_control {
+            return None;
+        }
+        let get_sync_values = glx.ext.glXGetSyncValuesOML?;
+        let (mut ust, mut msc, mut sbc) = (0, 0, 0);
+        let is_ok = unsafe {
+            get_sync_values(*self.context.lock_x_display(), drawable, &mut ust, &mut msc, &mut sbc)
+        };
+        if is_ok == x::False {
+            return None;
+        }
+        Some(msc)
+    }
+    pub fn set_present_stats_collection(&self, enable: bool) {
+        // This caches it; monitor changes keep it up to date, so presents never need a roundtrip.
+        if enable && self.refresh_rate.get().is_none() {
+            if let Err(e) = self.current_refresh_rate() {
+                debug!("The refresh rate is unknown, so missed vblanks will be estimated from the shortest interval between presents: {}", e);
+            }
+        }
+        let mut present_stats = self.present_stats.borrow_mut();
+        match (enable, present_stats.is_some()) {
+            (true, false) => *present_stats = Some(PresentStatsCollector::default()),
+            (false, true) => *present_stats = None,
+            _ => (),
+        }
+    }
+    pub fn present_stats(&self) -> PresentStats {
+        self.present_stats.borrow().as_ref().map(PresentStatsCollector::stats).unwrap_or_default()
+    }
+    pub fn gl_swap_interval(&self) -> Option<GLSwapInterval> {
+        self.gl_swap_interval.get()
+    }
     pub fn gl_set_swap_interval(&self, interval: GLSwapInterval) -> Result<()> {
+        self.gl_set_swap_interval_raw(interval)?;
+        self.gl_swap_interval.set(Some(interval));
+        Ok(())
+    }
+    fn gl_set_swap_interval_raw(&self, interval: GLSwapInterval) -> Result<()> {
+        self.ensure_gl_context_is_still_current()?;
+        if self.gl_api.get() == Some(GLApi::EGL) {
+            let interval = match interval {
+                GLSwapInterval::VSync => 1,
+                GLSwapInterval::Immediate => 0,
+                GLSwapInterval::Interval(i) if i >= 0 => i,
+                GLSwapInterval::LateSwapTearing | GLSwapInterval::Interval(_) => {
+                    return unsupported("EGL has no late swap tearing");
+                },
+            };
+            return self.context.egl()?.swap_interval(interval);
+        }
         let glx = self.context.glx()?;
 
         let interval = match interval {
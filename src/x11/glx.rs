@@ -7,7 +7,7 @@ use super::x11::glx::arb::*;
 use super::missing_bits::glx::*;
 use super::X11SharedContext;
 use gl::{GLPixelFormatSettings, GLContextSettings, GLVariant, GLContextResetNotificationStrategy, GLVersion, GLProfile};
-use error::{Result, failed};
+use error::{Result, failed, unsupported};
 use version_cmp;
 
 pub mod fn_types {
@@ -24,6 +24,9 @@ pub mod fn_types {
     pub type glXSwapIntervalEXT = unsafe extern fn(
         *mut x::Display, GLXDrawable, interval: c_int
     );
+    pub type glXGetSyncValuesOML = unsafe extern fn(
+        *mut x::Display, GLXDrawable, ust: *mut i64, msc: *mut i64, sbc: *mut i64
+    ) -> x::Bool;
     pub type glXCreateContextAttribsARB = unsafe extern fn(
         *mut x::Display, GLXFBConfig, share_context: GLXContext, 
         direct: x::Bool, attrib_list: *const c_int
@@ -81,6 +84,8 @@ macro_rules! glx_ext {
 
 glx_ext!((
     GLX_ARB_multisample
+    GLX_ARB_framebuffer_sRGB
+    GLX_EXT_framebuffer_sRGB
     GLX_EXT_swap_control
     GLX_EXT_swap_control_tear
     GLX_MESA_swap_control
@@ -98,6 +103,7 @@ glx_ext!((
     glXSwapIntervalMESA
     glXGetSwapIntervalMESA
     glXSwapIntervalSGI
+    glXGetSyncValuesOML
     glXCreateContextAttribsARB
 ));
 
@@ -121,6 +127,12 @@ impl X11SharedContext {
 }
 
 impl Glx {
+    pub fn check_pixel_format_support(&self, settings: &GLPixelFormatSettings) -> Result<()> {
+        if settings.srgb && !(self.ext.GLX_ARB_framebuffer_sRGB || self.ext.GLX_EXT_framebuffer_sRGB) {
+            return unsupported("Missing extension `GLX_ARB_framebuffer_sRGB` (or `GLX_EXT_framebuffer_sRGB`), required for sRGB");
+        }
+        Ok(())
+    }
     // Functions that generate context attrib arrays (i.e 0-terminated
     // arrays of i32).
     //
@@ -132,13 +144,13 @@ impl Glx {
     // instead.
 
     // GLX below 1.3
-    pub fn gen_visual_attribs(&self, settings: &GLPixelFormatSettings) -> [c_int; 30] {
+    pub fn gen_visual_attribs(&self, settings: &GLPixelFormatSettings) -> [c_int; 32] {
         let &GLPixelFormatSettings {
             depth_bits, stencil_bits, double_buffer, stereo,
             red_bits, blue_bits, green_bits, alpha_bits,
             accum_red_bits, accum_blue_bits, accum_green_bits, 
             accum_alpha_bits, aux_buffers, msaa,
-            transparent: _,
+            transparent: _, srgb,
         } = settings;
         let mut attr = [
             GLX_RGBA,
@@ -159,10 +171,12 @@ impl Glx {
             0, // GLX_SAMPLE_BUFFERS_ARB value, see below
             0, // GLX_SAMPLES_ARB attrib, see below
             0, // GLX_SAMPLES_ARB value, see below
+            0, // GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB attrib, see below
+            0, // GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB value, see below
             0 // end
         ];
 
-        let mut i = attr.len()-7;
+        let mut i = attr.len()-9;
         if double_buffer {
             attr[i] = GLX_DOUBLEBUFFER;
             i += 1;
@@ -176,17 +190,23 @@ impl Glx {
             attr[i+1] = msaa.buffer_count as _;
             attr[i+2] = GLX_SAMPLES; // Same as prefixed with _ARB
             attr[i+3] = msaa.sample_count as _;
+            i += 4;
+        }
+        // Unlike other booleans, this one is followed by its value.
+        if srgb {
+            attr[i] = GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB;
+            attr[i+1] = x::True;
         }
         attr
     }
 
     // GLX 1.3 and above
-    pub fn gen_fbconfig_attribs(&self, settings: &GLPixelFormatSettings) -> [c_int; 43] {
+    pub fn gen_fbconfig_attribs(&self, settings: &GLPixelFormatSettings) -> [c_int; 45] {
         let &GLPixelFormatSettings {
             depth_bits, stencil_bits, double_buffer, stereo,
             red_bits, blue_bits, green_bits, alpha_bits,
             accum_red_bits, accum_blue_bits, accum_green_bits, 
-            accum_alpha_bits, aux_buffers, msaa, ..
+            accum_alpha_bits, aux_buffers, msaa, srgb, ..
         } = settings;
         let mut attribs = [
             GLX_FBCONFIG_ID, GLX_DONT_CARE,
@@ -210,9 +230,10 @@ impl Glx {
             GLX_CONFIG_CAVEAT, GLX_DONT_CARE, // NOTE: Setting it to GLX_NONE is very strict.
             0, 0, // GLX_SAMPLE_BUFFERS, msaa.buffer_count as _, // FIXME: Nobody said we had GLX_ARB_MULTISAMPLE!
             0, 0, // GLX_SAMPLES, msaa.sample_count as _,
+            0, 0, // GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB, True,
             0 // keep last
         ];
-        let mut i = attribs.len() - 5;
+        let mut i = attribs.len() - 7;
         assert_eq!(0, attribs[i]);
         if self.ext.GLX_ARB_multisample {
             attribs[i] = GLX_SAMPLE_BUFFERS;
@@ -224,6 +245,12 @@ impl Glx {
             attribs[i] = msaa.sample_count as _;
             i += 1;
         }
+        if srgb {
+            attribs[i] = GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB;
+            i += 1;
+            attribs[i] = x::True;
+            i += 1;
+        }
         assert_eq!(0, *attribs.last().unwrap());
         attribs
     }
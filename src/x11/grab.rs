@@ -0,0 +1,166 @@
+use std::rc::{Rc, Weak};
+use std::os::raw::c_int;
+use error::{Result, failed, busy};
+use window::{GrabOptions, GrabHolder, WindowHandle};
+use super::context::X11SharedContext;
+use super::window::X11SharedWindow;
+use super::xlib_error;
+use super::missing_bits;
+use super::x11::xlib as x;
+use super::x11::xinput2 as xi2;
+use Vec2;
+
+/// Releases the grab on drop.
+#[derive(Debug)]
+pub struct X11InputGrab {
+    context: Rc<X11SharedContext>,
+    x_window: x::Window,
+    pointer: c_int,
+    has_keyboard: bool,
+}
+
+impl Drop for X11InputGrab {
+    fn drop(&mut self) {
+        {
+            let x_display = self.context.lock_x_display();
+            unsafe {
+                xi2::XIUngrabDevice(*x_display, self.pointer, x::CurrentTime);
+                if self.has_keyboard {
+                    x::XUngrabKeyboard(*x_display, x::CurrentTime);
+                }
+                x::XFlush(*x_display);
+            }
+        }
+        self.context.input_grab_window.set(None);
+        self.context.input_grab_has_keyboard.set(false);
+        trace!("Released input grab of window {}", self.x_window);
+    }
+}
+
+fn grab_status_to_result(status: c_int, func: &str) -> Result<()> {
+    let reason = match status {
+        x::GrabSuccess => return Ok(()),
+        x::AlreadyGrabbed => return busy(format!("{}() returned AlreadyGrabbed (another client holds a grab)", func)),
+        x::GrabFrozen => return busy(format!("{}() returned GrabFrozen (another client froze the device)", func)),
+        x::GrabNotViewable => "GrabNotViewable (the window must be visible)",
+        x::GrabInvalidTime => "GrabInvalidTime",
+        _ => "garbage",
+    };
+    failed(format!("{}() returned {}", func, reason))
+}
+
+impl X11SharedContext {
+    /// Is this button press outside of the window that holds our input grab, if any?
+    pub fn is_outside_input_grab(&self, x_window: x::Window, position: Vec2<f64>) -> bool {
+        if self.input_grab_window.get() != Some(x_window) {
+            return false;
+        }
+        let size = match self.weak_windows.borrow().get(&x_window).and_then(Weak::upgrade) {
+            Some(w) => w.prev_size.get(),
+            None => return false,
+        };
+        position.x < 0. || position.y < 0. || position.x >= size.w as f64 || position.y >= size.h as f64
+    }
+}
+
+impl X11SharedContext {
+    pub fn pointer_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        if let Some(x_window) = self.input_grab_window.get() {
+            return Ok(Some(GrabHolder::Window(WindowHandle(x_window))));
+        }
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        let status = unsafe {
+            x::XGrabPointer(*x_display, root, x::False, 0, x::GrabModeAsync, x::GrabModeAsync, 0, 0, x::CurrentTime)
+        };
+        probe_status_to_holder(status, "XGrabPointer", || unsafe {
+            x::XUngrabPointer(*x_display, x::CurrentTime);
+            x::XFlush(*x_display);
+        })
+    }
+    pub fn keyboard_grab_holder(&self) -> Result<Option<GrabHolder>> {
+        if let Some(x_window) = self.input_grab_window.get() {
+            if self.input_grab_has_keyboard.get() {
+                return Ok(Some(GrabHolder::Window(WindowHandle(x_window))));
+            }
+        }
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        let status = unsafe {
+            x::XGrabKeyboard(*x_display, root, x::False, x::GrabModeAsync, x::GrabModeAsync, x::CurrentTime)
+        };
+        probe_status_to_holder(status, "XGrabKeyboard", || unsafe {
+            x::XUngrabKeyboard(*x_display, x::CurrentTime);
+            x::XFlush(*x_display);
+        })
+    }
+}
+
+// Interprets the result of a grab made only to find out whether someone else holds one.
+fn probe_status_to_holder<F: FnOnce()>(status: c_int, func: &str, ungrab: F) -> Result<Option<GrabHolder>> {
+    match status {
+        x::GrabSuccess => {
+            ungrab();
+            Ok(None)
+        },
+        x::AlreadyGrabbed | x::GrabFrozen => Ok(Some(GrabHolder::OtherClient)),
+        _ => grab_status_to_result(status, func).map(|()| None),
+    }
+}
+
+impl X11SharedWindow {
+    pub fn grab_input(&self, options: GrabOptions) -> Result<X11InputGrab> {
+        self.context.xi()?;
+        if let Some(x_window) = self.context.input_grab_window.get() {
+            return busy(format!("Window {} already holds an input grab", x_window));
+        }
+        let GrabOptions { owner_events, keyboard } = options;
+        let x_display = self.context.lock_x_display();
+
+        let mut pointer: c_int = 0;
+        let has_client_pointer = unsafe {
+            xi2::XIGetClientPointer(*x_display, 0, &mut pointer)
+        };
+        if has_client_pointer == x::False {
+            return failed("XIGetClientPointer() returned False");
+        }
+
+        let mask_len = missing_bits::xi::XIMaskLen(xi2::XI_LASTEVENT);
+        let mut mask_mem = vec![0; mask_len as usize];
+        for ev in &[xi2::XI_ButtonPress, xi2::XI_ButtonRelease, xi2::XI_Motion] {
+            xi2::XISetMask(&mut mask_mem, *ev);
+        }
+        let mut mask = xi2::XIEventMask { deviceid: pointer, mask_len, mask: mask_mem.as_mut_ptr() };
+        let status = unsafe {
+            xlib_error::sync_catch(*x_display, || xi2::XIGrabDevice(
+                *x_display, pointer, self.x_window, x::CurrentTime, 0,
+                xi2::XIGrabModeAsync, xi2::XIGrabModeAsync, owner_events as _, &mut mask
+            ))?
+        };
+        grab_status_to_result(status, "XIGrabDevice")?;
+
+        // The keyboard is grabbed via the core protocol: an XI2 grab would only deliver XI2 key
+        // events, and we rely on core KeyPress events for text input (see src/x11/xi.rs).
+        if keyboard {
+            let status = unsafe {
+                x::XGrabKeyboard(*x_display, self.x_window, owner_events as _, x::GrabModeAsync, x::GrabModeAsync, x::CurrentTime)
+            };
+            if let Err(e) = grab_status_to_result(status, "XGrabKeyboard") {
+                unsafe {
+                    xi2::XIUngrabDevice(*x_display, pointer, x::CurrentTime);
+                    x::XFlush(*x_display);
+                }
+                return Err(e);
+            }
+        }
+
+        self.context.input_grab_window.set(Some(self.x_window));
+        self.context.input_grab_has_keyboard.set(keyboard);
+        Ok(X11InputGrab {
+            context: Rc::clone(&self.context),
+            x_window: self.x_window,
+            pointer,
+            has_keyboard: keyboard,
+        })
+    }
+}
@@ -16,6 +16,7 @@ pub mod glx {
     pub const GLX_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB: i32 = 0x8256;
     pub const GLX_NO_RESET_NOTIFICATION_ARB              : i32 = 0x8261;
     pub const GLX_LOSE_CONTEXT_ON_RESET_ARB              : i32 = 0x8252;
+    pub const GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB           : i32 = 0x20B2; // Same as the EXT one
 }
 
 // TODO: Send a PR to x11-rs.
@@ -40,6 +41,21 @@ pub mod xutil {
     pub const XConverterNotFound: i32 = -3;
 }
 
+// TODO: Send a PR to x11-rs.
+// Missing items for Xlib's input methods
+pub mod xim {
+    use std::os::raw::{c_ushort, c_ulong};
+
+    pub type XIMStyle = c_ulong;
+
+    #[repr(C)]
+    #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+    pub struct XIMStyles {
+        pub count_styles: c_ushort,
+        pub supported_styles: *mut XIMStyle,
+    }
+}
+
 #[allow(non_upper_case_globals)]
 pub mod wm_state {
     pub const WithdrawnState: i32 = 0;
@@ -60,3 +76,86 @@ pub mod xrender {
     }
 }
 
+
+// TODO: Send a PR to x11-rs.
+// Missing items for XFixes (the crate only has the PointerBarrier type)
+#[allow(non_upper_case_globals)]
+pub mod xfixes {
+    use std::os::raw::c_int;
+    use super::super::x11::xlib as x;
+    use super::super::x11::xfixes::PointerBarrier;
+
+    pub const BarrierPositiveX: c_int = 1 << 0;
+    pub const BarrierPositiveY: c_int = 1 << 1;
+    pub const BarrierNegativeX: c_int = 1 << 2;
+    pub const BarrierNegativeY: c_int = 1 << 3;
+
+    // The x11 crate has no "xfixes" feature, so link it ourselves.
+    #[link(name = "Xfixes")]
+    extern "C" {
+        pub fn XFixesQueryExtension(dpy: *mut x::Display, event_base_return: *mut c_int, error_base_return: *mut c_int) -> x::Bool;
+        pub fn XFixesQueryVersion(dpy: *mut x::Display, major_version_return: *mut c_int, minor_version_return: *mut c_int) -> x::Status;
+        pub fn XFixesCreatePointerBarrier(dpy: *mut x::Display, w: x::Window, x1: c_int, y1: c_int, x2: c_int, y2: c_int, directions: c_int, num_devices: c_int, devices: *mut c_int) -> PointerBarrier;
+        pub fn XFixesDestroyPointerBarrier(dpy: *mut x::Display, b: PointerBarrier);
+    }
+}
+
+// TODO: Send a PR to x11-rs.
+// Missing items for the X Nonrectangular Window Shape Extension (the crate has none)
+#[allow(non_upper_case_globals)]
+pub mod xshape {
+    use std::os::raw::c_int;
+    use super::super::x11::xlib as x;
+
+    pub const ShapeSet: c_int = 0;
+
+    pub const ShapeBounding: c_int = 0;
+    pub const ShapeClip: c_int = 1;
+    pub const ShapeInput: c_int = 2;
+
+    // XShape lives in libXext, which the x11 crate doesn't link.
+    #[link(name = "Xext")]
+    extern "C" {
+        pub fn XShapeQueryExtension(dpy: *mut x::Display, event_base_return: *mut c_int, error_base_return: *mut c_int) -> x::Bool;
+        pub fn XShapeQueryVersion(dpy: *mut x::Display, major_version_return: *mut c_int, minor_version_return: *mut c_int) -> x::Status;
+        pub fn XShapeCombineMask(dpy: *mut x::Display, dest: x::Window, dest_kind: c_int, x_off: c_int, y_off: c_int, src: x::Pixmap, op: c_int);
+        pub fn XShapeCombineRegion(dpy: *mut x::Display, dest: x::Window, dest_kind: c_int, x_off: c_int, y_off: c_int, region: x::Region, op: c_int);
+    }
+}
+
+// TODO: Send a PR to x11-rs.
+// Missing items for the X Keyboard Extension (the crate has the functions, but `XkbStateRec` is opaque)
+#[allow(non_upper_case_globals, non_snake_case)]
+pub mod xkb {
+    use std::os::raw::{c_uint, c_ulong, c_uchar, c_ushort};
+
+    pub const XkbMajorVersion: c_uint = 1;
+    pub const XkbMinorVersion: c_uint = 0;
+    pub const XkbUseCoreKbd: c_uint = 0x0100;
+    pub const XkbGroupStateMask: c_ulong = 1 << 4;
+    pub const XkbGroupNamesMask: c_uint = 1 << 12;
+
+    #[repr(C)]
+    #[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+    pub struct XkbStateRec {
+        pub group: c_uchar,
+        pub locked_group: c_uchar,
+        pub base_group: c_ushort,
+        pub latched_group: c_ushort,
+        pub mods: c_uchar,
+        pub base_mods: c_uchar,
+        pub latched_mods: c_uchar,
+        pub locked_mods: c_uchar,
+        pub compat_state: c_uchar,
+        pub grab_mods: c_uchar,
+        pub compat_grab_mods: c_uchar,
+        pub lookup_mods: c_uchar,
+        pub compat_lookup_mods: c_uchar,
+        pub ptr_buttons: c_ushort,
+    }
+
+    /// The group that a core event's `state` field refers to.
+    pub fn XkbGroupForCoreState(state: c_uint) -> c_uint {
+        (state >> 13) & 0x3
+    }
+}
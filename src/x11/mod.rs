@@ -64,8 +64,19 @@ pub use self::gl::{
     X11GLContext,
 };
 pub mod glx;
+pub mod egl;
 pub mod xrender;
 pub mod xi;
+pub mod xfixes;
+pub mod xshape;
+pub mod xkb;
+pub mod grab;
+pub use self::grab::X11InputGrab;
+#[cfg(feature = "synthetic-input")]
+pub mod xtest;
+pub use self::xfixes::X11PointerBarrierID;
+pub mod xrandr;
+pub use self::xrandr::{X11MonitorID, X11DisplayModeGuard};
 pub mod atoms;
 pub mod prop;
 pub mod xlib_error;
@@ -73,11 +84,16 @@ pub mod missing_bits;
 pub mod net_wm;
 pub mod motif_wm;
 pub mod keys;
+pub mod compose;
+pub mod keysym_ucs;
+pub mod accessibility;
+pub mod taskbar_progress;
+pub mod dbus;
 pub mod device;
 pub use self::device::{X11DeviceID, X11TabletInfo, X11KeyboardState, X11MouseButtonsState, X11TabletPadButtonsState, X11TabletStylusButtonsState};
 
 use hint::Hint;
-use error::{Result, failed};
+use error::{Result, failed, unsupported_unexplained};
 
 pub fn set_hint(hint: Hint) -> Result<()> {
     match hint {
@@ -89,5 +105,12 @@ pub fn set_hint(hint: Hint) -> Result<()> {
             xlib_error::DO_USE_DMC_XLIB_ERROR_HANDLERS = !use_xlib;
             Ok(())
         },
+        Hint::XlibRoundtripTimeout(timeout) => unsafe {
+            xlib_error::ROUNDTRIP_TIMEOUT = timeout;
+            Ok(())
+        },
+        // Handled by the Linux backend, which is the only one that caches device capabilities.
+        Hint::LazyDeviceInfo(_) => unsupported_unexplained(),
+        Hint::LogLevel(_) => unreachable!("Handled by hint::set_hint()"),
     }
 }
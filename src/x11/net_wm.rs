@@ -9,6 +9,22 @@ pub enum NetWMStateAction {
     Add    = 1,
     Toggle = 2,
 }
+/// Directions for `_NET_WM_MOVERESIZE`.
+pub mod moveresize {
+    use std::os::raw::c_long;
+    pub const SIZE_TOPLEFT     : c_long = 0;
+    pub const SIZE_TOP         : c_long = 1;
+    pub const SIZE_TOPRIGHT    : c_long = 2;
+    pub const SIZE_RIGHT       : c_long = 3;
+    pub const SIZE_BOTTOMRIGHT : c_long = 4;
+    pub const SIZE_BOTTOM      : c_long = 5;
+    pub const SIZE_BOTTOMLEFT  : c_long = 6;
+    pub const SIZE_LEFT        : c_long = 7;
+    pub const MOVE             : c_long = 8;
+    pub const SIZE_KEYBOARD    : c_long = 9;
+    pub const MOVE_KEYBOARD    : c_long = 10;
+    pub const CANCEL           : c_long = 11;
+}
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 #[repr(u32)]
 pub enum BypassCompositor {
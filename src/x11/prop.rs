@@ -11,6 +11,7 @@ pub enum PropType {
     Any = x::AnyPropertyType as _,
     Cardinal = x::XA_CARDINAL,
     Atom = x::XA_ATOM,
+    Window = x::XA_WINDOW,
 }
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -41,15 +42,28 @@ impl PropElement for c_char   { const SERVER_BITS: usize =  8; }
 
 
 pub fn set<T: PropElement>(x_display: *mut x::Display, x_window: x::Window, prop: x::Atom, prop_type: PropType, mode: PropMode, data: &[T]) -> Result<()> {
+    set_with_type_atom(x_display, x_window, prop, prop_type as _, mode, data)
+}
+
+// For types that aren't predefined atoms, such as UTF8_STRING.
+pub fn set_with_type_atom<T: PropElement>(x_display: *mut x::Display, x_window: x::Window, prop: x::Atom, prop_type: x::Atom, mode: PropMode, data: &[T]) -> Result<()> {
     unsafe {
         xlib_error::sync_catch(x_display, || {
-            x::XChangeProperty(x_display, x_window, prop, prop_type as _, T::SERVER_BITS as _, mode as _, data.as_ptr() as *const _ as *mut _, data.len() as _);
+            x::XChangeProperty(x_display, x_window, prop, prop_type, T::SERVER_BITS as _, mode as _, data.as_ptr() as *const _ as *mut _, data.len() as _);
         })
     }
 }
 
 // `long_offset` and `long_length` are expressed in multiples of server-side 32-bit elements.
 pub fn get<T: PropElement>(x_display: *mut x::Display, x_window: x::Window, prop: x::Atom, req_type: PropType, long_range: Range<usize>) -> Result<PropData<T>> {
+    match get_if_exists(x_display, x_window, prop, req_type, long_range)? {
+        Some(prop_data) => Ok(prop_data),
+        None => failed("Property doesn't exist for this window"),
+    }
+}
+
+/// Same as `get()`, but a missing property is `Ok(None)` rather than an error.
+pub fn get_if_exists<T: PropElement>(x_display: *mut x::Display, x_window: x::Window, prop: x::Atom, req_type: PropType, long_range: Range<usize>) -> Result<Option<PropData<T>>> {
     assert!(long_range.start <= long_range.end);
     let long_offset = long_range.start;
     let long_length = long_range.end - long_range.start;
@@ -78,13 +92,13 @@ pub fn get<T: PropElement>(x_display: *mut x::Display, x_window: x::Window, prop
         data.set_len(nitems_return as usize);
     }
     if actual_type_return == 0 || actual_format_return == 0 {
-        return failed("Property doesn't exist for this window");
+        return Ok(None);
     }
     if actual_format_return != T::SERVER_BITS as _ {
         return failed(format!("The actual format for this property is {} server-side bits", actual_format_return));
     }
-    Ok(PropData {
+    Ok(Some(PropData {
         data, bytes_remaining_to_be_read: bytes_remaining_to_be_read as _,
-    })
+    }))
 }
 
@@ -0,0 +1,45 @@
+//! Taskbar progress, via the Unity `LauncherEntry` D-Bus API.
+//!
+//! There's no X11 standard for this. The `com.canonical.Unity.LauncherEntry` signal is
+//! understood by KDE Plasma's task manager and by popular GNOME docks (e.g Dash to Dock).
+//! The signal is sent over our connection to the session bus (see `X11SharedContext::session_bus()`).
+//!
+//! The progress is tied to the application's `.desktop` file rather than to a window, so
+//! all windows of the application share it.
+
+use std::env;
+use std::process;
+use error::{Result, failed};
+use super::window::X11SharedWindow;
+use super::dbus::DBUS_TYPE_ARRAY;
+
+impl X11SharedWindow {
+    pub fn set_taskbar_progress(&self, progress: Option<f64>) -> Result<()> {
+        let desktop_file_id = match self.context.app_name {
+            Some(ref app_name) => app_name.clone(),
+            None => match env::current_exe() {
+                Ok(exe) => exe.file_stem().unwrap().to_string_lossy().into_owned(),
+                Err(e) => return failed(format!("Could not guess the application's .desktop file: {}", e)),
+            },
+        };
+        let bus = self.context.session_bus()?;
+        let path = format!("/com/canonical/unity/launcherentry/{}", process::id());
+        let mut msg = bus.new_signal(&path, "com.canonical.Unity.LauncherEntry", "Update")?;
+        {
+            let mut args = msg.append();
+            args.string(&format!("application://{}.desktop", desktop_file_id));
+            args.container(DBUS_TYPE_ARRAY, Some("{sv}"), |properties| match progress {
+                Some(progress) => {
+                    properties.dict_entry("progress", "d", |v| { v.double(progress); });
+                    properties.dict_entry("progress-visible", "b", |v| { v.boolean(true); });
+                },
+                None => {
+                    properties.dict_entry("progress-visible", "b", |v| { v.boolean(false); });
+                },
+            });
+            args.finish()?;
+        }
+        bus.send(&msg)?;
+        Ok(())
+    }
+}
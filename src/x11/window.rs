@@ -1,29 +1,38 @@
 extern crate libc;
 
 use std::ptr;
-use std::rc::Rc;
+use std::slice;
+use std::rc::{Rc, Weak};
 use std::cell::{Cell, RefCell};
 use std::os::raw::{c_void, c_char, c_int, c_uint, c_long, c_ulong};
 use std::ops::{Deref, Range};
 use std::mem;
+use std::fmt;
 use std::env;
-use std::ffi::CString;
-
-use window::{self, Window, WindowSettings, WindowHandle, WindowTypeHint, WindowStyleHint};
-use error::{Result, failed, failed_unexplained};
-use device::{self, DeviceID, WindowMouseState, WindowTabletState};
+use std::ffi::{CStr, CString};
+use std::time::{Duration, Instant};
+
+use window::{self, Window, HitTestFn, WindowSettings, WindowHandle, WindowTypeHint, WindowStyleHint, TitleBarFeatures, ResizeEdge, WindowOp, Capability, WindowStateFlags};
+use error::{Result, failed, failed_unexplained, invalid_arg, unsupported};
+use event::{Event, EventInstant};
+use os::OsEventInstant;
+use device::{self, DeviceID, MouseButton, WindowMouseState, WindowTabletState};
 use vek::{Vec2, Extent2, Rect, Clamp, Rgba};
 use version_cmp;
+use gl::{PresentStatsCollector, GLSwapInterval, GLApi};
 
 use super::x11::xlib as x;
 use super::x11::glx::*;
+use super::egl::EGLSurface;
 use super::{X11Context, X11SharedContext, X11GLPixelFormat};
 use super::cursor::X11Cursor;
 use super::missing_bits;
-use super::net_wm::{NetWMStateAction, NetWMWindowType, BypassCompositor};
+use super::net_wm::{self, NetWMStateAction, NetWMWindowType, BypassCompositor};
 use super::motif_wm;
 use super::prop::{self, PropType, PropMode, PropElement, PropData};
 use super::xlib_error;
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle, XlibWindowHandle};
 
 
 pub type X11WindowHandle = x::Window;
@@ -43,15 +52,53 @@ pub struct X11SharedWindow {
     // NOTE: If I implement child windows one day, they should not have their own XIC.
     // Or should they?
     pub xic: Option<x::XIC>,
+    // An unmapped child window that carries `_NET_WM_USER_TIME` (see `_NET_WM_USER_TIME_WINDOW`),
+    // so that the window manager doesn't have to watch all property changes of `x_window`.
+    pub user_time_window: Option<x::Window>,
+    pub is_text_input_active: Cell<bool>,
     pub user_cursor: RefCell<Option<X11Cursor>>,
     pub is_cursor_visible: Cell<bool>,
+    pub title_bar_buttons: Cell<TitleBarFeatures>,
     pub prev_pos: Cell<Vec2<i32>>,
     pub prev_size: Cell<Extent2<u32>>,
+    // The context's GL make-current generation at the time a GL context was last
+    // made current to this window, if any.
+    pub gl_generation: Cell<Option<u64>>,
+    /// The API of the GL context that was last made current to this window, if any.
+    pub gl_api: Cell<Option<GLApi>>,
+    /// Created along with the first GL context that is created via EGL for this window.
+    pub egl_surface: Cell<Option<EGLSurface>>,
+    /// `None` unless enabled via `set_present_stats_collection()`.
+    pub(crate) present_stats: RefCell<Option<PresentStatsCollector>>,
+    /// The last swap interval that was successfully set via `gl_set_swap_interval()`.
+    pub gl_swap_interval: Cell<Option<GLSwapInterval>>,
+    /// The scale factor of the monitor the window was last known to be on, if it was ever computed.
+    pub scale_factor: Cell<Option<f64>>,
+    /// The refresh rate of the monitor the window was last known to be on, if it was ever computed.
+    pub refresh_rate: Cell<Option<f64>>,
+    /// The rect of the monitor the window's center was on when `scale_factor` and `refresh_rate`
+    /// were last checked, if any. They are only checked again once the center leaves it.
+    pub monitor_rect: Cell<Option<Rect<i32, u32>>>,
+    pub(crate) hit_test: RefCell<Option<HitTestFn>>,
+    /// Was `set_icon_name()` ever called? If so, `set_title()` leaves `WM_ICON_NAME` alone.
+    pub has_icon_name: Cell<bool>,
+    /// Was `flash()` called, without the window having gained focus since?
+    pub is_flashing: Cell<bool>,
+    /// When bounded flashing (see `flash()`) should stop.
+    pub flash_deadline: Cell<Option<Instant>>,
+    /// Is the window neither maximized nor full-screen, as of the last `_NET_WM_STATE` change?
+    pub is_in_normal_state: Cell<bool>,
+    /// The window's rect the last time it was configured while in normal state, if known.
+    pub normal_rect: Cell<Option<Rect<i32, u32>>>,
 }
 
 #[derive(Debug)]
 pub struct X11Window(pub Rc<X11SharedWindow>);
 
+/// How long a single flash lasts, for `Window::flash()`. X11 has no notion of flashes,
+/// so this mimics the default on Windows.
+const FLASH_PERIOD: Duration = Duration::from_millis(500);
+
 
 impl Window {
     /// (X11-only) Gets the X input context (XIC) associated with this `Window`, if present.
@@ -60,6 +107,61 @@ impl Window {
     pub fn xic(&self) -> Option<x::XIC> {
         self.0.xic
     }
+    /// (X11-only) Sets a property of type `CARDINAL` (format 32) on this window, replacing
+    /// any previous value.
+    ///
+    /// Atoms can be obtained via `Context::intern_atom()` or `Context::atoms()`.
+    pub fn set_property_cardinal(&self, prop: x::Atom, data: &[u32]) -> Result<()> {
+        // Format-32 data is passed to Xlib as an array of longs, whatever their size.
+        let data: Vec<c_ulong> = data.iter().map(|x| *x as _).collect();
+        self.0.set_prop(prop, PropType::Cardinal, PropMode::Replace, &data)
+    }
+    /// (X11-only) Sets a property of type `UTF8_STRING` (format 8) on this window, replacing
+    /// any previous value.
+    pub fn set_property_utf8(&self, prop: x::Atom, data: &str) -> Result<()> {
+        let utf8_string = self.0.context.atoms.UTF8_STRING()?;
+        prop::set_with_type_atom(*self.0.context.lock_x_display(), self.0.x_window, prop, utf8_string, PropMode::Replace, data.as_bytes())
+    }
+    /// (X11-only) Gets a property of type `CARDINAL` (format 32) of this window.
+    ///
+    /// It is an error if the property doesn't exist or doesn't have format 32; If it has
+    /// another type, the result is empty.
+    pub fn property_cardinal(&self, prop: x::Atom) -> Result<Vec<u32>> {
+        // The length is in 32-bit units; The server returns at most the property's actual length.
+        let PropData { data, .. } = self.0.prop::<c_ulong>(prop, PropType::Cardinal, 0..(::std::i32::MAX as usize))?;
+        Ok(data.into_iter().map(|x| x as u32).collect())
+    }
+    /// (X11-only) Deletes a property of this window. Deleting a property that doesn't exist
+    /// is not an error.
+    pub fn delete_property(&self, prop: x::Atom) -> Result<()> {
+        self.0.delete_prop(prop)
+    }
+}
+
+impl<'a> From<&'a Window> for window::NativeWindowHandle {
+    fn from(w: &'a Window) -> Self {
+        window::NativeWindowHandle::X11 {
+            window: w.0.x_window as _,
+            display: *w.0.context.lock_x_display() as _,
+        }
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+unsafe impl HasRawWindowHandle for Window {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = XlibWindowHandle::empty();
+        handle.window = self.0.x_window;
+        // Zero means "unknown" for raw-window-handle.
+        handle.visual_id = self.0.x_visual_id().unwrap_or(0);
+        RawWindowHandle::Xlib(handle)
+    }
+}
+
+impl fmt::Display for WindowHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "x11:{:#x}", self.0)
+    }
 }
 
 impl WindowHandle {
@@ -82,13 +184,36 @@ impl Drop for X11SharedWindow {
             ref mut context,
             x_window, 
             glx_window,
-            colormap, xic, user_cursor: _,
+            colormap, xic, user_time_window, user_cursor: _,
+            is_text_input_active: _,
             is_cursor_visible: _,
+            title_bar_buttons: _,
             x11_gl_pixel_format: _,
             prev_pos: _,
             prev_size: _,
+            gl_generation: _,
+            gl_api: _,
+            ref egl_surface,
+            present_stats: _,
+            gl_swap_interval: _,
+            scale_factor: _,
+            refresh_rate: _,
+            monitor_rect: _,
+            hit_test: _,
+            has_icon_name: _,
+            is_flashing: _,
+            flash_deadline: _,
+            is_in_normal_state: _,
+            normal_rect: _,
         } = self;
 
+        if let Some(surface) = egl_surface.get() {
+            // EGL was necessarily loaded in order to create the surface.
+            if let Ok(egl) = context.egl() {
+                egl.destroy_surface(surface);
+            }
+        }
+
         let x_display = context.lock_x_display();
 
         match context.weak_windows.borrow_mut().remove(&x_window) {
@@ -104,6 +229,9 @@ impl Drop for X11SharedWindow {
             if let Some(w) = glx_window {
                 glXDestroyWindow(*x_display, w);
             };
+            if let Some(w) = user_time_window {
+                x::XDestroyWindow(*x_display, w);
+            }
             x::XDestroyWindow(*x_display, x_window);
             trace!("Destroyed X Window {}", x_window);
             x::XFreeColormap(*x_display, colormap);
@@ -114,27 +242,64 @@ impl Drop for X11SharedWindow {
 
 
 impl X11Context {
+    /// Centers a window of the given size within the current desktop's work area, or
+    /// the whole screen if the window manager doesn't tell.
+    fn centered_window_position(&self, screen_num: c_int, size: Extent2<u32>) -> Vec2<i32> {
+        let work_area = if screen_num != self.x_default_screen_num() {
+            None // The work area is only advertised for the default screen's root window.
+        } else {
+            self.current_desktop().and_then(|i| self.desktops().map(|d| d.get(i).map(|d| d.work_area))).unwrap_or(None)
+        };
+        let work_area = work_area.unwrap_or_else(|| {
+            let x_display = self.lock_x_display();
+            let (w, h) = unsafe {
+                (x::XDisplayWidth(*x_display, screen_num), x::XDisplayHeight(*x_display, screen_num))
+            };
+            Rect::new(0, 0, w as _, h as _)
+        });
+        Vec2::new(
+            work_area.x + (work_area.w as i32 - size.w as i32) / 2,
+            work_area.y + (work_area.h as i32 - size.h as i32) / 2,
+        )
+    }
     pub fn create_window(&self, window_settings: &WindowSettings) -> Result<X11Window> {
+        self.create_window_on_screen(self.x_default_screen_num() as _, window_settings)
+    }
+    pub fn create_window_on_screen(&self, screen: usize, window_settings: &WindowSettings) -> Result<X11Window> {
+        let screen_count = self.screen_count()?;
+        if screen >= screen_count {
+            return invalid_arg(format!("There is no X screen {} (screen count: {})", screen, screen_count));
+        }
+        let screen_num = screen as c_int;
+
         let x_display = self.lock_x_display();
 
         let parent = unsafe {
-            x::XDefaultRootWindow(*x_display)
+            x::XRootWindow(*x_display, screen_num)
         };
 
         let &WindowSettings {
-            ref opengl, high_dpi,
+            ref opengl, high_dpi, parent: foreign_parent, position, centered, override_redirect, ref wm_class,
         } = window_settings;
+        // Embedded windows aren't managed by the window manager anyway.
+        let override_redirect = override_redirect && foreign_parent.is_none();
 
         if high_dpi {
             warn!("The `high_dpi` setting was set to `true`, but will not be handled.");
         }
 
-        let (x, y, w, h) = (0, 0, 600, 480);
+        let (w, h) = (600, 480);
+        let position = match (foreign_parent, position) {
+            (Some(_), _) => None,
+            (None, Some(position)) => Some(position),
+            (None, None) if centered => Some(self.centered_window_position(screen_num, Extent2::new(w, h))),
+            (None, None) => None,
+        };
+        let (x, y) = position.map(|p| (p.x, p.y)).unwrap_or((0, 0));
 
         let (visual, depth, colormap, x11_gl_pixel_format) = match *opengl {
             Some(ref chooser) => {
-                let _ = self.glx()?; // Return early if GLX is not supported.
-                let x11_gl_pixel_format = self.choose_gl_pixel_format(*chooser)?;
+                let x11_gl_pixel_format = self.choose_gl_pixel_format_on_screen(*chooser, screen_num)?;
                 let vi = unsafe { &*x11_gl_pixel_format.visual_info };
                 let colormap = unsafe {
                     x::XCreateColormap(*x_display, parent, vi.visual, x::AllocNone)
@@ -142,9 +307,6 @@ impl X11Context {
                 (vi.visual, vi.depth, colormap, Ok(x11_gl_pixel_format))
             },
             None => {
-                let screen_num = unsafe {
-                    x::XDefaultScreen(*x_display)
-                };
                 let depth = x::CopyFromParent;
                 let visual = unsafe {
                     x::XDefaultVisual(*x_display, screen_num)
@@ -162,7 +324,7 @@ impl X11Context {
 
         let border_thickness = 0;
         let class = x::InputOutput;
-        let valuemask = x::CWColormap /*| x::CWEventMask /*x::CWBackPixel |*/*/;
+        let valuemask = x::CWColormap | x::CWOverrideRedirect /*| x::CWEventMask /*x::CWBackPixel |*/*/;
         let eventmask_sdl2 = {
              x::FocusChangeMask | x::EnterWindowMask | x::LeaveWindowMask |
              x::ExposureMask | x::ButtonPressMask | x::ButtonReleaseMask |
@@ -203,7 +365,7 @@ impl X11Context {
             event_mask: 0,
             background_pixmap    : 0,
             background_pixel     : unsafe {
-                x::XWhitePixel(*x_display, 0)
+                x::XWhitePixel(*x_display, screen_num)
             },
             border_pixmap        : 0,
             border_pixel         : 0,
@@ -214,7 +376,7 @@ impl X11Context {
             backing_pixel        : 0,
             save_under           : 0,
             do_not_propagate_mask: 0,
-            override_redirect    : 0,
+            override_redirect    : override_redirect as _,
             cursor               : 0,
         };
 
@@ -236,9 +398,26 @@ impl X11Context {
             x::XSelectInput(*x_display, x_window, eventmask_dmc);
         }
 
+        // Embed into a foreign window. We still create the window as a child of the root window
+        // above, because the colormap has to be created for the same screen.
+        if let Some(WindowHandle(foreign_parent)) = foreign_parent {
+            let status = unsafe { xlib_error::sync_catch(*x_display, || {
+                x::XReparentWindow(*x_display, x_window, foreign_parent, 0, 0)
+            })};
+            if let Err(e) = status {
+                unsafe {
+                    x::XDestroyWindow(*x_display, x_window);
+                    x::XFreeColormap(*x_display, colormap);
+                }
+                return failed(format!("XReparentWindow() into X Window {} generated {}", foreign_parent, e));
+            }
+            trace!("Reparented X Window {} into foreign X Window {}", x_window, foreign_parent);
+        }
+
         // We're not done: Say which protocols we support, and
         // set our process ID property for _NET_WM_PING.
-        {
+        // None of this applies to override-redirect windows, since no window manager sees them.
+        if !override_redirect {
             let mut protocols_len = 0_usize;
             let mut protocols = [0; 3];
             if let Ok(atom) = self.atoms.WM_DELETE_WINDOW() {
@@ -265,7 +444,7 @@ impl X11Context {
             }
         }
 
-        if let Ok(net_wm_pid) = self.atoms._NET_WM_PID() {
+        if let (false, Ok(net_wm_pid)) = (override_redirect, self.atoms._NET_WM_PID()) {
             unsafe {
                 match libc::getpid() {
                     pid if pid <= 0 => warn!("getpid() returned {}; _NET_WM_PID won't be set!", pid),
@@ -277,13 +456,29 @@ impl X11Context {
             }
         }
 
+        let user_time_window = match self.atoms._NET_WM_USER_TIME_WINDOW() {
+            Ok(net_wm_user_time_window) if !override_redirect => unsafe {
+                let mut swa: x::XSetWindowAttributes = mem::zeroed();
+                let w = x::XCreateWindow(*x_display, x_window, -1, -1, 1, 1, 0, 0, x::InputOnly as _, ptr::null_mut(), 0, &mut swa);
+                match prop::set(*x_display, x_window, net_wm_user_time_window, PropType::Window, PropMode::Replace, &[w]) {
+                    Ok(()) => Some(w),
+                    Err(e) => {
+                        warn!("Failed to set _NET_WM_USER_TIME_WINDOW: {}", e);
+                        x::XDestroyWindow(*x_display, w);
+                        None
+                    },
+                }
+            },
+            _ => None,
+        };
+
         // Getting an X Input Context for this window
         let xic = if let Some(xim) = self.xim {
-            match unsafe { xlib_error::sync_catch(*x_display, || {
+            let create_ic = |input_style: c_ulong| match unsafe { xlib_error::sync_catch(*x_display, || {
                 x::XCreateIC(xim, 
                     x::XNClientWindow_0.as_ptr(), x_window as c_ulong,
                     x::XNFocusWindow_0.as_ptr(), x_window as c_ulong,
-                    x::XNInputStyle_0.as_ptr(), (x::XIMPreeditNothing | x::XIMStatusNothing) as c_ulong,
+                    x::XNInputStyle_0.as_ptr(), input_style,
                     ptr::null_mut() as *mut c_void,
                 )
             })} {
@@ -300,95 +495,124 @@ impl X11Context {
                         Some(xic)
                     }
                 },
+            };
+            // Prefer XIMPreeditPosition so that `set_text_input_rect()` can place the
+            // candidate window, but not every input method supports it.
+            let nothing = (x::XIMPreeditNothing | x::XIMStatusNothing) as c_ulong;
+            let position = (x::XIMPreeditPosition | x::XIMStatusNothing) as c_ulong;
+            if unsafe { xim_supports_input_style(xim, position) } {
+                create_ic(position).or_else(|| create_ic(nothing))
+            } else {
+                create_ic(nothing)
             }
         } else {
             warn!("X Window {} won't have an XIC because the Context has no XIM.", x_window);
             None
         };
 
-        let glx13 = {
-            let glx = self.glx()?;
-            version_cmp::ge((glx.major_version, glx.minor_version), (1, 3))
-        };
+        let glx13 = self.glx().map(|glx| version_cmp::ge((glx.major_version, glx.minor_version), (1, 3))).unwrap_or(false);
 
-        // Getting a GLXWindow ID
+        // Getting a GLXWindow ID. There's no FBConfig if the visual was chosen via EGL.
         let glx_window = if !glx13 {
             None
         } else {
-            x11_gl_pixel_format.as_ref().map(|pf| {
-                let fbconfig = pf.fbconfig.unwrap();
-                unsafe {
-                    glXCreateWindow(*x_display, fbconfig, x_window, ptr::null_mut())
-                }
-            }).ok()
+            x11_gl_pixel_format.as_ref().ok().and_then(|pf| pf.fbconfig).map(|fbconfig| unsafe {
+                glXCreateWindow(*x_display, fbconfig, x_window, ptr::null_mut())
+            })
         };
 
         let context = Rc::clone(&self.0);
         let is_cursor_visible = Cell::new(true);
         let user_cursor = RefCell::new(None);
         let window = X11Window(Rc::new(X11SharedWindow { 
-            context, x_window, glx_window, colormap, xic, is_cursor_visible, user_cursor,
+            context, x_window, glx_window, colormap, xic, user_time_window, is_cursor_visible, user_cursor,
+            is_text_input_active: Cell::new(true),
+            title_bar_buttons: Cell::new(Default::default()),
             x11_gl_pixel_format,
             prev_pos: Cell::new(Vec2::new(x, y)),
             prev_size: Cell::new(Extent2::new(w, h)),
+            is_in_normal_state: Cell::new(true),
+            normal_rect: Cell::new(Some(Rect::new(x, y, w, h))),
+            gl_generation: Cell::new(None),
+            gl_api: Cell::new(None),
+            egl_surface: Cell::new(None),
+            present_stats: RefCell::new(None),
+            gl_swap_interval: Cell::new(None),
+            scale_factor: Cell::new(None),
+            refresh_rate: Cell::new(None),
+            monitor_rect: Cell::new(None),
+            hit_test: RefCell::new(None),
+            has_icon_name: Cell::new(false),
+            is_flashing: Cell::new(false),
+            flash_deadline: Cell::new(None),
         }));
         match self.weak_windows.borrow_mut().insert(x_window, Rc::downgrade(&window.0)) {
             Some(_) => warn!("Newly created X Window {} was somewhat already present in the context's list", x_window),
             None => trace!("Inserted X Window {} into the context's list", x_window),
         }
 
-        // Even though XCreateWindow takes x, y, w and h, window managers often ignore it
-        // and place the window wherever they want.
-        // We have to enforce this by setting what's called size hints for our window.
-        window.x_set_wm_normal_hints(x::XSizeHints {
-            flags: x::PPosition | x::PSize /*| x::PBaseSize*/,
-            x, y, 
-            width: w as _, 
-            height: h as _,
-            base_width: w as _, 
-            base_height: h as _,
-            // All of the below fields are ignored because of `flags` above
-            min_width:  0, 
-            min_height: 0,
-            max_width:  0,
-            max_height: 0,
-            width_inc: 1,
-            height_inc: 1,
-            min_aspect: x::AspectRatio { x: 0, y: 0 },
-            max_aspect: x::AspectRatio { x: 0, y: 0 },
-            win_gravity: 0,
-        });
-
-        window.x_set_wm_hints(x::XWMHints {
-            flags: x::InputHint,
-            input: x::True,
-            // initial_state: x::NormalState,
-            .. unsafe { mem::zeroed() }
-        });
-
-        {
-            let exe = env::current_exe();
-            let class_name = match exe {
-                Ok(ref exe) => exe.file_stem().unwrap().to_string_lossy(),
-                Err(_) => env::args().nth(0).unwrap().into(),
-            };
-            trace!("Using \"{}\" for X Window {}'s `XClassHint` `res_name` and `res_class` strings.", class_name, x_window);
-            let class_name = CString::new(class_name.into_owned()).unwrap();
+        // Override-redirect windows get no say from the window manager, so there's no point
+        // in telling it anything.
+        if !override_redirect {
+            // Even though XCreateWindow takes x, y, w and h, window managers often ignore it
+            // and place the window wherever they want.
+            // We have to enforce this by setting what's called size hints for our window.
+            // If we don't have a position, don't pretend we do, and let the window manager decide.
+            let position_flags = if position.is_some() { x::PPosition | x::USPosition } else { 0 };
+            window.x_set_wm_normal_hints(x::XSizeHints {
+                flags: position_flags | x::PSize /*| x::PBaseSize*/,
+                x, y, 
+                width: w as _, 
+                height: h as _,
+                base_width: w as _, 
+                base_height: h as _,
+                // All of the below fields are ignored because of `flags` above
+                min_width:  0, 
+                min_height: 0,
+                max_width:  0,
+                max_height: 0,
+                width_inc: 1,
+                height_inc: 1,
+                min_aspect: x::AspectRatio { x: 0, y: 0 },
+                max_aspect: x::AspectRatio { x: 0, y: 0 },
+                win_gravity: 0,
+            });
 
-            window.x_set_class_hint(x::XClassHint {
-                res_name: class_name.as_bytes_with_nul().as_ptr() as *const _ as *mut _,
-                res_class: class_name.as_bytes_with_nul().as_ptr() as *const _ as *mut _,
+            window.x_set_wm_hints(x::XWMHints {
+                flags: x::InputHint,
+                input: x::True,
+                // initial_state: x::NormalState,
+                .. unsafe { mem::zeroed() }
             });
-        }
 
-        unsafe {
-            let argv_owned: Vec<_> = env::args().map(|s| CString::new(s).unwrap()).collect();
-            let argv: Vec<_> = argv_owned.iter().map(|cs| cs.as_bytes_with_nul().as_ptr()).collect();
-            window.x_set_command(argv.len() as _, argv.as_ptr() as _);
-        }
+            {
+                let status = match *wm_class {
+                    Some((ref instance, ref class)) => window.set_wm_class(instance, class),
+                    None => {
+                        let exe = env::current_exe();
+                        let class_name = match (&self.app_name, &exe) {
+                            (&Some(ref app_name), _) => app_name.as_str().into(),
+                            (&None, &Ok(ref exe)) => exe.file_stem().unwrap().to_string_lossy(),
+                            (&None, &Err(_)) => env::args().nth(0).unwrap().into(),
+                        };
+                        trace!("Using \"{}\" for X Window {}'s `XClassHint` `res_name` and `res_class` strings.", class_name, x_window);
+                        window.set_wm_class(&class_name, &class_name)
+                    },
+                };
+                if let Err(e) = status {
+                    warn!("Could not set X Window {}'s `WM_CLASS`: {}", x_window, e);
+                }
+            }
 
-        if let Err(e) = window.set_net_wm_window_type(&[NetWMWindowType::Normal]) {
-            warn!("Could not set the X Window {}'s `_NET_WM_WINDOW_TYPE` to `_NET_WM_WINDOW_TYPE_NORMAL`: {}", x_window, e);
+            unsafe {
+                let argv_owned: Vec<_> = env::args().map(|s| CString::new(s).unwrap()).collect();
+                let argv: Vec<_> = argv_owned.iter().map(|cs| cs.as_bytes_with_nul().as_ptr()).collect();
+                window.x_set_command(argv.len() as _, argv.as_ptr() as _);
+            }
+
+            if let Err(e) = window.set_net_wm_window_type(&[NetWMWindowType::Normal]) {
+                warn!("Could not set the X Window {}'s `_NET_WM_WINDOW_TYPE` to `_NET_WM_WINDOW_TYPE_NORMAL`: {}", x_window, e);
+            }
         }
 
         if let Err(e) = self.xi_select_all_non_raw_events_all_devices(x_window) {
@@ -400,6 +624,15 @@ impl X11Context {
         Ok(window)
     }
 
+    pub fn window_count(&self) -> usize {
+        self.weak_windows.borrow().len()
+    }
+    pub fn windows(&self) -> Vec<x::Window> {
+        self.weak_windows.borrow().keys().cloned().collect()
+    }
+    pub fn window_by_handle(&self, x_window: x::Window) -> Option<X11Window> {
+        self.weak_windows.borrow().get(&x_window).and_then(Weak::upgrade).map(X11Window)
+    }
     pub fn window_from_handle(&self, x_window: x::Window, params: Option<&X11WindowFromHandleParams>) -> Result<X11Window> {
         if let Some(weak) = self.weak_windows.borrow().get(&x_window) {
             if let Some(strong) = weak.upgrade() {
@@ -434,9 +667,26 @@ impl X11Context {
         let glx_window = params.map(|p| p.glx_window).unwrap_or(None);
         let window = X11Window(Rc::new(X11SharedWindow {
             context, x_window, glx_window, colormap, xic, is_cursor_visible, user_cursor,
+            user_time_window: None,
+            is_text_input_active: Cell::new(true),
+            title_bar_buttons: Cell::new(Default::default()),
             x11_gl_pixel_format: failed("OpenGL is not guaranteed on foreign windows"),
             prev_pos: unimplemented!(),
             prev_size: unimplemented!(),
+            is_in_normal_state: Cell::new(true),
+            normal_rect: Cell::new(None),
+            gl_generation: Cell::new(None),
+            gl_api: Cell::new(None),
+            egl_surface: Cell::new(None),
+            present_stats: RefCell::new(None),
+            gl_swap_interval: Cell::new(None),
+            scale_factor: Cell::new(None),
+            refresh_rate: Cell::new(None),
+            monitor_rect: Cell::new(None),
+            hit_test: RefCell::new(None),
+            has_icon_name: Cell::new(false),
+            is_flashing: Cell::new(false),
+            flash_deadline: Cell::new(None),
         }));
         self.weak_windows.borrow_mut().insert(x_window, Rc::downgrade(&window.0));
         trace!("Inserted foreign X Window {} into the context's list", x_window);
@@ -444,6 +694,18 @@ impl X11Context {
     }
 }
 
+unsafe fn xim_supports_input_style(xim: x::XIM, input_style: c_ulong) -> bool {
+    let mut styles: *mut missing_bits::xim::XIMStyles = ptr::null_mut();
+    let unset = x::XGetIMValues(xim, x::XNQueryInputStyle_0.as_ptr(), &mut styles as *mut *mut missing_bits::xim::XIMStyles, ptr::null_mut() as *mut c_void);
+    if !unset.is_null() || styles.is_null() {
+        return false;
+    }
+    let supported = slice::from_raw_parts((*styles).supported_styles, (*styles).count_styles as _);
+    let is_supported = supported.contains(&input_style);
+    x::XFree(styles as _);
+    is_supported
+}
+
 impl X11SharedWindow {
     fn set_prop<T: PropElement>(&self, prop: x::Atom, prop_type: PropType, mode: PropMode, data: &[T]) -> Result<()> {
         prop::set(*self.context.lock_x_display(), self.x_window, prop, prop_type, mode, data)
@@ -451,6 +713,9 @@ impl X11SharedWindow {
     fn prop<T: PropElement>(&self, prop: x::Atom, req_type: PropType, long_range: Range<usize>) -> Result<PropData<T>> {
         prop::get(*self.context.lock_x_display(), self.x_window, prop, req_type, long_range)
     }
+    fn prop_if_exists<T: PropElement>(&self, prop: x::Atom, req_type: PropType, long_range: Range<usize>) -> Result<Option<PropData<T>>> {
+        prop::get_if_exists(*self.context.lock_x_display(), self.x_window, prop, req_type, long_range)
+    }
     fn delete_prop(&self, prop: x::Atom) -> Result<()> {
         unsafe {
             let x_display = self.context.lock_x_display();
@@ -493,19 +758,18 @@ impl X11SharedWindow {
             }
         }
     }
-    fn x_set_class_hint(&self, class_hint: x::XClassHint) {
+    fn x_set_class_hint(&self, class_hint: x::XClassHint) -> Result<()> {
         let x_display = self.context.lock_x_display();
         unsafe {
             let mem = x::XAllocClassHint();
             assert_ne!(mem, ptr::null_mut());
             *mem = class_hint;
+            // BadAlloc, BadWindow
             let status = xlib_error::sync_catch(*x_display, || {
                 x::XSetClassHint(*x_display, self.x_window, mem)
             });
             x::XFree(mem as _);
-            if let Err(e) = status {
-                error!("XSetClassHint generated {}", e);
-            }
+            status.map(|_| ())
         }
     }
     unsafe fn x_set_command(&self, argc: c_int, argv: *const *const c_char) {
@@ -513,7 +777,8 @@ impl X11SharedWindow {
     }
 
     pub(crate) fn set_net_wm_user_time(&self, time: x::Time) -> Result<()> {
-        self.set_prop(self.context.atoms._NET_WM_USER_TIME()?, PropType::Cardinal, PropMode::Replace, &[time])
+        let x_window = self.user_time_window.unwrap_or(self.x_window);
+        prop::set(*self.context.lock_x_display(), x_window, self.context.atoms._NET_WM_USER_TIME()?, PropType::Cardinal, PropMode::Replace, &[time])
     }
 
     fn set_net_wm_window_type(&self, t: &[NetWMWindowType]) -> Result<()> {
@@ -541,10 +806,10 @@ impl X11SharedWindow {
         let source_indication = 1;
         self.send_client_message_to_root_window_long(
             self.context.atoms._NET_WM_STATE()?, 
-            [action as _, prop1 as _, prop2 as _, source_indication]
+            [action as _, prop1 as _, prop2 as _, source_indication, 0]
         )
     }
-    fn send_client_message_to_root_window_long(&self, message_type: x::Atom, data: [c_long; 4]) -> Result<()> {
+    fn send_client_message_to_root_window_long(&self, message_type: x::Atom, data: [c_long; 5]) -> Result<()> {
         let x_display = self.context.lock_x_display();
 
         let mut e = x::XClientMessageEvent {
@@ -562,6 +827,7 @@ impl X11SharedWindow {
             e.data.set_long(1, data[1]);
             e.data.set_long(2, data[2]);
             e.data.set_long(3, data[3]);
+            e.data.set_long(4, data[4]);
 
             let root = self.x_root_window()?;
             let event_mask = x::SubstructureNotifyMask | x::SubstructureRedirectMask;
@@ -663,6 +929,55 @@ impl X11SharedWindow {
         WindowHandle(self.x_window)
     }
 
+    pub fn capability(&self, op: WindowOp) -> Capability {
+        let atoms = &self.context.atoms;
+        let net_wm_atom = match op {
+              WindowOp::SetTitle
+            | WindowOp::Raise
+            | WindowOp::SetMinMaxSize
+            | WindowOp::Minimize
+            | WindowOp::SetPositionAndSize
+            | WindowOp::SetMousePosition
+            | WindowOp::TrapMouse
+            | WindowOp::Clear => return Capability::Supported,
+            WindowOp::TextInput => return match self.xic {
+                Some(_) => Capability::Supported,
+                None => Capability::Unsupported,
+            },
+            // Motif hints and opacity are honored (or not) silently by the WM or compositor.
+            WindowOp::SetStyleHint | WindowOp::SetTitleBarButtons | WindowOp::SetResizable | WindowOp::SetMovable => return match atoms._MOTIF_WM_HINTS() {
+                Ok(_) => Capability::Unknown,
+                Err(_) => Capability::Unsupported,
+            },
+            WindowOp::SetOpacity => return Capability::Unknown,
+            WindowOp::SetShapeMask => return match self.context.xshape() {
+                Ok(_) => Capability::Supported,
+                Err(_) => Capability::Unsupported,
+            },
+            WindowOp::SetInputShapeMask => return match self.context.xshape() {
+                Ok(xshape) if xshape.has_input_shape() => Capability::Supported,
+                _ => Capability::Unsupported,
+            },
+            WindowOp::SetIcon => atoms._NET_WM_ICON(),
+            WindowOp::SetTypeHint => atoms._NET_WM_WINDOW_TYPE(),
+            WindowOp::Maximize | WindowOp::MaximizeWidthOrHeight => atoms._NET_WM_STATE_MAXIMIZED_VERT(),
+            WindowOp::Fullscreen => atoms._NET_WM_STATE_FULLSCREEN(),
+            WindowOp::DemandAttention => atoms._NET_WM_STATE_DEMANDS_ATTENTION(),
+            WindowOp::SetDesktop => atoms._NET_WM_DESKTOP(),
+            WindowOp::BeginMoveResizeDrag => atoms._NET_WM_MOVERESIZE(),
+            WindowOp::SetVisibleOnAllDesktops => atoms._NET_WM_STATE_STICKY(),
+        };
+        // The atom not even existing means nobody (including the WM) has ever interned it.
+        let net_wm_atom = match net_wm_atom {
+            Ok(atom) => atom,
+            Err(_) => return Capability::Unsupported,
+        };
+        match self.context.net_supported() {
+            Ok(ref supported) if supported.contains(&net_wm_atom) => Capability::Supported,
+            Ok(_) => Capability::Unsupported,
+            Err(_) => Capability::Unknown,
+        }
+    }
     pub fn set_type_hint(&self, type_hint: &WindowTypeHint) -> Result<()> {
         let is_visible = self.is_visible();
         if is_visible.is_ok() && is_visible.unwrap() {
@@ -674,46 +989,115 @@ impl X11SharedWindow {
     pub fn title(&self) -> Result<String> {
         failed("This is not implemented yet")
     }
-    pub fn set_title(&self, title: &str) -> Result<()> {
+    pub fn set_wm_class(&self, instance: &str, class: &str) -> Result<()> {
+        let (instance, class) = match (CString::new(instance), CString::new(class)) {
+            (Ok(instance), Ok(class)) => (instance, class),
+            _ => return invalid_arg("`WM_CLASS` strings can't contain NUL bytes"),
+        };
+        self.x_set_class_hint(x::XClassHint {
+            res_name: instance.as_ptr() as *mut _,
+            res_class: class.as_ptr() as *mut _,
+        })
+    }
+    pub fn wm_class(&self) -> Result<Option<(String, String)>> {
         let x_display = self.context.lock_x_display();
         unsafe {
-            let c_title = match CString::new(title) {
-                Ok(s) => s,
-                Err(e) => return failed(format!("Could not convert title to UTF-8: {}", e)),
-            };
-            let c_title_ptr = c_title.as_bytes_with_nul().as_ptr() as *mut c_char;
-            let title_ptr = &mut [c_title_ptr];
-            let mut prop: x::XTextProperty = mem::uninitialized();
-            let status = x::Xutf8TextListToTextProperty(
-                *x_display, title_ptr.as_mut_ptr(), title_ptr.len() as _, x::XUTF8StringStyle, &mut prop
-            );
-            match status {
-                s if s == x::Success as c_int => {
-                    // BadAlloc, BadWindow
-                    let status1 = xlib_error::sync_catch(*x_display, || x::XSetWMName(*x_display, self.x_window, &mut prop));
-                    let status2 = xlib_error::sync_catch(*x_display, || x::XSetWMIconName(*x_display, self.x_window, &mut prop));
-                    let status3 = match self.context.atoms._NET_WM_NAME() {
-                        // BadAlloc, BadAtom, BadValue, BadWindow
-                        Ok(net_wm_name) => xlib_error::sync_catch(*x_display, || {
-                            x::XSetTextProperty(*x_display, self.x_window, &mut prop, net_wm_name)
-                        }),
-                        Err(e) => Err(e),
-                    };
-                    x::XFree(prop.value as _);
-                    status1?;
-                    status2?;
-                    if let Err(e) = status3 {
-                        warn!("Could not set _NET_WM_NAME: {}", e);
-                    }
-                    Ok(())
-                },
-                missing_bits::xutil::XNoMemory => failed("Xutf8TextListToTextProperty() returned XNoMemory"),
-                missing_bits::xutil::XLocaleNotSupported => failed("Xutf8TextListToTextProperty() returned XLocaleNotSupported"),
-                _ => failed_unexplained(),
+            let mut class_hint: x::XClassHint = mem::zeroed();
+            // BadWindow
+            let status = xlib_error::sync_catch(*x_display, || {
+                x::XGetClassHint(*x_display, self.x_window, &mut class_hint)
+            })?;
+            if status == 0 {
+                return Ok(None);
             }
+            let take = |s: *mut c_char| if s.is_null() { String::new() } else {
+                let owned = CStr::from_ptr(s).to_string_lossy().into_owned();
+                x::XFree(s as _);
+                owned
+            };
+            Ok(Some((take(class_hint.res_name), take(class_hint.res_class))))
+        }
+    }
+    pub fn set_title(&self, title: &str) -> Result<()> {
+        let x_display = self.context.lock_x_display();
+        let has_icon_name = self.has_icon_name.get();
+        unsafe {
+            self.with_utf8_text_property(*x_display, title, |prop| {
+                // BadAlloc, BadWindow
+                let status1 = xlib_error::sync_catch(*x_display, || x::XSetWMName(*x_display, self.x_window, prop));
+                let status2 = if has_icon_name { Ok(()) } else {
+                    xlib_error::sync_catch(*x_display, || x::XSetWMIconName(*x_display, self.x_window, prop))
+                };
+                let status3 = match self.context.atoms._NET_WM_NAME() {
+                    // BadAlloc, BadAtom, BadValue, BadWindow
+                    Ok(net_wm_name) => xlib_error::sync_catch(*x_display, || {
+                        x::XSetTextProperty(*x_display, self.x_window, prop, net_wm_name)
+                    }),
+                    Err(e) => Err(e),
+                };
+                status1?;
+                status2?;
+                if let Err(e) = status3 {
+                    warn!("Could not set _NET_WM_NAME: {}", e);
+                }
+                Ok(())
+            })
+        }
+    }
+    pub fn set_icon_name(&self, icon_name: &str) -> Result<()> {
+        let x_display = self.context.lock_x_display();
+        unsafe {
+            self.with_utf8_text_property(*x_display, icon_name, |prop| {
+                // BadAlloc, BadWindow
+                xlib_error::sync_catch(*x_display, || x::XSetWMIconName(*x_display, self.x_window, prop))?;
+                match self.context.atoms._NET_WM_ICON_NAME() {
+                    // BadAlloc, BadAtom, BadValue, BadWindow
+                    Ok(net_wm_icon_name) => {
+                        let status = xlib_error::sync_catch(*x_display, || {
+                            x::XSetTextProperty(*x_display, self.x_window, prop, net_wm_icon_name)
+                        });
+                        if let Err(e) = status {
+                            warn!("Could not set _NET_WM_ICON_NAME: {}", e);
+                        }
+                    },
+                    Err(e) => warn!("Could not set _NET_WM_ICON_NAME: {}", e),
+                }
+                self.has_icon_name.set(true);
+                Ok(())
+            })
+        }
+    }
+    /// Converts `text` to a UTF-8 `XTextProperty` for the duration of `f`.
+    unsafe fn with_utf8_text_property<F>(&self, x_display: *mut x::Display, text: &str, f: F) -> Result<()>
+        where F: FnOnce(&mut x::XTextProperty) -> Result<()>
+    {
+        let c_text = match CString::new(text) {
+            Ok(s) => s,
+            Err(e) => return failed(format!("Could not convert text to UTF-8: {}", e)),
+        };
+        let c_text_ptr = c_text.as_bytes_with_nul().as_ptr() as *mut c_char;
+        let text_ptr = &mut [c_text_ptr];
+        let mut prop: x::XTextProperty = mem::uninitialized();
+        let status = x::Xutf8TextListToTextProperty(
+            x_display, text_ptr.as_mut_ptr(), text_ptr.len() as _, x::XUTF8StringStyle, &mut prop
+        );
+        match status {
+            s if s == x::Success as c_int => {
+                let result = f(&mut prop);
+                x::XFree(prop.value as _);
+                result
+            },
+            missing_bits::xutil::XNoMemory => failed("Xutf8TextListToTextProperty() returned XNoMemory"),
+            missing_bits::xutil::XLocaleNotSupported => failed("Xutf8TextListToTextProperty() returned XLocaleNotSupported"),
+            _ => failed_unexplained(),
         }
     }
 
+    pub fn set_content_protection(&self, _enabled: bool) -> Result<()> {
+        // _NET_WM_BYPASS_COMPOSITOR only affects compositing; Clients can still XGetImage() the window.
+        unsupported("X11 has no way to prevent other clients from capturing a window's contents")
+    }
+
     pub fn reset_icon(&self) -> Result<()> {
         self.delete_prop(self.context.atoms._NET_WM_ICON()?)
     }
@@ -751,16 +1135,22 @@ impl X11SharedWindow {
         // even though the WM supports _MOTIF_WM_HINTS.
         let mut hints = self.motif_wm_hints().unwrap_or_default();
 
-        if let Some(window::Borders { thickness: _, color: _ }) = borders {
-            hints.flags |= flags::DECORATIONS;
-            hints.decorations |= decorations::BORDER;
-        } else {
-            hints.decorations &= !decorations::BORDER;
+        match borders {
+            Some(window::Borders { thickness: _, color: _, client_side: false }) => {
+                hints.flags |= flags::DECORATIONS;
+                hints.decorations |= decorations::BORDER;
+            },
+            // Client-side borders are drawn by the app, see Borders::rects().
+            Some(window::Borders { client_side: true, .. }) | None => {
+                hints.decorations &= !decorations::BORDER;
+            },
         }
 
-        if let Some(window::TitleBarFeatures {
-            minimize, maximize, close
-        }) = title_bar_features {
+        if let Some(features) = title_bar_features {
+            self.title_bar_buttons.set(features);
+            let window::TitleBarFeatures {
+                minimize, maximize, close
+            } = features;
             hints.flags |= flags::DECORATIONS;
             hints.decorations |= decorations::TITLE | decorations::MENU;
             if minimize {
@@ -785,6 +1175,58 @@ impl X11SharedWindow {
 
         self.set_motif_wm_hints(hints)
     }
+    pub fn set_title_bar_buttons(&self, buttons: TitleBarFeatures) -> Result<()> {
+        use self::motif_wm::{flags, decorations, functions};
+
+        let TitleBarFeatures { minimize, maximize, close } = buttons;
+        self.title_bar_buttons.set(buttons);
+
+        // NOTE: unwrap_or_default() because window might not have that property at first,
+        // even though the WM supports _MOTIF_WM_HINTS.
+        let mut hints = self.motif_wm_hints().unwrap_or_default();
+
+        // Once a flag is set, the WM only grants what the matching field lists. Start from
+        // "everything" so that we don't accidentally take away e.g moving or resizing.
+        if hints.flags & flags::FUNCTIONS == 0 || hints.functions & functions::ALL != 0 {
+            hints.functions = functions::RESIZE | functions::MOVE | functions::MINIMIZE | functions::MAXIMIZE | functions::CLOSE;
+        }
+        if hints.flags & flags::DECORATIONS == 0 || hints.decorations & decorations::ALL != 0 {
+            hints.decorations = decorations::BORDER | decorations::RESIZE | decorations::TITLE | decorations::MENU | decorations::MINIMIZE | decorations::MAXIMIZE;
+        }
+        hints.flags |= flags::FUNCTIONS | flags::DECORATIONS;
+
+        for &(enable, function, decoration) in &[
+            (minimize, functions::MINIMIZE, decorations::MINIMIZE),
+            (maximize, functions::MAXIMIZE, decorations::MAXIMIZE),
+            (close, functions::CLOSE, 0),
+        ] {
+            if enable {
+                hints.functions |= function;
+                hints.decorations |= decoration;
+            } else {
+                hints.functions &= !function;
+                hints.decorations &= !decoration;
+            }
+        }
+
+        // Most WMs overwrite _NET_WM_ALLOWED_ACTIONS anyway, so don't fail on these.
+        let atoms = &self.context.atoms;
+        for (enable, action) in vec![
+            (minimize, atoms._NET_WM_ACTION_MINIMIZE()),
+            (maximize, atoms._NET_WM_ACTION_MAXIMIZE_HORZ()),
+            (maximize, atoms._NET_WM_ACTION_MAXIMIZE_VERT()),
+            (close, atoms._NET_WM_ACTION_CLOSE()),
+        ] {
+            if let Ok(action) = action {
+                let _ = self.set_net_wm_allowed_action(action, enable);
+            }
+        }
+
+        self.set_motif_wm_hints(hints)
+    }
+    pub fn title_bar_buttons(&self) -> TitleBarFeatures {
+        self.title_bar_buttons.get()
+    }
     
     pub fn is_minimized(&self) -> Result<bool> {
         Ok(self.wm_state_property()?[0] == missing_bits::wm_state::IconicState as _)
@@ -821,6 +1263,34 @@ impl X11SharedWindow {
     pub fn is_fullscreen(&self) -> Result<bool> {
         Ok(self.net_wm_state()?.contains(&self.context.atoms._NET_WM_STATE_FULLSCREEN()?))
     }
+    pub fn state(&self) -> Result<WindowStateFlags> {
+        let atoms = &self.context.atoms;
+        // Neither property exists until the window manager has managed the window.
+        let state = self.prop_if_exists::<c_ulong>(atoms._NET_WM_STATE()?, PropType::Any, 0..64)?
+            .map(|pd| pd.data).unwrap_or_default();
+        let wm_state = self.prop_if_exists::<c_ulong>(atoms.WM_STATE()?, PropType::Any, 0..2)?
+            .and_then(|pd| pd.data.first().cloned())
+            .unwrap_or(missing_bits::wm_state::WithdrawnState as _);
+        Ok(WindowStateFlags {
+            fullscreen: state.contains(&atoms._NET_WM_STATE_FULLSCREEN()?),
+            maximized_width: state.contains(&atoms._NET_WM_STATE_MAXIMIZED_HORZ()?),
+            maximized_height: state.contains(&atoms._NET_WM_STATE_MAXIMIZED_VERT()?),
+            minimized: state.contains(&atoms._NET_WM_STATE_HIDDEN()?),
+            focused: self.has_input_focus(),
+            hidden: wm_state == missing_bits::wm_state::WithdrawnState as _,
+            above: state.contains(&atoms._NET_WM_STATE_ABOVE()?),
+            below: state.contains(&atoms._NET_WM_STATE_BELOW()?),
+            demands_attention: state.contains(&atoms._NET_WM_STATE_DEMANDS_ATTENTION()?),
+        })
+    }
+    fn has_input_focus(&self) -> bool {
+        let mut focus = 0;
+        let mut revert_to = 0;
+        unsafe {
+            x::XGetInputFocus(*self.context.lock_x_display(), &mut focus, &mut revert_to);
+        }
+        focus == self.x_window
+    }
 
     pub fn set_opacity(&self, alpha: f64) -> Result<()> {
         self.set_prop(
@@ -831,6 +1301,14 @@ impl X11SharedWindow {
     }
 
     pub fn show(&self) -> Result<()> {
+        // Otherwise, the window manager may assume the window wasn't opened in response to
+        // user input, and not give it the focus.
+        let time = self.context.last_user_time.get();
+        if time != x::CurrentTime {
+            if let Err(e) = self.set_net_wm_user_time(time) {
+                trace!("Could not set _NET_WM_USER_TIME for X Window {}: {}", self.x_window, e);
+            }
+        }
         let x_display = self.context.lock_x_display();
         unsafe {
             xlib_error::sync_catch(*x_display, || x::XMapWindow(*x_display, self.x_window)).map(|_| ())
@@ -848,6 +1326,31 @@ impl X11SharedWindow {
             xlib_error::sync_catch(*x_display, || x::XRaiseWindow(*x_display, self.x_window)).map(|_| ())
         }
     }
+    pub fn focus(&self) -> Result<()> {
+        self.focus_with_x_time(self.context.last_user_time.get())
+    }
+    pub fn focus_with_timestamp(&self, instant: EventInstant) -> Result<()> {
+        match instant.0 {
+            OsEventInstant::X11EventTimeMillis(time) => self.focus_with_x_time(time),
+            _ => invalid_arg("This EventInstant does not come from an X event"),
+        }
+    }
+    fn focus_with_x_time(&self, time: x::Time) -> Result<()> {
+        if let Ok(net_active_window) = self.context.atoms._NET_ACTIVE_WINDOW() {
+            if let Ok(true) = self.context.net_supported().map(|s| s.contains(&net_active_window)) {
+                // https://specifications.freedesktop.org/wm-spec/wm-spec-latest.html#sourceindication
+                let source_indication = 1;
+                let currently_active_window = 0;
+                return self.send_client_message_to_root_window_long(
+                    net_active_window, [source_indication, time as _, currently_active_window, 0, 0]
+                );
+            }
+        }
+        let x_display = self.context.lock_x_display();
+        unsafe {
+            xlib_error::sync_catch(*x_display, || x::XSetInputFocus(*x_display, self.x_window, x::RevertToParent, time)).map(|_| ())
+        }
+    }
     pub fn clear(&self) -> Result<()> {
         let x_display = self.context.lock_x_display();
         unsafe {
@@ -898,13 +1401,31 @@ impl X11SharedWindow {
             self.context.atoms._NET_WM_STATE_MAXIMIZED_HORZ()?
         )
     }
+    /// Gets the number of the X screen this window belongs to, which might not be the default one.
+    fn x_screen_num(&self) -> c_int {
+        let x_display = self.context.lock_x_display();
+        unsafe {
+            let mut attrs = mem::zeroed();
+            match x::XGetWindowAttributes(*x_display, self.x_window, &mut attrs) {
+                0 => x::XDefaultScreen(*x_display),
+                _ => x::XScreenNumberOfScreen(attrs.screen),
+            }
+        }
+    }
+    fn x_visual_id(&self) -> Result<x::VisualID> {
+        let x_display = self.context.lock_x_display();
+        unsafe {
+            let mut attrs = mem::zeroed();
+            match x::XGetWindowAttributes(*x_display, self.x_window, &mut attrs) {
+                0 => failed("XGetWindowAttributes() returned 0"),
+                _ => Ok(x::XVisualIDFromVisual(attrs.visual)),
+            }
+        }
+    }
     pub fn minimize(&self) -> Result<()> {
         let x_display = self.context.lock_x_display();
         let status = unsafe { xlib_error::sync_catch(*x_display, || {
-            x::XIconifyWindow(
-                *x_display, self.x_window,
-                self.context.x_default_screen_num()
-            )
+            x::XIconifyWindow(*x_display, self.x_window, self.x_screen_num())
         })};
         match status {
             Err(e) => Err(e),
@@ -937,6 +1458,29 @@ impl X11SharedWindow {
             self.context.atoms._NET_WM_STATE_DEMANDS_ATTENTION()?, 0
         )
     }
+    pub fn flash(&self, count: Option<u32>) -> Result<()> {
+        if count == Some(0) {
+            return self.stop_flashing();
+        }
+        self.demand_attention()?;
+        self.is_flashing.set(true);
+        // Absurdly large counts just flash until focused.
+        let deadline = count.and_then(|n| FLASH_PERIOD.checked_mul(n)).and_then(|d| Instant::now().checked_add(d));
+        self.flash_deadline.set(deadline);
+        Ok(())
+    }
+    pub fn stop_flashing(&self) -> Result<()> {
+        self.is_flashing.set(false);
+        self.flash_deadline.set(None);
+        self.set_net_wm_state(
+            NetWMStateAction::Remove,
+            self.context.atoms._NET_WM_STATE_DEMANDS_ATTENTION()?, 0
+        )
+    }
+    pub fn set_visible_on_all_desktops(&self, sticky: bool) -> Result<()> {
+        let action = if sticky { NetWMStateAction::Add } else { NetWMStateAction::Remove };
+        self.set_net_wm_state(action, self.context.atoms._NET_WM_STATE_STICKY()?, 0)
+    }
     pub fn demand_urgent_attention(&self) -> Result<()> {
         let _ = self.demand_attention();
         self.x_set_wm_hints(x::XWMHints {
@@ -1001,6 +1545,18 @@ impl X11SharedWindow {
     pub fn position_and_size(&self) -> Result<Rect<i32, u32>> {
         self.x_geometry()
     }
+    /// The window manager may send the `ConfigureNotify` for a maximized window before it
+    /// updates `_NET_WM_STATE`, in which case the maximized rect may be recorded here.
+    pub fn normal_position_and_size(&self) -> Result<Option<Rect<i32, u32>>> {
+        Ok(self.normal_rect.get())
+    }
+    /// Called when `_NET_WM_STATE` changes.
+    pub(crate) fn refresh_is_in_normal_state(&self) {
+        match self.state() {
+            Ok(state) => self.is_in_normal_state.set(!(state.maximized_width || state.maximized_height || state.fullscreen)),
+            Err(e) => warn!("Could not get the state of X Window {}: {}", self.x_window, e),
+        }
+    }
     pub fn position(&self) -> Result<Vec2<i32>> {
         // WISH: Maybe use XTranslateCoordinates() instead ?
         let Rect { x, y, .. } = self.x_geometry()?;
@@ -1013,7 +1569,29 @@ impl X11SharedWindow {
     pub fn canvas_size(&self) -> Result<Extent2<u32>> {
         self.size()
     }
+    pub fn scale_factor(&self) -> Result<f64> {
+        let Rect { x, y, w, h } = self.position_and_size()?;
+        let center = Vec2::new(x + w as i32 / 2, y + h as i32 / 2);
+        let scale_factor = self.context.monitor_scale_factor_at(center)?.unwrap_or(1.);
+        self.scale_factor.set(Some(scale_factor));
+        Ok(scale_factor)
+    }
 
+    pub fn current_refresh_rate(&self) -> Result<f64> {
+        let Rect { x, y, w, h } = self.position_and_size()?;
+        let center = Vec2::new(x + w as i32 / 2, y + h as i32 / 2);
+        let refresh_rate = match self.context.monitor_refresh_rate_at(center)? {
+            Some(refresh_rate) => refresh_rate,
+            None => return failed("Neither the window's monitor nor the primary one has a known refresh rate"),
+        };
+        self.refresh_rate.set(Some(refresh_rate));
+        Ok(refresh_rate)
+    }
+
+    /// Gets the position and size of each monitor, in `Context::monitors()` order.
+    pub fn monitor_rects(&self) -> Result<Vec<Rect<i32, u32>>> {
+        self.context.monitors()?.into_iter().map(|crtc| self.context.monitor_rect(crtc)).collect()
+    }
     pub fn set_position(&self, pos: Vec2<i32>) -> Result<()> {
         let x_display = self.context.lock_x_display();
         unsafe {
@@ -1039,7 +1617,47 @@ impl X11SharedWindow {
         // https://specifications.freedesktop.org/wm-spec/wm-spec-latest.html#sourceindication
         let source_indication = 1; 
         self.send_client_message_to_root_window_long(
-            self.context.atoms._NET_WM_DESKTOP()?, [i as _, source_indication, 0, 0]
+            self.context.atoms._NET_WM_DESKTOP()?, [i as _, source_indication, 0, 0, 0]
+        )
+    }
+    pub fn begin_move_drag(&self, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        self.begin_net_wm_moveresize(net_wm::moveresize::MOVE, button, root_position, instant)
+    }
+    pub fn begin_resize_drag(&self, edge: ResizeEdge, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        let direction = match edge {
+            ResizeEdge::TopLeft     => net_wm::moveresize::SIZE_TOPLEFT,
+            ResizeEdge::Top         => net_wm::moveresize::SIZE_TOP,
+            ResizeEdge::TopRight    => net_wm::moveresize::SIZE_TOPRIGHT,
+            ResizeEdge::Right       => net_wm::moveresize::SIZE_RIGHT,
+            ResizeEdge::BottomRight => net_wm::moveresize::SIZE_BOTTOMRIGHT,
+            ResizeEdge::Bottom      => net_wm::moveresize::SIZE_BOTTOM,
+            ResizeEdge::BottomLeft  => net_wm::moveresize::SIZE_BOTTOMLEFT,
+            ResizeEdge::Left        => net_wm::moveresize::SIZE_LEFT,
+        };
+        self.begin_net_wm_moveresize(direction, button, root_position, instant)
+    }
+    fn begin_net_wm_moveresize(&self, direction: c_long, button: MouseButton, root_position: Vec2<i32>, instant: EventInstant) -> Result<()> {
+        let x_button = match X11SharedContext::mousebutton_to_x11_button(button) {
+            Some(x_button) => x_button,
+            None => return invalid_arg(format!("{:?} has no core X11 button number", button)),
+        };
+        let time = match instant.0 {
+            OsEventInstant::X11EventTimeMillis(time) => time,
+            _ => x::CurrentTime,
+        };
+        {
+            // The WM can't grab the pointer as long as we hold the implicit grab
+            // that came with the button press.
+            let x_display = self.context.lock_x_display();
+            unsafe {
+                x::XUngrabPointer(*x_display, time);
+            }
+        }
+        // https://specifications.freedesktop.org/wm-spec/wm-spec-latest.html#sourceindication
+        let source_indication = 1;
+        self.send_client_message_to_root_window_long(
+            self.context.atoms._NET_WM_MOVERESIZE()?,
+            [root_position.x as _, root_position.y as _, direction, x_button as _, source_indication]
         )
     }
     pub fn recenter_in_desktop(&self) -> Result<()> {
@@ -1105,5 +1723,87 @@ impl X11SharedWindow {
     pub fn tablet_state(&self, tablet: DeviceID) -> device::Result<WindowTabletState> {
         unimplemented!{}
     }
+
+    pub fn text_input_active(&self) -> bool {
+        self.is_text_input_active.get()
+    }
+    pub fn enable_text_input(&self) -> Result<()> {
+        if let Some(xic) = self.xic {
+            let _x_display = self.context.lock_x_display();
+            unsafe {
+                x::XSetICFocus(xic);
+            }
+        }
+        self.is_text_input_active.set(true);
+        Ok(())
+    }
+    pub fn disable_text_input(&self) -> Result<()> {
+        if !self.is_text_input_active.replace(false) {
+            return Ok(());
+        }
+        if let Some(xic) = self.xic {
+            let _x_display = self.context.lock_x_display();
+            unsafe {
+                // Cancels the composition; The returned string is the preedit text, which we discard.
+                let preedit = x::XmbResetIC(xic);
+                if !preedit.is_null() {
+                    x::XFree(preedit as _);
+                }
+                x::XUnsetICFocus(xic);
+            }
+        }
+        self.context.push_event(Event::KeyboardTextComposition { window: self.handle(), text: String::new() });
+        Ok(())
+    }
+    #[cfg(feature = "synthetic-input")]
+    pub fn push_committed_text_event(&self, s: &str) -> Result<()> {
+        if self.xic.is_none() {
+            return failed("This window has no X input context");
+        }
+        if !self.is_text_input_active.get() {
+            return failed("Text input is not enabled for this window");
+        }
+        // This is what we would report after a successful Xutf8LookupString() on a KeyPress event.
+        // There is no actual event here, hence no server timestamp to use.
+        self.context.push_event(Event::KeyboardTextString {
+            keyboard: self.context.core_x_keyboard_deviceid(),
+            window: self.handle(),
+            instant: EventInstant(OsEventInstant::X11EventTimeMillis(x::CurrentTime)),
+            text: s.to_owned(),
+            is_repeat: false,
+            repeat_count: 1,
+        });
+        Ok(())
+    }
+    pub fn set_text_input_rect(&self, r: Rect<i32, u32>) -> Result<()> {
+        let xic = match self.xic {
+            Some(xic) => xic,
+            None => return failed(format!("X Window {} has no XIC", self.x_window)),
+        };
+        let _x_display = self.context.lock_x_display();
+        // The spot location is the position of the text cursor's baseline.
+        let mut spot = x::XPoint {
+            x: r.x as _,
+            y: (r.y + r.h as i32) as _,
+        };
+        unsafe {
+            let preedit_attributes = x::XVaCreateNestedList(0,
+                x::XNSpotLocation_0.as_ptr(), &mut spot as *mut x::XPoint,
+                ptr::null_mut() as *mut c_void,
+            );
+            if preedit_attributes.is_null() {
+                return failed("XVaCreateNestedList() returned NULL");
+            }
+            let unset = x::XSetICValues(xic,
+                x::XNPreeditAttributes_0.as_ptr(), preedit_attributes,
+                ptr::null_mut() as *mut c_void,
+            );
+            x::XFree(preedit_attributes);
+            if !unset.is_null() {
+                return failed("XSetICValues() could not set XNSpotLocation (the XIC's input style is probably not XIMPreeditPosition)");
+            }
+        }
+        Ok(())
+    }
 }
 
@@ -0,0 +1,98 @@
+use std::os::raw::c_int;
+use std::ptr;
+use error::{Result, failed};
+use pointer_barrier::PointerBarrierDirections;
+use super::context::X11SharedContext;
+use super::xi;
+use super::xlib_error;
+use super::missing_bits::xfixes as mxfixes;
+use super::x11::xlib as x;
+use super::x11::xinput2 as xi2;
+use super::x11::xfixes::PointerBarrier;
+use Vec2;
+
+pub type X11PointerBarrierID = PointerBarrier;
+
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct XFixes {
+    pub event_base: c_int,
+    pub error_base: c_int,
+    pub major_version: c_int,
+    pub minor_version: c_int,
+}
+
+impl X11SharedContext {
+    pub fn xfixes(&self) -> Result<&XFixes> {
+        self.xfixes.as_ref().map_err(Clone::clone)
+    }
+    pub fn create_pointer_barrier(&self, from: Vec2<i32>, to: Vec2<i32>, directions: PointerBarrierDirections) -> Result<PointerBarrier> {
+        let xfixes = self.xfixes()?;
+        if xfixes.major_version < 5 {
+            return failed(format!("Pointer barriers require XFixes 5.0, but the X server only has XFixes {}.{}", xfixes.major_version, xfixes.minor_version));
+        }
+
+        let PointerBarrierDirections { positive_x, positive_y, negative_x, negative_y } = directions;
+        let mut x_directions = 0;
+        if positive_x { x_directions |= mxfixes::BarrierPositiveX; }
+        if positive_y { x_directions |= mxfixes::BarrierPositiveY; }
+        if negative_x { x_directions |= mxfixes::BarrierNegativeX; }
+        if negative_y { x_directions |= mxfixes::BarrierNegativeY; }
+
+        // Barrier events are reported to the window the barrier was created on.
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+
+        match self.xi() {
+            Ok(&xi::XI { major_version, minor_version, .. }) if (major_version, minor_version) >= (2, 3) => {
+                let status = unsafe {
+                    xi::xi_select_events(*x_display, root, &[(xi2::XIAllMasterDevices, &[xi2::XI_BarrierHit])])
+                };
+                if let Err(e) = status {
+                    warn!("Could not select XI barrier events on the root window: {}", e);
+                }
+            },
+            _ => warn!("Pointer barrier events require XInput 2.3; The barrier will work, but no event will be reported"),
+        }
+
+        let (num_devices, devices) = (0, ptr::null_mut()); // All devices
+        let barrier = unsafe {
+            xlib_error::sync_catch(*x_display, || {
+                mxfixes::XFixesCreatePointerBarrier(
+                    *x_display, root, from.x, from.y, to.x, to.y,
+                    x_directions, num_devices, devices
+                )
+            })?
+        };
+        if barrier == 0 {
+            return failed("XFixesCreatePointerBarrier() returned 0");
+        }
+        Ok(barrier)
+    }
+    pub fn destroy_pointer_barrier(&self, barrier: PointerBarrier) -> Result<()> {
+        let x_display = self.lock_x_display();
+        unsafe {
+            xlib_error::sync_catch(*x_display, || {
+                mxfixes::XFixesDestroyPointerBarrier(*x_display, barrier)
+            })
+        }
+    }
+}
+
+impl XFixes {
+    pub unsafe fn query(x_display: *mut x::Display) -> Result<Self> {
+        let mut xfixes = Self::default();
+
+        let has_it = mxfixes::XFixesQueryExtension(x_display, &mut xfixes.event_base, &mut xfixes.error_base);
+        if has_it == x::False {
+            return failed("XFixesQueryExtension() returned False");
+        }
+        // The client must announce the version it supports; Pointer barriers are in 5.0.
+        xfixes.major_version = 5;
+        xfixes.minor_version = 0;
+        let success = mxfixes::XFixesQueryVersion(x_display, &mut xfixes.major_version, &mut xfixes.minor_version);
+        if success == 0 {
+            return failed("XFixesQueryVersion() returned 0");
+        }
+        Ok(xfixes)
+    }
+}
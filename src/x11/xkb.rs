@@ -0,0 +1,133 @@
+//! The X Keyboard Extension, for layout groups (e.g switching between Russian and English layouts).
+
+use std::os::raw::{c_int, c_uint, c_ulong};
+use std::ffi::CStr;
+use error::{Result, failed};
+use event::Event;
+use super::context::X11SharedContext;
+use super::missing_bits::xkb as mxkb;
+use super::x11::xlib as x;
+
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct Xkb {
+    pub opcode: c_int,
+    pub event_base: c_int,
+    pub error_base: c_int,
+    pub major_version: c_int,
+    pub minor_version: c_int,
+}
+
+impl Xkb {
+    pub unsafe fn query(x_display: *mut x::Display) -> Result<Self> {
+        let mut xkb = Self::default();
+        // The client must announce the version it supports.
+        xkb.major_version = mxkb::XkbMajorVersion as _;
+        xkb.minor_version = mxkb::XkbMinorVersion as _;
+        let has_it = x::XkbQueryExtension(x_display, &mut xkb.opcode, &mut xkb.event_base, &mut xkb.error_base, &mut xkb.major_version, &mut xkb.minor_version);
+        if has_it == x::False {
+            return failed("XkbQueryExtension() returned False");
+        }
+        // We only care about group changes; Modifier changes would flood the queue.
+        let status = x::XkbSelectEventDetails(x_display, mxkb::XkbUseCoreKbd, x::XkbStateNotify as _, mxkb::XkbGroupStateMask, mxkb::XkbGroupStateMask);
+        if status == x::False {
+            warn!("XkbSelectEventDetails() returned False; Layout group changes won't be reported");
+        }
+        Ok(xkb)
+    }
+}
+
+impl X11SharedContext {
+    pub fn xkb(&self) -> Result<&Xkb> {
+        self.xkb.as_ref().map_err(Clone::clone)
+    }
+    /// Asks the X server for the core keyboard's active group.
+    /// `device_spec` is either `XkbUseCoreKbd` or an XInput device ID, as for all XKB requests.
+    pub fn xkb_query_current_group(&self, device_spec: c_uint) -> Result<u32> {
+        self.xkb()?;
+        let mut state = mxkb::XkbStateRec::default();
+        let status = unsafe {
+            x::XkbGetState(*self.lock_x_display(), device_spec, &mut state as *mut _ as x::XkbStatePtr)
+        };
+        if status != x::Success as c_int {
+            return failed(format!("XkbGetState() returned {}", status));
+        }
+        Ok(state.group as _)
+    }
+    pub fn xkb_lock_group(&self, device_spec: c_uint, group: u32) -> Result<()> {
+        self.xkb()?;
+        let x_display = self.lock_x_display();
+        let status = unsafe {
+            x::XkbLockGroup(*x_display, device_spec, group)
+        };
+        if status == x::False {
+            return failed("XkbLockGroup() returned False");
+        }
+        unsafe {
+            x::XFlush(*x_display);
+        }
+        Ok(())
+    }
+    /// The names of the keyboard's groups (e.g "English (US)", "Russian"), by index.
+    pub fn xkb_group_names(&self, device_spec: c_uint) -> Result<Vec<String>> {
+        self.xkb()?;
+        let x_display = self.lock_x_display();
+        unsafe {
+            let desc = x::XkbAllocKeyboard();
+            if desc.is_null() {
+                return failed("XkbAllocKeyboard() returned NULL");
+            }
+            // XkbGetNames() asks for the names of this device.
+            (*desc).device_spec = device_spec as _;
+            let status = x::XkbGetNames(*x_display, mxkb::XkbGroupNamesMask, desc);
+            if status != x::Success as c_int || (*desc).names.is_null() {
+                x::XkbFreeKeyboard(desc, 0, x::True);
+                return failed(format!("XkbGetNames() returned {}", status));
+            }
+            // Unused trailing groups are named `None`.
+            let names = (*(*desc).names).groups.iter().take_while(|atom| **atom != 0).map(|atom| {
+                let name = x::XGetAtomName(*x_display, *atom);
+                if name.is_null() {
+                    return String::new();
+                }
+                let s = CStr::from_ptr(name).to_string_lossy().into_owned();
+                x::XFree(name as _);
+                s
+            }).collect();
+            x::XkbFreeKeyboard(desc, 0, x::True);
+            Ok(names)
+        }
+    }
+    /// The keysym at `level` for `keycode`, in the given group.
+    pub fn xkb_keycode_to_keysym(&self, keycode: x::KeyCode, group: u32, level: c_int) -> Option<x::KeySym> {
+        if self.xkb().is_err() {
+            // Without XKB, the first two columns are the first group.
+            return self.x_keycode_to_keysym(keycode, level);
+        }
+        match unsafe { x::XkbKeycodeToKeysym(*self.lock_x_display(), keycode, group as _, level) } {
+            x if x == x::NoSymbol as _ => None,
+            sym => Some(sym),
+        }
+    }
+    /// The group that was active when a core event with this `state` happened.
+    pub fn xkb_group_for_core_state(&self, state: c_uint) -> u32 {
+        mxkb::XkbGroupForCoreState(state)
+    }
+    pub fn pump_xkb_event(&self, e: &x::XEvent) {
+        let any: &x::XkbAnyEvent = unsafe { &*(e as *const _ as *const x::XkbAnyEvent) };
+        if any.xkb_type != x::XkbStateNotify {
+            return;
+        }
+        let e: &x::XkbStateNotifyEvent = unsafe { &*(e as *const _ as *const x::XkbStateNotifyEvent) };
+        if (e.changed as c_ulong & mxkb::XkbGroupStateMask) == 0 {
+            return;
+        }
+        let group = e.group as u32;
+        if self.xkb_current_group.replace(group) == group {
+            return;
+        }
+        self.push_event(Event::KeyboardLayoutGroupChanged {
+            keyboard: self.core_x_keyboard_deviceid(),
+            group,
+        });
+    }
+}
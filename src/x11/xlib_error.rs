@@ -1,14 +1,27 @@
 // Useful reference: _XPrintDefaultError() in XlibInt.c (X11 sources)
 
-use std::os::raw::c_int;
+use std::os::raw::{c_int, c_char, c_void};
 use std::ffi::CStr;
+use std::mem;
+use std::ptr;
+use std::time::{Duration, Instant};
+use nix::poll::{poll, PollFd, EventFlags};
 use super::x11::xlib as x;
-use error::{Result, failed};
+use super::libc;
+use error::{Result, failed, display_timeout};
 
 pub static mut DO_USE_DMC_XLIB_ERROR_HANDLERS: bool = true;
+// For `ContextStats`; X errors are caught globally anyway.
+pub static mut TRAPPED_ERROR_COUNT: u64 = 0;
+// Set by `Hint::XlibRoundtripTimeout`.
+pub static mut ROUNDTRIP_TIMEOUT: Option<Duration> = None;
 
 pub unsafe fn sync_catch<T, F: FnMut() -> T>(x_display: *mut x::Display, mut f: F) -> Result<T> {
     assert!(!x_display.is_null());
+    if let Some(timeout) = ROUNDTRIP_TIMEOUT {
+        // XSync() blocks until the server replies; Make sure that it does reply in time.
+        wait_for_roundtrip(x_display, timeout)?;
+    }
     let mut doit = || {
         xsync_then_report(x_display, ())?; // Sync first to catch any error possibly caused by previous, unsynced calls
         xsync_then_report(x_display, f())  // ... and do it again with the closure.
@@ -20,6 +33,116 @@ pub unsafe fn sync_catch<T, F: FnMut() -> T>(x_display: *mut x::Display, mut f:
     }
 }
 
+/// The parts of libxcb and libX11-xcb that are needed for issuing a roundtrip without blocking.
+/// Xlib can't do that by itself, but it is built on top of XCB, which can.
+#[allow(non_snake_case)]
+#[derive(Debug, Copy, Clone)]
+struct XcbFns {
+    XGetXCBConnection: xcb_fn_types::XGetXCBConnection,
+    xcb_get_input_focus: xcb_fn_types::xcb_get_input_focus,
+    xcb_flush: xcb_fn_types::xcb_flush,
+    xcb_poll_for_reply: xcb_fn_types::xcb_poll_for_reply,
+}
+
+mod xcb_fn_types {
+    #![allow(non_camel_case_types)]
+
+    use std::os::raw::{c_void, c_int, c_uint};
+    use super::x;
+
+    pub type XGetXCBConnection = unsafe extern "C" fn(*mut x::Display) -> *mut c_void;
+    // Returns an `xcb_get_input_focus_cookie_t`, which only holds the sequence number.
+    pub type xcb_get_input_focus = unsafe extern "C" fn(*mut c_void) -> c_uint;
+    pub type xcb_flush = unsafe extern "C" fn(*mut c_void) -> c_int;
+    pub type xcb_poll_for_reply = unsafe extern "C" fn(*mut c_void, c_uint, *mut *mut c_void, *mut *mut c_void) -> c_int;
+}
+
+impl XcbFns {
+    /// Loads the libraries once; They are never unloaded.
+    fn get() -> Option<Self> {
+        thread_local! {
+            static XCB_FNS: Option<XcbFns> = unsafe { XcbFns::load() };
+        }
+        XCB_FNS.with(|fns| *fns)
+    }
+    unsafe fn load() -> Option<Self> {
+        let open = |name: &[u8]| libc::dlopen(name.as_ptr() as *const c_char, libc::RTLD_NOW | libc::RTLD_LOCAL);
+        let (x11_xcb, xcb) = (open(b"libX11-xcb.so.1\0"), open(b"libxcb.so.1\0"));
+        if x11_xcb.is_null() || xcb.is_null() {
+            warn!("Could not load libX11-xcb or libxcb; The X roundtrip timeout only covers writing requests");
+            return None;
+        }
+        let sym = |lib, name: &[u8]| {
+            let sym = libc::dlsym(lib, name.as_ptr() as *const c_char);
+            if sym.is_null() { None } else { Some(sym) }
+        };
+        Some(Self {
+            XGetXCBConnection: mem::transmute::<*mut c_void, xcb_fn_types::XGetXCBConnection>(sym(x11_xcb, b"XGetXCBConnection\0")?),
+            xcb_get_input_focus: mem::transmute::<*mut c_void, xcb_fn_types::xcb_get_input_focus>(sym(xcb, b"xcb_get_input_focus\0")?),
+            xcb_flush: mem::transmute::<*mut c_void, xcb_fn_types::xcb_flush>(sym(xcb, b"xcb_flush\0")?),
+            xcb_poll_for_reply: mem::transmute::<*mut c_void, xcb_fn_types::xcb_poll_for_reply>(sym(xcb, b"xcb_poll_for_reply\0")?),
+        })
+    }
+}
+
+/// Milliseconds until `deadline`, for `poll()`.
+fn poll_timeout_ms(deadline: Instant) -> c_int {
+    let now = Instant::now();
+    let remaining = if deadline > now { deadline - now } else { Duration::default() };
+    let ms = remaining.as_secs().saturating_mul(1000).saturating_add(remaining.subsec_nanos() as u64 / 1_000_000);
+    ms.min(c_int::MAX as u64) as c_int
+}
+
+/// Waits for `events` on the X connection until `deadline`, failing with `ErrorKind::DisplayTimeout`
+/// if the peer is gone or the deadline is reached.
+fn poll_x_connection(fd: c_int, events: EventFlags, deadline: Instant, timeout: Duration) -> Result<()> {
+    loop {
+        let mut fds = [PollFd::new(fd, events)];
+        match poll(&mut fds, poll_timeout_ms(deadline)) {
+            Err(::nix::Error::Sys(::nix::errno::Errno::EINTR)) => continue,
+            Err(e) => return failed(format!("poll() on the X connection failed: {}", e)),
+            Ok(0) => return display_timeout(format!("The X server did not respond within {:?}", timeout)),
+            Ok(_) => (),
+        };
+        let revents = fds[0].revents().unwrap_or(EventFlags::empty());
+        if revents.intersects(EventFlags::POLLHUP | EventFlags::POLLERR | EventFlags::POLLNVAL) {
+            return display_timeout("The X connection was closed by the peer");
+        }
+        return Ok(());
+    }
+}
+
+/// Issues a roundtrip via XCB and waits for its reply, failing with `ErrorKind::DisplayTimeout`
+/// if the peer is gone or doesn't reply in time.
+///
+/// Without XCB, this only checks that the connection is writable.
+unsafe fn wait_for_roundtrip(x_display: *mut x::Display, timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    let fd = x::XConnectionNumber(x_display);
+    // Sending the request must not block either.
+    poll_x_connection(fd, EventFlags::POLLOUT, deadline, timeout)?;
+    let xcb = match XcbFns::get() {
+        Some(xcb) => xcb,
+        None => return Ok(()),
+    };
+    let connection = (xcb.XGetXCBConnection)(x_display);
+    // GetInputFocus is what XSync() uses as well; It's cheap and always has a reply.
+    let sequence = (xcb.xcb_get_input_focus)(connection);
+    if (xcb.xcb_flush)(connection) <= 0 {
+        return display_timeout("The X connection was closed by the peer");
+    }
+    loop {
+        let (mut reply, mut error) = (ptr::null_mut(), ptr::null_mut());
+        // This reads what's available without blocking. Events that come along are kept for Xlib.
+        if (xcb.xcb_poll_for_reply)(connection, sequence, &mut reply, &mut error) != 0 {
+            libc::free(reply);
+            libc::free(error);
+            return Ok(());
+        }
+        poll_x_connection(fd, EventFlags::POLLIN, deadline, timeout)?;
+    }
+}
+
 fn swapping_error_handlers<T, F: FnMut() -> T>(mut f: F) -> T {
     unsafe {
         let previous_error_handler = x::XSetErrorHandler(Some(our_xlib_error_handler));
@@ -56,6 +179,7 @@ unsafe fn xsync_then_report<T>(x_display: *mut x::Display, result: T) -> Result<
             resourceid, serial, error_code,
             request_code, minor_code,
         }) => {
+            TRAPPED_ERROR_COUNT += 1;
             let mut buf = [0_u8; 1024];
             x::XGetErrorText(x_display, error_code as _, buf.as_mut_ptr() as _, buf.len() as _);
             let error_text = CStr::from_ptr(buf.as_ptr() as _).to_string_lossy().into_owned(); // buf is overwritten later
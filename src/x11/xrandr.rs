@@ -14,9 +14,65 @@
 // XRRGetOutputPrimary(0x18f3010, 705, 0, 0x7f453fa85b00)                
 // XRRGetOutputInfo(0x18f3010, 0x19031c0, 67, 8)                         
 
-use std::os::raw::c_int;
-use error::{Result, failed};
+use std::os::raw::{c_int, c_ulong};
+use std::rc::{Rc, Weak};
+use std::slice;
+use error::{Result, failed, invalid_arg, unsupported};
+use monitor::{GammaRamps, DisplayMode};
+use {Vec2, Rect};
+use super::context::{X11Context, X11SharedContext};
+use super::xlib_error;
 use super::x11::xlib as x;
+use super::x11::xrandr as xrr;
+
+pub type X11MonitorID = xrr::RRCrtc;
+
+/// The monitors' rects, scale factors and refresh rates, so that windows that are being moved
+/// or resized don't cause roundtrips. See `X11SharedContext::monitor_cache`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorCache {
+    monitors: Vec<CachedMonitor>,
+    primary_refresh_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CachedMonitor {
+    crtc: X11MonitorID,
+    rect: Rect<i32, u32>,
+    scale_factor: Option<f64>,
+    refresh_rate: Option<f64>,
+}
+
+/// Restores the CRTC's original configuration on drop.
+#[derive(Debug)]
+pub struct X11DisplayModeGuard {
+    context: Rc<X11SharedContext>,
+    crtc: X11MonitorID,
+    original: CrtcConfig,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CrtcConfig {
+    x: c_int,
+    y: c_int,
+    mode: xrr::RRMode,
+    rotation: xrr::Rotation,
+    outputs: Vec<xrr::RROutput>,
+}
+
+impl Drop for X11DisplayModeGuard {
+    fn drop(&mut self) {
+        let root = self.context.x_default_root_window();
+        let x_display = self.context.lock_x_display();
+        let status = unsafe {
+            xlib_error::sync_catch(*x_display, || xrr_set_crtc_config(*x_display, root, self.crtc, &self.original))
+        };
+        match status {
+            Ok(Ok(())) => trace!("Restored the original mode of CRTC {}", self.crtc),
+            Ok(Err(e)) | Err(e) => warn!("Could not restore the original mode of CRTC {}: {}", self.crtc, e),
+        }
+    }
+}
 
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
 pub struct XRR {
@@ -26,21 +82,425 @@ pub struct XRR {
     pub minor_version: c_int,
 }
 
-impl X11SharedContext {
-    fn query_xrr(&self) -> Result<XRR> {
-        let x_display = self.x_display;
-        let mut xrr = XRR::default();
-        let success = unsafe {
-            x::XRRQueryExtension(x_display, &mut xrr.event_base, &mut xrr.error_base)
-        };
-        if success == x::False as _ {
+impl XRR {
+    pub unsafe fn query(x_display: *mut x::Display) -> Result<Self> {
+        let mut xrr = Self::default();
+        let has_it = xrr::XRRQueryExtension(x_display, &mut xrr.event_base, &mut xrr.error_base);
+        if has_it == x::False {
             return failed("XRRQueryExtension() returned False");
         }
-        let status = unsafe {
-            x::XRRQueryVersion(x_display, &mut xrr.major_version, &mut xrr.minor_version)
-        };
-        if status != x::Success as _ {
-            return failed(format!("XRRQueryExtension() returned {}", status));
+        let success = xrr::XRRQueryVersion(x_display, &mut xrr.major_version, &mut xrr.minor_version);
+        if success == 0 {
+            return failed("XRRQueryVersion() returned 0");
+        }
+        // So that we can report refresh rate changes, see `pump_xrr_event()`.
+        let mut mask = xrr::RRScreenChangeNotifyMask;
+        if (xrr.major_version, xrr.minor_version) >= (1, 2) {
+            mask |= xrr::RRCrtcChangeNotifyMask;
         }
+        xrr::XRRSelectInput(x_display, x::XDefaultRootWindow(x_display), mask);
+        Ok(xrr)
+    }
+}
+
+impl X11SharedContext {
+    pub fn xrr(&self) -> Result<&XRR> {
+        self.xrr.as_ref().map_err(Clone::clone)
+    }
+    fn xrr_check_crtc_support(&self) -> Result<()> {
+        let xrr = self.xrr()?;
+        if (xrr.major_version, xrr.minor_version) < (1, 2) {
+            return failed(format!("Per-CRTC gamma ramps require XRandR 1.2, but the X server only has XRandR {}.{}", xrr.major_version, xrr.minor_version));
+        }
+        Ok(())
+    }
+    pub fn monitors(&self) -> Result<Vec<X11MonitorID>> {
+        self.xrr_check_crtc_support()?;
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        unsafe {
+            let res = xrr::XRRGetScreenResourcesCurrent(*x_display, root);
+            if res.is_null() {
+                return failed("XRRGetScreenResourcesCurrent() returned NULL");
+            }
+            let crtcs = slice::from_raw_parts((*res).crtcs, (*res).ncrtc as usize);
+            let mut monitors = Vec::with_capacity(crtcs.len());
+            for crtc in crtcs.iter().cloned() {
+                let info = xrr::XRRGetCrtcInfo(*x_display, res, crtc);
+                if info.is_null() {
+                    continue;
+                }
+                // A CRTC without a mode is disabled.
+                if (*info).mode != 0 {
+                    monitors.push(crtc);
+                }
+                xrr::XRRFreeCrtcInfo(info);
+            }
+            xrr::XRRFreeScreenResources(res);
+            Ok(monitors)
+        }
+    }
+    /// Gets the position and size of a monitor, in root window coordinates.
+    pub fn monitor_rect(&self, crtc: X11MonitorID) -> Result<Rect<i32, u32>> {
+        self.xrr_check_crtc_support()?;
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        unsafe {
+            let res = xrr::XRRGetScreenResourcesCurrent(*x_display, root);
+            if res.is_null() {
+                return failed("XRRGetScreenResourcesCurrent() returned NULL");
+            }
+            let info = xrr::XRRGetCrtcInfo(*x_display, res, crtc);
+            xrr::XRRFreeScreenResources(res);
+            if info.is_null() {
+                return invalid_arg("XRRGetCrtcInfo() returned NULL; This monitor may have been disconnected");
+            }
+            let xrr::XRRCrtcInfo { x, y, width, height, mode, .. } = *info;
+            xrr::XRRFreeCrtcInfo(info);
+            if mode == 0 {
+                return invalid_arg("This monitor is disabled");
+            }
+            Ok(Rect::new(x, y, width, height))
+        }
+    }
+    /// Gets the monitor of the primary output, if any.
+    pub fn primary_monitor(&self) -> Result<Option<X11MonitorID>> {
+        self.xrr_check_crtc_support()?;
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        unsafe {
+            let primary = xrr::XRRGetOutputPrimary(*x_display, root);
+            if primary == 0 {
+                return Ok(None);
+            }
+            let res = xrr::XRRGetScreenResourcesCurrent(*x_display, root);
+            if res.is_null() {
+                return failed("XRRGetScreenResourcesCurrent() returned NULL");
+            }
+            let output = xrr::XRRGetOutputInfo(*x_display, res, primary);
+            let mut crtc = None;
+            if !output.is_null() {
+                crtc = Some((*output).crtc).filter(|crtc| *crtc != 0);
+                xrr::XRRFreeOutputInfo(output);
+            }
+            xrr::XRRFreeScreenResources(res);
+            Ok(crtc)
+        }
+    }
+    /// Gets the scale factor of the monitor which contains `point` (in root window coordinates),
+    /// if any.
+    ///
+    /// X11 has no notion of per-monitor scaling, so this is derived from the physical size that
+    /// the monitor's first output reports, relative to 96 DPI.
+    pub fn monitor_scale_factor_at(&self, point: Vec2<i32>) -> Result<Option<f64>> {
+        self.with_monitor_cache(|cache| {
+            cache.monitors.iter().filter(|m| rect_contains_point(m.rect, point)).filter_map(|m| m.scale_factor).next()
+        })
+    }
+    /// Gets the refresh rate (in Hz) of the monitor which contains `point` (in root window
+    /// coordinates), or else of the monitor of the primary output, if any.
+    pub fn monitor_refresh_rate_at(&self, point: Vec2<i32>) -> Result<Option<f64>> {
+        self.with_monitor_cache(|cache| {
+            cache.monitors.iter().filter(|m| rect_contains_point(m.rect, point)).filter_map(|m| m.refresh_rate).next()
+                .or(cache.primary_refresh_rate)
+        })
+    }
+    /// Gets the rect of the monitor which contains `point` (in root window coordinates), if any.
+    pub fn monitor_rect_at(&self, point: Vec2<i32>) -> Result<Option<Rect<i32, u32>>> {
+        self.with_monitor_cache(|cache| {
+            cache.monitors.iter().map(|m| m.rect).find(|r| rect_contains_point(*r, point))
+        })
+    }
+    fn with_monitor_cache<T, F: FnOnce(&MonitorCache) -> T>(&self, f: F) -> Result<T> {
+        if let Some(ref cache) = *self.monitor_cache.borrow() {
+            return Ok(f(cache));
+        }
+        let cache = self.query_monitor_cache()?;
+        let result = f(&cache);
+        *self.monitor_cache.borrow_mut() = Some(cache);
+        Ok(result)
+    }
+    fn query_monitor_cache(&self) -> Result<MonitorCache> {
+        self.xrr_check_crtc_support()?;
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        unsafe {
+            let res = xrr::XRRGetScreenResourcesCurrent(*x_display, root);
+            if res.is_null() {
+                return failed("XRRGetScreenResourcesCurrent() returned NULL");
+            }
+            let all_modes = slice::from_raw_parts((*res).modes, (*res).nmode as usize);
+            let crtcs = slice::from_raw_parts((*res).crtcs, (*res).ncrtc as usize);
+            let mut monitors = Vec::with_capacity(crtcs.len());
+            for crtc in crtcs.iter().cloned() {
+                let info = xrr::XRRGetCrtcInfo(*x_display, res, crtc);
+                if info.is_null() {
+                    continue;
+                }
+                let xrr::XRRCrtcInfo { x, y, width, height, mode, noutput, outputs, .. } = *info;
+                if mode != 0 {
+                    let mut scale_factor = None;
+                    if noutput > 0 {
+                        let output = xrr::XRRGetOutputInfo(*x_display, res, *outputs);
+                        if !output.is_null() {
+                            scale_factor = Some(scale_factor_from_physical_size(width, (*output).mm_width as _));
+                            xrr::XRRFreeOutputInfo(output);
+                        }
+                    }
+                    monitors.push(CachedMonitor {
+                        crtc,
+                        rect: Rect::new(x, y, width, height),
+                        scale_factor,
+                        refresh_rate: all_modes.iter().find(|m| m.id == mode).and_then(refresh_rate_from_mode_info),
+                    });
+                }
+                xrr::XRRFreeCrtcInfo(info);
+            }
+            let mut primary_refresh_rate = None;
+            let primary = xrr::XRRGetOutputPrimary(*x_display, root);
+            if primary != 0 {
+                let output = xrr::XRRGetOutputInfo(*x_display, res, primary);
+                if !output.is_null() {
+                    let crtc = (*output).crtc;
+                    primary_refresh_rate = monitors.iter().find(|m| m.crtc == crtc).and_then(|m| m.refresh_rate);
+                    xrr::XRRFreeOutputInfo(output);
+                }
+            }
+            xrr::XRRFreeScreenResources(res);
+            Ok(MonitorCache { monitors, primary_refresh_rate })
+        }
+    }
+    /// Handles `RRScreenChangeNotify` and `RRNotify` events, which may mean that monitors
+    /// were reconfigured, by invalidating the monitor cache and checking the scale factor and
+    /// refresh rate of every window.
+    pub fn pump_xrr_event(&self, e: &mut x::XEvent) {
+        let is_screen_change = self.xrr().map(|xrr| e.get_type() == xrr.event_base + xrr::RRScreenChangeNotify).unwrap_or(false);
+        if is_screen_change {
+            // Keeps Xlib's idea of the screen's size up to date.
+            unsafe {
+                xrr::XRRUpdateConfiguration(e);
+            }
+        }
+        *self.monitor_cache.borrow_mut() = None;
+        let windows: Vec<_> = self.weak_windows.borrow().values().filter_map(Weak::upgrade).collect();
+        for w in windows.iter() {
+            w.monitor_rect.set(None);
+            self.check_monitor_change(w);
+        }
+    }
+    pub fn display_modes(&self, crtc: X11MonitorID) -> Result<Vec<DisplayMode>> {
+        self.xrr_check_crtc_support()?;
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        unsafe {
+            let bits_per_pixel = x::XDefaultDepth(*x_display, x::XDefaultScreen(*x_display)) as u8;
+            with_crtc_info(*x_display, root, crtc, |res, info| {
+                if info.noutput <= 0 {
+                    return Ok(Vec::new());
+                }
+                // All outputs of a CRTC have to support its mode, so the first one is as good as any.
+                let output = xrr::XRRGetOutputInfo(*x_display, res, *info.outputs);
+                if output.is_null() {
+                    return failed("XRRGetOutputInfo() returned NULL");
+                }
+                let mode_ids = slice::from_raw_parts((*output).modes, (*output).nmode as usize);
+                let all_modes = slice::from_raw_parts((*res).modes, (*res).nmode as usize);
+                let mut modes = Vec::with_capacity(mode_ids.len());
+                for mode_info in mode_ids.iter().filter_map(|id| all_modes.iter().find(|m| m.id == *id)) {
+                    let mode = display_mode_from_mode_info(mode_info, bits_per_pixel);
+                    // Modes that only differ by their timings look the same to us.
+                    if !modes.contains(&mode) {
+                        modes.push(mode);
+                    }
+                }
+                xrr::XRRFreeOutputInfo(output);
+                Ok(modes)
+            })
+        }
+    }
+    pub fn current_display_mode(&self, crtc: X11MonitorID) -> Result<DisplayMode> {
+        self.xrr_check_crtc_support()?;
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        unsafe {
+            let bits_per_pixel = x::XDefaultDepth(*x_display, x::XDefaultScreen(*x_display)) as u8;
+            with_crtc_info(*x_display, root, crtc, |res, info| {
+                let all_modes = slice::from_raw_parts((*res).modes, (*res).nmode as usize);
+                match all_modes.iter().find(|m| m.id == info.mode) {
+                    Some(mode_info) => Ok(display_mode_from_mode_info(mode_info, bits_per_pixel)),
+                    None => failed(format!("CRTC {} is disabled", crtc)),
+                }
+            })
+        }
+    }
+    pub fn monitor_gamma(&self, crtc: X11MonitorID) -> Result<GammaRamps> {
+        self.xrr_check_crtc_support()?;
+        let x_display = self.lock_x_display();
+        unsafe {
+            xlib_error::sync_catch(*x_display, || xrr_get_crtc_gamma(*x_display, crtc))?
+        }
+    }
+    pub fn original_monitor_gamma(&self, crtc: X11MonitorID) -> Result<GammaRamps> {
+        if let Some(ramps) = self.original_gamma_ramps.borrow().get(&crtc) {
+            return Ok(ramps.clone());
+        }
+        self.monitor_gamma(crtc)
+    }
+    pub fn set_monitor_gamma(&self, crtc: X11MonitorID, ramps: &GammaRamps) -> Result<()> {
+        let original = self.original_monitor_gamma(crtc)?;
+        if ramps.size() != original.size() {
+            return invalid_arg(format!("The gamma ramps of this monitor must have {} entries", original.red.len()));
+        }
+        self.original_gamma_ramps.borrow_mut().entry(crtc).or_insert(original);
+        let x_display = self.lock_x_display();
+        unsafe {
+            xlib_error::sync_catch(*x_display, || xrr_set_crtc_gamma(*x_display, crtc, ramps))?
+        }
+    }
+}
+
+impl X11Context {
+    pub fn set_display_mode(&self, crtc: X11MonitorID, mode: &DisplayMode) -> Result<X11DisplayModeGuard> {
+        self.xrr_check_crtc_support()?;
+        let root = self.x_default_root_window();
+        let x_display = self.lock_x_display();
+        let original = unsafe {
+            let screen = x::XDefaultScreen(*x_display);
+            let (screen_w, screen_h) = (x::XDisplayWidth(*x_display, screen) as u32, x::XDisplayHeight(*x_display, screen) as u32);
+            let bits_per_pixel = x::XDefaultDepth(*x_display, screen) as u8;
+            with_crtc_info(*x_display, root, crtc, |res, info| {
+                if info.mode == 0 {
+                    return failed(format!("CRTC {} is disabled", crtc));
+                }
+                if info.x as u32 + mode.width > screen_w || info.y as u32 + mode.height > screen_h {
+                    return unsupported("This mode doesn't fit in the X screen, which would have to be resized first");
+                }
+                let original = CrtcConfig {
+                    x: info.x,
+                    y: info.y,
+                    mode: info.mode,
+                    rotation: info.rotation,
+                    outputs: slice::from_raw_parts(info.outputs, info.noutput as usize).to_vec(),
+                };
+                let all_modes = slice::from_raw_parts((*res).modes, (*res).nmode as usize);
+                let new_mode = match all_modes.iter().find(|m| display_mode_from_mode_info(m, bits_per_pixel) == *mode) {
+                    Some(mode_info) => mode_info.id,
+                    None => return invalid_arg(format!("{:?} is not a mode of CRTC {}", mode, crtc)),
+                };
+                let config = CrtcConfig { mode: new_mode, .. original.clone() };
+                xlib_error::sync_catch(*x_display, || xrr_set_crtc_config(*x_display, root, crtc, &config))??;
+                Ok(original)
+            })?
+        };
+        Ok(X11DisplayModeGuard { context: self.0.clone(), crtc, original })
+    }
+}
+
+/// Calls `f` with the current screen resources and the info of `crtc`.
+unsafe fn with_crtc_info<T, F>(x_display: *mut x::Display, root: x::Window, crtc: xrr::RRCrtc, f: F) -> Result<T>
+    where F: FnOnce(*mut xrr::XRRScreenResources, &xrr::XRRCrtcInfo) -> Result<T>
+{
+    let res = xrr::XRRGetScreenResourcesCurrent(x_display, root);
+    if res.is_null() {
+        return failed("XRRGetScreenResourcesCurrent() returned NULL");
+    }
+    let info = xrr::XRRGetCrtcInfo(x_display, res, crtc);
+    if info.is_null() {
+        xrr::XRRFreeScreenResources(res);
+        return failed(format!("XRRGetCrtcInfo() returned NULL for CRTC {}", crtc));
+    }
+    let result = f(res, &*info);
+    xrr::XRRFreeCrtcInfo(info);
+    xrr::XRRFreeScreenResources(res);
+    result
+}
+
+unsafe fn xrr_set_crtc_config(x_display: *mut x::Display, root: x::Window, crtc: xrr::RRCrtc, config: &CrtcConfig) -> Result<()> {
+    let res = xrr::XRRGetScreenResourcesCurrent(x_display, root);
+    if res.is_null() {
+        return failed("XRRGetScreenResourcesCurrent() returned NULL");
+    }
+    let mut outputs = config.outputs.clone();
+    let status = xrr::XRRSetCrtcConfig(
+        x_display, res, crtc, x::CurrentTime, config.x, config.y,
+        config.mode, config.rotation, outputs.as_mut_ptr(), outputs.len() as _
+    );
+    xrr::XRRFreeScreenResources(res);
+    if status != xrr::RRSetConfigSuccess {
+        return failed(format!("XRRSetCrtcConfig() returned {}", status));
+    }
+    Ok(())
+}
+
+/// Computes the refresh rate of a mode in Hz, without rounding (e.g 59.94 Hz stays as is).
+fn refresh_rate_from_mode_info(mode: &xrr::XRRModeInfo) -> Option<f64> {
+    let mut v_total = mode.vTotal as u64;
+    if (mode.modeFlags & xrr::RR_DoubleScan as c_ulong) != 0 {
+        v_total *= 2;
+    }
+    if (mode.modeFlags & xrr::RR_Interlace as c_ulong) != 0 {
+        v_total /= 2;
+    }
+    let total = mode.hTotal as u64 * v_total;
+    if total == 0 {
+        return None;
+    }
+    Some(mode.dotClock as f64 / total as f64)
+}
+
+fn display_mode_from_mode_info(mode: &xrr::XRRModeInfo, bits_per_pixel: u8) -> DisplayMode {
+    DisplayMode {
+        width: mode.width,
+        height: mode.height,
+        refresh_rate_mhz: refresh_rate_from_mode_info(mode).map_or(0, |hz| (hz * 1000.).round() as u32),
+        bits_per_pixel,
+    }
+}
+
+pub fn rect_contains_point(r: Rect<i32, u32>, p: Vec2<i32>) -> bool {
+    p.x >= r.x && p.y >= r.y && p.x < r.x + r.w as i32 && p.y < r.y + r.h as i32
+}
+
+fn scale_factor_from_physical_size(width_px: u32, width_mm: u32) -> f64 {
+    // Projectors and some TVs report a size of zero (or a bogus one, such as their aspect ratio).
+    if width_mm < 50 {
+        return 1.;
+    }
+    let dpi = width_px as f64 * 25.4 / width_mm as f64;
+    // Round to the nearest quarter, otherwise monitors with nearly the same DPI would have
+    // slightly different scale factors, and moving windows between them would cause spurious resizes.
+    ((dpi / 96.) * 4.).round().max(4.) / 4.
+}
+
+pub unsafe fn xrr_get_crtc_gamma(x_display: *mut x::Display, crtc: xrr::RRCrtc) -> Result<GammaRamps> {
+    let size = xrr::XRRGetCrtcGammaSize(x_display, crtc);
+    if size <= 0 {
+        return failed(format!("XRRGetCrtcGammaSize() returned {}", size));
+    }
+    let gamma = xrr::XRRGetCrtcGamma(x_display, crtc);
+    if gamma.is_null() {
+        return failed("XRRGetCrtcGamma() returned NULL");
+    }
+    let size = (*gamma).size as usize;
+    let ramps = GammaRamps {
+        red: slice::from_raw_parts((*gamma).red, size).to_vec(),
+        green: slice::from_raw_parts((*gamma).green, size).to_vec(),
+        blue: slice::from_raw_parts((*gamma).blue, size).to_vec(),
+    };
+    xrr::XRRFreeGamma(gamma);
+    Ok(ramps)
+}
+
+pub unsafe fn xrr_set_crtc_gamma(x_display: *mut x::Display, crtc: xrr::RRCrtc, ramps: &GammaRamps) -> Result<()> {
+    let size = ramps.red.len();
+    let gamma = xrr::XRRAllocGamma(size as _);
+    if gamma.is_null() {
+        return failed("XRRAllocGamma() returned NULL");
     }
+    slice::from_raw_parts_mut((*gamma).red, size).copy_from_slice(&ramps.red);
+    slice::from_raw_parts_mut((*gamma).green, size).copy_from_slice(&ramps.green);
+    slice::from_raw_parts_mut((*gamma).blue, size).copy_from_slice(&ramps.blue);
+    xrr::XRRSetCrtcGamma(x_display, crtc, gamma);
+    xrr::XRRFreeGamma(gamma);
+    Ok(())
 }
@@ -0,0 +1,150 @@
+use std::os::raw::{c_int, c_char};
+use error::{Result, failed};
+use window::{ImageMask, HitTestFn, HitTestResult};
+use {Extent2, Rect};
+use super::context::X11SharedContext;
+use super::window::X11SharedWindow;
+use super::xlib_error;
+use super::missing_bits::xshape as mxshape;
+use super::x11::xlib as x;
+
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct XShape {
+    pub event_base: c_int,
+    pub error_base: c_int,
+    pub major_version: c_int,
+    pub minor_version: c_int,
+}
+
+impl XShape {
+    pub unsafe fn query(x_display: *mut x::Display) -> Result<Self> {
+        let mut xshape = Self::default();
+
+        let has_it = mxshape::XShapeQueryExtension(x_display, &mut xshape.event_base, &mut xshape.error_base);
+        if has_it == x::False {
+            return failed("XShapeQueryExtension() returned False");
+        }
+        let success = mxshape::XShapeQueryVersion(x_display, &mut xshape.major_version, &mut xshape.minor_version);
+        if success == 0 {
+            return failed("XShapeQueryVersion() returned 0");
+        }
+        Ok(xshape)
+    }
+    /// Input shapes are in XShape 1.1.
+    pub fn has_input_shape(&self) -> bool {
+        (self.major_version, self.minor_version) >= (1, 1)
+    }
+}
+
+impl X11SharedContext {
+    pub fn xshape(&self) -> Result<&XShape> {
+        self.xshape.as_ref().map_err(Clone::clone)
+    }
+}
+
+/// Packs the mask into XBM data, which is what `XCreateBitmapFromData()` expects:
+/// rows are padded to a whole number of bytes, and the least significant bit comes first.
+fn xbm_data(mask: &ImageMask) -> Vec<u8> {
+    let (w, h) = (mask.width as usize, mask.height as usize);
+    let bytes_per_row = (w + 7) / 8;
+    let mut data = vec![0_u8; bytes_per_row * h];
+    for y in 0..h {
+        for x in 0..w {
+            if mask.pixels[y * w + x] {
+                data[y * bytes_per_row + x / 8] |= 1 << (x % 8);
+            }
+        }
+    }
+    data
+}
+
+unsafe fn union_rect_with_region(region: x::Region, r: Rect<i32, u32>) {
+    let mut xrect = x::XRectangle {
+        x: r.x.max(i16::MIN as i32).min(i16::MAX as i32) as _,
+        y: r.y.max(i16::MIN as i32).min(i16::MAX as i32) as _,
+        width: r.w.min(u16::MAX as u32) as _,
+        height: r.h.min(u16::MAX as u32) as _,
+    };
+    x::XUnionRectWithRegion(&mut xrect, region, region);
+}
+
+impl X11SharedWindow {
+    pub fn set_shape_mask(&self, mask: Option<&ImageMask>) -> Result<()> {
+        self.context.xshape()?;
+        self.combine_shape_mask(mxshape::ShapeBounding, mask)
+    }
+    pub fn set_input_shape_mask(&self, mask: Option<&ImageMask>) -> Result<()> {
+        let xshape = self.context.xshape()?;
+        if !xshape.has_input_shape() {
+            return failed(format!("Input shapes require XShape 1.1, but the X server only has XShape {}.{}", xshape.major_version, xshape.minor_version));
+        }
+        self.combine_shape_mask(mxshape::ShapeInput, mask)
+    }
+    pub(crate) fn set_cursor_hit_test(&self, hit_test: Option<HitTestFn>) -> Result<()> {
+        let is_some = hit_test.is_some();
+        *self.hit_test.borrow_mut() = hit_test;
+        if is_some {
+            self.refresh_hit_test_shape(self.size()?)
+        } else {
+            self.set_input_shape_mask(None)
+        }
+    }
+    /// Rebuilds the input shape from the hit test function's areas, if any; `Transparent` areas
+    /// are left out. This must be called again whenever the window is resized.
+    pub fn refresh_hit_test_shape(&self, size: Extent2<u32>) -> Result<()> {
+        let hit_test = match self.hit_test.borrow().clone() {
+            Some(hit_test) => hit_test,
+            None => return Ok(()),
+        };
+        let xshape = self.context.xshape()?;
+        if !xshape.has_input_shape() {
+            return failed(format!("Input shapes require XShape 1.1, but the X server only has XShape {}.{}", xshape.major_version, xshape.minor_version));
+        }
+        let areas = hit_test.areas(size);
+        let x_display = self.context.lock_x_display();
+        unsafe {
+            // Points that are in no area are `Client`, and later areas take precedence.
+            let region = x::XCreateRegion();
+            union_rect_with_region(region, Rect::new(0, 0, size.w, size.h));
+            for area in areas.iter() {
+                if area.result == HitTestResult::Transparent {
+                    let transparent = x::XCreateRegion();
+                    union_rect_with_region(transparent, area.rect);
+                    x::XSubtractRegion(region, transparent, region);
+                    x::XDestroyRegion(transparent);
+                } else {
+                    union_rect_with_region(region, area.rect);
+                }
+            }
+            let status = xlib_error::sync_catch(*x_display, || {
+                mxshape::XShapeCombineRegion(*x_display, self.x_window, mxshape::ShapeInput, 0, 0, region, mxshape::ShapeSet)
+            });
+            x::XDestroyRegion(region);
+            status
+        }
+    }
+    fn combine_shape_mask(&self, dest_kind: c_int, mask: Option<&ImageMask>) -> Result<()> {
+        let x_display = self.context.lock_x_display();
+        let mask = match mask {
+            // A `None` pixmap removes the shape, which restores the window's rectangle.
+            None => return unsafe {
+                xlib_error::sync_catch(*x_display, || {
+                    mxshape::XShapeCombineMask(*x_display, self.x_window, dest_kind, 0, 0, 0, mxshape::ShapeSet)
+                })
+            },
+            Some(mask) => mask,
+        };
+        let data = xbm_data(mask);
+        unsafe {
+            let pixmap = x::XCreateBitmapFromData(*x_display, self.x_window, data.as_ptr() as *const c_char, mask.width, mask.height);
+            if pixmap == 0 {
+                return failed("XCreateBitmapFromData() returned 0");
+            }
+            let status = xlib_error::sync_catch(*x_display, || {
+                mxshape::XShapeCombineMask(*x_display, self.x_window, dest_kind, 0, 0, pixmap, mxshape::ShapeSet)
+            });
+            x::XFreePixmap(*x_display, pixmap);
+            status
+        }
+    }
+}
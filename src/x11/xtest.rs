@@ -0,0 +1,90 @@
+use std::os::raw::c_int;
+use error::{Result, failed, invalid_arg};
+use device::{Keysym, MouseButton, ButtonState};
+use super::context::X11SharedContext;
+use super::xlib_error;
+use super::x11::xlib as x;
+use super::x11::xtest;
+use Vec2;
+
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct XTest {
+    pub event_base: c_int,
+    pub error_base: c_int,
+    pub major_version: c_int,
+    pub minor_version: c_int,
+}
+
+impl X11SharedContext {
+    pub fn xtest(&self) -> Result<&XTest> {
+        self.xtest.as_ref().map_err(Clone::clone)
+    }
+    pub fn synthesize_key(&self, keysym: Keysym, state: ButtonState) -> Result<()> {
+        self.xtest()?;
+        let x_keysym = match keysym.x_keysym() {
+            Some(x_keysym) => x_keysym,
+            None => return invalid_arg(format!("{:?} has no X11 equivalent", keysym)),
+        };
+        let x_display = self.lock_x_display();
+        let keycode = unsafe {
+            x::XKeysymToKeycode(*x_display, x_keysym)
+        };
+        if keycode == 0 {
+            return failed(format!("No keycode maps to {:?} in the current keyboard mapping", keysym));
+        }
+        let status = unsafe {
+            xlib_error::sync_catch(*x_display, || {
+                xtest::XTestFakeKeyEvent(*x_display, keycode as _, (state == ButtonState::Down) as _, x::CurrentTime)
+            })?
+        };
+        match status {
+            0 => failed("XTestFakeKeyEvent() returned 0"),
+            _ => Ok(()),
+        }
+    }
+    pub fn synthesize_mouse_move_to(&self, position: Vec2<i32>) -> Result<()> {
+        self.xtest()?;
+        let x_display = self.lock_x_display();
+        let screen_number = self.x_default_screen_num();
+        let status = unsafe {
+            xlib_error::sync_catch(*x_display, || {
+                xtest::XTestFakeMotionEvent(*x_display, screen_number, position.x, position.y, x::CurrentTime)
+            })?
+        };
+        match status {
+            0 => failed("XTestFakeMotionEvent() returned 0"),
+            _ => Ok(()),
+        }
+    }
+    pub fn synthesize_mouse_button(&self, button: MouseButton, state: ButtonState) -> Result<()> {
+        self.xtest()?;
+        let x_button = match X11SharedContext::mousebutton_to_x11_button(button) {
+            Some(x_button) => x_button,
+            None => return invalid_arg(format!("{:?} has no core X11 button number", button)),
+        };
+        let x_display = self.lock_x_display();
+        let status = unsafe {
+            xlib_error::sync_catch(*x_display, || {
+                xtest::XTestFakeButtonEvent(*x_display, x_button as _, (state == ButtonState::Down) as _, x::CurrentTime)
+            })?
+        };
+        match status {
+            0 => failed("XTestFakeButtonEvent() returned 0"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl XTest {
+    pub unsafe fn query(x_display: *mut x::Display) -> Result<Self> {
+        let mut xtest = Self::default();
+        let has_it = xtest::XTestQueryExtension(
+            x_display, &mut xtest.event_base, &mut xtest.error_base,
+            &mut xtest.major_version, &mut xtest.minor_version
+        );
+        if has_it == x::False {
+            return failed("XTestQueryExtension() returned False");
+        }
+        Ok(xtest)
+    }
+}
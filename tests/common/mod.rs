@@ -0,0 +1,34 @@
+//! Helpers shared by the integration tests. Each test file includes this via `mod common;`.
+
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use std::env;
+use std::time::{Duration, Instant};
+use dmc::{Context, Event};
+
+/// Returns the first event matching `f`, discarding any other event.
+///
+/// Panics if none is received within 5 seconds.
+pub fn wait_for<T, F: FnMut(&Event) -> Option<T>>(context: &Context, mut f: F) -> T {
+    let start = Instant::now();
+    let timeout = Duration::from_secs(5);
+    while start.elapsed() < timeout {
+        if let Some(ev) = context.wait_event(Duration::from_millis(100).into()) {
+            if let Some(x) = f(&ev) {
+                return x;
+            }
+        }
+    }
+    panic!("Timed out while waiting for an event");
+}
+
+/// Creates a `Context`, or returns `None` if there's no display to connect to, so that tests
+/// which only need a display (and not `synthetic-input`) pass trivially on headless machines.
+pub fn context_if_display_is_available() -> Option<Context> {
+    if cfg!(target_os = "linux") && env::var_os("DISPLAY").is_none() {
+        eprintln!("DISPLAY is not set; Skipping this test");
+        return None;
+    }
+    Some(Context::new().unwrap())
+}
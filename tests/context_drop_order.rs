@@ -0,0 +1,53 @@
+//! Tests for objects that outlive the `Context` they were created from.
+//!
+//! This requires a running display (e.g an X server), and passes trivially without one.
+
+extern crate dmc;
+
+mod common;
+
+use dmc::{
+    WindowSettings, SystemCursor,
+    GLContextSettings, GLDefaultPixelFormatChooser, GLPixelFormatSettings,
+};
+
+#[test]
+fn window_and_cursor_outlive_the_context() {
+    let context = match common::context_if_display_is_available() {
+        Some(context) => context,
+        None => return,
+    };
+    let window = context.create_window(&WindowSettings::default()).unwrap();
+    let cursor = context.create_system_cursor(SystemCursor::Crosshair).unwrap();
+    drop(context);
+
+    window.set_title("Outliving its Context").unwrap();
+    window.show().unwrap();
+    window.set_cursor(&cursor).unwrap();
+    window.hide().unwrap();
+    drop(cursor);
+    drop(window);
+}
+
+#[test]
+fn gl_context_outlives_the_context_and_window() {
+    let context = match common::context_if_display_is_available() {
+        Some(context) => context,
+        None => return,
+    };
+    let pixel_format = GLPixelFormatSettings::default();
+    let chooser = GLDefaultPixelFormatChooser::from(&pixel_format);
+    let window = match context.create_window(&WindowSettings { opengl: Some(&chooser), .. Default::default() }) {
+        Ok(window) => window,
+        Err(_) => return, // No OpenGL here; Nothing to test.
+    };
+    let gl_context = match window.create_gl_context(&GLContextSettings::default()) {
+        Ok(gl_context) => gl_context,
+        Err(_) => return,
+    };
+    drop(context);
+    window.make_gl_context_current(Some(&gl_context)).unwrap();
+    window.make_gl_context_current(None).unwrap();
+    drop(window);
+    drop(gl_context);
+}
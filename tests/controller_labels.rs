@@ -0,0 +1,39 @@
+//! Tests for guessing controller families and labelling their buttons.
+//!
+//! These don't need a display or a controller.
+
+extern crate dmc;
+
+use dmc::device::{ButtonGlyphHint, ButtonLabel, ControllerButton, ControllerAxis};
+
+#[test]
+fn glyphs_are_inferred_from_vendor_ids() {
+    assert_eq!(ButtonGlyphHint::infer(Some(0x045e), None), ButtonGlyphHint::Xbox);
+    assert_eq!(ButtonGlyphHint::infer(Some(0x054c), Some("Wireless Controller")), ButtonGlyphHint::PlayStation);
+    assert_eq!(ButtonGlyphHint::infer(Some(0x057e), None), ButtonGlyphHint::Nintendo);
+}
+
+#[test]
+fn glyphs_are_inferred_from_names() {
+    assert_eq!(ButtonGlyphHint::infer(Some(0x0e6f), Some("Afterglow Xbox 360 Controller")), ButtonGlyphHint::Xbox);
+    assert_eq!(ButtonGlyphHint::infer(None, Some("Sony DualSense")), ButtonGlyphHint::PlayStation);
+    assert_eq!(ButtonGlyphHint::infer(None, Some("Nintendo Switch Pro Controller")), ButtonGlyphHint::Nintendo);
+}
+
+#[test]
+fn unknown_controllers_get_generic_labels() {
+    let glyphs = ButtonGlyphHint::infer(Some(0x1234), Some("Generic USB Joystick"));
+    assert_eq!(glyphs, ButtonGlyphHint::Generic);
+    assert_eq!(ButtonGlyphHint::infer(None, None), ButtonGlyphHint::Generic);
+    let label = ButtonLabel::for_button(ControllerButton::LShoulder, glyphs);
+    assert_eq!(label.text, ControllerButton::LShoulder.display_name());
+}
+
+#[test]
+fn south_button_labels() {
+    let label = |glyphs| ButtonLabel::for_button(ControllerButton::SOUTH, glyphs).text;
+    assert_eq!(label(ButtonGlyphHint::Xbox), "A");
+    assert_eq!(label(ButtonGlyphHint::PlayStation), "Cross");
+    assert_eq!(label(ButtonGlyphHint::Nintendo), "B");
+    assert_eq!(ButtonLabel::for_axis(ControllerAxis::RTrigger, ButtonGlyphHint::Nintendo).text, "ZR");
+}
@@ -0,0 +1,72 @@
+//! Tests for controllers whose events are not read fast enough (`SYN_DROPPED` on Linux).
+//!
+//! Run with `cargo test --features synthetic-input`.
+//! This creates a virtual controller via `/dev/uinput`, which must be writable (this normally
+//! requires being in the `input` group).
+
+#![cfg(all(feature = "synthetic-input", target_os = "linux"))]
+
+extern crate dmc;
+extern crate libc;
+#[macro_use]
+extern crate nix;
+
+mod common;
+mod uinput;
+
+use std::thread;
+use std::time::Duration;
+use dmc::{Context, Event};
+use dmc::device::DeviceID;
+use uinput::{VirtualController, ABS_X};
+
+const NAME: &str = "dmc resync test controller";
+
+/// Collects the events of `controller` until none has been received for a while.
+fn drain_controller_events(context: &Context, controller: DeviceID) -> Vec<Event> {
+    let mut events = vec![];
+    while let Some(ev) = context.wait_event(Duration::from_millis(500).into()) {
+        match ev {
+            Event::ControllerEventsDropped { controller: c, .. }
+            | Event::ControllerAxisMotion { controller: c, .. } if c == controller => events.push(ev),
+            _ => (),
+        }
+    }
+    events
+}
+
+#[test]
+fn dropped_events_are_reported_before_the_resync() {
+    let context = Context::new().unwrap();
+    let virtual_controller = VirtualController::new(NAME);
+    let controller = common::wait_for(&context, |ev| match *ev {
+        Event::DeviceConnected { device, ref info, .. } if info.name() == Some(NAME) => Some(device),
+        _ => None,
+    });
+    drain_controller_events(&context, controller);
+
+    // Way more than the evdev client buffer can hold, while we're not reading.
+    const LAST_VALUE: i32 = 1234;
+    for i in 0..10_000 {
+        virtual_controller.move_axis(ABS_X, i % 1000);
+    }
+    virtual_controller.move_axis(ABS_X, LAST_VALUE);
+    thread::sleep(Duration::from_millis(100));
+
+    let events = drain_controller_events(&context, controller);
+    let (dropped_at, dropped_instant) = events.iter().enumerate().filter_map(|(i, ev)| match *ev {
+        Event::ControllerEventsDropped { instant, .. } => Some((i, instant)),
+        _ => None,
+    }).next().expect("No ControllerEventsDropped was reported");
+
+    // The resync reports the final value, after the marker, as of the time of the resync.
+    match events.last() {
+        Some(&Event::ControllerAxisMotion { value, instant, .. }) => {
+            assert_eq!(value, LAST_VALUE as f64);
+            // We slept for 100ms between the drop and the resync.
+            assert!(instant.duration_since(dropped_instant).unwrap() >= Duration::from_millis(50));
+        },
+        other => panic!("Expected the last event to be a ControllerAxisMotion, got {:?}", other),
+    }
+    assert!(events.len() - 1 > dropped_at);
+}
@@ -0,0 +1,62 @@
+//! Compares `Context::all_controller_states()` with one `controller_state()` call per controller.
+//!
+//! Run with `cargo test --release --features synthetic-input --test controller_states_bench -- --ignored --nocapture`.
+//! This creates virtual controllers via `/dev/uinput`, and prints the average time per frame
+//! of each approach.
+
+#![cfg(all(feature = "synthetic-input", target_os = "linux"))]
+
+extern crate dmc;
+extern crate libc;
+#[macro_use]
+extern crate nix;
+
+mod common;
+mod uinput;
+
+use std::time::{Duration, Instant};
+use dmc::{Context, Event};
+use dmc::device::DeviceID;
+use uinput::VirtualController;
+
+const NAME: &str = "dmc benchmark controller";
+const NB_CONTROLLERS: usize = 4;
+const NB_FRAMES: u32 = 10_000;
+
+fn per_frame<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..NB_FRAMES {
+        f();
+    }
+    start.elapsed() / NB_FRAMES
+}
+
+#[test]
+#[ignore]
+fn all_controller_states_vs_individual_calls() {
+    let context = Context::new().unwrap();
+    let virtual_controllers: Vec<_> = (0..NB_CONTROLLERS).map(|_| VirtualController::new(NAME)).collect();
+    let controllers: Vec<DeviceID> = virtual_controllers.iter().map(|_| common::wait_for(&context, |ev| match *ev {
+        Event::DeviceConnected { device, ref info, .. } if info.name() == Some(NAME) => Some(device),
+        _ => None,
+    })).collect();
+
+    // Some input on each frame, so that there's something to pump.
+    let mut value = 0;
+    let batched = per_frame(|| {
+        value = (value + 1) % 1000;
+        virtual_controllers[0].move_axis(uinput::ABS_X, value);
+        let states = context.all_controller_states().unwrap();
+        assert!(states.len() >= NB_CONTROLLERS);
+        while context.poll_event().is_some() {}
+    });
+    let individual = per_frame(|| {
+        value = (value + 1) % 1000;
+        virtual_controllers[0].move_axis(uinput::ABS_X, value);
+        for controller in controllers.iter() {
+            context.controller_state(*controller).unwrap();
+        }
+        while context.poll_event().is_some() {}
+    });
+    println!("{} controllers: all_controller_states(): {:?}/frame, {} x controller_state(): {:?}/frame", NB_CONTROLLERS, batched, NB_CONTROLLERS, individual);
+}
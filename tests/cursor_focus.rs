@@ -0,0 +1,155 @@
+//! Checks that a window's cursor state survives focus changes.
+//!
+//! Run with `cargo test --features synthetic-input`.
+//! This requires a running X server with a click-to-focus window manager, and moves and clicks
+//! your actual mouse cursor.
+
+#![cfg(all(feature = "synthetic-input", target_os = "linux"))]
+
+extern crate dmc;
+extern crate x11;
+
+mod common;
+
+use std::ptr;
+use std::slice;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::os::raw::{c_short, c_ushort, c_ulong, c_char};
+use x11::xlib as x;
+use dmc::{Context, Event, Vec2, Rect, Window, WindowSettings};
+use dmc::device::{MouseButton, ButtonState};
+
+// The x11 crate has no bindings for this.
+#[repr(C)]
+struct XFixesCursorImage {
+    x: c_short,
+    y: c_short,
+    width: c_ushort,
+    height: c_ushort,
+    xhot: c_ushort,
+    yhot: c_ushort,
+    cursor_serial: c_ulong,
+    pixels: *mut c_ulong,
+    atom: x::Atom,
+    name: *const c_char,
+}
+
+#[link(name = "Xfixes")]
+extern "C" {
+    fn XFixesGetCursorImage(dpy: *mut x::Display) -> *mut XFixesCursorImage;
+}
+
+/// Our own connection, for asking the X server which cursor it actually displays.
+struct CursorSpy(*mut x::Display);
+
+impl CursorSpy {
+    fn new() -> Self {
+        let x_display = unsafe { x::XOpenDisplay(ptr::null()) };
+        assert!(!x_display.is_null(), "Could not open the X display");
+        CursorSpy(x_display)
+    }
+    /// Is the displayed cursor fully transparent?
+    fn is_cursor_blank(&self) -> bool {
+        unsafe {
+            let image = XFixesGetCursorImage(self.0);
+            assert!(!image.is_null(), "XFixesGetCursorImage() returned NULL");
+            let len = (*image).width as usize * (*image).height as usize;
+            let pixels = slice::from_raw_parts((*image).pixels, len);
+            // Pixels are ARGB, in the low 32 bits.
+            let is_blank = pixels.iter().all(|p| (p >> 24) & 0xff == 0);
+            x::XFree(image as *mut _);
+            is_blank
+        }
+    }
+    /// Panics unless the displayed cursor is (or becomes) blank or not, as expected.
+    fn expect_cursor_blank(&self, context: &Context, expected: bool) {
+        let start = Instant::now();
+        loop {
+            // Also flushes our requests.
+            while context.poll_event().is_some() {}
+            if self.is_cursor_blank() == expected {
+                return;
+            }
+            if start.elapsed() > Duration::from_secs(2) {
+                panic!("The X server's cursor should {}be blank", if expected { "" } else { "not " });
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+impl Drop for CursorSpy {
+    fn drop(&mut self) {
+        unsafe {
+            x::XCloseDisplay(self.0);
+        }
+    }
+}
+
+fn create_shown_window(context: &Context, position: Vec2<i32>) -> Window {
+    let window = context.create_window(&WindowSettings::default()).unwrap();
+    let handle = window.handle();
+    window.set_position_and_size(Rect::new(position.x, position.y, 200, 200)).unwrap();
+    window.show().unwrap();
+    common::wait_for(context, |ev| match *ev {
+        Event::WindowShown { window } if window == handle => Some(()),
+        _ => None,
+    });
+    window.clear().unwrap();
+    window
+}
+
+fn move_mouse_into(context: &Context, window: &Window) {
+    let handle = window.handle();
+    let size = window.canvas_size().unwrap();
+    let center = window.position().unwrap() + Vec2::new(size.w as i32 / 2, size.h as i32 / 2);
+    context.synthesize_mouse_move_to(center).unwrap();
+    common::wait_for(context, |ev| match *ev {
+        Event::MouseEnter { window, .. } if window == handle => Some(()),
+        _ => None,
+    });
+}
+
+/// Moves the mouse into `window` and clicks it, which gives it the focus.
+fn click_into(context: &Context, window: &Window) {
+    move_mouse_into(context, window);
+    if window.is_focused() == Ok(true) {
+        return;
+    }
+    let handle = window.handle();
+    context.synthesize_mouse_button(MouseButton::Left, ButtonState::Down).unwrap();
+    context.synthesize_mouse_button(MouseButton::Left, ButtonState::Up).unwrap();
+    common::wait_for(context, |ev| match *ev {
+        Event::KeyboardFocusGained { window, .. } if window == handle => Some(()),
+        _ => None,
+    });
+}
+
+#[test]
+fn hidden_cursor_is_restored_after_focus_changes() {
+    let context = Context::new().unwrap();
+    let spy = CursorSpy::new();
+    let a = create_shown_window(&context, Vec2::new(100, 100));
+    let b = create_shown_window(&context, Vec2::new(400, 100));
+
+    click_into(&context, &a);
+    a.hide_cursor().unwrap();
+    assert_eq!(a.is_cursor_visible(), Ok(false));
+    spy.expect_cursor_blank(&context, true);
+
+    for _ in 0..2 {
+        click_into(&context, &b);
+        assert_eq!(b.is_cursor_visible(), Ok(true));
+        spy.expect_cursor_blank(&context, false);
+        click_into(&context, &a);
+        assert_eq!(a.is_cursor_visible(), Ok(false));
+        spy.expect_cursor_blank(&context, true);
+    }
+
+    a.show_cursor().unwrap();
+    click_into(&context, &b);
+    click_into(&context, &a);
+    assert_eq!(a.is_cursor_visible(), Ok(true));
+    spy.expect_cursor_blank(&context, false);
+}
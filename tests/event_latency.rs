@@ -0,0 +1,88 @@
+//! Tests for `Context::next_event_with_metadata()` and `DeliveredEvent::latency()`.
+//!
+//! Run with `cargo test --features synthetic-input`.
+//! This creates a virtual controller via `/dev/uinput`, which must be writable (this normally
+//! requires being in the `input` group).
+
+#![cfg(all(feature = "synthetic-input", target_os = "linux"))]
+
+extern crate dmc;
+extern crate libc;
+#[macro_use]
+extern crate nix;
+
+mod uinput;
+
+use std::thread;
+use std::time::{Duration, Instant};
+use dmc::{Context, Event, DeliveredEvent};
+use uinput::{VirtualController, BTN_SOUTH};
+
+const NAME: &str = "dmc latency test controller";
+
+/// Returns the first delivered event matching `f`, discarding any other event.
+fn wait_for_delivered<F: FnMut(&Event) -> bool>(context: &Context, mut f: F) -> DeliveredEvent {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if let Some(delivered) = context.next_event_with_metadata(Duration::from_millis(100).into()) {
+            if f(&delivered.event) {
+                return delivered;
+            }
+        }
+    }
+    panic!("Timed out while waiting for an event");
+}
+
+/// Checks that `latency()` is consistent with the instants it is computed from.
+fn check_latency(delivered: &DeliveredEvent) -> Duration {
+    let instant = delivered.event.instant().expect("The event should have an instant");
+    let delivered_instant = delivered.delivered_instant.expect("The event's clock should be readable");
+    let latency = delivered.latency().expect("The event should have a latency");
+    assert_eq!(delivered_instant.duration_since(instant), Some(latency));
+    assert!(latency < Duration::from_secs(1), "Unexpectedly high latency: {:?}", latency);
+    latency
+}
+
+#[test]
+fn events_without_an_instant_have_no_latency() {
+    let delivered = DeliveredEvent {
+        event: Event::Quit,
+        delivered_at: Instant::now(),
+        delivered_instant: None,
+    };
+    assert_eq!(delivered.latency(), None);
+}
+
+#[test]
+fn controller_events_have_a_latency() {
+    let context = Context::new().unwrap();
+    let virtual_controller = VirtualController::new(NAME);
+
+    // From udev's monotonic clock.
+    let connected = wait_for_delivered(&context, |ev| match *ev {
+        Event::DeviceConnected { ref info, .. } => info.name() == Some(NAME),
+        _ => false,
+    });
+    check_latency(&connected);
+    let controller = match connected.event {
+        Event::DeviceConnected { device, .. } => device,
+        _ => unreachable!(),
+    };
+
+    // From evdev's clock, which may be CLOCK_REALTIME.
+    virtual_controller.press_button(BTN_SOUTH);
+    let pressed = wait_for_delivered(&context, |ev| match *ev {
+        Event::ControllerButtonPressed { controller: c, .. } => c == controller,
+        _ => false,
+    });
+    check_latency(&pressed);
+
+    // Waiting before getting the event counts towards its latency.
+    virtual_controller.release_button(BTN_SOUTH);
+    thread::sleep(Duration::from_millis(50));
+    let released = wait_for_delivered(&context, |ev| match *ev {
+        Event::ControllerButtonReleased { controller: c, .. } => c == controller,
+        _ => false,
+    });
+    assert!(check_latency(&released) >= Duration::from_millis(50));
+}
@@ -0,0 +1,31 @@
+//! Tests for validating gamma ramps before they reach the monitors.
+//!
+//! This requires a running display (e.g an X server), and passes trivially without one.
+//! No monitor's gamma is actually changed.
+
+extern crate dmc;
+
+mod common;
+
+use dmc::{GammaRamps, ErrorKind};
+
+#[test]
+fn empty_gamma_ramps_are_rejected() {
+    let context = match common::context_if_display_is_available() {
+        Some(context) => context,
+        None => return,
+    };
+    let empty = GammaRamps { red: vec![], green: vec![], blue: vec![] };
+    assert_eq!(context.set_gamma_ramp(&empty).unwrap_err().kind, ErrorKind::InvalidArgument);
+}
+
+#[test]
+fn mismatched_gamma_ramps_are_rejected() {
+    let context = match common::context_if_display_is_available() {
+        Some(context) => context,
+        None => return,
+    };
+    let mut ramps = GammaRamps::linear(256);
+    ramps.blue.pop();
+    assert_eq!(context.set_gamma_ramp(&ramps).unwrap_err().kind, ErrorKind::InvalidArgument);
+}
@@ -0,0 +1,32 @@
+//! Test for text input as reported after an input method commits a string.
+//!
+//! Run with `cargo test --features synthetic-input`.
+//! This requires a running display (e.g an X server).
+
+#![cfg(feature = "synthetic-input")]
+
+extern crate dmc;
+
+mod common;
+
+use dmc::{Context, Event, WindowSettings};
+
+#[test]
+fn committed_ime_string_is_reported_as_text() {
+    let context = Context::new().unwrap();
+    let window = context.create_window(&WindowSettings::default()).unwrap();
+    let handle = window.handle();
+    window.show().unwrap();
+    common::wait_for(&context, |ev| match *ev {
+        Event::WindowShown { window } if window == handle => Some(()),
+        _ => None,
+    });
+
+    window.enable_text_input().unwrap();
+    window.push_committed_text_event("日本語").unwrap();
+    let text = common::wait_for(&context, |ev| match *ev {
+        Event::KeyboardTextString { window, ref text, .. } if window == handle => Some(text.clone()),
+        _ => None,
+    });
+    assert_eq!(text, "日本語");
+}
@@ -0,0 +1,103 @@
+//! Tests for several `Context`s living in the same thread.
+//!
+//! Run with `cargo test --features synthetic-input`.  
+//! This requires a running display (e.g an X server), and creates a virtual controller
+//! via `/dev/uinput`.
+
+#![cfg(all(feature = "synthetic-input", target_os = "linux"))]
+
+extern crate dmc;
+extern crate libc;
+#[macro_use]
+extern crate nix;
+
+mod common;
+mod uinput;
+
+use std::time::{Duration, Instant};
+use dmc::{Context, Event};
+use dmc::device::{DeviceID, ControllerButton};
+use uinput::VirtualController;
+
+const NAME: &str = "dmc multiple contexts test controller";
+
+/// Counts the events matching `f` over a short period of time, discarding any other event.
+fn count_events<F: FnMut(&Event) -> bool>(context: &Context, mut f: F) -> usize {
+    let start = Instant::now();
+    let mut count = 0;
+    while start.elapsed() < Duration::from_millis(500) {
+        if let Some(ev) = context.wait_event(Duration::from_millis(50).into()) {
+            if f(&ev) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Waits until `context` reports the virtual controller as connected.
+fn wait_for_connection(context: &Context) -> DeviceID {
+    common::wait_for(context, |ev| match *ev {
+        Event::DeviceConnected { device, ref info, .. } if info.name() == Some(NAME) => Some(device),
+        _ => None,
+    })
+}
+
+#[test]
+fn controllers_are_visible_from_both_contexts() {
+    let a = Context::new().unwrap();
+    let b = Context::new().unwrap();
+    let _virtual_controller = VirtualController::new(NAME);
+    let controller = wait_for_connection(&a);
+    assert_eq!(wait_for_connection(&b), controller);
+    assert!(a.devices().unwrap().contains_key(&controller));
+    assert!(b.devices().unwrap().contains_key(&controller));
+    assert_eq!(a.controller_player_index(controller).unwrap(), b.controller_player_index(controller).unwrap());
+}
+
+#[test]
+fn controller_input_reaches_each_context_exactly_once() {
+    let a = Context::new().unwrap();
+    let b = Context::new().unwrap();
+    let virtual_controller = VirtualController::new(NAME);
+    let controller = wait_for_connection(&a);
+    wait_for_connection(&b);
+    count_events(&a, |_| false);
+    count_events(&b, |_| false);
+
+    virtual_controller.press_button(uinput::BTN_SOUTH);
+    virtual_controller.release_button(uinput::BTN_SOUTH);
+    for context in &[&a, &b] {
+        let (mut nb_pressed, mut nb_released) = (0, 0);
+        count_events(context, |ev| {
+            match *ev {
+                Event::ControllerButtonPressed { controller: c, button: ControllerButton::A, .. } if c == controller => nb_pressed += 1,
+                Event::ControllerButtonReleased { controller: c, button: ControllerButton::A, .. } if c == controller => nb_released += 1,
+                _ => (),
+            }
+            false
+        });
+        assert_eq!((nb_pressed, nb_released), (1, 1));
+    }
+}
+
+#[test]
+fn controller_events_reach_each_context_exactly_once() {
+    let a = Context::new().unwrap();
+    let b = Context::new().unwrap();
+    let _virtual_controller = VirtualController::new(NAME);
+    let controller = wait_for_connection(&a);
+    wait_for_connection(&b);
+    count_events(&a, |_| false);
+    count_events(&b, |_| false);
+
+    let new_index = a.controller_player_index(controller).unwrap().map_or(0, |i| i + 1);
+    a.set_controller_player_index(controller, Some(new_index)).unwrap();
+    for context in &[&a, &b] {
+        let count = count_events(context, |ev| match *ev {
+            Event::ControllerPlayerIndexChanged { controller: c, player_index, .. } => c == controller && player_index == Some(new_index),
+            _ => false,
+        });
+        assert_eq!(count, 1);
+    }
+}
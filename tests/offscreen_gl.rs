@@ -0,0 +1,54 @@
+//! Tests for OpenGL contexts that are not tied to a window.
+//!
+//! This requires a running display (e.g an X server), and passes trivially without one, or if
+//! offscreen contexts (or the requested API) are unsupported there.
+
+extern crate dmc;
+
+mod common;
+
+use std::mem;
+use std::os::raw::{c_void, c_char};
+use dmc::{GLContextSettings, GLApiPreference, GLApi, ErrorKind};
+
+const GL_VERSION: u32 = 0x1F02;
+
+type GlGetString = extern "system" fn(u32) -> *const c_char;
+
+#[test]
+fn offscreen_context_can_be_made_current() {
+    let context = match common::context_if_display_is_available() {
+        Some(context) => context,
+        None => return,
+    };
+    let gl_context = match context.create_offscreen_gl_context(&GLContextSettings::default()) {
+        Ok(c) => c,
+        Err(ref e) if e.kind == ErrorKind::Unsupported { requires_optin: false } => return,
+        Err(e) => panic!("Could not create an offscreen GL context: {}", e),
+    };
+    gl_context.make_current_offscreen().unwrap();
+    let gl_get_string = gl_context.proc_address("glGetString");
+    assert!(!gl_get_string.is_null());
+    let gl_get_string = unsafe { mem::transmute::<*const c_void, GlGetString>(gl_get_string) };
+    assert!(!gl_get_string(GL_VERSION).is_null());
+}
+
+#[test]
+fn offscreen_context_can_be_created_via_egl() {
+    let context = match common::context_if_display_is_available() {
+        Some(context) => context,
+        None => return,
+    };
+    let settings = GLContextSettings { api_preference: GLApiPreference::OnlyEGL, .. Default::default() };
+    let gl_context = match context.create_offscreen_gl_context(&settings) {
+        Ok(c) => c,
+        Err(ref e) if e.kind == ErrorKind::Unsupported { requires_optin: false } => return,
+        Err(e) => panic!("Could not create an offscreen GL context via EGL: {}", e),
+    };
+    assert_eq!(gl_context.api(), GLApi::EGL);
+    gl_context.make_current_offscreen().unwrap();
+    let gl_get_string = gl_context.proc_address("glGetString");
+    assert!(!gl_get_string.is_null());
+    let gl_get_string = unsafe { mem::transmute::<*const c_void, GlGetString>(gl_get_string) };
+    assert!(!gl_get_string(GL_VERSION).is_null());
+}
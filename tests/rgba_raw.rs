@@ -0,0 +1,66 @@
+//! Tests for creating images and cursor data from raw RGBA bytes.
+//!
+//! These don't need a display.
+
+extern crate dmc;
+
+use dmc::{Extent2, Vec2, Rgba, RgbaImage, RgbaCursorData, ErrorKind};
+
+fn bytes_2x2() -> Vec<u8> {
+    vec![
+        255, 0, 0, 255,   0, 255, 0, 255,
+        0, 0, 255, 255,   255, 128, 0, 128,
+    ]
+}
+
+#[test]
+fn from_raw_reuses_the_buffer() {
+    let bytes = bytes_2x2();
+    let ptr = bytes.as_ptr();
+    let image = RgbaImage::from_raw(Extent2::new(2, 2), bytes).unwrap();
+    assert_eq!(image.pixels.as_ptr() as *const u8, ptr);
+    assert_eq!(image.pixels[1], Rgba::new(0, 255, 0, 255));
+    assert_eq!(image.pixels[3], Rgba::new(255, 128, 0, 128));
+}
+
+#[test]
+fn from_raw_copies_when_the_capacity_is_odd() {
+    let mut bytes = Vec::with_capacity(17);
+    bytes.extend(bytes_2x2());
+    if bytes.capacity() % 4 == 0 {
+        return; // The allocator rounded it up; Nothing to test.
+    }
+    let image = RgbaImage::from_raw(Extent2::new(2, 2), bytes).unwrap();
+    assert_eq!(image.pixels[3], Rgba::new(255, 128, 0, 128));
+}
+
+#[test]
+fn from_raw_rejects_bad_lengths() {
+    let mut bytes = bytes_2x2();
+    bytes.pop();
+    assert_eq!(RgbaImage::from_raw(Extent2::new(2, 2), bytes).unwrap_err().kind, ErrorKind::InvalidArgument);
+    assert_eq!(RgbaImage::from_raw(Extent2::new(3, 2), bytes_2x2()).unwrap_err().kind, ErrorKind::InvalidArgument);
+    assert_eq!(RgbaImage::from_raw(Extent2::new(0, 0), vec![]).unwrap_err().kind, ErrorKind::InvalidArgument);
+    assert_eq!(RgbaImage::from_raw(Extent2::new(::std::u32::MAX, ::std::u32::MAX), vec![]).unwrap_err().kind, ErrorKind::InvalidArgument);
+}
+
+#[test]
+fn pixels_from_raw_slice_accepts_misaligned_slices() {
+    let mut bytes = vec![0xff];
+    bytes.extend(bytes_2x2());
+    let pixels = RgbaImage::pixels_from_raw_slice(Extent2::new(2, 2), &bytes[1..]).unwrap();
+    assert_eq!(pixels.as_ptr() as *const u8, bytes[1..].as_ptr());
+    assert_eq!(pixels[0], Rgba::new(255, 0, 0, 255));
+    assert!(RgbaImage::pixels_from_raw_slice(Extent2::new(2, 2), &bytes[2..]).is_err());
+}
+
+#[test]
+fn cursor_from_raw() {
+    let straight = RgbaCursorData::from_raw(Extent2::new(2, 2), Vec2::new(1, 1), bytes_2x2(), false).unwrap();
+    assert_eq!(straight.rgba[3], Rgba::new(255, 128, 0, 128));
+    let premultiplied = RgbaCursorData::from_raw(Extent2::new(2, 2), Vec2::new(1, 1), bytes_2x2(), true).unwrap();
+    assert_eq!(premultiplied.rgba[0], Rgba::new(255, 0, 0, 255));
+    assert_eq!(premultiplied.rgba[3], Rgba::new(128, 64, 0, 128));
+    let err = RgbaCursorData::from_raw(Extent2::new(2, 2), Vec2::new(2, 0), bytes_2x2(), false).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::InvalidArgument);
+}
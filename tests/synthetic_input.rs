@@ -0,0 +1,55 @@
+//! End-to-end test for synthesized input.
+//!
+//! Run with `cargo test --features synthetic-input`.  
+//! This requires a running display (e.g an X server), and moves your actual mouse cursor.
+
+#![cfg(feature = "synthetic-input")]
+
+extern crate dmc;
+
+mod common;
+
+use dmc::{Context, Event, Vec2, Extent2, WindowSettings};
+use dmc::device::{MouseButton, ButtonState};
+
+#[test]
+fn synthesized_click_at_window_center() {
+    let context = Context::new().unwrap();
+    let window = context.create_window(&WindowSettings::default()).unwrap();
+    let handle = window.handle();
+    window.set_size(Extent2::new(400, 300)).unwrap();
+    window.show().unwrap();
+    common::wait_for(&context, |ev| match *ev {
+        Event::WindowShown { window } if window == handle => Some(()),
+        _ => None,
+    });
+    window.clear().unwrap();
+
+    // `Window::position()` may or may not account for the window manager's decorations,
+    // so first figure out where the window's canvas actually is on the desktop.
+    let size = window.canvas_size().unwrap();
+    let rough_center = window.position().unwrap() + Vec2::new(size.w as i32 / 2, size.h as i32 / 2);
+    context.synthesize_mouse_move_to(rough_center).unwrap();
+    let canvas_origin = common::wait_for(&context, |ev| match *ev {
+        Event::MouseMotion { window, position, root_position, .. } if window == handle => Some(root_position - position),
+        _ => None,
+    });
+    let canvas_origin = canvas_origin.map(|x| x.round() as i32);
+
+    let center = Vec2::new(size.w as i32 / 2, size.h as i32 / 2);
+    context.synthesize_mouse_move_to(canvas_origin + center).unwrap();
+    context.synthesize_mouse_button(MouseButton::Left, ButtonState::Down).unwrap();
+    context.synthesize_mouse_button(MouseButton::Left, ButtonState::Up).unwrap();
+
+    // No motion event is reported if the cursor happened to be at the center already.
+    let mut last_position = Some((rough_center - canvas_origin).map(|x| x as f64));
+    common::wait_for(&context, |ev| match *ev {
+        Event::MouseMotion { window, position, .. } if window == handle => {
+            last_position = Some(position);
+            None
+        },
+        Event::MouseButtonPressed { window, button: MouseButton::Left, .. } if window == handle => Some(()),
+        _ => None,
+    });
+    assert_eq!(last_position.map(|p| p.map(|x| x.round() as i32)), Some(center));
+}
@@ -0,0 +1,101 @@
+//! A virtual controller for tests that need real input on Linux. Each test file includes this
+//! via `mod uinput;`, and must have `extern crate libc;` and `#[macro_use] extern crate nix;`.
+//!
+//! Creating the controller requires write access to `/dev/uinput` (which normally requires being
+//! in the `input` group). Tests fail instead of passing trivially when it can't be opened.
+
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use std::ffi::CString;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::slice;
+use libc;
+
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_ABS: u16 = 0x03;
+pub const SYN_REPORT: u16 = 0x00;
+pub const BTN_SOUTH: u16 = 0x130;
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+const BUS_VIRTUAL: u16 = 0x06;
+
+// #define UI_DEV_CREATE		_IO(UINPUT_IOCTL_BASE, 1)
+ioctl!(none ui_dev_create with b'U', 1);
+// #define UI_DEV_DESTROY		_IO(UINPUT_IOCTL_BASE, 2)
+ioctl!(none ui_dev_destroy with b'U', 2);
+// #define UI_SET_EVBIT		_IOW(UINPUT_IOCTL_BASE, 100, int)
+ioctl!(write_int ui_set_evbit with b'U', 100);
+// #define UI_SET_KEYBIT		_IOW(UINPUT_IOCTL_BASE, 101, int)
+ioctl!(write_int ui_set_keybit with b'U', 101);
+// #define UI_SET_ABSBIT		_IOW(UINPUT_IOCTL_BASE, 103, int)
+ioctl!(write_int ui_set_absbit with b'U', 103);
+
+/// A virtual gamepad with one button and two axes, destroyed on drop.
+pub struct VirtualController(File);
+
+impl VirtualController {
+    /// Panics if `/dev/uinput` can't be opened for writing.
+    pub fn new(name: &str) -> Self {
+        let file = match OpenOptions::new().write(true).open("/dev/uinput") {
+            Ok(file) => file,
+            Err(e) => panic!("Could not open /dev/uinput for writing ({}); These tests need a virtual controller", e),
+        };
+        let fd = file.as_raw_fd();
+        unsafe {
+            ui_set_evbit(fd, EV_KEY as _).unwrap();
+            ui_set_keybit(fd, BTN_SOUTH as _).unwrap();
+            ui_set_evbit(fd, EV_ABS as _).unwrap();
+            ui_set_absbit(fd, ABS_X as _).unwrap();
+            ui_set_absbit(fd, ABS_Y as _).unwrap();
+
+            let mut setup: libc::uinput_user_dev = mem::zeroed();
+            for (dst, src) in setup.name.iter_mut().zip(CString::new(name).unwrap().as_bytes()) {
+                *dst = *src as _;
+            }
+            setup.id.bustype = BUS_VIRTUAL;
+            setup.id.vendor = 0xdead;
+            setup.id.product = 0xbeef;
+            for &code in &[ABS_X, ABS_Y] {
+                setup.absmin[code as usize] = -32768;
+                setup.absmax[code as usize] = 32767;
+            }
+            let bytes = slice::from_raw_parts(&setup as *const _ as *const u8, mem::size_of_val(&setup));
+            (&file).write_all(bytes).unwrap();
+            ui_dev_create(fd).unwrap();
+        }
+        VirtualController(file)
+    }
+    pub fn emit(&self, type_: u16, code: u16, value: i32) {
+        let mut ev: libc::input_event = unsafe { mem::zeroed() };
+        ev.type_ = type_;
+        ev.code = code;
+        ev.value = value;
+        let bytes = unsafe {
+            slice::from_raw_parts(&ev as *const _ as *const u8, mem::size_of_val(&ev))
+        };
+        (&self.0).write_all(bytes).unwrap();
+    }
+    pub fn move_axis(&self, code: u16, value: i32) {
+        self.emit(EV_ABS, code, value);
+        self.emit(EV_SYN, SYN_REPORT, 0);
+    }
+    pub fn press_button(&self, code: u16) {
+        self.emit(EV_KEY, code, 1);
+        self.emit(EV_SYN, SYN_REPORT, 0);
+    }
+    pub fn release_button(&self, code: u16) {
+        self.emit(EV_KEY, code, 0);
+        self.emit(EV_SYN, SYN_REPORT, 0);
+    }
+}
+
+impl Drop for VirtualController {
+    fn drop(&mut self) {
+        let _ = unsafe { ui_dev_destroy(self.0.as_raw_fd()) };
+    }
+}